@@ -0,0 +1,30 @@
+#!/usr/bin/env python3
+"""
+This example shows how to record a headless render into a video or an image sequence.
+While recording, every frame advances time by exactly 1/fps independently of how long it took to render.
+"""
+
+from gloss import ViewerHeadless, geom
+from gloss.log import LogLevel, gloss_setup_logger as setup_logger
+from gloss.types import RecordFormat
+
+# Set up the logger
+# To be called only once per process. Can select between Off, Error, Warn, Info, Debug, Trace
+setup_logger(log_level = LogLevel.Info)
+
+if __name__ == "__main__":
+    viewer = ViewerHeadless(1280, 720)
+
+    cube = viewer.get_or_create_entity(name = "cube")
+    cube.insert_builder(geom.build_cube(center = [0, 0, 0]))
+
+    cam = viewer.get_camera()
+
+    # Can also be RecordFormat.Png or RecordFormat.Exr which will write a numbered sequence inside the ./recording directory
+    # The y4m can be converted afterwards with: ffmpeg -i recording.y4m -pix_fmt yuv420p recording.mp4
+    viewer.start_recording("./recording.y4m", format = RecordFormat.Y4m, fps = 30.0)
+    for _ in range(90):
+        dt = viewer.start_frame() # will be 1/30 while recording
+        cam.orbit_y(dt * 120.0)
+        viewer.update()
+    viewer.stop_recording()
@@ -0,0 +1,36 @@
+#!/usr/bin/env python3
+"""
+Outline a mesh and a point cloud. The entity selected in the GUI also gets an outline whose color and width can be changed from the Scene
+section
+"""
+
+import os
+
+import numpy as np
+from gloss import Viewer, geom
+from gloss.log import LogLevel, gloss_setup_logger as setup_logger
+from gloss.components import Verts, VisPoints, Highlight
+
+# Set up the logger
+# To be called only once per process. Can select between Off, Error, Warn, Info, Debug, Trace
+setup_logger(log_level = LogLevel.Info)
+
+if __name__ == "__main__":
+    data_path = os.path.join( os.path.dirname( os.path.realpath(__file__) ),"../../../data")
+    mesh_path = os.path.join(data_path,"bust.obj")
+
+    viewer = Viewer()
+
+    mesh = viewer.get_or_create_entity(name = "mesh")
+    mesh.insert_builder(geom.build_from_file(mesh_path))
+    mesh.insert(Highlight(color = np.array([0.1, 0.6, 1.0, 1.0], dtype = np.float32), width = 4.0))
+
+    # the same vertices shown next to it as a point cloud
+    vertices = mesh.get(Verts).numpy()
+    extent = vertices[:, 0].max() - vertices[:, 0].min()
+    points = viewer.get_or_create_entity(name = "points")
+    points.insert(Verts(vertices + np.array([1.2 * extent, 0.0, 0.0], dtype = np.float32)))
+    points.insert(VisPoints(show_points = True, point_size = 2.0))
+    points.insert(Highlight(color = np.array([1.0, 0.2, 0.6, 1.0], dtype = np.float32), width = 2.0))
+
+    viewer.run()
@@ -0,0 +1,27 @@
+#!/usr/bin/env python3
+"""
+Shows a mesh inline in a Jupyter notebook. Paste this in a cell, the mesh can be
+orbited with the left mouse button, panned with the right one and zoomed with
+the scroll wheel. Requires `pip install ipywidgets ipyevents`
+"""
+
+import os
+
+from gloss import geom
+from gloss.notebook import NotebookViewer
+
+viewer = NotebookViewer(800, 600)
+
+#get paths to data
+data_path = os.path.join(os.path.dirname(os.path.realpath(__file__)), "../../../data")
+mesh_path = os.path.join(data_path, "bust.obj")
+
+mesh = viewer.get_or_create_entity(name = "mesh")
+mesh.insert_builder(geom.build_from_file(mesh_path))
+
+cam = viewer.get_camera()
+cam.set_position([0.0, 1.5, 2.0])
+cam.set_lookat([0.0, 1.0, 0.0])
+
+# displaying the viewer as the last expression of the cell shows the widget
+viewer
@@ -0,0 +1,37 @@
+#!/usr/bin/env python3
+"""
+Play back a mesh sequence with the timeline at the bottom of the gui.
+Sequences can also be loaded lazily from a folder of OBJ/PLY files with MeshSequence.from_folder(path)
+"""
+
+import os
+
+import numpy as np
+from gloss import Viewer, geom
+from gloss.log import LogLevel, gloss_setup_logger as setup_logger
+from gloss.components import Verts, MeshSequence
+
+# Set up the logger
+# To be called only once per process. Can select between Off, Error, Warn, Info, Debug, Trace
+setup_logger(log_level = LogLevel.Info)
+
+if __name__ == "__main__":
+    data_path = os.path.join( os.path.dirname( os.path.realpath(__file__) ),"../../../data")
+    mesh_path = os.path.join(data_path,"bust.obj")
+
+    viewer = Viewer()
+
+    mesh = viewer.get_or_create_entity(name = "mesh")
+    mesh.insert_builder(geom.build_from_file(mesh_path))
+
+    # a wave travelling upwards through the mesh as a [frames, verts, 3] array
+    vertices = mesh.get(Verts).numpy()
+    nr_frames = 120
+    phases = np.linspace(0, 2 * np.pi, nr_frames, endpoint = False, dtype = np.float32)
+    heights = vertices[:, 1]
+    scale = 1.0 + 0.05 * np.sin(10.0 * heights[None, :] - phases[:, None])
+    frames = vertices[None, :, :] * scale[:, :, None]
+    frames[:, :, 1] = vertices[None, :, 1]
+    mesh.insert(MeshSequence(frames.astype(np.float32), fps = 30.0))
+
+    viewer.run()
@@ -0,0 +1,64 @@
+#!/usr/bin/env python3
+"""
+This example shows how to drive the viewer from python callbacks instead of a
+render loop: a logic system that runs every frame, an event handler and a gui
+window whose widgets call back into python
+"""
+
+import numpy as np
+
+from gloss import Viewer, geom
+from gloss.log import LogLevel, gloss_setup_logger as setup_logger
+from gloss.components import ModelMatrix, VisMesh
+from gloss.gui import GuiWindow, Slider, Checkbox, Button, SelectableList
+
+# Set up the logger
+# To be called only once per process. Can select between Off, Error, Warn, Info, Debug, Trace
+setup_logger(log_level = LogLevel.Info)
+
+state = {"angle": 0.0, "speed": 1.0, "paused": False}
+colors = {"red": [1.0, 0.1, 0.1, 1.0], "green": [0.1, 1.0, 0.1, 1.0], "blue": [0.1, 0.1, 1.0, 1.0]}
+
+def rotate(scene, dt):
+    if state["paused"]:
+        return False
+    state["angle"] += state["speed"] * dt
+    cube = scene.get_or_create_entity("cube")
+    cube.insert(ModelMatrix.default().with_rotation_axis_angle([0.0, state["angle"], 0.0]))
+    # returning True requests a redraw so the animation keeps going
+    return True
+
+def on_event(scene, event):
+    if event.kind == "dropped_file":
+        print(f"Dropped {event.path}")
+    return False
+
+def set_speed(value, entity, scene):
+    state["speed"] = value
+
+def set_paused(value, entity, scene):
+    state["paused"] = value
+
+def reset(entity, scene):
+    state["angle"] = 0.0
+
+def set_color(item, entity, scene):
+    entity.insert(VisMesh(solid_color = np.array(colors[item], dtype = np.float32)))
+
+if __name__ == "__main__":
+    viewer = Viewer()
+
+    cube = viewer.get_or_create_entity(name = "cube")
+    cube.insert_builder(geom.build_cube(center = [0, 1, 0]))
+
+    viewer.add_logic_system(rotate)
+    viewer.add_event_handler(on_event)
+    # the window is shown when an entity is selected and the callbacks receive that entity
+    viewer.add_gui_window(GuiWindow("Controls", [
+        Slider("speed", set_speed, value = state["speed"], min = 0.0, max = 5.0),
+        Checkbox("paused", set_paused),
+        Button("reset", reset),
+        SelectableList(list(colors.keys()), set_color, horizontal = True),
+    ]))
+
+    viewer.run()
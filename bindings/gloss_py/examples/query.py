@@ -0,0 +1,34 @@
+#!/usr/bin/env python3
+"""
+Iterate over all the entities that have a certain set of components
+"""
+
+import numpy as np
+
+from gloss import Viewer
+from gloss.log import LogLevel, gloss_setup_logger as setup_logger
+from gloss.components import Verts, Colors, VisPoints
+
+# Set up the logger
+# To be called only once per process. Can select between Off, Error, Warn, Info, Debug, Trace
+setup_logger(log_level = LogLevel.Info)
+
+if __name__ == "__main__":
+    viewer = Viewer()
+
+    for i in range(3):
+        cloud = viewer.get_or_create_entity(name = f"cloud_{i}")
+        verts = np.random.rand(100, 3).astype(np.float32) + np.array([i * 1.5, 0, 0], dtype=np.float32)
+        cloud.insert(Verts(verts))
+        cloud.insert(Colors(np.random.rand(100, 3).astype(np.float32)))
+        cloud.insert(VisPoints(show_points=True, point_size=5.0))
+
+    scene = viewer.get_scene()
+    for entity, verts, _vis in scene.query([Verts, VisPoints]):
+        # numpy_view() doesn't copy the data, the array is read-only
+        v = verts.numpy_view()
+        print("centroid", v.mean(axis=0))
+        # components are copies so changing them requires inserting them again
+        entity.insert(VisPoints(show_points=True, point_size=10.0))
+
+    viewer.run()
@@ -0,0 +1,39 @@
+#!/usr/bin/env python3
+"""
+Cut a mesh with a clip plane and fill the cut with a solid color
+"""
+
+import os
+
+import numpy as np
+from gloss import Viewer, geom
+from gloss.log import LogLevel, gloss_setup_logger as setup_logger
+from gloss.components import Verts, ClipPlane, NoClip
+
+# Set up the logger
+# To be called only once per process. Can select between Off, Error, Warn, Info, Debug, Trace
+setup_logger(log_level = LogLevel.Info)
+
+if __name__ == "__main__":
+    data_path = os.path.join( os.path.dirname( os.path.realpath(__file__) ),"../../../data")
+    mesh_path = os.path.join(data_path,"bust.obj")
+
+    viewer = Viewer()
+
+    mesh = viewer.get_or_create_entity(name = "mesh")
+    mesh.insert_builder(geom.build_from_file(mesh_path))
+
+    # the same mesh shown next to it is not cut
+    mesh_whole = viewer.get_or_create_entity(name = "mesh_whole")
+    mesh_whole.insert_builder(geom.build_from_file(mesh_path))
+    vertices = mesh_whole.get(Verts).numpy()
+    extent = vertices[:, 0].max() - vertices[:, 0].min()
+    mesh_whole.insert(Verts(vertices + np.array([1.2 * extent, 0.0, 0.0], dtype = np.float32)))
+    mesh_whole.insert(NoClip())
+
+    # keep everything in front of the plane going through the center of the mesh. The plane can be moved from the Clipping section of the GUI
+    center = vertices.mean(axis = 0)
+    plane = viewer.get_or_create_entity(name = "clip_plane")
+    plane.insert(ClipPlane(point = center, normal = np.array([0.0, 0.0, -1.0], dtype = np.float32), cap_color = np.array([0.8, 0.3, 0.3, 1.0], dtype = np.float32)))
+
+    viewer.run()
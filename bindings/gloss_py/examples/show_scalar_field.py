@@ -0,0 +1,36 @@
+#!/usr/bin/env python3
+"""
+Show a per-vertex scalar field through a colormap with isolines
+"""
+
+import os
+
+import numpy as np
+from gloss import Viewer, geom
+from gloss.log import LogLevel, gloss_setup_logger as setup_logger
+from gloss.components import Verts, Scalars, VisMesh, VisScalars
+from gloss.types import Colormap, MeshColorType
+
+# Set up the logger
+# To be called only once per process. Can select between Off, Error, Warn, Info, Debug, Trace
+setup_logger(log_level = LogLevel.Info)
+
+if __name__ == "__main__":
+    data_path = os.path.join( os.path.dirname( os.path.realpath(__file__) ),"../../../data")
+    mesh_path = os.path.join(data_path,"bust.obj")
+
+    viewer = Viewer()
+
+    mesh = viewer.get_or_create_entity(name = "mesh")
+    mesh.insert_builder(geom.build_from_file(mesh_path))
+
+    # height of every vertex as the scalar field
+    vertices = mesh.get(Verts).numpy()
+    heights = vertices[:, 1].astype(np.float32)
+    mesh.insert(Scalars(heights))
+
+    # the range and colormap can be changed at any time without uploading the scalars again
+    mesh.insert(VisMesh(color_type = MeshColorType.Scalar))
+    mesh.insert(VisScalars(colormap = Colormap.Coolwarm, min = float(heights.min()), max = float(heights.max()), nr_isolines = 10))
+
+    viewer.run()
@@ -88,7 +88,7 @@ def generate_stubs():
     # Common imports for all stub files
     common_imports = [
         "from __future__ import annotations",
-        "from typing import Any, Optional, List, Type, TypeVar, Tuple",
+        "from typing import Any, Callable, Optional, List, Type, TypeVar, Tuple",
         "import numpy as np",
         "from numpy.typing import NDArray",
         "",
@@ -99,18 +99,20 @@ def generate_stubs():
     # Module-specific imports
     module_imports = {
         '__init__': [
-            "from gloss.types import IndirRemovalPolicy, SplatType",
+            "from gloss.types import Aov, Event, IndirRemovalPolicy, RecordFormat, SplatType",
             "from gloss.log import LogLevel, LogLevelCaps",
-            "from gloss.components import Colors, DiffuseImg, Edges, Faces, Normals, Tangents, UVs, Verts, VisLines, VisMesh, VisPoints, ModelMatrix",
+            "from gloss.components import Colors, DiffuseImg, Edges, Faces, Normals, Tangents, UVs, Verts, VisLines, VisMesh, VisPoints, VisWireframe, VisNormals, ModelMatrix, NormalImg, RoughnessImg, MetalnessImg, LightEmit, ShadowCaster, PosLookat, Projection, EnvironmentMap, Name",
             "from gloss.builders import EntityBuilder",
+            "from gloss.gui import GuiWindow",
         ],
         'types': [],
         'log': [],
         'components': [],
-        'builders': []
+        'builders': [],
+        'gui': ["from gloss import Entity, Scene"]
     }
 
-    for submod in ['types', 'log', 'components', 'builders', '__init__']:
+    for submod in ['types', 'log', 'components', 'builders', 'gui', '__init__']:
         try:
             with open(f'gloss/{submod}.pyi', 'w') as f:
                 f.write('\n'.join(common_imports))
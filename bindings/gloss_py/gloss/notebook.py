@@ -0,0 +1,89 @@
+"""
+Inline viewer for Jupyter notebooks. The scene is rendered with the headless
+viewer and every frame is streamed as an image to an ipywidget. Mouse drags and
+the scroll wheel over the image are forwarded to the camera so the scene can
+be orbited, panned and zoomed like in the windowed viewer.
+
+Requires `ipywidgets` and `ipyevents`:
+    pip install ipywidgets ipyevents
+"""
+
+from .gloss import ViewerHeadless
+
+_MOUSE_BUTTONS = {0: "left", 1: "middle", 2: "right"}
+
+
+class NotebookViewer:
+    """
+    Same API as `Viewer`, like `get_or_create_entity()`, `get_scene()` and
+    `get_camera()`, but it is displayed inside the notebook cell. Call
+    `render()` after changing the scene to update the image.
+    """
+
+    def __init__(self, width=640, height=480, config_path=None, format="jpeg", quality=85, max_fps=30.0):
+        try:
+            import ipywidgets
+            from ipyevents import Event
+        except ImportError as err:
+            raise ImportError("NotebookViewer requires ipywidgets and ipyevents: pip install ipywidgets ipyevents") from err
+
+        self.viewer = ViewerHeadless(width, height, config_path)
+        self.width = width
+        self.height = height
+        self.format = format
+        self.quality = quality
+        self.image = ipywidgets.Image(format=format, width=width, height=height)
+        # mouse moves are throttled so that we don't queue more frames than we can render
+        self._events = Event(
+            source=self.image,
+            watched_events=["mousedown", "mouseup", "mousemove", "mouseleave", "wheel"],
+            prevent_default_action=True,
+            wait=int(1000.0 / max_fps),
+            throttle_or_debounce="throttle",
+        )
+        self._events.on_dom_event(self._handle_event)
+
+    def __getattr__(self, name):
+        # everything that is not specific to the notebook goes to the headless viewer
+        if name == "viewer":
+            raise AttributeError(name)
+        return getattr(self.viewer, name)
+
+    def render(self):
+        """Renders a new frame and sends it to the widget"""
+        self.viewer.render_next_frame()
+        self.image.value = self.viewer.encode_last_render(self.format, self.quality)
+
+    def show(self):
+        """Renders the scene and displays the widget in the current cell"""
+        from IPython.display import display
+
+        self.render()
+        display(self.image)
+
+    def _ipython_display_(self):
+        self.show()
+
+    def _mouse_pos(self, event):
+        # dataX/dataY are in pixels of the image, regardless of how big the widget is displayed
+        return float(event.get("dataX", event["relativeX"])), float(event.get("dataY", event["relativeY"]))
+
+    def _handle_event(self, event):
+        cam = self.viewer.get_camera()
+        kind = event["type"]
+        if kind == "mousedown":
+            cam.mouse_pressed(_MOUSE_BUTTONS.get(event["button"], "middle"))
+            x, y = self._mouse_pos(event)
+            cam.process_mouse_move(x, y, self.width, self.height)
+        elif kind in ("mouseup", "mouseleave"):
+            cam.mouse_released()
+        elif kind == "mousemove":
+            if event.get("buttons", 0) == 0:
+                return
+            x, y = self._mouse_pos(event)
+            cam.process_mouse_move(x, y, self.width, self.height)
+            self.render()
+        elif kind == "wheel":
+            # the browser reports positive values when scrolling down, which should zoom out
+            cam.process_mouse_scroll(-float(event["deltaY"]))
+            self.render()
@@ -44,41 +44,42 @@ pub fn pycomponent_derive(input: TokenStream) -> TokenStream {
         impl #name {
             //ideally we would use a function that takes PyEntityMut as parameter. However, PyEntityMut cannot be shared between multiple crates due to this:
             // https://github.com/PyO3/pyo3/issues/1444
-            // therefore we pass around just the raw data for the entity and the scene_ptr
-            pub fn insert_to_entity(&mut self, entity_bits: u64, scene_ptr_idx: u64) {
+            // therefore we pass around just the raw data for the entity together with the handle of the scene which checks that the entity is still alive
+            pub fn insert_to_entity(&mut self, entity_bits: u64, mut scene: PyRefMut<'_, SceneHandle>) -> PyResult<()> {
                 let entity = Entity::from_bits(entity_bits).unwrap();
-                let scene_ptr = scene_ptr_idx as *mut Scene;
-                let scene: &mut Scene = unsafe { &mut *scene_ptr };
-                scene.world
+                scene.check_entity(entity)?;
+                scene.scene_mut().world
                     .insert_one(entity, self.inner.clone())
                     .ok();
+                Ok(())
             }
             #[staticmethod]
-            pub fn get(entity_bits: u64, scene_ptr_idx: u64) -> Self {
+            pub fn get(entity_bits: u64, scene: PyRef<'_, SceneHandle>) -> PyResult<Self> {
                 let entity = Entity::from_bits(entity_bits).unwrap();
-                //TODO this is super brittle because if the scene obj is ever compiled differently in gloss, any plugin that depends on derefering it will fail because the Scene object will have different size
-                let scene_ptr = scene_ptr_idx as *mut Scene;
-                let scene: &mut Scene = unsafe { &mut *scene_ptr };
-                let comp = scene.get_comp::<&mut #first_field_ty>(&entity).unwrap();
-                Self {
-                    inner: comp.clone(),
-                }
+                scene.check_entity(entity)?;
+                let comp = scene.scene().get_comp::<&#first_field_ty>(&entity).unwrap();
+                Ok(Self {
+                    inner: (*comp).clone(),
+                })
             }
             #[staticmethod]
-            pub fn exists(entity_bits: u64, scene_ptr_idx: u64) -> bool {
+            pub fn exists(entity_bits: u64, scene: PyRef<'_, SceneHandle>) -> PyResult<bool> {
                 let entity = Entity::from_bits(entity_bits).unwrap();
-                //TODO this is super brittle because if the scene obj is ever compiled differently in gloss, any plugin that depends on derefering it will fail because the Scene object will have different size
-                let scene_ptr = scene_ptr_idx as *mut Scene;
-                let scene: &mut Scene = unsafe { &mut *scene_ptr };
-                scene.world.has::<#first_field_ty>(entity).unwrap()
+                scene.check_entity(entity)?;
+                Ok(scene.scene().world.has::<#first_field_ty>(entity).unwrap())
             }
             #[staticmethod]
-            pub fn remove(entity_bits: u64, scene_ptr_idx: u64) {
+            pub fn remove(entity_bits: u64, mut scene: PyRefMut<'_, SceneHandle>) -> PyResult<()> {
                 let entity = Entity::from_bits(entity_bits).unwrap();
-                //TODO this is super brittle because if the scene obj is ever compiled differently in gloss, any plugin that depends on derefering it will fail because the Scene object will have different size
-                let scene_ptr = scene_ptr_idx as *mut Scene;
-                let scene: &mut Scene = unsafe { &mut *scene_ptr };
-                scene.world.remove_one::<#first_field_ty>(entity).ok(); //don't unwrap because we don't care if the component exists or not
+                scene.check_entity(entity)?;
+                scene.scene_mut().world.remove_one::<#first_field_ty>(entity).ok(); //don't unwrap because we don't care if the component exists or not
+                Ok(())
+            }
+            #[staticmethod]
+            pub fn query_entities(scene: PyRef<'_, SceneHandle>) -> Vec<u64> {
+                //the borrow is released when the QueryBorrow is dropped at the end of this function
+                let mut query = scene.scene().world.query::<&#first_field_ty>();
+                query.iter().map(|(entity, _)| entity.to_bits().get()).collect()
             }
         }
     };
@@ -3,6 +3,8 @@ use gloss_renderer::{actor::Actor, scene::Scene};
 // use numpy::Element;
 use pyo3::{prelude::*, types::PyType};
 
+use crate::handle::SceneHandle;
+
 //https://stackoverflow.com/questions/67412827/pyo3-deriving-frompyobject-for-enums
 // https://stackoverflow.com/questions/75779700/retrieve-a-pyclass-from-an-attribute-of-an-arbitrary-pyany
 //https://pyo3.rs/v0.19.2/conversions/traits
@@ -12,43 +14,53 @@ use pyo3::{prelude::*, types::PyType};
 //mut
 #[pyclass(name = "Entity", unsendable)]
 // it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
-#[derive(Clone, Copy)]
 pub struct PyActorMut {
     pub actor: Actor,
-    scene_ptr: *mut Scene, /* we only store a ptr to the scene because we pyo3 cannot deal with lifetime so EntityMut cannot have a reference to
-                            * the world as it does on the rust side */
+    scene: SceneHandle, /* pyo3 cannot deal with lifetimes so EntityMut cannot have a reference to the world as it does on the rust side. Instead
+                         * we keep the viewer owning the scene alive and check that the entity is still there before every access */
 }
 impl PyActorMut {
-    pub fn new(entity: Entity, scene_ptr: *mut Scene) -> Self {
+    pub fn new(entity: Entity, scene: SceneHandle) -> Self {
         Self {
             actor: Actor::from_entity(entity),
-            scene_ptr,
+            scene,
         }
     }
-    pub fn scene(&self) -> &Scene {
-        unsafe { &*self.scene_ptr }
+    /// # Errors
+    /// Will return a `StaleHandleError` if the entity has been despawned
+    pub fn scene(&self) -> PyResult<&Scene> {
+        self.scene.check_entity(self.actor.entity)?;
+        Ok(self.scene.scene())
+    }
+    /// # Errors
+    /// Will return a `StaleHandleError` if the entity has been despawned
+    pub fn scene_mut(&mut self) -> PyResult<&mut Scene> {
+        self.scene.check_entity(self.actor.entity)?;
+        Ok(self.scene.scene_mut())
     }
-    pub fn scene_mut(&mut self) -> &mut Scene {
-        unsafe { &mut *self.scene_ptr }
+    //raw data passed to the components since they can't take a PyActorMut, the components check that the entity is still alive
+    fn component_args(&self, py: Python<'_>) -> PyResult<(u64, Py<SceneHandle>)> {
+        Ok((self.actor.entity.to_bits().get(), self.scene.to_py(py)?))
     }
 }
 #[pymethods]
 impl PyActorMut {
     #[pyo3(text_signature = "($self, component: Any) -> Entity")]
-    pub fn insert(slf: PyRefMut<'_, Self>, pycomp: Py<PyAny>) -> PyRefMut<'_, Self> {
-        Python::with_gil(|py| {
+    pub fn insert(slf: PyRefMut<'_, Self>, pycomp: Py<PyAny>) -> PyResult<PyRefMut<'_, Self>> {
+        Python::with_gil(|py| -> PyResult<()> {
             let pyany = pycomp.bind(py);
             // Ideally we would use a function that takes PyEntityMut as parameter. However,
             // PyEntityMut cannot be shared between multiple crates due to this: https://github.com/PyO3/pyo3/issues/1444
-            // therefore we pass around just the raw data for the entity and the scene_ptr
-            let args = (slf.actor.entity.to_bits().get(), slf.scene_ptr as u64);
-            let _ = pyany.call_method("insert_to_entity", args, None).unwrap();
-        });
-        slf
+            // therefore we pass around just the raw data for the entity and the scene handle
+            let args = slf.component_args(py)?;
+            pyany.call_method("insert_to_entity", args, None)?;
+            Ok(())
+        })?;
+        Ok(slf)
     }
 
     #[pyo3(text_signature = "($self, builder: Any) -> Entity")]
-    pub fn insert_builder(slf: PyRefMut<'_, Self>, pybuilder: Py<PyAny>) -> PyRefMut<'_, Self> {
+    pub fn insert_builder(slf: PyRefMut<'_, Self>, pybuilder: Py<PyAny>) -> PyResult<PyRefMut<'_, Self>> {
         //we can't use this because we may have other builders made by other libraries like Smpl-rs they can't create a PyEntityBuilder because of this https://github.com/PyO3/pyo3/issues/1444
         //so we just treat the builder as any other component and make the builder
         // insert itself into the entity let entity = slf.entity;
@@ -61,40 +73,46 @@ impl PyActorMut {
     }
 
     #[pyo3(text_signature = "($self, cls: Type[T]) -> T")]
-    pub fn get(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> Py<PyAny> {
+    pub fn get(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> PyResult<Py<PyAny>> {
         let result = Python::with_gil(|py| {
             //ideally we would use a function that takes PyEntityMut as parameter. However,
             // PyEntityMut cannot be shared between multiple crates due to this: https://github.com/PyO3/pyo3/issues/1444
-            // therefore we pass around just the raw data for the entity and the scene_ptr
-            let args = (slf.actor.entity.to_bits().get(), slf.scene_ptr as u64);
-            let result = cls.call_method("get", args, None);
-            let pyany_ref = result.unwrap();
-            pyany_ref.into_py(py)
+            // therefore we pass around just the raw data for the entity and the scene handle
+            let args = slf.component_args(py)?;
+            let pyany_ref = cls.call_method("get", args, None)?;
+            Ok(pyany_ref.into_py(py))
         });
         result
     }
 
     #[pyo3(text_signature = "($self, cls: Type[T]) -> bool")]
-    pub fn has(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> Py<PyAny> {
+    pub fn has(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> PyResult<Py<PyAny>> {
         let result = Python::with_gil(|py| {
             //ideally we would use a function that takes PyEntityMut as parameter. However,
             // PyEntityMut cannot be shared between multiple crates due to this: https://github.com/PyO3/pyo3/issues/1444
-            // therefore we pass around just the raw data for the entity and the scene_ptr
-            let args = (slf.actor.entity.to_bits().get(), slf.scene_ptr as u64);
-            let result = cls.call_method("exists", args, None);
-            let pyany_ref = result.unwrap();
-            pyany_ref.into_py(py)
+            // therefore we pass around just the raw data for the entity and the scene handle
+            let args = slf.component_args(py)?;
+            let pyany_ref = cls.call_method("exists", args, None)?;
+            Ok(pyany_ref.into_py(py))
         });
         result
     }
 
     #[pyo3(text_signature = "($self, cls: Type[T]) -> bool")]
-    pub fn remove(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) {
+    pub fn remove(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> PyResult<()> {
         //ideally we would use a function that takes PyEntityMut as parameter. However,
         // PyEntityMut cannot be shared between multiple crates due to this: https://github.com/PyO3/pyo3/issues/1444
-        // therefore we pass around just the raw data for the entity and the scene_ptr
-        let args = (slf.actor.entity.to_bits().get(), slf.scene_ptr as u64);
-        let _ = cls.call_method("remove", args, None).unwrap();
+        // therefore we pass around just the raw data for the entity and the scene handle
+        let args = slf.component_args(cls.py())?;
+        cls.call_method("remove", args, None)?;
+        Ok(())
+    }
+
+    /// Whether the entity is still in the scene. Every other method raises a
+    /// `StaleHandleError` once the entity has been despawned
+    #[pyo3(text_signature = "($self) -> bool")]
+    pub fn is_alive(&self) -> bool {
+        self.scene.scene().world.contains(self.actor.entity)
     }
 
     #[pyo3(text_signature = "($self) -> int")]
@@ -103,22 +121,55 @@ impl PyActorMut {
     }
 
     #[pyo3(text_signature = "($self) -> None")]
-    pub fn apply_model_matrix(&mut self) {
+    pub fn apply_model_matrix(&mut self) -> PyResult<()> {
         let mut actor = self.actor; // we need to clone because of the borrow checker and having mutable ref to both
                                     // self and scene
-        let scene: &mut Scene = self.scene_mut();
+        let scene: &mut Scene = self.scene_mut()?;
         actor.apply_model_matrix(scene);
+        Ok(())
+    }
+
+    /// Attaches this entity to a parent while keeping its pose in the world.
+    /// From then on it moves together with the parent
+    #[pyo3(text_signature = "($self, parent: Entity) -> None")]
+    pub fn set_parent(&mut self, parent: &PyActorMut) -> PyResult<()> {
+        let entity = self.actor.entity;
+        let scene: &mut Scene = self.scene_mut()?;
+        scene.set_parent(entity, parent.actor.entity);
+        Ok(())
+    }
+
+    #[pyo3(text_signature = "($self) -> None")]
+    pub fn remove_parent(&mut self) -> PyResult<()> {
+        let entity = self.actor.entity;
+        let scene: &mut Scene = self.scene_mut()?;
+        scene.remove_parent(entity);
+        Ok(())
+    }
+
+    #[pyo3(text_signature = "($self) -> Optional[Entity]")]
+    pub fn parent(&self, py: Python<'_>) -> PyResult<Option<PyActorMut>> {
+        let parent = self.scene()?.get_parent(self.actor.entity);
+        Ok(parent.map(|p| PyActorMut::new(p, self.scene.clone_ref(py))))
+    }
+
+    #[pyo3(text_signature = "($self) -> List[Entity]")]
+    pub fn children(&self, py: Python<'_>) -> PyResult<Vec<PyActorMut>> {
+        let children = self.scene()?.get_children(self.actor.entity);
+        Ok(children.into_iter().map(|c| PyActorMut::new(c, self.scene.clone_ref(py))).collect())
     }
 
     #[pyo3(text_signature = "($self, path: str) -> None")]
-    pub fn save_obj(&self, path: &str) {
-        let scene: &Scene = self.scene();
+    pub fn save_obj(&self, path: &str) -> PyResult<()> {
+        let scene: &Scene = self.scene()?;
         self.actor.save_obj(scene, path);
+        Ok(())
     }
 
     #[pyo3(text_signature = "($self, path: str) -> None")]
-    pub fn save_ply(&self, path: &str) {
-        let scene: &Scene = self.scene();
+    pub fn save_ply(&self, path: &str) -> PyResult<()> {
+        let scene: &Scene = self.scene()?;
         self.actor.save_ply(scene, path);
+        Ok(())
     }
 }
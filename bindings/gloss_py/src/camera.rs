@@ -1,17 +1,19 @@
 use crate::scene::PyScene;
 use gloss_renderer::{
     camera::Camera,
-    components::{PosLookat, Projection, ProjectionWithIntrinsics},
+    components::{LensDistortion, PosLookat, Projection, ProjectionOrthographic, ProjectionWithIntrinsics, TargetResolution},
     scene::Scene,
 };
 use nalgebra as na;
 use numpy::{AllowTypeChange, PyArrayLike1, PyArrayLike2, PyArrayMethods, PyUntypedArrayMethods};
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use winit::event::{MouseButton, MouseScrollDelta};
 
 #[pyclass(name = "Camera", module = "gloss", unsendable)]
 // it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
 pub struct PyCamera {
     obj_ptr: *mut Camera,
+    //the camera lives in the same viewer as the scene so the scene handle also keeps the camera alive
     py_scene: PyScene,
 }
 impl std::ops::Deref for PyCamera {
@@ -147,9 +149,72 @@ impl PyCamera {
                 cy,
                 near: near.unwrap_or(0.01),
                 far: far.unwrap_or(100.0),
+                distortion: None,
             }),
         );
     }
+    /// Sets the lens distortion of a camera that has intrinsics. The coefficients follow the order of OpenCV,
+    /// (k1, k2, p1, p2, k3) for Brown-Conrady and (k1, k2, k3, k4) for fisheye. Missing coefficients are set to zero.
+    #[pyo3(signature = (coeffs, fisheye=false))]
+    #[pyo3(text_signature = "($self, coeffs: List[float], fisheye: bool = False) -> None")]
+    pub fn set_lens_distortion(&mut self, coeffs: Vec<f32>, fisheye: bool) {
+        let max_coeffs = if fisheye { 4 } else { 5 };
+        assert!(coeffs.len() <= max_coeffs, "expected at most {max_coeffs} distortion coefficients");
+        let c = |i: usize| coeffs.get(i).copied().unwrap_or(0.0);
+        let distortion = if fisheye {
+            LensDistortion::KannalaBrandt {
+                k1: c(0),
+                k2: c(1),
+                k3: c(2),
+                k4: c(3),
+            }
+        } else {
+            LensDistortion::BrownConrady {
+                k1: c(0),
+                k2: c(1),
+                k3: c(4),
+                p1: c(2),
+                p2: c(3),
+            }
+        };
+        let mut cam = Camera::from_entity(self.entity);
+        let scene_native: &mut Scene = &mut self.py_scene;
+        cam.set_lens_distortion(Some(distortion), scene_native);
+    }
+    #[pyo3(text_signature = "($self) -> None")]
+    pub fn clear_lens_distortion(&mut self) {
+        let mut cam = Camera::from_entity(self.entity);
+        let scene_native: &mut Scene = &mut self.py_scene;
+        cam.set_lens_distortion(None, scene_native);
+    }
+    #[pyo3(signature = (height, near=None, far=None))]
+    #[pyo3(text_signature = "($self, height: float, near: Optional[float] = None, far: Optional[float] = None) -> None")]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn set_orthographic(&mut self, height: f32, near: Option<f32>, far: Option<f32>) {
+        let ent = self.entity;
+        let default = ProjectionOrthographic::default();
+        let aspect_ratio = self
+            .py_scene
+            .get_comp::<&TargetResolution>(&ent)
+            .ok()
+            .filter(|res| res.width != 0 && res.height != 0)
+            .map_or(default.aspect_ratio, |res| res.width as f32 / res.height as f32);
+        let _ = self.py_scene.world.insert_one(
+            ent,
+            Projection::Orthographic(ProjectionOrthographic {
+                aspect_ratio,
+                height,
+                near: near.unwrap_or(default.near),
+                far: far.unwrap_or(default.far),
+            }),
+        );
+    }
+    #[pyo3(text_signature = "($self) -> bool")]
+    pub fn is_orthographic(&self) -> bool {
+        self.py_scene
+            .get_comp::<&Projection>(&self.entity)
+            .is_ok_and(|proj| proj.is_orthographic())
+    }
     #[pyo3(text_signature = "($self, degrees: float) -> None")]
     pub fn orbit_y(&mut self, degrees: f32) {
         let ent = self.entity;
@@ -157,4 +222,50 @@ impl PyCamera {
         let mut pos_lookat = self.py_scene.get_comp::<&mut PosLookat>(&ent).unwrap();
         pos_lookat.orbit_y(degrees);
     }
+    /// Starts dragging the camera with the mouse. The left button orbits and
+    /// the right one pans. Used to drive the camera from outside a window,
+    /// for example from a notebook widget
+    ///
+    /// # Errors
+    /// Will return an error if the button is not "left", "right" or "middle"
+    #[pyo3(text_signature = "($self, button: str) -> None")]
+    pub fn mouse_pressed(&mut self, button: &str) -> PyResult<()> {
+        let button = match button {
+            "left" => MouseButton::Left,
+            "right" => MouseButton::Right,
+            "middle" => MouseButton::Middle,
+            _ => return Err(PyValueError::new_err(format!("unknown mouse button {button}"))),
+        };
+        let mut cam = Camera::from_entity(self.entity);
+        let scene_native: &mut Scene = &mut self.py_scene;
+        cam.mouse_pressed(&button, scene_native);
+        Ok(())
+    }
+    #[pyo3(text_signature = "($self) -> None")]
+    pub fn mouse_released(&mut self) {
+        let mut cam = Camera::from_entity(self.entity);
+        let scene_native: &mut Scene = &mut self.py_scene;
+        cam.mouse_released(scene_native);
+    }
+    /// Moves the camera according to the mouse position in pixels, with the
+    /// origin in the top left corner of the viewport
+    #[pyo3(text_signature = "($self, x: float, y: float, viewport_width: int, viewport_height: int) -> None")]
+    pub fn process_mouse_move(&mut self, x: f32, y: f32, viewport_width: u32, viewport_height: u32) {
+        let ent = self.entity;
+        self.py_scene.insert_if_doesnt_exist::<PosLookat>(ent);
+        self.py_scene.insert_if_doesnt_exist::<Projection>(ent);
+        let mut cam = Camera::from_entity(ent);
+        let scene_native: &mut Scene = &mut self.py_scene;
+        cam.process_mouse_move(x, y, viewport_width, viewport_height, scene_native);
+    }
+    /// Zooms the camera. Positive values, which correspond to scrolling up,
+    /// zoom in
+    #[pyo3(text_signature = "($self, delta: float) -> None")]
+    pub fn process_mouse_scroll(&mut self, delta: f32) {
+        let ent = self.entity;
+        self.py_scene.insert_if_doesnt_exist::<PosLookat>(ent);
+        let mut cam = Camera::from_entity(ent);
+        let scene_native: &mut Scene = &mut self.py_scene;
+        cam.process_mouse_scroll(&MouseScrollDelta::LineDelta(0.0, delta), scene_native);
+    }
 }
@@ -0,0 +1,29 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::BlendShapeCoeffs;
+use numpy::{PyArray1, PyReadonlyArray1, ToPyArray};
+use pyo3::prelude::*;
+
+#[pyclass(name = "BlendShapeCoeffs", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyBlendShapeCoeffs {
+    pub inner: BlendShapeCoeffs,
+}
+#[pymethods]
+impl PyBlendShapeCoeffs {
+    /// One weight for every shape of BlendShapes. Inserting new coefficients
+    /// every frame only uploads these and not the vertices
+    #[new]
+    #[pyo3(text_signature = "(array: NDArray[np.float32]) -> BlendShapeCoeffs")]
+    pub fn new(array: PyReadonlyArray1<f32>) -> Self {
+        Self {
+            inner: BlendShapeCoeffs(array.as_array().to_vec()),
+        }
+    }
+    #[pyo3(text_signature = "($self) -> NDArray[np.float32]")]
+    pub fn numpy(&mut self, py: Python<'_>) -> Py<PyArray1<f32>> {
+        self.inner.0.to_pyarray_bound(py).into()
+    }
+}
@@ -0,0 +1,41 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::BlendShapes;
+use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
+use numpy::{PyArray2, PyReadonlyArray2, PyUntypedArrayMethods, ToPyArray};
+use pyo3::prelude::*;
+
+#[pyclass(name = "BlendShapes", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyBlendShapes {
+    pub inner: BlendShapes,
+}
+#[pymethods]
+impl PyBlendShapes {
+    /// Basis of K shapes stacked as a (K*N)x3 matrix of offsets from the rest
+    /// pose in Verts
+    #[new]
+    #[pyo3(text_signature = "(array: NDArray[np.float32]) -> BlendShapes")]
+    pub fn new(array: PyReadonlyArray2<f32>) -> Self {
+        let shape = array.shape();
+        assert_eq!(shape[1], 3, "BlendShapes need to be a (K*N)x3 matrix but it has shape {shape:?}");
+        Self {
+            inner: BlendShapes(DynamicTensorFloat2D::from_dmatrix(&array.as_matrix().into())),
+        }
+    }
+    /// Basis as a (N*3)xK matrix with one flattened shape per column, which is
+    /// the layout of the SMPL shape and pose blend shapes
+    #[staticmethod]
+    #[pyo3(text_signature = "(array: NDArray[np.float32]) -> BlendShapes")]
+    pub fn from_flattened(array: PyReadonlyArray2<f32>) -> Self {
+        Self {
+            inner: BlendShapes::from_flattened(&array.as_matrix().into()),
+        }
+    }
+    #[pyo3(text_signature = "($self) -> NDArray[np.float32]")]
+    pub fn numpy(&mut self, py: Python<'_>) -> Py<PyArray2<f32>> {
+        self.inner.0.to_dmatrix().to_pyarray_bound(py).into()
+    }
+}
@@ -0,0 +1,104 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::{ClipPlane, NoClip};
+use nalgebra as na;
+use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+#[pyclass(name = "ClipPlane", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyClipPlane {
+    pub inner: ClipPlane,
+}
+#[pymethods]
+impl PyClipPlane {
+    /// Plane that cuts away everything on the side opposite to its normal
+    #[new]
+    #[pyo3(signature = (point, normal, enabled=None, cap_color=None))]
+    #[pyo3(
+        text_signature = "(point: NDArray[np.float32], normal: NDArray[np.float32], enabled: Optional[bool] = None, cap_color: Optional[NDArray[np.float32]] = None) -> ClipPlane"
+    )]
+    pub fn new(
+        point: PyArrayLike1<'_, f32, AllowTypeChange>,
+        normal: PyArrayLike1<'_, f32, AllowTypeChange>,
+        enabled: Option<bool>,
+        cap_color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+    ) -> Self {
+        assert_eq!(point.len(), 3, "point should have 3 components");
+        assert_eq!(normal.len(), 3, "normal should have 3 components");
+        let mut clip_plane = ClipPlane::new(
+            na::Point3::<f32>::from_slice(&point.to_vec().unwrap()),
+            na::Vector3::<f32>::from_vec(normal.to_vec().unwrap()),
+        );
+        clip_plane.enabled = enabled.unwrap_or(true);
+        //the cut is only capped if a color for it is given
+        if let Some(cap_color) = cap_color {
+            assert_eq!(cap_color.len(), 4, "cap_color should have 4 components");
+            clip_plane = clip_plane.with_cap(na::Vector4::<f32>::from_vec(cap_color.to_vec().unwrap()));
+        }
+        PyClipPlane { inner: clip_plane }
+    }
+    #[getter]
+    pub fn get_point(&self) -> (f32, f32, f32) {
+        (self.inner.point.x, self.inner.point.y, self.inner.point.z)
+    }
+    #[setter]
+    pub fn set_point(&mut self, point: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(point.len(), 3, "point should have 3 components");
+        self.inner.point = na::Point3::<f32>::from_slice(&point.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_normal(&self) -> (f32, f32, f32) {
+        (self.inner.normal.x, self.inner.normal.y, self.inner.normal.z)
+    }
+    #[setter]
+    pub fn set_normal(&mut self, normal: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(normal.len(), 3, "normal should have 3 components");
+        self.inner.normal = na::Vector3::<f32>::from_vec(normal.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_enabled(&self) -> bool {
+        self.inner.enabled
+    }
+    #[setter]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.inner.enabled = enabled;
+    }
+    #[getter]
+    pub fn get_cap(&self) -> bool {
+        self.inner.cap
+    }
+    #[setter]
+    pub fn set_cap(&mut self, cap: bool) {
+        self.inner.cap = cap;
+    }
+    #[getter]
+    pub fn get_cap_color(&self) -> (f32, f32, f32, f32) {
+        let c = self.inner.cap_color;
+        (c.x, c.y, c.z, c.w)
+    }
+    #[setter]
+    pub fn set_cap_color(&mut self, cap_color: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(cap_color.len(), 4, "cap_color should have 4 components");
+        self.inner.cap_color = na::Vector4::<f32>::from_vec(cap_color.to_vec().unwrap());
+    }
+}
+
+#[pyclass(name = "NoClip", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyNoClip {
+    pub inner: NoClip,
+}
+#[pymethods]
+#[allow(clippy::new_without_default)]
+impl PyNoClip {
+    /// Entities with this component are not cut by any clip plane
+    #[new]
+    #[pyo3(text_signature = "() -> NoClip")]
+    pub fn new() -> Self {
+        PyNoClip { inner: NoClip }
+    }
+}
@@ -1,9 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{
-    components::{DiffuseImg, ImgConfig},
-    scene::Scene,
-};
+use gloss_renderer::components::{DiffuseImg, ImgConfig};
 use pyo3::prelude::*;
 
 #[pyclass(name = "DiffuseImg", module = "gloss.components", unsendable)]
@@ -1,6 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{components::Edges, scene::Scene};
+use gloss_renderer::components::Edges;
 use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorInt2D};
 use numpy::{PyArray2, PyReadonlyArray2, PyUntypedArrayMethods, ToPyArray};
 use pyo3::prelude::*;
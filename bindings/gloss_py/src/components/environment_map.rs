@@ -0,0 +1,38 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::EnvironmentMap;
+use pyo3::prelude::*;
+
+#[pyclass(name = "EnvironmentMap", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyEnvironmentMap {
+    pub inner: EnvironmentMap,
+}
+#[pymethods]
+impl PyEnvironmentMap {
+    #[new]
+    #[pyo3(text_signature = "(diffuse_path: str, specular_path: str) -> EnvironmentMap")]
+    pub fn new(diffuse_path: &str, specular_path: &str) -> Self {
+        Self {
+            inner: EnvironmentMap::new_from_path(diffuse_path, specular_path),
+        }
+    }
+    #[getter]
+    pub fn get_diffuse_path(&self) -> String {
+        self.inner.diffuse_path.clone()
+    }
+    #[setter]
+    pub fn set_diffuse_path(&mut self, diffuse_path: String) {
+        self.inner.diffuse_path = diffuse_path;
+    }
+    #[getter]
+    pub fn get_specular_path(&self) -> String {
+        self.inner.specular_path.clone()
+    }
+    #[setter]
+    pub fn set_specular_path(&mut self, specular_path: String) {
+        self.inner.specular_path = specular_path;
+    }
+}
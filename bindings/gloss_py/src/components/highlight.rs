@@ -0,0 +1,51 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::Highlight;
+use nalgebra as na;
+use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+#[pyclass(name = "Highlight", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyHighlight {
+    pub inner: Highlight,
+}
+#[pymethods]
+impl PyHighlight {
+    /// Draws an outline of the given color and width in pixels around the
+    /// entity
+    #[new]
+    #[pyo3(signature = (color=None, width=None))]
+    #[pyo3(text_signature = "(color: Optional[NDArray[np.float32]] = None, width: Optional[float] = None) -> Highlight")]
+    pub fn new(color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>, width: Option<f32>) -> Self {
+        let mut highlight = Highlight::default();
+        if let Some(color) = color {
+            assert_eq!(color.len(), 4, "color should have 4 components");
+            highlight.color = na::Vector4::<f32>::from_vec(color.to_vec().unwrap());
+        }
+        if let Some(width) = width {
+            highlight.width = width;
+        }
+        PyHighlight { inner: highlight }
+    }
+    #[getter]
+    pub fn get_color(&self) -> (f32, f32, f32, f32) {
+        let c = self.inner.color;
+        (c.x, c.y, c.z, c.w)
+    }
+    #[setter]
+    pub fn set_color(&mut self, color: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(color.len(), 4, "color should have 4 components");
+        self.inner.color = na::Vector4::<f32>::from_vec(color.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_width(&self) -> f32 {
+        self.inner.width
+    }
+    #[setter]
+    pub fn set_width(&mut self, width: f32) {
+        self.inner.width = width;
+    }
+}
@@ -0,0 +1,44 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::JointTransforms;
+use nalgebra as na;
+use numpy::{PyArray3, PyReadonlyArray3, PyUntypedArrayMethods, ToPyArray};
+use pyo3::prelude::*;
+
+#[pyclass(name = "JointTransforms", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyJointTransforms {
+    pub inner: JointTransforms,
+}
+#[pymethods]
+impl PyJointTransforms {
+    /// Jx4x4 array with the transform of every joint from the rest pose to the
+    /// current pose, in object coordinates
+    #[new]
+    #[pyo3(text_signature = "(array: NDArray[np.float32]) -> JointTransforms")]
+    pub fn new(array: PyReadonlyArray3<f32>) -> Self {
+        let shape = array.shape();
+        assert_eq!(
+            &shape[1..],
+            &[4, 4],
+            "JointTransforms need to be a Jx4x4 array but it has shape {shape:?}"
+        );
+        let array = array.as_array();
+        let transforms = array.outer_iter().map(|m| na::Matrix4::<f32>::from_fn(|r, c| m[[r, c]])).collect();
+        Self {
+            inner: JointTransforms(transforms),
+        }
+    }
+    #[pyo3(text_signature = "($self) -> NDArray[np.float32]")]
+    pub fn numpy(&mut self, py: Python<'_>) -> Py<PyArray3<f32>> {
+        let nr_joints = self.inner.0.len();
+        //numpy is row major so every matrix is written transposed relative to the column major storage of nalgebra
+        let data: Vec<f32> = self.inner.0.iter().flat_map(|m| m.transpose().as_slice().to_vec()).collect();
+        numpy::ndarray::Array3::from_shape_vec((nr_joints, 4, 4), data)
+            .unwrap()
+            .to_pyarray_bound(py)
+            .into()
+    }
+}
@@ -0,0 +1,99 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::LightEmit;
+use nalgebra as na;
+use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+#[pyclass(name = "LightEmit", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyLightEmit {
+    pub inner: LightEmit,
+}
+#[pymethods]
+impl PyLightEmit {
+    #[new]
+    #[pyo3(signature = (color=None, intensity=None, range=None, radius=None, outer_angle=None, inner_angle=None))]
+    #[pyo3(
+        text_signature = "(color: Optional[NDArray[np.float32]] = None, intensity: Optional[float] = None, range: Optional[float] = None, radius: Optional[float] = None, outer_angle: Optional[float] = None, inner_angle: Optional[float] = None) -> LightEmit"
+    )]
+    pub fn new(
+        color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+        intensity: Option<f32>,
+        range: Option<f32>,
+        radius: Option<f32>,
+        outer_angle: Option<f32>,
+        inner_angle: Option<f32>,
+    ) -> Self {
+        let def = LightEmit::default();
+
+        let color = if let Some(color) = color {
+            assert_eq!(color.len(), 3, "color should have 3 components");
+            na::Vector3::<f32>::from_vec(color.to_vec().unwrap())
+        } else {
+            def.color
+        };
+
+        let light_emit = LightEmit {
+            color,
+            intensity: intensity.unwrap_or(def.intensity),
+            range: range.unwrap_or(def.range),
+            radius: radius.unwrap_or(def.radius),
+            outer_angle: outer_angle.unwrap_or(def.outer_angle),
+            inner_angle: inner_angle.unwrap_or(def.inner_angle),
+        };
+
+        PyLightEmit { inner: light_emit }
+    }
+    #[getter]
+    pub fn get_color(&self) -> (f32, f32, f32) {
+        (self.inner.color.x, self.inner.color.y, self.inner.color.z)
+    }
+    #[setter]
+    pub fn set_color(&mut self, color: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(color.len(), 3, "color should have 3 components");
+        self.inner.color = na::Vector3::<f32>::from_vec(color.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_intensity(&self) -> f32 {
+        self.inner.intensity
+    }
+    #[setter]
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.inner.intensity = intensity;
+    }
+    #[getter]
+    pub fn get_range(&self) -> f32 {
+        self.inner.range
+    }
+    #[setter]
+    pub fn set_range(&mut self, range: f32) {
+        self.inner.range = range;
+    }
+    #[getter]
+    pub fn get_radius(&self) -> f32 {
+        self.inner.radius
+    }
+    #[setter]
+    pub fn set_radius(&mut self, radius: f32) {
+        self.inner.radius = radius;
+    }
+    #[getter]
+    pub fn get_outer_angle(&self) -> f32 {
+        self.inner.outer_angle
+    }
+    #[setter]
+    pub fn set_outer_angle(&mut self, outer_angle: f32) {
+        self.inner.outer_angle = outer_angle;
+    }
+    #[getter]
+    pub fn get_inner_angle(&self) -> f32 {
+        self.inner.inner_angle
+    }
+    #[setter]
+    pub fn set_inner_angle(&mut self, inner_angle: f32) {
+        self.inner.inner_angle = inner_angle;
+    }
+}
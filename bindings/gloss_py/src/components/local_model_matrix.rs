@@ -0,0 +1,29 @@
+use crate::{components::model_matrix::PyModelMatrix, handle::SceneHandle};
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::LocalModelMatrix;
+use numpy::{PyArray2, ToPyArray};
+use pyo3::prelude::*;
+
+#[pyclass(name = "LocalModelMatrix", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyLocalModelMatrix {
+    pub inner: LocalModelMatrix,
+}
+#[pymethods]
+impl PyLocalModelMatrix {
+    /// Transform of an entity relative to its parent, built with the same
+    /// helpers as a `ModelMatrix`
+    #[new]
+    #[pyo3(text_signature = "(model_matrix: ModelMatrix) -> LocalModelMatrix")]
+    pub fn new(model_matrix: &PyModelMatrix) -> Self {
+        Self {
+            inner: LocalModelMatrix(model_matrix.inner.0),
+        }
+    }
+    #[pyo3(text_signature = "($self) -> NDArray[np.float32]")]
+    pub fn numpy(&mut self, py: Python<'_>) -> Py<PyArray2<f32>> {
+        self.inner.0.to_homogeneous().to_pyarray_bound(py).into()
+    }
+}
@@ -0,0 +1,97 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::sequence::MeshSequence;
+use numpy::{PyArray2, PyReadonlyArray3, PyUntypedArrayMethods, ToPyArray};
+use pyo3::{exceptions::PyIOError, prelude::*};
+use std::path::Path;
+
+#[pyclass(name = "MeshSequence", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyMeshSequence {
+    pub inner: MeshSequence,
+}
+#[pymethods]
+impl PyMeshSequence {
+    /// Sequence from an array of shape [frames, verts, 3]. The playback state
+    /// is part of the component so after changing it on a copy obtained from an
+    /// entity, the copy has to be inserted again
+    #[new]
+    #[pyo3(signature = (array, fps=None))]
+    #[pyo3(text_signature = "(array: NDArray[np.float32], fps: Optional[float] = None) -> MeshSequence")]
+    pub fn new(array: PyReadonlyArray3<f32>, fps: Option<f32>) -> Self {
+        let shape = array.shape();
+        assert_eq!(shape[2], 3, "MeshSequence needs a [frames, verts, 3] array but it has shape {shape:?}");
+        let (nr_frames, nr_verts) = (shape[0], shape[1]);
+        let data = array.as_array().iter().copied().collect();
+        let sequence = MeshSequence::from_array(data, nr_frames, nr_verts);
+        Self {
+            inner: sequence.with_fps(fps.unwrap_or(30.0)),
+        }
+    }
+    /// Sequence from all the OBJ and PLY files of a folder which are loaded
+    /// lazily when their frame is shown
+    #[staticmethod]
+    #[pyo3(signature = (path, fps=None))]
+    #[pyo3(text_signature = "(path: str, fps: Optional[float] = None) -> MeshSequence")]
+    pub fn from_folder(path: &str, fps: Option<f32>) -> PyResult<Self> {
+        let sequence = MeshSequence::from_folder(Path::new(path)).map_err(|err| PyIOError::new_err(format!("could not read {path}: {err}")))?;
+        Ok(Self {
+            inner: sequence.with_fps(fps.unwrap_or(30.0)),
+        })
+    }
+    #[pyo3(text_signature = "($self) -> None")]
+    pub fn play(&mut self) {
+        self.inner.play();
+    }
+    #[pyo3(text_signature = "($self) -> None")]
+    pub fn pause(&mut self) {
+        self.inner.pause();
+    }
+    #[pyo3(text_signature = "($self, frame: int) -> None")]
+    pub fn set_frame(&mut self, frame: usize) {
+        self.inner.set_frame(frame);
+    }
+    #[pyo3(text_signature = "($self) -> int")]
+    pub fn current_frame(&self) -> usize {
+        self.inner.current_frame()
+    }
+    #[pyo3(text_signature = "($self) -> int")]
+    pub fn nr_frames(&self) -> usize {
+        self.inner.nr_frames()
+    }
+    /// Vertices of a frame as a Nx3 array, reading it from disk if needed
+    #[pyo3(text_signature = "($self, frame: int) -> Optional[NDArray[np.float32]]")]
+    pub fn frame_verts(&self, py: Python<'_>, frame: usize) -> Option<Py<PyArray2<f32>>> {
+        self.inner.frame_verts(frame).map(|v| v.to_pyarray_bound(py).into())
+    }
+    #[getter]
+    pub fn get_fps(&self) -> f32 {
+        self.inner.fps
+    }
+    #[setter]
+    pub fn set_fps(&mut self, fps: f32) {
+        self.inner.fps = fps;
+    }
+    #[getter]
+    pub fn get_speed(&self) -> f32 {
+        self.inner.speed
+    }
+    #[setter]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.inner.speed = speed;
+    }
+    #[getter]
+    pub fn get_playing(&self) -> bool {
+        self.inner.playing
+    }
+    #[getter]
+    pub fn get_looping(&self) -> bool {
+        self.inner.looping
+    }
+    #[setter]
+    pub fn set_looping(&mut self, looping: bool) {
+        self.inner.looping = looping;
+    }
+}
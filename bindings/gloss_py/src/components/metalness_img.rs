@@ -0,0 +1,26 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::{ImgConfig, MetalnessImg};
+use pyo3::prelude::*;
+
+#[pyclass(name = "MetalnessImg", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyMetalnessImg {
+    pub inner: MetalnessImg,
+}
+#[pymethods]
+impl PyMetalnessImg {
+    #[new]
+    #[pyo3(text_signature = "(path: str) -> MetalnessImg")]
+    pub fn new(path: &str) -> Self {
+        Self {
+            inner: MetalnessImg::new_from_path(path, &ImgConfig::default()),
+        }
+    }
+    #[getter]
+    pub fn get_path(&self) -> Option<String> {
+        self.inner.generic_img.path.clone()
+    }
+}
@@ -1,13 +1,33 @@
+pub mod blend_shape_coeffs;
+pub mod blend_shapes;
+pub mod clip_plane;
 pub mod colors;
 pub mod diffuse_img;
 pub mod edges;
+pub mod environment_map;
 pub mod faces;
+pub mod highlight;
+pub mod joint_transforms;
+pub mod light_emit;
+pub mod local_model_matrix;
+pub mod mesh_sequence;
+pub mod metalness_img;
 pub mod model_matrix;
+pub mod name;
 pub mod normal_img;
 pub mod normals;
+pub mod pos_lookat;
+pub mod projection;
+pub mod roughness_img;
+pub mod scalars;
+pub mod shadow_caster;
+pub mod skin_weights;
 pub mod tangents;
 pub mod uvs;
 pub mod verts;
 pub mod vis_edges;
 pub mod vis_mesh;
+pub mod vis_normals;
 pub mod vis_points;
+pub mod vis_scalars;
+pub mod vis_wireframe;
@@ -1,6 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{components::ModelMatrix, scene::Scene};
+use gloss_renderer::components::ModelMatrix;
 use nalgebra as na;
 use numpy::{AllowTypeChange, PyArray2, PyArrayLike1, PyArrayLike2, PyUntypedArrayMethods, ToPyArray};
 use pyo3::prelude::*;
@@ -0,0 +1,66 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_renderer::components::Name;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+//not a PyComponent since the scene keeps a map from names to entities which would go out of sync if the name was inserted or removed directly
+#[pyclass(name = "Name", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone)]
+pub struct PyName {
+    pub inner: Name,
+}
+#[pymethods]
+impl PyName {
+    #[new]
+    #[pyo3(text_signature = "(name: str) -> Name")]
+    pub fn new(name: &str) -> Self {
+        Self {
+            inner: Name(name.to_string()),
+        }
+    }
+    //no setter for the same reason
+    #[getter]
+    pub fn get_name(&self) -> String {
+        self.inner.0.clone()
+    }
+    pub fn __str__(&self) -> String {
+        self.inner.0.clone()
+    }
+    /// # Errors
+    /// Always returns an error since names can only be given at creation
+    #[allow(clippy::unused_self)]
+    pub fn insert_to_entity(&self, _entity_bits: u64, _scene: PyRefMut<'_, SceneHandle>) -> PyResult<()> {
+        Err(PyValueError::new_err(
+            "the name of an entity is given when creating it with get_or_create_entity",
+        ))
+    }
+    #[staticmethod]
+    /// # Errors
+    /// Will return a `StaleHandleError` if the entity was despawned
+    pub fn get(entity_bits: u64, scene: PyRef<'_, SceneHandle>) -> PyResult<Self> {
+        let entity = Entity::from_bits(entity_bits).unwrap();
+        scene.check_entity(entity)?;
+        let comp = scene.scene().get_comp::<&Name>(&entity).unwrap();
+        Ok(Self { inner: (*comp).clone() })
+    }
+    #[staticmethod]
+    /// # Errors
+    /// Will return a `StaleHandleError` if the entity was despawned
+    pub fn exists(entity_bits: u64, scene: PyRef<'_, SceneHandle>) -> PyResult<bool> {
+        let entity = Entity::from_bits(entity_bits).unwrap();
+        scene.check_entity(entity)?;
+        Ok(scene.scene().world.has::<Name>(entity).unwrap())
+    }
+    #[staticmethod]
+    /// # Errors
+    /// Always returns an error since every entity needs a name
+    pub fn remove(_entity_bits: u64, _scene: PyRefMut<'_, SceneHandle>) -> PyResult<()> {
+        Err(PyValueError::new_err("every entity needs a name, use scene.remove_entity to despawn it"))
+    }
+    #[staticmethod]
+    pub fn query_entities(scene: PyRef<'_, SceneHandle>) -> Vec<u64> {
+        let mut query = scene.scene().world.query::<&Name>();
+        query.iter().map(|(entity, _)| entity.to_bits().get()).collect()
+    }
+}
@@ -1,9 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{
-    components::{ImgConfig, NormalImg},
-    scene::Scene,
-};
+use gloss_renderer::components::{ImgConfig, NormalImg};
 use pyo3::prelude::*;
 
 #[pyclass(name = "NormalImg", module = "gloss.components", unsendable)]
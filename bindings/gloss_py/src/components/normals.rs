@@ -1,6 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{components::Normals, scene::Scene};
+use gloss_renderer::components::Normals;
 use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
 use numpy::{PyArray2, PyReadonlyArray2, PyUntypedArrayMethods, ToPyArray};
 use pyo3::prelude::*;
@@ -0,0 +1,72 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::PosLookat;
+use nalgebra as na;
+use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+#[pyclass(name = "PosLookat", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyPosLookat {
+    pub inner: PosLookat,
+}
+#[pymethods]
+impl PyPosLookat {
+    #[new]
+    #[pyo3(signature = (position=None, lookat=None, up=None))]
+    #[pyo3(
+        text_signature = "(position: Optional[NDArray[np.float32]] = None, lookat: Optional[NDArray[np.float32]] = None, up: Optional[NDArray[np.float32]] = None) -> PosLookat"
+    )]
+    pub fn new(
+        position: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+        lookat: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+        up: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+    ) -> Self {
+        let mut pos_lookat = PosLookat::default();
+        if let Some(position) = position {
+            assert_eq!(position.len(), 3, "position should have 3 components");
+            pos_lookat.position = na::Point3::<f32>::from_slice(&position.to_vec().unwrap());
+        }
+        if let Some(lookat) = lookat {
+            assert_eq!(lookat.len(), 3, "lookat should have 3 components");
+            pos_lookat.lookat = na::Point3::<f32>::from_slice(&lookat.to_vec().unwrap());
+        }
+        if let Some(up) = up {
+            assert_eq!(up.len(), 3, "up should have 3 components");
+            pos_lookat.up = na::Vector3::<f32>::from_vec(up.to_vec().unwrap());
+        }
+        PyPosLookat { inner: pos_lookat }
+    }
+    #[getter]
+    pub fn get_position(&self) -> (f32, f32, f32) {
+        let position = self.inner.position;
+        (position.x, position.y, position.z)
+    }
+    #[setter]
+    pub fn set_position(&mut self, position: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(position.len(), 3, "position should have 3 components");
+        self.inner.position = na::Point3::<f32>::from_slice(&position.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_lookat(&self) -> (f32, f32, f32) {
+        let lookat = self.inner.lookat;
+        (lookat.x, lookat.y, lookat.z)
+    }
+    #[setter]
+    pub fn set_lookat(&mut self, lookat: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(lookat.len(), 3, "lookat should have 3 components");
+        self.inner.lookat = na::Point3::<f32>::from_slice(&lookat.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_up(&self) -> (f32, f32, f32) {
+        let up = self.inner.up;
+        (up.x, up.y, up.z)
+    }
+    #[setter]
+    pub fn set_up(&mut self, up: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(up.len(), 3, "up should have 3 components");
+        self.inner.up = na::Vector3::<f32>::from_vec(up.to_vec().unwrap());
+    }
+}
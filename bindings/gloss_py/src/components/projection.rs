@@ -0,0 +1,161 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::{Projection, ProjectionOrthographic, ProjectionWithFov, ProjectionWithIntrinsics};
+use pyo3::{exceptions::PyAttributeError, prelude::*};
+
+#[pyclass(name = "Projection", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyProjection {
+    pub inner: Projection,
+}
+//fields that only some of the projection types have will raise an AttributeError on the others
+fn not_in_variant(field: &str) -> PyErr {
+    PyAttributeError::new_err(format!("Projection of this type has no field {field}"))
+}
+#[pymethods]
+impl PyProjection {
+    #[staticmethod]
+    #[allow(clippy::should_implement_trait)]
+    #[pyo3(text_signature = "() -> Projection")]
+    pub fn default() -> Self {
+        Self {
+            inner: Projection::default(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (fovy=None, aspect_ratio=None, near=None, far=None))]
+    #[pyo3(
+        text_signature = "(fovy: Optional[float] = None, aspect_ratio: Optional[float] = None, near: Optional[float] = None, far: Optional[float] = None) -> Projection"
+    )]
+    pub fn with_fov(fovy: Option<f32>, aspect_ratio: Option<f32>, near: Option<f32>, far: Option<f32>) -> Self {
+        let def = ProjectionWithFov::default();
+        let proj = ProjectionWithFov {
+            aspect_ratio: aspect_ratio.unwrap_or(def.aspect_ratio),
+            fovy: fovy.unwrap_or(def.fovy),
+            near: near.unwrap_or(def.near),
+            far: far.unwrap_or(def.far),
+        };
+        Self {
+            inner: Projection::WithFov(proj),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (fx, fy, cx, cy, near=None, far=None))]
+    #[pyo3(text_signature = "(fx: float, fy: float, cx: float, cy: float, near: Optional[float] = None, far: Optional[float] = None) -> Projection")]
+    pub fn with_intrinsics(fx: f32, fy: f32, cx: f32, cy: f32, near: Option<f32>, far: Option<f32>) -> Self {
+        let def = ProjectionWithFov::default();
+        let proj = ProjectionWithIntrinsics {
+            fx,
+            fy,
+            cx,
+            cy,
+            near: near.unwrap_or(def.near),
+            far: far.unwrap_or(def.far),
+            distortion: None,
+        };
+        Self {
+            inner: Projection::WithIntrinsics(proj),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (height=None, aspect_ratio=None, near=None, far=None))]
+    #[pyo3(
+        text_signature = "(height: Optional[float] = None, aspect_ratio: Optional[float] = None, near: Optional[float] = None, far: Optional[float] = None) -> Projection"
+    )]
+    pub fn orthographic(height: Option<f32>, aspect_ratio: Option<f32>, near: Option<f32>, far: Option<f32>) -> Self {
+        let def = ProjectionOrthographic::default();
+        let proj = ProjectionOrthographic {
+            aspect_ratio: aspect_ratio.unwrap_or(def.aspect_ratio),
+            height: height.unwrap_or(def.height),
+            near: near.unwrap_or(def.near),
+            far: far.unwrap_or(def.far),
+        };
+        Self {
+            inner: Projection::Orthographic(proj),
+        }
+    }
+    #[getter]
+    pub fn get_is_orthographic(&self) -> bool {
+        self.inner.is_orthographic()
+    }
+    #[getter]
+    pub fn get_near(&self) -> f32 {
+        self.inner.near_far().0
+    }
+    #[setter]
+    pub fn set_near(&mut self, near: f32) {
+        self.inner.set_near(near);
+    }
+    #[getter]
+    pub fn get_far(&self) -> f32 {
+        self.inner.near_far().1
+    }
+    #[setter]
+    pub fn set_far(&mut self, far: f32) {
+        self.inner.set_far(far);
+    }
+    #[getter]
+    pub fn get_fovy(&self) -> PyResult<f32> {
+        match &self.inner {
+            Projection::WithFov(proj) => Ok(proj.fovy),
+            _ => Err(not_in_variant("fovy")),
+        }
+    }
+    #[setter]
+    pub fn set_fovy(&mut self, fovy: f32) -> PyResult<()> {
+        match &mut self.inner {
+            Projection::WithFov(proj) => proj.fovy = fovy,
+            _ => return Err(not_in_variant("fovy")),
+        }
+        Ok(())
+    }
+    #[getter]
+    pub fn get_aspect_ratio(&self) -> PyResult<f32> {
+        match &self.inner {
+            Projection::WithFov(proj) => Ok(proj.aspect_ratio),
+            Projection::Orthographic(proj) => Ok(proj.aspect_ratio),
+            Projection::WithIntrinsics(_) => Err(not_in_variant("aspect_ratio")),
+        }
+    }
+    #[setter]
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) -> PyResult<()> {
+        match &mut self.inner {
+            Projection::WithFov(proj) => proj.aspect_ratio = aspect_ratio,
+            Projection::Orthographic(proj) => proj.aspect_ratio = aspect_ratio,
+            Projection::WithIntrinsics(_) => return Err(not_in_variant("aspect_ratio")),
+        }
+        Ok(())
+    }
+    #[getter]
+    pub fn get_height(&self) -> PyResult<f32> {
+        match &self.inner {
+            Projection::Orthographic(proj) => Ok(proj.height),
+            _ => Err(not_in_variant("height")),
+        }
+    }
+    #[setter]
+    pub fn set_height(&mut self, height: f32) -> PyResult<()> {
+        match &mut self.inner {
+            Projection::Orthographic(proj) => proj.height = height,
+            _ => return Err(not_in_variant("height")),
+        }
+        Ok(())
+    }
+    #[getter]
+    pub fn get_intrinsics(&self) -> PyResult<(f32, f32, f32, f32)> {
+        match &self.inner {
+            Projection::WithIntrinsics(proj) => Ok((proj.fx, proj.fy, proj.cx, proj.cy)),
+            _ => Err(not_in_variant("intrinsics")),
+        }
+    }
+    #[setter]
+    pub fn set_intrinsics(&mut self, intrinsics: (f32, f32, f32, f32)) -> PyResult<()> {
+        match &mut self.inner {
+            Projection::WithIntrinsics(proj) => (proj.fx, proj.fy, proj.cx, proj.cy) = intrinsics,
+            _ => return Err(not_in_variant("intrinsics")),
+        }
+        Ok(())
+    }
+}
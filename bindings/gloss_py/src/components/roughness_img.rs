@@ -0,0 +1,26 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::{ImgConfig, RoughnessImg};
+use pyo3::prelude::*;
+
+#[pyclass(name = "RoughnessImg", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyRoughnessImg {
+    pub inner: RoughnessImg,
+}
+#[pymethods]
+impl PyRoughnessImg {
+    #[new]
+    #[pyo3(text_signature = "(path: str) -> RoughnessImg")]
+    pub fn new(path: &str) -> Self {
+        Self {
+            inner: RoughnessImg::new_from_path(path, &ImgConfig::default()),
+        }
+    }
+    #[getter]
+    pub fn get_path(&self) -> Option<String> {
+        self.inner.generic_img.path.clone()
+    }
+}
@@ -0,0 +1,34 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::Scalars;
+use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
+use nalgebra as na;
+use numpy::{PyArray1, PyReadonlyArray1, ToPyArray};
+use pyo3::prelude::*;
+
+#[pyclass(name = "Scalars", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyScalars {
+    pub inner: Scalars,
+}
+#[pymethods]
+impl PyScalars {
+    /// One scalar per vertex which is drawn through the colormap of
+    /// `VisScalars`
+    #[new]
+    #[pyo3(text_signature = "(array: NDArray[np.float32]) -> Scalars")]
+    pub fn new(array: PyReadonlyArray1<f32>) -> Self {
+        let values = array.as_array().to_vec();
+        let mat = na::DMatrix::<f32>::from_column_slice(values.len(), 1, &values);
+        PyScalars {
+            inner: Scalars(DynamicTensorFloat2D::from_dmatrix(&mat)),
+        }
+    }
+    #[pyo3(text_signature = "($self) -> NDArray[np.float32]")]
+    pub fn numpy(&mut self, py: Python<'_>) -> Py<PyArray1<f32>> {
+        //the scalars are a Nx1 matrix so the column major storage is already the flat array
+        self.inner.0.to_dmatrix().as_slice().to_pyarray_bound(py).into()
+    }
+}
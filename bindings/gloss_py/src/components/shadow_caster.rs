@@ -0,0 +1,64 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::ShadowCaster;
+use pyo3::prelude::*;
+
+#[pyclass(name = "ShadowCaster", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyShadowCaster {
+    pub inner: ShadowCaster,
+}
+#[pymethods]
+impl PyShadowCaster {
+    #[new]
+    #[pyo3(signature = (shadow_res=None, shadow_bias_fixed=None, shadow_bias=None, shadow_bias_normal=None))]
+    #[pyo3(
+        text_signature = "(shadow_res: Optional[int] = None, shadow_bias_fixed: Optional[float] = None, shadow_bias: Optional[float] = None, shadow_bias_normal: Optional[float] = None) -> ShadowCaster"
+    )]
+    pub fn new(shadow_res: Option<u32>, shadow_bias_fixed: Option<f32>, shadow_bias: Option<f32>, shadow_bias_normal: Option<f32>) -> Self {
+        let def = ShadowCaster::default();
+
+        let shadow_caster = ShadowCaster {
+            shadow_res: shadow_res.unwrap_or(def.shadow_res),
+            shadow_bias_fixed: shadow_bias_fixed.unwrap_or(def.shadow_bias_fixed),
+            shadow_bias: shadow_bias.unwrap_or(def.shadow_bias),
+            shadow_bias_normal: shadow_bias_normal.unwrap_or(def.shadow_bias_normal),
+        };
+
+        PyShadowCaster { inner: shadow_caster }
+    }
+    #[getter]
+    pub fn get_shadow_res(&self) -> u32 {
+        self.inner.shadow_res
+    }
+    #[setter]
+    pub fn set_shadow_res(&mut self, shadow_res: u32) {
+        self.inner.shadow_res = shadow_res;
+    }
+    #[getter]
+    pub fn get_shadow_bias_fixed(&self) -> f32 {
+        self.inner.shadow_bias_fixed
+    }
+    #[setter]
+    pub fn set_shadow_bias_fixed(&mut self, shadow_bias_fixed: f32) {
+        self.inner.shadow_bias_fixed = shadow_bias_fixed;
+    }
+    #[getter]
+    pub fn get_shadow_bias(&self) -> f32 {
+        self.inner.shadow_bias
+    }
+    #[setter]
+    pub fn set_shadow_bias(&mut self, shadow_bias: f32) {
+        self.inner.shadow_bias = shadow_bias;
+    }
+    #[getter]
+    pub fn get_shadow_bias_normal(&self) -> f32 {
+        self.inner.shadow_bias_normal
+    }
+    #[setter]
+    pub fn set_shadow_bias_normal(&mut self, shadow_bias_normal: f32) {
+        self.inner.shadow_bias_normal = shadow_bias_normal;
+    }
+}
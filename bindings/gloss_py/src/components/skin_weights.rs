@@ -0,0 +1,52 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::{SkinWeights, MAX_JOINT_INFLUENCES};
+use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D, DynamicTensorInt2D};
+use numpy::{PyArray2, PyReadonlyArray2, PyUntypedArrayMethods, ToPyArray};
+use pyo3::prelude::*;
+
+#[pyclass(name = "SkinWeights", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PySkinWeights {
+    pub inner: SkinWeights,
+}
+#[pymethods]
+impl PySkinWeights {
+    /// Indices and weights of the joints influencing every vertex, both as Nx4
+    /// matrices
+    #[new]
+    #[pyo3(text_signature = "(joints: NDArray[np.uint32], weights: NDArray[np.float32]) -> SkinWeights")]
+    pub fn new(joints: PyReadonlyArray2<u32>, weights: PyReadonlyArray2<f32>) -> Self {
+        assert_eq!(
+            joints.shape()[1],
+            MAX_JOINT_INFLUENCES,
+            "joints need to be a Nx{MAX_JOINT_INFLUENCES} matrix"
+        );
+        assert_eq!(joints.shape(), weights.shape(), "joints and weights need to have the same shape");
+        Self {
+            inner: SkinWeights {
+                joints: DynamicTensorInt2D::from_dmatrix(&joints.as_matrix().into()),
+                weights: DynamicTensorFloat2D::from_dmatrix(&weights.as_matrix().into()),
+            },
+        }
+    }
+    /// Keeps the largest weights of a dense NxJ matrix with one column per
+    /// joint, like the SMPL skinning weights
+    #[staticmethod]
+    #[pyo3(text_signature = "(weights: NDArray[np.float32]) -> SkinWeights")]
+    pub fn from_dense(weights: PyReadonlyArray2<f32>) -> Self {
+        Self {
+            inner: SkinWeights::from_dense(&weights.as_matrix().into()),
+        }
+    }
+    #[pyo3(text_signature = "($self) -> NDArray[np.uint32]")]
+    pub fn joints(&mut self, py: Python<'_>) -> Py<PyArray2<u32>> {
+        self.inner.joints.to_dmatrix().to_pyarray_bound(py).into()
+    }
+    #[pyo3(text_signature = "($self) -> NDArray[np.float32]")]
+    pub fn weights(&mut self, py: Python<'_>) -> Py<PyArray2<f32>> {
+        self.inner.weights.to_dmatrix().to_pyarray_bound(py).into()
+    }
+}
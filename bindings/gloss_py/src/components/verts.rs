@@ -1,6 +1,7 @@
+use crate::{handle::SceneHandle, tensor_view::float2d_to_numpy};
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{components::Verts, scene::Scene};
+use gloss_renderer::components::Verts;
 use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
 use numpy::{PyArray2, PyReadonlyArray2, PyUntypedArrayMethods, ToPyArray};
 use pyo3::prelude::*;
@@ -26,4 +27,13 @@ impl PyVerts {
     pub fn numpy(&mut self, py: Python<'_>) -> Py<PyArray2<f32>> {
         self.inner.0.to_dmatrix().to_pyarray_bound(py).into()
     }
+    /// Same as numpy() but without copying the data when the tensor lives on
+    /// the `NdArray` backend. The returned array is read-only and stays
+    /// valid even after the Verts is removed from the entity
+    #[pyo3(text_signature = "($self) -> NDArray[np.float32]")]
+    pub fn numpy_view(slf: Bound<'_, Self>) -> Py<PyArray2<f32>> {
+        let py = slf.py();
+        let tensor = slf.borrow().inner.0.clone();
+        float2d_to_numpy(py, &tensor, slf.into_any()).into()
+    }
 }
@@ -1,9 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{
-    components::{LineColorType, VisLines},
-    scene::Scene,
-};
+use gloss_renderer::components::{LineColorType, VisLines};
 use gloss_utils::convert_enum_from;
 use nalgebra as na;
 use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
@@ -14,10 +12,11 @@ use pyo3::prelude::*;
 pub enum PyLineColorType {
     Solid = 0,
     PerVert,
+    Scalar,
 }
 
 // https://stackoverflow.com/questions/59984712/rust-macro-to-convert-between-identical-enums
-convert_enum_from!(PyLineColorType, LineColorType, Solid, PerVert,);
+convert_enum_from!(PyLineColorType, LineColorType, Solid, PerVert, Scalar,);
 
 #[pyclass(name = "VisLines", module = "gloss.components", unsendable)]
 // it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
@@ -1,9 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{
-    components::{MeshColorType, VisMesh},
-    scene::Scene,
-};
+use gloss_renderer::components::{MeshColorType, VisMesh};
 use gloss_utils::convert_enum_from;
 use nalgebra as na;
 use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
@@ -18,10 +16,21 @@ pub enum PyMeshColorType {
     UV,
     Normal,
     NormalViewCoords,
+    Scalar,
 }
 
 // https://stackoverflow.com/questions/59984712/rust-macro-to-convert-between-identical-enums
-convert_enum_from!(PyMeshColorType, MeshColorType, Solid, PerVert, Texture, UV, Normal, NormalViewCoords,);
+convert_enum_from!(
+    PyMeshColorType,
+    MeshColorType,
+    Solid,
+    PerVert,
+    Texture,
+    UV,
+    Normal,
+    NormalViewCoords,
+    Scalar,
+);
 
 #[pyclass(name = "VisMesh", module = "gloss.components", unsendable)]
 // it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
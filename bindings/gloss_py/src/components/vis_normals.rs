@@ -0,0 +1,81 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::VisNormals;
+use nalgebra as na;
+use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+#[pyclass(name = "VisNormals", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyVisNormals {
+    pub inner: VisNormals,
+}
+#[pymethods]
+impl PyVisNormals {
+    #[new]
+    #[pyo3(signature = (show_normals=None, normals_color=None, normals_width=None, normals_scale=None))]
+    #[pyo3(
+        text_signature = "(show_normals: Optional[bool] = None, normals_color: Optional[NDArray[np.float32]] = None, normals_width: Optional[float] = None, normals_scale: Optional[float] = None) -> VisNormals"
+    )]
+    pub fn new(
+        show_normals: Option<bool>,
+        normals_color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+        normals_width: Option<f32>,
+        normals_scale: Option<f32>,
+    ) -> Self {
+        let def = VisNormals::default();
+
+        let normals_color = if let Some(normals_color) = normals_color {
+            assert_eq!(normals_color.len(), 4, "normals_color should have 4 components");
+            na::Vector4::<f32>::from_vec(normals_color.to_vec().unwrap())
+        } else {
+            def.normals_color
+        };
+
+        let vis_normals = VisNormals {
+            show_normals: show_normals.unwrap_or(def.show_normals),
+            normals_color,
+            normals_width: normals_width.unwrap_or(def.normals_width),
+            normals_scale: normals_scale.unwrap_or(def.normals_scale),
+            ..Default::default()
+        };
+
+        PyVisNormals { inner: vis_normals }
+    }
+    #[getter]
+    pub fn get_show_normals(&self) -> bool {
+        self.inner.show_normals
+    }
+    #[setter]
+    pub fn set_show_normals(&mut self, show_normals: bool) {
+        self.inner.show_normals = show_normals;
+    }
+    #[getter]
+    pub fn get_normals_color(&self) -> (f32, f32, f32, f32) {
+        let c = self.inner.normals_color;
+        (c.x, c.y, c.z, c.w)
+    }
+    #[setter]
+    pub fn set_normals_color(&mut self, normals_color: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(normals_color.len(), 4, "normals_color should have 4 components");
+        self.inner.normals_color = na::Vector4::<f32>::from_vec(normals_color.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_normals_width(&self) -> f32 {
+        self.inner.normals_width
+    }
+    #[setter]
+    pub fn set_normals_width(&mut self, normals_width: f32) {
+        self.inner.normals_width = normals_width;
+    }
+    #[getter]
+    pub fn get_normals_scale(&self) -> f32 {
+        self.inner.normals_scale
+    }
+    #[setter]
+    pub fn set_normals_scale(&mut self, normals_scale: f32) {
+        self.inner.normals_scale = normals_scale;
+    }
+}
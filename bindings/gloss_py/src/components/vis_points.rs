@@ -1,9 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::{
-    components::{PointColorType, VisPoints},
-    scene::Scene,
-};
+use gloss_renderer::components::{PointColorType, PointRenderMode, VisPoints};
 use gloss_utils::convert_enum_from;
 use nalgebra as na;
 use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
@@ -14,10 +12,19 @@ use pyo3::prelude::*;
 pub enum PyPointColorType {
     Solid = 0,
     PerVert,
+    Scalar,
 }
 
 // https://stackoverflow.com/questions/59984712/rust-macro-to-convert-between-identical-enums
-convert_enum_from!(PyPointColorType, PointColorType, Solid, PerVert,);
+convert_enum_from!(PyPointColorType, PointColorType, Solid, PerVert, Scalar,);
+
+#[pyclass(name = "PointRenderMode", module = "gloss.types", unsendable, eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PyPointRenderMode {
+    Sprite = 0,
+    Surfel,
+}
+convert_enum_from!(PyPointRenderMode, PointRenderMode, Sprite, Surfel,);
 
 #[pyclass(name = "VisPoints", module = "gloss.components", unsendable)]
 // it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
@@ -28,10 +35,11 @@ pub struct PyVisPoints {
 #[pymethods]
 impl PyVisPoints {
     #[new]
-    #[pyo3(signature = (show_points=None, point_color=None, point_size=None, color_type=None, zbuffer=None, show_points_indices=None))]
+    #[pyo3(signature = (show_points=None, point_color=None, point_size=None, color_type=None, zbuffer=None, show_points_indices=None, render_mode=None, is_point_size_in_world_space=None))]
     #[pyo3(
-        text_signature = "(show_points: Optional[bool] = None, point_color: Optional[NDArray[np.float32]] = None, point_size: Optional[float] = None, color_type: Optional[PointColorType] = None, zbuffer: Optional[bool] = None, show_points_indices: Optional[bool] = None) -> VisPoints"
+        text_signature = "(show_points: Optional[bool] = None, point_color: Optional[NDArray[np.float32]] = None, point_size: Optional[float] = None, color_type: Optional[PointColorType] = None, zbuffer: Optional[bool] = None, show_points_indices: Optional[bool] = None, render_mode: Optional[PointRenderMode] = None, is_point_size_in_world_space: Optional[bool] = None) -> VisPoints"
     )]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         show_points: Option<bool>,
         point_color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
@@ -39,6 +47,8 @@ impl PyVisPoints {
         color_type: Option<PyPointColorType>,
         zbuffer: Option<bool>,
         show_points_indices: Option<bool>,
+        render_mode: Option<PyPointRenderMode>,
+        is_point_size_in_world_space: Option<bool>,
     ) -> Self {
         let def = VisPoints::default();
 
@@ -57,6 +67,8 @@ impl PyVisPoints {
             color_type: PointColorType::from(color_type.unwrap_or(PyPointColorType::Solid)),
             zbuffer: zbuffer.unwrap_or(def.zbuffer),
             show_points_indices: show_points_indices.unwrap_or(def.show_points_indices),
+            render_mode: render_mode.map_or(def.render_mode, PointRenderMode::from),
+            is_point_size_in_world_space: is_point_size_in_world_space.unwrap_or(def.is_point_size_in_world_space),
             ..Default::default()
         };
 
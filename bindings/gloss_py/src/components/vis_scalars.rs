@@ -0,0 +1,77 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::{Colormap, VisScalars};
+use gloss_utils::convert_enum_from;
+use nalgebra as na;
+use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+#[pyclass(name = "Colormap", module = "gloss.types", unsendable, eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PyColormap {
+    Viridis = 0,
+    Magma,
+    Coolwarm,
+    Jet,
+}
+
+// https://stackoverflow.com/questions/59984712/rust-macro-to-convert-between-identical-enums
+convert_enum_from!(PyColormap, Colormap, Viridis, Magma, Coolwarm, Jet,);
+
+#[pyclass(name = "VisScalars", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyVisScalars {
+    pub inner: VisScalars,
+}
+#[pymethods]
+impl PyVisScalars {
+    #[new]
+    #[pyo3(signature = (colormap=None, min=None, max=None, clamp=None, out_of_range_color=None, nr_isolines=None, isoline_width=None, isoline_color=None, show_colorbar=None))]
+    #[pyo3(
+        text_signature = "(colormap: Optional[Colormap] = None, min: Optional[float] = None, max: Optional[float] = None, clamp: Optional[bool] = None, out_of_range_color: Optional[NDArray[np.float32]] = None, nr_isolines: Optional[int] = None, isoline_width: Optional[float] = None, isoline_color: Optional[NDArray[np.float32]] = None, show_colorbar: Optional[bool] = None) -> VisScalars"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        colormap: Option<PyColormap>,
+        min: Option<f32>,
+        max: Option<f32>,
+        clamp: Option<bool>,
+        out_of_range_color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+        nr_isolines: Option<u32>,
+        isoline_width: Option<f32>,
+        isoline_color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>,
+        show_colorbar: Option<bool>,
+    ) -> Self {
+        let def = VisScalars::default();
+
+        let out_of_range_color = if let Some(out_of_range_color) = out_of_range_color {
+            assert_eq!(out_of_range_color.len(), 4, "out_of_range_color should have 4 components");
+            na::Vector4::<f32>::from_vec(out_of_range_color.to_vec().unwrap())
+        } else {
+            def.out_of_range_color
+        };
+        let isoline_color = if let Some(isoline_color) = isoline_color {
+            assert_eq!(isoline_color.len(), 4, "isoline_color should have 4 components");
+            na::Vector4::<f32>::from_vec(isoline_color.to_vec().unwrap())
+        } else {
+            def.isoline_color
+        };
+
+        let vis_scalars = VisScalars {
+            colormap: colormap.map_or(def.colormap, Colormap::from),
+            min: min.unwrap_or(def.min),
+            max: max.unwrap_or(def.max),
+            clamp: clamp.unwrap_or(def.clamp),
+            out_of_range_color,
+            nr_isolines: nr_isolines.unwrap_or(def.nr_isolines),
+            isoline_width: isoline_width.unwrap_or(def.isoline_width),
+            isoline_color,
+            show_colorbar: show_colorbar.unwrap_or(def.show_colorbar),
+            ..Default::default()
+        };
+
+        PyVisScalars { inner: vis_scalars }
+    }
+}
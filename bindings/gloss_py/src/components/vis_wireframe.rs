@@ -0,0 +1,67 @@
+use crate::handle::SceneHandle;
+use gloss_hecs::Entity;
+use gloss_py_macros::PyComponent;
+use gloss_renderer::components::VisWireframe;
+use nalgebra as na;
+use numpy::{AllowTypeChange, PyArrayLike1, PyArrayMethods, PyUntypedArrayMethods};
+use pyo3::prelude::*;
+
+#[pyclass(name = "VisWireframe", module = "gloss.components", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+#[derive(Clone, PyComponent)]
+pub struct PyVisWireframe {
+    pub inner: VisWireframe,
+}
+#[pymethods]
+impl PyVisWireframe {
+    #[new]
+    #[pyo3(signature = (show_wireframe=None, wire_color=None, wire_width=None))]
+    #[pyo3(
+        text_signature = "(show_wireframe: Optional[bool] = None, wire_color: Optional[NDArray[np.float32]] = None, wire_width: Optional[float] = None) -> VisWireframe"
+    )]
+    pub fn new(show_wireframe: Option<bool>, wire_color: Option<PyArrayLike1<'_, f32, AllowTypeChange>>, wire_width: Option<f32>) -> Self {
+        let def = VisWireframe::default();
+
+        let wire_color = if let Some(wire_color) = wire_color {
+            assert_eq!(wire_color.len(), 4, "wire_color should have 4 components");
+            na::Vector4::<f32>::from_vec(wire_color.to_vec().unwrap())
+        } else {
+            def.wire_color
+        };
+
+        let vis_wireframe = VisWireframe {
+            show_wireframe: show_wireframe.unwrap_or(def.show_wireframe),
+            wire_color,
+            wire_width: wire_width.unwrap_or(def.wire_width),
+            ..Default::default()
+        };
+
+        PyVisWireframe { inner: vis_wireframe }
+    }
+    #[getter]
+    pub fn get_show_wireframe(&self) -> bool {
+        self.inner.show_wireframe
+    }
+    #[setter]
+    pub fn set_show_wireframe(&mut self, show_wireframe: bool) {
+        self.inner.show_wireframe = show_wireframe;
+    }
+    #[getter]
+    pub fn get_wire_color(&self) -> (f32, f32, f32, f32) {
+        let c = self.inner.wire_color;
+        (c.x, c.y, c.z, c.w)
+    }
+    #[setter]
+    pub fn set_wire_color(&mut self, wire_color: PyArrayLike1<'_, f32, AllowTypeChange>) {
+        assert_eq!(wire_color.len(), 4, "wire_color should have 4 components");
+        self.inner.wire_color = na::Vector4::<f32>::from_vec(wire_color.to_vec().unwrap());
+    }
+    #[getter]
+    pub fn get_wire_width(&self) -> f32 {
+        self.inner.wire_width
+    }
+    #[setter]
+    pub fn set_wire_width(&mut self, wire_width: f32) {
+        self.inner.wire_width = wire_width;
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{components::model_matrix::PyModelMatrix, entity_builder::PyEntityBuilder, img::PyDynImage};
 use gloss_py_macros::DirectDeref;
-use gloss_renderer::geom::{Geom, IndirRemovalPolicy, SplatType};
+use gloss_renderer::geom::{Geom, IndirRemovalPolicy, PointNeighbourhood, SplatType};
 use gloss_utils::convert_enum_from;
 use nalgebra as na;
 use numpy::{
@@ -24,6 +24,12 @@ pub enum PyIndirRemovalPolicy {
 }
 convert_enum_from!(PyIndirRemovalPolicy, IndirRemovalPolicy, RemoveInvalidRows, RemoveInvalidCols,);
 
+fn point_from_array(array: &PyArrayLike1<'_, f32, AllowTypeChange>) -> na::Point3<f32> {
+    assert_eq!(array.len(), 3, "point should have 3 components");
+    let array_na: na::DMatrix<f32> = array.try_readonly().unwrap().as_matrix().into();
+    na::Point3::<f32>::new(array_na.row(0)[0], array_na.row(1)[0], array_na.row(2)[0])
+}
+
 //Geom---------------------
 
 #[pyclass(name = "geom", module = "gloss.geom", unsendable)]
@@ -38,9 +44,103 @@ impl PyGeom {
     #[pyo3(text_signature = "(center: NDArray[np.float32]) -> EntityBuilder")]
     pub fn build_cube(center: PyArrayLike1<'_, f32, AllowTypeChange>) -> PyEntityBuilder {
         assert_eq!(center.len(), 3, "center should have 3 components");
-        let center_na: na::DMatrix<f32> = center.try_readonly().unwrap().as_matrix().into();
-        let center_point = na::Point3::<f32>::new(center_na.row(0)[0], center_na.row(1)[0], center_na.row(2)[0]);
-        PyEntityBuilder::new(Geom::build_cube(center_point))
+        PyEntityBuilder::new(Geom::build_cube(point_from_array(&center)))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (center, radius=1.0, nr_rings=16, nr_segments=32))]
+    #[pyo3(text_signature = "(center: NDArray[np.float32], radius: float = 1.0, nr_rings: int = 16, nr_segments: int = 32) -> EntityBuilder")]
+    pub fn build_uv_sphere(center: PyArrayLike1<'_, f32, AllowTypeChange>, radius: f32, nr_rings: u32, nr_segments: u32) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_uv_sphere(point_from_array(&center), radius, nr_rings, nr_segments))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (center, radius=1.0, nr_subdivisions=3))]
+    #[pyo3(text_signature = "(center: NDArray[np.float32], radius: float = 1.0, nr_subdivisions: int = 3) -> EntityBuilder")]
+    pub fn build_ico_sphere(center: PyArrayLike1<'_, f32, AllowTypeChange>, radius: f32, nr_subdivisions: u32) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_ico_sphere(point_from_array(&center), radius, nr_subdivisions))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (center, radius=1.0, height=2.0, nr_segments=32, capped=true))]
+    #[pyo3(
+        text_signature = "(center: NDArray[np.float32], radius: float = 1.0, height: float = 2.0, nr_segments: int = 32, capped: bool = True) -> EntityBuilder"
+    )]
+    pub fn build_cylinder(
+        center: PyArrayLike1<'_, f32, AllowTypeChange>,
+        radius: f32,
+        height: f32,
+        nr_segments: u32,
+        capped: bool,
+    ) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_cylinder(point_from_array(&center), radius, height, nr_segments, capped))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (center, radius=1.0, height=2.0, nr_segments=32, capped=true))]
+    #[pyo3(
+        text_signature = "(center: NDArray[np.float32], radius: float = 1.0, height: float = 2.0, nr_segments: int = 32, capped: bool = True) -> EntityBuilder"
+    )]
+    pub fn build_cone(center: PyArrayLike1<'_, f32, AllowTypeChange>, radius: f32, height: f32, nr_segments: u32, capped: bool) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_cone(point_from_array(&center), radius, height, nr_segments, capped))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (center, radius=0.5, height=2.0, nr_rings=8, nr_segments=32))]
+    #[pyo3(
+        text_signature = "(center: NDArray[np.float32], radius: float = 0.5, height: float = 2.0, nr_rings: int = 8, nr_segments: int = 32) -> EntityBuilder"
+    )]
+    pub fn build_capsule(
+        center: PyArrayLike1<'_, f32, AllowTypeChange>,
+        radius: f32,
+        height: f32,
+        nr_rings: u32,
+        nr_segments: u32,
+    ) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_capsule(point_from_array(&center), radius, height, nr_rings, nr_segments))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (center, major_radius=1.0, minor_radius=0.25, nr_rings=16, nr_segments=32))]
+    #[pyo3(
+        text_signature = "(center: NDArray[np.float32], major_radius: float = 1.0, minor_radius: float = 0.25, nr_rings: int = 16, nr_segments: int = 32) -> EntityBuilder"
+    )]
+    pub fn build_torus(
+        center: PyArrayLike1<'_, f32, AllowTypeChange>,
+        major_radius: f32,
+        minor_radius: f32,
+        nr_rings: u32,
+        nr_segments: u32,
+    ) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_torus(
+            point_from_array(&center),
+            major_radius,
+            minor_radius,
+            nr_rings,
+            nr_segments,
+        ))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (start, end, shaft_radius=0.02, head_radius=0.05, head_length=0.1, nr_segments=16))]
+    #[pyo3(
+        text_signature = "(start: NDArray[np.float32], end: NDArray[np.float32], shaft_radius: float = 0.02, head_radius: float = 0.05, head_length: float = 0.1, nr_segments: int = 16) -> EntityBuilder"
+    )]
+    pub fn build_arrow(
+        start: PyArrayLike1<'_, f32, AllowTypeChange>,
+        end: PyArrayLike1<'_, f32, AllowTypeChange>,
+        shaft_radius: f32,
+        head_radius: f32,
+        head_length: f32,
+        nr_segments: u32,
+    ) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_arrow(
+            point_from_array(&start),
+            point_from_array(&end),
+            shaft_radius,
+            head_radius,
+            head_length,
+            nr_segments,
+        ))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (center, size=1.0, line_width=2.0))]
+    #[pyo3(text_signature = "(center: NDArray[np.float32], size: float = 1.0, line_width: float = 2.0) -> EntityBuilder")]
+    pub fn build_axes(center: PyArrayLike1<'_, f32, AllowTypeChange>, size: f32, line_width: f32) -> PyEntityBuilder {
+        PyEntityBuilder::new(Geom::build_axes(point_from_array(&center), size, line_width))
     }
     #[staticmethod]
     #[pyo3(text_signature = "() -> EntityBuilder")]
@@ -132,5 +232,164 @@ impl PyGeom {
         (reindexed.to_pyarray_bound(py).into(), mask.to_pyarray_bound(py).into())
     }
 
+    #[staticmethod]
+    #[pyo3(signature = (verts, faces, target_nr_faces, attribs=Vec::new()))]
+    #[pyo3(
+        text_signature = "(verts: NDArray[np.float32], faces: NDArray[np.uint32], target_nr_faces: int, attribs: List[NDArray[np.float32]] = []) -> Tuple[NDArray[np.float32], NDArray[np.uint32], List[NDArray[np.float32]]]"
+    )]
+    #[allow(clippy::type_complexity)]
+    pub fn decimate(
+        py: Python<'_>,
+        verts: PyReadonlyArray2<f32>,
+        faces: PyReadonlyArray2<u32>,
+        target_nr_faces: usize,
+        attribs: Vec<PyReadonlyArray2<f32>>,
+    ) -> (Py<PyArray2<f32>>, Py<PyArray2<u32>>, Vec<Py<PyArray2<f32>>>) {
+        let attribs: Vec<na::DMatrix<f32>> = attribs.iter().map(|a| a.as_matrix().clone_owned()).collect();
+        let attribs_refs: Vec<&na::DMatrix<f32>> = attribs.iter().collect();
+        let (verts, faces, attribs) = Geom::decimate(
+            &verts.as_matrix().clone_owned(),
+            &faces.as_matrix().clone_owned(),
+            target_nr_faces,
+            &attribs_refs,
+        );
+        (
+            verts.to_pyarray_bound(py).into(),
+            faces.to_pyarray_bound(py).into(),
+            attribs.iter().map(|a| a.to_pyarray_bound(py).into()).collect(),
+        )
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (verts, faces, nr_iters=1, attribs=Vec::new()))]
+    #[pyo3(
+        text_signature = "(verts: NDArray[np.float32], faces: NDArray[np.uint32], nr_iters: int = 1, attribs: List[NDArray[np.float32]] = []) -> Tuple[NDArray[np.float32], NDArray[np.uint32], List[NDArray[np.float32]]]"
+    )]
+    #[allow(clippy::type_complexity)]
+    pub fn subdivide_loop(
+        py: Python<'_>,
+        verts: PyReadonlyArray2<f32>,
+        faces: PyReadonlyArray2<u32>,
+        nr_iters: u32,
+        attribs: Vec<PyReadonlyArray2<f32>>,
+    ) -> (Py<PyArray2<f32>>, Py<PyArray2<u32>>, Vec<Py<PyArray2<f32>>>) {
+        let attribs: Vec<na::DMatrix<f32>> = attribs.iter().map(|a| a.as_matrix().clone_owned()).collect();
+        let attribs_refs: Vec<&na::DMatrix<f32>> = attribs.iter().collect();
+        let (verts, faces, attribs) = Geom::subdivide_loop(
+            &verts.as_matrix().clone_owned(),
+            &faces.as_matrix().clone_owned(),
+            nr_iters,
+            &attribs_refs,
+        );
+        (
+            verts.to_pyarray_bound(py).into(),
+            faces.to_pyarray_bound(py).into(),
+            attribs.iter().map(|a| a.to_pyarray_bound(py).into()).collect(),
+        )
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (verts, faces, nr_iters=10, lambda_=0.5))]
+    #[pyo3(
+        text_signature = "(verts: NDArray[np.float32], faces: NDArray[np.uint32], nr_iters: int = 10, lambda_: float = 0.5) -> NDArray[np.float32]"
+    )]
+    pub fn smooth_laplacian(
+        py: Python<'_>,
+        verts: PyReadonlyArray2<f32>,
+        faces: PyReadonlyArray2<u32>,
+        nr_iters: u32,
+        lambda_: f32,
+    ) -> Py<PyArray2<f32>> {
+        Geom::smooth_laplacian(&verts.as_matrix().clone_owned(), &faces.as_matrix().clone_owned(), nr_iters, lambda_)
+            .to_pyarray_bound(py)
+            .into()
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (verts, faces, nr_iters=10, lambda_=0.5, mu=-0.53))]
+    #[pyo3(
+        text_signature = "(verts: NDArray[np.float32], faces: NDArray[np.uint32], nr_iters: int = 10, lambda_: float = 0.5, mu: float = -0.53) -> NDArray[np.float32]"
+    )]
+    pub fn smooth_taubin(
+        py: Python<'_>,
+        verts: PyReadonlyArray2<f32>,
+        faces: PyReadonlyArray2<u32>,
+        nr_iters: u32,
+        lambda_: f32,
+        mu: f32,
+    ) -> Py<PyArray2<f32>> {
+        Geom::smooth_taubin(&verts.as_matrix().clone_owned(), &faces.as_matrix().clone_owned(), nr_iters, lambda_, mu)
+            .to_pyarray_bound(py)
+            .into()
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (verts, viewpoint, nr_neighbours=20, radius=None))]
+    #[pyo3(
+        text_signature = "(verts: NDArray[np.float32], viewpoint: NDArray[np.float32], nr_neighbours: int = 20, radius: Optional[float] = None) -> NDArray[np.float32]"
+    )]
+    pub fn estimate_point_normals(
+        py: Python<'_>,
+        verts: PyReadonlyArray2<f32>,
+        viewpoint: PyArrayLike1<'_, f32, AllowTypeChange>,
+        nr_neighbours: usize,
+        radius: Option<f32>,
+    ) -> Py<PyArray2<f32>> {
+        //the radius takes precedence over the number of neighbours if it's given
+        let neighbourhood = radius.map_or(PointNeighbourhood::Knn(nr_neighbours), PointNeighbourhood::Radius);
+        Geom::estimate_point_normals(&verts.as_matrix().clone_owned(), neighbourhood, &point_from_array(&viewpoint))
+            .to_pyarray_bound(py)
+            .into()
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (verts, voxel_size, attribs=Vec::new()))]
+    #[pyo3(
+        text_signature = "(verts: NDArray[np.float32], voxel_size: float, attribs: List[NDArray[np.float32]] = []) -> Tuple[NDArray[np.float32], List[NDArray[np.float32]]]"
+    )]
+    #[allow(clippy::type_complexity)]
+    pub fn voxel_downsample(
+        py: Python<'_>,
+        verts: PyReadonlyArray2<f32>,
+        voxel_size: f32,
+        attribs: Vec<PyReadonlyArray2<f32>>,
+    ) -> (Py<PyArray2<f32>>, Vec<Py<PyArray2<f32>>>) {
+        let attribs: Vec<na::DMatrix<f32>> = attribs.iter().map(|a| a.as_matrix().clone_owned()).collect();
+        let attribs_refs: Vec<&na::DMatrix<f32>> = attribs.iter().collect();
+        let (verts, attribs) = Geom::voxel_downsample(&verts.as_matrix().clone_owned(), voxel_size, &attribs_refs);
+        (
+            verts.to_pyarray_bound(py).into(),
+            attribs.iter().map(|a| a.to_pyarray_bound(py).into()).collect(),
+        )
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (verts, nr_neighbours=20, std_ratio=2.0))]
+    #[pyo3(text_signature = "(verts: NDArray[np.float32], nr_neighbours: int = 20, std_ratio: float = 2.0) -> NDArray[np.bool_]")]
+    pub fn statistical_outlier_mask(py: Python<'_>, verts: PyReadonlyArray2<f32>, nr_neighbours: usize, std_ratio: f32) -> Py<PyArray1<bool>> {
+        Geom::statistical_outlier_mask(&verts.as_matrix().clone_owned(), nr_neighbours, std_ratio)
+            .to_pyarray_bound(py)
+            .into()
+    }
+
+    #[staticmethod]
+    #[pyo3(
+        text_signature = "(src_verts: NDArray[np.float32], tgt_verts: NDArray[np.float32], tgt_faces: NDArray[np.uint32]) -> Tuple[NDArray[np.float32], float, float, float]"
+    )]
+    pub fn mesh_distance(
+        py: Python<'_>,
+        src_verts: PyReadonlyArray2<f32>,
+        tgt_verts: PyReadonlyArray2<f32>,
+        tgt_faces: PyReadonlyArray2<u32>,
+    ) -> (Py<PyArray2<f32>>, f32, f32, f32) {
+        //returns the signed distances together with the hausdorff, mean and rms distance
+        let (distances, stats) = Geom::mesh_distance(
+            &src_verts.as_matrix().clone_owned(),
+            &tgt_verts.as_matrix().clone_owned(),
+            &tgt_faces.as_matrix().clone_owned(),
+        );
+        (distances.to_pyarray_bound(py).into(), stats.hausdorff, stats.mean, stats.rms)
+    }
+
     //methods that are not static and act directly on the entity
 }
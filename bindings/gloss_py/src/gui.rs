@@ -0,0 +1,330 @@
+use gloss_hecs::Entity;
+use gloss_renderer::{
+    plugin_manager::gui::{
+        Button, Checkbox, GuiWindow, GuiWindowType, Selectable, SelectableList, Slider, Widgets, WindowPivot, WindowPosition, WindowPositionType,
+    },
+    scene::Scene,
+};
+use gloss_utils::abi_stable_aliases::std_types::{ROption, RString, RVec};
+use log::warn;
+use pyo3::{prelude::*, PyClass};
+
+use crate::{
+    actor::PyActorMut,
+    scene::PyScene,
+    systems::{gui_window, scene_handle},
+};
+
+/// Slider that calls `on_change(value, entity, scene)` while it's dragged and
+/// the optional `on_release(entity, scene)` when the drag stops
+#[pyclass(name = "Slider", module = "gloss.gui", unsendable)]
+pub struct PySlider {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get, set)]
+    pub value: f32,
+    #[pyo3(get, set)]
+    pub min: f32,
+    #[pyo3(get, set)]
+    pub max: f32,
+    pub width: Option<f32>,
+    pub on_change: Py<PyAny>,
+    pub on_release: Option<Py<PyAny>>,
+}
+#[pymethods]
+impl PySlider {
+    #[new]
+    #[pyo3(signature = (name, on_change, value=0.0, min=0.0, max=1.0, width=None, on_release=None))]
+    #[pyo3(
+        text_signature = "(name: str, on_change: Callable[[float, Entity, Scene], None], value: float = 0.0, min: float = 0.0, max: float = 1.0, width: Optional[float] = None, on_release: Optional[Callable[[Entity, Scene], None]] = None) -> Slider"
+    )]
+    pub fn new(name: &str, on_change: Py<PyAny>, value: f32, min: f32, max: f32, width: Option<f32>, on_release: Option<Py<PyAny>>) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            min,
+            max,
+            width,
+            on_change,
+            on_release,
+        }
+    }
+}
+
+/// Checkbox that calls `on_click(value, entity, scene)` when toggled
+#[pyclass(name = "Checkbox", module = "gloss.gui", unsendable)]
+pub struct PyCheckbox {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get, set)]
+    pub value: bool,
+    pub on_click: Py<PyAny>,
+}
+#[pymethods]
+impl PyCheckbox {
+    #[new]
+    #[pyo3(signature = (name, on_click, value=false))]
+    #[pyo3(text_signature = "(name: str, on_click: Callable[[bool, Entity, Scene], None], value: bool = False) -> Checkbox")]
+    pub fn new(name: &str, on_click: Py<PyAny>, value: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            on_click,
+        }
+    }
+}
+
+/// Button that calls `on_click(entity, scene)` when clicked
+#[pyclass(name = "Button", module = "gloss.gui", unsendable)]
+pub struct PyButton {
+    #[pyo3(get)]
+    pub name: String,
+    pub on_click: Py<PyAny>,
+}
+#[pymethods]
+impl PyButton {
+    #[new]
+    #[pyo3(text_signature = "(name: str, on_click: Callable[[Entity, Scene], None]) -> Button")]
+    pub fn new(name: &str, on_click: Py<PyAny>) -> Self {
+        Self {
+            name: name.to_string(),
+            on_click,
+        }
+    }
+}
+
+/// List of items of which one can be selected. Calls `on_select(item, entity,
+/// scene)` when an item is clicked
+#[pyclass(name = "SelectableList", module = "gloss.gui", unsendable)]
+pub struct PySelectableList {
+    #[pyo3(get, set)]
+    pub items: Vec<String>,
+    #[pyo3(get, set)]
+    pub selected: Option<String>,
+    #[pyo3(get, set)]
+    pub horizontal: bool,
+    pub on_select: Py<PyAny>,
+}
+#[pymethods]
+impl PySelectableList {
+    #[new]
+    #[pyo3(signature = (items, on_select, selected=None, horizontal=false))]
+    #[pyo3(
+        text_signature = "(items: List[str], on_select: Callable[[str, Entity, Scene], None], selected: Optional[str] = None, horizontal: bool = False) -> SelectableList"
+    )]
+    pub fn new(items: Vec<String>, on_select: Py<PyAny>, selected: Option<String>, horizontal: bool) -> Self {
+        Self {
+            items,
+            selected,
+            horizontal,
+            on_select,
+        }
+    }
+}
+
+/// Lays out the widgets next to each other
+#[pyclass(name = "Horizontal", module = "gloss.gui", unsendable)]
+pub struct PyHorizontal {
+    pub widgets: Vec<Py<PyAny>>,
+}
+#[pymethods]
+impl PyHorizontal {
+    #[new]
+    #[pyo3(text_signature = "(widgets: List[Any]) -> Horizontal")]
+    pub fn new(widgets: Vec<Py<PyAny>>) -> Self {
+        Self { widgets }
+    }
+}
+
+/// Window made of widgets which is added to the viewer with
+/// `viewer.add_gui_window()`. Without a position the window is shown in the
+/// sidebar, otherwise it floats with its top left corner at the normalized
+/// position. Widgets are matched to their callbacks by name so names have to be
+/// unique within a window. Like the gui windows of plugins, it is only shown
+/// when an entity is selected.
+#[pyclass(name = "GuiWindow", module = "gloss.gui", unsendable)]
+pub struct PyGuiWindow {
+    #[pyo3(get)]
+    pub name: String,
+    pub widgets: Vec<Py<PyAny>>,
+    #[pyo3(get, set)]
+    pub position: Option<(f32, f32)>,
+}
+#[pymethods]
+impl PyGuiWindow {
+    #[new]
+    #[pyo3(signature = (name, widgets=Vec::new(), position=None))]
+    #[pyo3(text_signature = "(name: str, widgets: List[Any] = [], position: Optional[Tuple[float, float]] = None) -> GuiWindow")]
+    pub fn new(name: &str, widgets: Vec<Py<PyAny>>, position: Option<(f32, f32)>) -> Self {
+        Self {
+            name: name.to_string(),
+            widgets,
+            position,
+        }
+    }
+    #[pyo3(text_signature = "($self, widget: Any) -> None")]
+    pub fn add(&mut self, widget: Py<PyAny>) {
+        self.widgets.push(widget);
+    }
+}
+impl PyGuiWindow {
+    /// Converts the window to the ffi one used by the gui. `IDX` is the index
+    /// of the window in the scene which the widget callbacks use to find the
+    /// window again
+    pub fn to_gui_window<const IDX: usize>(&self, py: Python<'_>) -> GuiWindow {
+        let window_type = match self.position {
+            Some((x, y)) => GuiWindowType::FloatWindow(WindowPivot::LeftTop, WindowPosition([x, y]), WindowPositionType::Initial),
+            None => GuiWindowType::Sidebar,
+        };
+        GuiWindow {
+            window_name: RString::from(self.name.as_str()),
+            window_type,
+            widgets: to_widgets::<IDX>(py, &self.widgets),
+        }
+    }
+}
+
+fn to_widgets<const IDX: usize>(py: Python<'_>, widgets: &[Py<PyAny>]) -> RVec<Widgets> {
+    let mut res = RVec::new();
+    for widget in widgets {
+        let widget = widget.bind(py);
+        if let Ok(slider) = widget.downcast::<PySlider>() {
+            let slider = slider.borrow();
+            res.push(Widgets::Slider(Slider::new(
+                &slider.name,
+                slider.value,
+                slider.min,
+                slider.max,
+                slider.width.into(),
+                slider_changed::<IDX>,
+                if slider.on_release.is_some() {
+                    ROption::RSome(slider_released::<IDX>)
+                } else {
+                    ROption::RNone
+                },
+            )));
+        } else if let Ok(checkbox) = widget.downcast::<PyCheckbox>() {
+            let checkbox = checkbox.borrow();
+            res.push(Widgets::Checkbox(Checkbox::new(&checkbox.name, checkbox.value, checkbox_clicked::<IDX>)));
+        } else if let Ok(button) = widget.downcast::<PyButton>() {
+            res.push(Widgets::Button(Button::new(&button.borrow().name, button_clicked::<IDX>)));
+        } else if let Ok(list) = widget.downcast::<PySelectableList>() {
+            let list = list.borrow();
+            let items = list
+                .items
+                .iter()
+                .map(|item| Selectable::new(item, list.selected.as_ref() == Some(item), selectable_clicked::<IDX>))
+                .collect();
+            res.push(Widgets::SelectableList(SelectableList::new(items, list.horizontal)));
+        } else if let Ok(horizontal) = widget.downcast::<PyHorizontal>() {
+            res.push(Widgets::Horizontal(to_widgets::<IDX>(py, &horizontal.borrow().widgets)));
+        } else {
+            warn!("Unknown gui widget {widget}, it will not be shown");
+        }
+    }
+    res
+}
+
+//finds the first widget of type T in the window for which the predicate is true, looking also inside the horizontal layouts
+fn find_widget<'py, T: PyClass>(py: Python<'py>, widgets: &[Py<PyAny>], pred: &impl Fn(&T) -> bool) -> Option<Bound<'py, T>> {
+    for widget in widgets {
+        let widget = widget.bind(py);
+        if let Ok(w) = widget.downcast::<T>() {
+            if pred(&w.borrow()) {
+                return Some(w.clone());
+            }
+        } else if let Ok(horizontal) = widget.downcast::<PyHorizontal>() {
+            if let Some(w) = find_widget(py, &horizontal.borrow().widgets, pred) {
+                return Some(w);
+            }
+        }
+    }
+    None
+}
+
+//runs f with the widget of the window IDX that the callback was triggered for and prints the python exceptions since they can't unwind
+// through the extern "C" callbacks
+fn with_widget<const IDX: usize, T: PyClass>(
+    entity: Entity,
+    scene: &mut Scene,
+    pred: impl Fn(&T) -> bool,
+    f: impl FnOnce(Python<'_>, &Bound<'_, T>, PyActorMut, PyScene) -> PyResult<()>,
+) {
+    let scene_ptr: *mut Scene = scene;
+    Python::with_gil(|py| {
+        let (Some(window), Some(handle)) = (gui_window(py, scene_ptr, IDX), scene_handle(py, scene_ptr)) else {
+            return;
+        };
+        let widget = find_widget::<T>(py, &window.borrow(py).widgets, &pred);
+        if let Some(widget) = widget {
+            let entity = PyActorMut::new(entity, handle.clone_ref(py));
+            if let Err(err) = f(py, &widget, entity, PyScene::new(handle)) {
+                err.print(py);
+            }
+        }
+    });
+}
+
+extern "C" fn slider_changed<const IDX: usize>(val: f32, name: RString, entity: Entity, scene: &mut Scene) {
+    with_widget::<IDX, PySlider>(
+        entity,
+        scene,
+        |w| w.name == name.as_str(),
+        |py, slider, entity, scene| {
+            slider.borrow_mut().value = val;
+            //the borrow has to be released before calling into python since the callback may access the slider
+            let f = slider.borrow().on_change.clone_ref(py);
+            f.call1(py, (val, entity, scene)).map(|_| ())
+        },
+    );
+}
+
+extern "C" fn slider_released<const IDX: usize>(name: RString, entity: Entity, scene: &mut Scene) {
+    with_widget::<IDX, PySlider>(
+        entity,
+        scene,
+        |w| w.name == name.as_str(),
+        |py, slider, entity, scene| {
+            let f = slider.borrow().on_release.as_ref().map(|f| f.clone_ref(py));
+            f.map_or(Ok(()), |f| f.call1(py, (entity, scene)).map(|_| ()))
+        },
+    );
+}
+
+extern "C" fn checkbox_clicked<const IDX: usize>(val: bool, name: RString, entity: Entity, scene: &mut Scene) {
+    with_widget::<IDX, PyCheckbox>(
+        entity,
+        scene,
+        |w| w.name == name.as_str(),
+        |py, checkbox, entity, scene| {
+            checkbox.borrow_mut().value = val;
+            let f = checkbox.borrow().on_click.clone_ref(py);
+            f.call1(py, (val, entity, scene)).map(|_| ())
+        },
+    );
+}
+
+extern "C" fn button_clicked<const IDX: usize>(name: RString, entity: Entity, scene: &mut Scene) {
+    with_widget::<IDX, PyButton>(
+        entity,
+        scene,
+        |w| w.name == name.as_str(),
+        |py, button, entity, scene| {
+            let f = button.borrow().on_click.clone_ref(py);
+            f.call1(py, (entity, scene)).map(|_| ())
+        },
+    );
+}
+
+extern "C" fn selectable_clicked<const IDX: usize>(name: RString, entity: Entity, scene: &mut Scene) {
+    with_widget::<IDX, PySelectableList>(
+        entity,
+        scene,
+        |w| w.items.iter().any(|item| item == name.as_str()),
+        |py, list, entity, scene| {
+            list.borrow_mut().selected = Some(name.to_string());
+            let f = list.borrow().on_select.clone_ref(py);
+            f.call1(py, (name.to_string(), entity, scene)).map(|_| ())
+        },
+    );
+}
@@ -0,0 +1,73 @@
+use gloss_hecs::Entity;
+use gloss_renderer::scene::Scene;
+use pyo3::prelude::*;
+
+//pyo3 0.22 checks for a gil-refs feature of this crate inside the macro
+#[allow(unexpected_cfgs)]
+mod errors {
+    use pyo3::{create_exception, exceptions::PyRuntimeError};
+    create_exception!(
+        gloss,
+        StaleHandleError,
+        PyRuntimeError,
+        "Raised when using an entity that has been despawned from the scene"
+    );
+}
+pub use errors::StaleHandleError;
+
+/// Handle to a scene that lives inside a python viewer. The handle keeps a
+/// strong reference to the viewer so the scene can never be freed while
+/// python still has a handle to it, regardless of the order in which python
+/// drops its objects. Components get it as a python object when they are
+/// inserted into or read from an entity.
+#[pyclass(name = "SceneHandle", module = "gloss", unsendable)]
+// it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
+pub struct SceneHandle {
+    ptr: *mut Scene,
+    owner: Py<PyAny>,
+}
+impl SceneHandle {
+    /// `ptr` has to point into the object owned by `owner`
+    pub fn new(ptr: *mut Scene, owner: Py<PyAny>) -> Self {
+        Self { ptr, owner }
+    }
+    #[must_use]
+    pub fn clone_ref(&self, py: Python<'_>) -> Self {
+        Self {
+            ptr: self.ptr,
+            owner: self.owner.clone_ref(py),
+        }
+    }
+    /// Python object of the handle which is passed to the components
+    ///
+    /// # Errors
+    /// Will return an error if python fails to allocate the object
+    pub fn to_py(&self, py: Python<'_>) -> PyResult<Py<Self>> {
+        Py::new(py, self.clone_ref(py))
+    }
+    pub fn ptr(&self) -> *mut Scene {
+        self.ptr
+    }
+    pub fn scene(&self) -> &Scene {
+        //the owner is alive as long as we are so the scene is too
+        unsafe { &*self.ptr }
+    }
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        unsafe { &mut *self.ptr }
+    }
+    /// Checks that the entity has not been despawned. Entities are generation
+    /// checked so an entity whose slot was reused by a new one is also stale.
+    ///
+    /// # Errors
+    /// Will return a `StaleHandleError` if the entity is no longer in the scene
+    pub fn check_entity(&self, entity: Entity) -> PyResult<()> {
+        if self.scene().world.contains(entity) {
+            Ok(())
+        } else {
+            Err(StaleHandleError::new_err(format!(
+                "entity {} has been despawned from the scene",
+                entity.to_bits().get()
+            )))
+        }
+    }
+}
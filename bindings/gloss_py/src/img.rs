@@ -1,7 +1,7 @@
+use crate::handle::SceneHandle;
 use gloss_hecs::Entity;
 use gloss_img::DynImage;
 use gloss_py_macros::PyComponent;
-use gloss_renderer::scene::Scene;
 use image::ImageBuffer;
 use numpy::{dtype_bound, PyArray3, PyArrayDescrMethods, PyArrayMethods, PyUntypedArray, PyUntypedArrayMethods, ToPyArray};
 use pyo3::{exceptions::PyTypeError, prelude::*};
@@ -3,19 +3,39 @@
 #![allow(clippy::return_self_not_must_use)]
 
 use components::{
+    blend_shape_coeffs::PyBlendShapeCoeffs,
+    blend_shapes::PyBlendShapes,
+    clip_plane::{PyClipPlane, PyNoClip},
     colors::PyColors,
     diffuse_img::PyDiffuseImg,
     edges::PyEdges,
+    environment_map::PyEnvironmentMap,
     faces::PyFaces,
+    highlight::PyHighlight,
+    joint_transforms::PyJointTransforms,
+    light_emit::PyLightEmit,
+    local_model_matrix::PyLocalModelMatrix,
+    mesh_sequence::PyMeshSequence,
+    metalness_img::PyMetalnessImg,
     model_matrix::PyModelMatrix,
+    name::PyName,
     normal_img::PyNormalImg,
     normals::PyNormals,
+    pos_lookat::PyPosLookat,
+    projection::PyProjection,
+    roughness_img::PyRoughnessImg,
+    scalars::PyScalars,
+    shadow_caster::PyShadowCaster,
+    skin_weights::PySkinWeights,
     tangents::PyTangents,
     uvs::PyUVs,
     verts::PyVerts,
     vis_edges::{PyLineColorType, PyVisLines},
     vis_mesh::{PyMeshColorType, PyVisMesh},
-    vis_points::{PyPointColorType, PyVisPoints},
+    vis_normals::PyVisNormals,
+    vis_points::{PyPointColorType, PyPointRenderMode, PyVisPoints},
+    vis_scalars::{PyColormap, PyVisScalars},
+    vis_wireframe::PyVisWireframe,
 };
 use entity_builder::PyEntityBuilder;
 use img::PyDynImage;
@@ -27,11 +47,16 @@ pub mod components;
 pub mod device;
 pub mod entity_builder;
 pub mod geom;
+pub mod gui;
+pub mod handle;
 pub mod img;
 pub mod logger;
 pub mod plugin;
 pub mod queue;
+pub mod recorder;
 pub mod scene;
+pub mod systems;
+pub mod tensor_view;
 pub mod texture;
 pub mod viewer;
 pub mod viewer_headless;
@@ -39,12 +64,16 @@ use actor::PyActorMut;
 use camera::PyCamera;
 use device::PyDevice;
 use geom::{PyGeom, PyIndirRemovalPolicy, PySplatType};
+use gui::{PyButton, PyCheckbox, PyGuiWindow, PyHorizontal, PySelectableList, PySlider};
+use handle::{SceneHandle, StaleHandleError};
 use logger::{gloss_setup_logger, gloss_setup_logger_from_config_file, PyLogLevel, PyLogLevelCaps};
 use queue::PyQueue;
+use recorder::PyRecordFormat;
 use scene::PyScene;
+use systems::PyEvent;
 use texture::PyTexture;
 use viewer::PyViewer;
-use viewer_headless::PyViewerHeadless;
+use viewer_headless::{PyAov, PyViewerHeadless};
 
 /// A Python module implemented in Rust using tch to manipulate PyTorch
 /// objects.
@@ -57,6 +86,7 @@ pub fn extension(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     let components_module = PyModule::new_bound(_py, "components")?;
     let types_module = PyModule::new_bound(_py, "types")?;
     let builders_module = PyModule::new_bound(_py, "builders")?;
+    let gui_module = PyModule::new_bound(_py, "gui")?;
 
     // Add core classes to main module
     m.add_class::<PyTexture>()?;
@@ -70,12 +100,15 @@ pub fn extension(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyActorMut>()?;
     m.add_class::<PyDynImage>()?;
     m.add_class::<PyGeom>()?;
+    m.add_class::<SceneHandle>()?;
+    m.add("StaleHandleError", _py.get_type_bound::<StaleHandleError>())?;
 
     // Initialize submodules
     add_submod_log(_py, &log_module)?;
     add_submod_components_sm(_py, &components_module)?;
     add_submod_types(_py, &types_module)?;
     add_submod_builders(_py, &builders_module)?;
+    add_submod_gui(_py, &gui_module)?;
 
     // Register submodules in sys.modules
     let sys = _py.import_bound("sys")?.getattr("modules")?;
@@ -83,12 +116,14 @@ pub fn extension(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     sys.set_item("gloss.components", components_module.as_ref())?;
     sys.set_item("gloss.types", types_module.as_ref())?;
     sys.set_item("gloss.builders", builders_module.as_ref())?;
+    sys.set_item("gloss.gui", gui_module.as_ref())?;
 
     // Add submodules to main module
     m.add_submodule(&log_module)?;
     m.add_submodule(&components_module)?;
     m.add_submodule(&types_module)?;
     m.add_submodule(&builders_module)?;
+    m.add_submodule(&gui_module)?;
 
     Ok(())
 }
@@ -110,13 +145,34 @@ fn add_submod_components_sm(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()
     m.add_class::<PyEdges>()?;
     m.add_class::<PyTangents>()?;
     m.add_class::<PyColors>()?;
+    m.add_class::<PyScalars>()?;
     m.add_class::<PyFaces>()?;
+    m.add_class::<PyBlendShapes>()?;
+    m.add_class::<PyBlendShapeCoeffs>()?;
+    m.add_class::<PySkinWeights>()?;
+    m.add_class::<PyJointTransforms>()?;
+    m.add_class::<PyMeshSequence>()?;
     m.add_class::<PyVisLines>()?;
     m.add_class::<PyVisMesh>()?;
     m.add_class::<PyVisPoints>()?;
+    m.add_class::<PyVisScalars>()?;
     m.add_class::<PyModelMatrix>()?;
+    m.add_class::<PyLocalModelMatrix>()?;
     m.add_class::<PyDiffuseImg>()?;
     m.add_class::<PyNormalImg>()?;
+    m.add_class::<PyRoughnessImg>()?;
+    m.add_class::<PyMetalnessImg>()?;
+    m.add_class::<PyVisWireframe>()?;
+    m.add_class::<PyVisNormals>()?;
+    m.add_class::<PyLightEmit>()?;
+    m.add_class::<PyShadowCaster>()?;
+    m.add_class::<PyClipPlane>()?;
+    m.add_class::<PyNoClip>()?;
+    m.add_class::<PyHighlight>()?;
+    m.add_class::<PyPosLookat>()?;
+    m.add_class::<PyProjection>()?;
+    m.add_class::<PyEnvironmentMap>()?;
+    m.add_class::<PyName>()?;
     Ok(())
 }
 
@@ -124,9 +180,14 @@ fn add_submod_components_sm(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()
 fn add_submod_types(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyMeshColorType>()?;
     m.add_class::<PyPointColorType>()?;
+    m.add_class::<PyPointRenderMode>()?;
     m.add_class::<PyLineColorType>()?;
+    m.add_class::<PyColormap>()?;
     m.add_class::<PySplatType>()?;
     m.add_class::<PyIndirRemovalPolicy>()?;
+    m.add_class::<PyRecordFormat>()?;
+    m.add_class::<PyAov>()?;
+    m.add_class::<PyEvent>()?;
     Ok(())
 }
 
@@ -135,3 +196,14 @@ fn add_submod_builders(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyEntityBuilder>()?;
     Ok(())
 }
+
+#[pymodule]
+fn add_submod_gui(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGuiWindow>()?;
+    m.add_class::<PySlider>()?;
+    m.add_class::<PyCheckbox>()?;
+    m.add_class::<PyButton>()?;
+    m.add_class::<PySelectableList>()?;
+    m.add_class::<PyHorizontal>()?;
+    Ok(())
+}
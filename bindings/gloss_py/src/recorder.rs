@@ -0,0 +1,12 @@
+use gloss_renderer::config::RecordFormat;
+use gloss_utils::convert_enum_from;
+use pyo3::prelude::*;
+
+#[pyclass(name = "RecordFormat", module = "gloss.types", unsendable, eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PyRecordFormat {
+    Png = 0,
+    Exr,
+    Y4m,
+}
+convert_enum_from!(PyRecordFormat, RecordFormat, Png, Exr, Y4m,);
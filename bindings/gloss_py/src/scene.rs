@@ -1,35 +1,84 @@
-use gloss_py_macros::PtrDeref;
+use std::collections::HashSet;
+
+use gloss_hecs::Entity;
 use gloss_renderer::scene::Scene;
-use pyo3::{prelude::*, types::PyType};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyTuple, PyType},
+};
 
-use crate::actor::PyActorMut;
+use crate::{actor::PyActorMut, handle::SceneHandle};
 #[pyclass(name = "Scene", module = "gloss", unsendable)]
 // it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
-#[derive(PtrDeref)]
 pub struct PyScene {
-    pub obj_ptr: *mut Scene,
+    pub handle: SceneHandle,
+}
+impl std::ops::Deref for PyScene {
+    type Target = Scene;
+    fn deref(&self) -> &Self::Target {
+        self.handle.scene()
+    }
+}
+impl std::ops::DerefMut for PyScene {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.handle.scene_mut()
+    }
 }
 impl PyScene {
-    pub fn new(obj_ptr: *mut Scene) -> Self {
-        PyScene { obj_ptr }
+    pub fn new(handle: SceneHandle) -> Self {
+        PyScene { handle }
     }
 }
 #[pymethods]
 impl PyScene {
     #[pyo3(text_signature = "($self, name: str) -> Entity")]
-    pub fn get_or_create_entity(&mut self, name: &str) -> PyActorMut {
+    pub fn get_or_create_entity(&mut self, py: Python<'_>, name: &str) -> PyActorMut {
         let scene_native: &mut Scene = self;
         let entity = scene_native.get_or_create_entity(name).entity();
-        PyActorMut::new(entity, self.obj_ptr)
+        PyActorMut::new(entity, self.handle.clone_ref(py))
     }
     #[pyo3(text_signature = "($self) -> List[str]")]
     pub fn get_renderable_names(&mut self) -> Vec<String> {
         let scene_native: &mut Scene = self;
         scene_native.get_renderable_names()
     }
+    /// Returns a tuple of (entity, components...) for every entity that has
+    /// all of the given components, in the order they were requested. The
+    /// components are copies taken while the world is borrowed and all borrows
+    /// are released before returning, so the scene can be freely modified
+    /// while iterating over the result.
+    ///
+    /// # Errors
+    /// Will return an error if no component is given or if a class is not a
+    /// gloss component
+    #[pyo3(text_signature = "($self, components: List[Type[Any]]) -> List[Tuple[Any, ...]]")]
+    pub fn query(&self, py: Python<'_>, components: Vec<Bound<'_, PyType>>) -> PyResult<Vec<Py<PyTuple>>> {
+        let handle = self.handle.to_py(py)?;
+        let Some((first, rest)) = components.split_first() else {
+            return Err(PyValueError::new_err("query needs at least one component"));
+        };
+        //keep the order of the first component and intersect with the entities of the rest
+        let mut entities: Vec<u64> = first.call_method1("query_entities", (handle.clone_ref(py),))?.extract()?;
+        for cls in rest {
+            let others: HashSet<u64> = cls.call_method1("query_entities", (handle.clone_ref(py),))?.extract()?;
+            entities.retain(|e| others.contains(e));
+        }
+        entities
+            .into_iter()
+            .map(|bits| {
+                let entity = Entity::from_bits(bits).unwrap();
+                let mut items = vec![PyActorMut::new(entity, self.handle.clone_ref(py)).into_py(py)];
+                for cls in &components {
+                    items.push(cls.call_method1("get", (bits, handle.clone_ref(py)))?.unbind());
+                }
+                Ok(PyTuple::new_bound(py, items).unbind())
+            })
+            .collect()
+    }
     #[pyo3(text_signature = "($self) -> int")]
     pub fn ptr_idx(&mut self) -> u64 {
-        self.obj_ptr as u64
+        self.handle.ptr() as u64
     }
     /// Removes a renderable entity from the scene by its name.
     ///
@@ -42,32 +91,36 @@ impl PyScene {
         scene_native.despawn_with_name(name);
         Ok(())
     }
+    /// # Errors
+    /// Will return an error if the component cannot be inserted
     #[pyo3(text_signature = "($self, component: Any) -> None")]
-    pub fn add_resource(&mut self, pycomp: Py<PyAny>) {
+    pub fn add_resource(&mut self, pycomp: Py<PyAny>) -> PyResult<()> {
         let scene: &mut Scene = self;
         let entity = scene.get_entity_resource();
 
         Python::with_gil(|py| {
             let pyany = pycomp.bind(py);
-            let args = (entity.to_bits().get(), self.obj_ptr as u64);
-            let _result = pyany.call_method("insert_to_entity", args, None).unwrap();
-        });
+            let args = (entity.to_bits().get(), self.handle.to_py(py)?);
+            pyany.call_method("insert_to_entity", args, None)?;
+            Ok(())
+        })
     }
+    /// # Errors
+    /// Will return an error if the resource doesn't exist
     #[pyo3(text_signature = "($self, cls: Type[T]) -> T")]
-    pub fn get_resource(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> Py<PyAny> {
-        let result = Python::with_gil(|py| {
-            let entity = slf.get_entity_resource();
-            let args = (entity.to_bits().get(), slf.obj_ptr as u64);
-            let result = cls.call_method("get", args, None);
-            let pyany_ref = result.unwrap();
-            pyany_ref.into_py(py)
-        });
-        result
+    pub fn get_resource(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> PyResult<Py<PyAny>> {
+        let py = cls.py();
+        let entity = slf.get_entity_resource();
+        let args = (entity.to_bits().get(), slf.handle.to_py(py)?);
+        Ok(cls.call_method("get", args, None)?.into_py(py))
     }
+    /// # Errors
+    /// Will return an error if the class is not a gloss component
     #[pyo3(text_signature = "($self, cls: Type[T]) -> T")]
-    pub fn remove_resource(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) {
+    pub fn remove_resource(slf: PyRefMut<'_, Self>, cls: &Bound<'_, PyType>) -> PyResult<()> {
         let entity = slf.get_entity_resource();
-        let args = (entity.to_bits().get(), slf.obj_ptr as u64);
-        let _ = cls.call_method("remove", args, None).unwrap();
+        let args = (entity.to_bits().get(), slf.handle.to_py(cls.py())?);
+        cls.call_method("remove", args, None)?;
+        Ok(())
     }
 }
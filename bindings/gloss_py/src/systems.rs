@@ -0,0 +1,233 @@
+use std::{cell::RefCell, collections::HashMap, ptr::NonNull};
+
+use gloss_hecs::Entity;
+use gloss_renderer::{
+    plugin_manager::{
+        gui::{GuiWindow, GuiWindowType},
+        Event, EventSystem, GuiSystem, LogicSystem, RunnerState,
+    },
+    scene::Scene,
+};
+use gloss_utils::abi_stable_aliases::std_types::{ROption, RString, RVec};
+use pyo3::{exceptions::PyRuntimeError, ffi, prelude::*};
+
+use crate::{gui::PyGuiWindow, handle::SceneHandle, scene::PyScene};
+
+/// Maximum number of gui windows that can be added from python to a single
+/// viewer
+pub const MAX_PY_GUI_WINDOWS: usize = 16;
+
+//python callables cannot be turned into the extern "C" fn that the systems expect, so the viewer gets trampolines which look up the callables
+// registered for the scene they are called with
+#[derive(Default)]
+struct PySystems {
+    //the viewer owning the scene. It's not a Py<> because the registry must not keep the viewer alive, instead the viewer removes its
+    // entry with unregister_all when it's dropped so the pointer never dangles. Weak references are not available with the abi3-py38 feature
+    owner: Option<NonNull<ffi::PyObject>>,
+    logic_systems: Vec<Py<PyAny>>,
+    event_systems: Vec<Py<PyAny>>,
+    gui_windows: Vec<Py<PyGuiWindow>>,
+}
+
+impl PySystems {
+    fn set_owner(&mut self, owner: &Bound<'_, PyAny>) {
+        if self.owner.is_none() {
+            self.owner = NonNull::new(owner.as_ptr());
+        }
+    }
+}
+
+thread_local! {
+    static PY_SYSTEMS: RefCell<HashMap<usize, PySystems>> = RefCell::new(HashMap::new());
+}
+
+/// Event passed to the python event handlers
+#[pyclass(name = "Event", module = "gloss.types", unsendable)]
+#[derive(Clone)]
+pub struct PyEvent {
+    /// Kind of event. Currently only `"dropped_file"`
+    #[pyo3(get)]
+    pub kind: String,
+    /// Path of the file for `"dropped_file"` events
+    #[pyo3(get)]
+    pub path: Option<String>,
+}
+impl From<&Event> for PyEvent {
+    fn from(event: &Event) -> Self {
+        match event {
+            Event::DroppedFile(path) => Self {
+                kind: "dropped_file".to_string(),
+                path: Some(path.to_string()),
+            },
+        }
+    }
+}
+
+/// Registers a python callable as a logic system of the scene. Returns the
+/// trampoline system the first time a callable is registered for this scene,
+/// in which case it has to be added to the viewer. The `owner` is the viewer
+/// of the scene which has to call [`unregister_all`] when it's dropped
+pub fn register_logic_system(scene: &Scene, owner: &Bound<'_, PyAny>, f: Py<PyAny>) -> Option<LogicSystem> {
+    PY_SYSTEMS.with(|systems| {
+        let mut systems = systems.borrow_mut();
+        let systems = systems.entry(scene_key(scene)).or_default();
+        systems.set_owner(owner);
+        systems.logic_systems.push(f);
+        (systems.logic_systems.len() == 1).then(|| LogicSystem::new(run_py_logic_systems).with_name("python"))
+    })
+}
+
+/// Registers a python callable as an event handler of the scene. Returns the
+/// trampoline system the first time a handler is registered for this scene,
+/// in which case it has to be added to the viewer. The `owner` is the viewer
+/// of the scene which has to call [`unregister_all`] when it's dropped
+pub fn register_event_system(scene: &Scene, owner: &Bound<'_, PyAny>, f: Py<PyAny>) -> Option<EventSystem> {
+    PY_SYSTEMS.with(|systems| {
+        let mut systems = systems.borrow_mut();
+        let systems = systems.entry(scene_key(scene)).or_default();
+        systems.set_owner(owner);
+        systems.event_systems.push(f);
+        (systems.event_systems.len() == 1).then(|| EventSystem::new(handle_py_event).with_name("python"))
+    })
+}
+
+/// Registers a python gui window for the scene and returns the gui system
+/// that draws it.
+///
+/// # Errors
+/// Will return an error if the scene already has [`MAX_PY_GUI_WINDOWS`]
+/// windows
+pub fn register_gui_window(scene: &Scene, owner: &Bound<'_, PyAny>, window: Py<PyGuiWindow>) -> PyResult<GuiSystem> {
+    PY_SYSTEMS.with(|systems| {
+        let mut systems = systems.borrow_mut();
+        let systems = systems.entry(scene_key(scene)).or_default();
+        systems.set_owner(owner);
+        let idx = systems.gui_windows.len();
+        if idx >= MAX_PY_GUI_WINDOWS {
+            return Err(PyRuntimeError::new_err(format!(
+                "a viewer can have at most {MAX_PY_GUI_WINDOWS} gui windows from python"
+            )));
+        }
+        systems.gui_windows.push(window);
+        Ok(GuiSystem::new(PY_GUI_WINDOW_FNS[idx]))
+    })
+}
+
+/// Drops all the python callables of the scene. Has to be called when the
+/// viewer owning the scene is dropped, otherwise a new viewer allocated at the
+/// same address would inherit them
+pub fn unregister_all(scene: &Scene) {
+    //the thread local may already be gone if this runs during thread teardown
+    let _ = PY_SYSTEMS.try_with(|systems| systems.borrow_mut().remove(&scene_key(scene)));
+}
+
+/// Gui window registered at `idx` for the scene, if any
+pub fn gui_window(py: Python<'_>, scene: *const Scene, idx: usize) -> Option<Py<PyGuiWindow>> {
+    PY_SYSTEMS.with(|systems| {
+        systems
+            .borrow()
+            .get(&(scene as usize))
+            .and_then(|s| s.gui_windows.get(idx))
+            .map(|w| w.clone_ref(py))
+    })
+}
+
+/// Handle to the scene which keeps its viewer alive while python uses it. None
+/// if no python callable was registered for the scene
+pub fn scene_handle(py: Python<'_>, scene: *mut Scene) -> Option<SceneHandle> {
+    PY_SYSTEMS.with(|systems| {
+        let owner = systems.borrow().get(&(scene as usize))?.owner?;
+        //the viewer is alive since it unregisters the scene when it's dropped
+        let owner = unsafe { Py::<PyAny>::from_borrowed_ptr(py, owner.as_ptr()) };
+        Some(SceneHandle::new(scene, owner))
+    })
+}
+
+fn scene_key(scene: &Scene) -> usize {
+    let scene_ptr: *const Scene = scene;
+    scene_ptr as usize
+}
+
+//the callables are cloned out of the registry so that a callable can register new systems while it runs
+fn callables(py: Python<'_>, scene: *const Scene, f: impl Fn(&PySystems) -> &[Py<PyAny>]) -> Vec<Py<PyAny>> {
+    PY_SYSTEMS.with(|systems| {
+        systems
+            .borrow()
+            .get(&(scene as usize))
+            .map(|s| f(s).iter().map(|c| c.clone_ref(py)).collect())
+            .unwrap_or_default()
+    })
+}
+
+extern "C" fn run_py_logic_systems(scene: &mut Scene, runner: &mut RunnerState) {
+    let scene_ptr: *mut Scene = scene;
+    let dt = runner.dt().as_secs_f32();
+    Python::with_gil(|py| {
+        let Some(handle) = scene_handle(py, scene_ptr) else {
+            return;
+        };
+        for f in callables(py, scene_ptr, |s| &s.logic_systems) {
+            //a python exception cannot unwind through the extern "C" fn so we print it and carry on
+            match f.call1(py, (PyScene::new(handle.clone_ref(py)), dt)) {
+                Ok(ret) => {
+                    if ret.bind(py).is_truthy().unwrap_or(false) {
+                        runner.request_redraw();
+                    }
+                }
+                Err(err) => err.print(py),
+            }
+        }
+    });
+}
+
+extern "C" fn handle_py_event(scene: &mut Scene, _runner: &mut RunnerState, event: &Event) -> bool {
+    let scene_ptr: *mut Scene = scene;
+    let event = PyEvent::from(event);
+    Python::with_gil(|py| {
+        let Some(handle) = scene_handle(py, scene_ptr) else {
+            return false;
+        };
+        let mut handled = false;
+        for f in callables(py, scene_ptr, |s| &s.event_systems) {
+            match f.call1(py, (PyScene::new(handle.clone_ref(py)), event.clone())) {
+                Ok(ret) => handled |= ret.bind(py).is_truthy().unwrap_or(false),
+                Err(err) => err.print(py),
+            }
+        }
+        handled
+    })
+}
+
+extern "C" fn draw_py_gui_window<const IDX: usize>(_selected_entity: ROption<Entity>, scene: &mut Scene) -> GuiWindow {
+    let scene_ptr: *mut Scene = scene;
+    Python::with_gil(|py| match gui_window(py, scene_ptr, IDX) {
+        Some(window) => window.borrow(py).to_gui_window::<IDX>(py),
+        //a window with no widgets is not drawn
+        None => GuiWindow {
+            window_name: RString::new(),
+            window_type: GuiWindowType::Sidebar,
+            widgets: RVec::new(),
+        },
+    })
+}
+
+type GuiSystemFn = extern "C" fn(ROption<Entity>, &mut Scene) -> GuiWindow;
+//every window needs its own function so that it knows which python window to draw
+const PY_GUI_WINDOW_FNS: [GuiSystemFn; MAX_PY_GUI_WINDOWS] = [
+    draw_py_gui_window::<0>,
+    draw_py_gui_window::<1>,
+    draw_py_gui_window::<2>,
+    draw_py_gui_window::<3>,
+    draw_py_gui_window::<4>,
+    draw_py_gui_window::<5>,
+    draw_py_gui_window::<6>,
+    draw_py_gui_window::<7>,
+    draw_py_gui_window::<8>,
+    draw_py_gui_window::<9>,
+    draw_py_gui_window::<10>,
+    draw_py_gui_window::<11>,
+    draw_py_gui_window::<12>,
+    draw_py_gui_window::<13>,
+    draw_py_gui_window::<14>,
+    draw_py_gui_window::<15>,
+];
@@ -0,0 +1,23 @@
+use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
+use numpy::{PyArray2, ToPyArray};
+use pyo3::{prelude::*, types::IntoPyDict};
+
+/// Numpy array of a tensor. When the tensor is on the `NdArray` backend the
+/// array is a read-only view of the data and `container` is the python object
+/// that keeps the data alive for as long as the view exists. Other backends
+/// make a copy.
+///
+/// # Panics
+/// Will panic if the view cannot be made read-only
+pub fn float2d_to_numpy<'py>(py: Python<'py>, tensor: &DynamicTensorFloat2D, container: Bound<'py, PyAny>) -> Bound<'py, PyArray2<f32>> {
+    if let Some(array) = tensor.as_ndarray() {
+        //the container holds a clone of the tensor which shares the same data as the array, so the data outlives the view
+        let view = unsafe { PyArray2::borrow_from_array_bound(&array, container) };
+        //writing into the view would modify the component behind the back of the change trackers
+        view.call_method("setflags", (), Some(&[("write", false)].into_py_dict_bound(py)))
+            .unwrap();
+        view
+    } else {
+        tensor.to_dmatrix().to_pyarray_bound(py)
+    }
+}
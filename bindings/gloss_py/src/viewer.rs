@@ -1,6 +1,18 @@
 #![allow(clippy::new_without_default)]
 
-use crate::{actor::PyActorMut, camera::PyCamera, device::PyDevice, plugin::PyPluginList, queue::PyQueue, scene::PyScene, texture::PyTexture};
+use crate::{
+    actor::PyActorMut,
+    camera::PyCamera,
+    device::PyDevice,
+    gui::PyGuiWindow,
+    handle::SceneHandle,
+    plugin::PyPluginList,
+    queue::PyQueue,
+    recorder::PyRecordFormat,
+    scene::PyScene,
+    systems::{register_event_system, register_gui_window, register_logic_system, unregister_all},
+    texture::PyTexture,
+};
 
 use gloss_renderer::{camera::Camera, config::Config, plugin_manager::Plugins, scene::Scene, viewer::Viewer};
 
@@ -19,6 +31,19 @@ impl std::ops::Deref for PyViewer {
         &self.0
     }
 }
+impl PyViewer {
+    //the handle keeps the python viewer alive so the scene it points to cannot be freed while python still uses it
+    fn scene_handle(slf: &Bound<'_, Self>) -> SceneHandle {
+        //a shared borrow is enough to take the address and doesn't fail while the viewer is borrowed elsewhere
+        let scene: *mut Scene = std::ptr::addr_of!(slf.borrow().0.scene).cast_mut();
+        SceneHandle::new(scene, slf.clone().into_any().unbind())
+    }
+}
+impl Drop for PyViewer {
+    fn drop(&mut self) {
+        unregister_all(&self.0.scene);
+    }
+}
 #[pymethods]
 impl PyViewer {
     #[new]
@@ -28,15 +53,16 @@ impl PyViewer {
         Self(Viewer::new_with_config(&Config::new(config_path)))
     }
     #[pyo3(text_signature = "($self, name: str) -> Entity")]
-    pub fn get_or_create_entity(&mut self, name: &str) -> PyActorMut {
-        let scene: &mut Scene = &mut self.0.scene;
-        let entity = scene.get_or_create_entity(name).entity();
-        PyActorMut::new(entity, &mut self.0.scene)
+    pub fn get_or_create_entity(slf: &Bound<'_, Self>, name: &str) -> PyActorMut {
+        let entity = slf.borrow_mut().0.scene.get_or_create_entity(name).entity();
+        PyActorMut::new(entity, Self::scene_handle(slf))
     }
+    /// # Errors
+    /// Will return an error if the component cannot be inserted
     #[pyo3(text_signature = "($self, component: Any) -> None")]
-    pub fn add_resource(&mut self, pycomp: Py<PyAny>) {
-        let mut pyscene = self.get_scene();
-        pyscene.add_resource(pycomp);
+    pub fn add_resource(slf: &Bound<'_, Self>, pycomp: Py<PyAny>) -> PyResult<()> {
+        let mut pyscene = Self::get_scene(slf);
+        pyscene.add_resource(pycomp)
     }
     #[pyo3(text_signature = "($self) -> float")]
     pub fn start_frame(&mut self) -> f32 {
@@ -47,6 +73,19 @@ impl PyViewer {
     pub fn override_dt(&mut self, new_dt: f32) {
         self.0.runner.override_dt(new_dt);
     }
+    #[pyo3(signature = (output_path, format=PyRecordFormat::Png, fps=30.0))]
+    #[pyo3(text_signature = "($self, output_path: str, format: RecordFormat = RecordFormat.Png, fps: float = 30.0) -> None")]
+    pub fn start_recording(&mut self, output_path: &str, format: PyRecordFormat, fps: f32) {
+        self.0.start_recording(output_path, format.into(), fps);
+    }
+    #[pyo3(text_signature = "($self) -> None")]
+    pub fn stop_recording(&mut self) {
+        self.0.stop_recording();
+    }
+    #[pyo3(text_signature = "($self) -> bool")]
+    pub fn is_recording(&self) -> bool {
+        self.0.is_recording()
+    }
     #[pyo3(text_signature = "($self) -> None")]
     pub fn update(&mut self) {
         self.0.update();
@@ -65,6 +104,32 @@ impl PyViewer {
 
         self.0.run();
     }
+    /// Enables or disables the gpu timestamp queries around every render
+    /// pass. Returns False if the device doesn't support timestamps
+    #[pyo3(signature = (enabled=true, nr_frames_to_average=30))]
+    #[pyo3(text_signature = "($self, enabled: bool = True, nr_frames_to_average: int = 30) -> bool")]
+    pub fn enable_gpu_profiling(&mut self, enabled: bool, nr_frames_to_average: usize) -> bool {
+        let profiler = self.0.gpu_res.as_ref().unwrap().gpu.profiler();
+        profiler.set_nr_frames_to_average(nr_frames_to_average);
+        profiler.set_enabled(enabled);
+        profiler.is_supported()
+    }
+    /// Gpu time of every render pass averaged over the last frames as a list of
+    /// `(name, depth, avg_ms, min_ms, max_ms)`. Nested scopes have a higher depth
+    /// than the scope containing them
+    #[pyo3(text_signature = "($self) -> List[Tuple[str, int, float, float, float]]")]
+    pub fn gpu_timings(&self) -> Vec<(String, u32, f32, f32, f32)> {
+        self.0
+            .gpu_res
+            .as_ref()
+            .unwrap()
+            .gpu
+            .profiler()
+            .timings()
+            .into_iter()
+            .map(|t| (t.name, t.depth, t.avg_ms, t.min_ms, t.max_ms))
+            .collect()
+    }
     #[pyo3(text_signature = "($self) -> Device")]
     pub fn get_device(&mut self) -> PyDevice {
         let obj_ptr: *const wgpu::Device = self.0.gpu_res.as_ref().unwrap().gpu.device();
@@ -76,15 +141,13 @@ impl PyViewer {
         PyQueue::new(obj_ptr)
     }
     #[pyo3(text_signature = "($self) -> Scene")]
-    pub fn get_scene(&mut self) -> PyScene {
-        //attempt3 just with a weakptr
-        let obj_ptr: *mut Scene = &mut self.0.scene;
-        PyScene::new(obj_ptr)
+    pub fn get_scene(slf: &Bound<'_, Self>) -> PyScene {
+        PyScene::new(Self::scene_handle(slf))
     }
     #[pyo3(text_signature = "($self) -> Camera")]
-    pub fn get_camera(&mut self) -> PyCamera {
-        let obj_ptr: *mut Camera = &mut self.0.camera;
-        PyCamera::new(obj_ptr, self.get_scene())
+    pub fn get_camera(slf: &Bound<'_, Self>) -> PyCamera {
+        let obj_ptr: *mut Camera = &mut slf.borrow_mut().0.camera;
+        PyCamera::new(obj_ptr, Self::get_scene(slf))
     }
     #[pyo3(text_signature = "($self) -> None")]
     pub fn render_next_frame(&mut self) {
@@ -145,4 +208,33 @@ impl PyViewer {
         let v = &mut self.0;
         v.run_manual_plugins();
     }
+    /// Adds a python callable which runs every frame as `f(scene, dt)`. If it
+    /// returns True a redraw is requested.
+    #[pyo3(text_signature = "($self, f: Callable[[Scene, float], Optional[bool]]) -> None")]
+    pub fn add_logic_system(slf: &Bound<'_, Self>, f: Py<PyAny>) {
+        let mut viewer = slf.borrow_mut();
+        if let Some(sys) = register_logic_system(&viewer.0.scene, slf.as_any(), f) {
+            viewer.0.add_logic_system(sys);
+        }
+    }
+    /// Adds a python callable which is called as `f(scene, event)` for every
+    /// event. It should return True if it handled the event.
+    #[pyo3(text_signature = "($self, f: Callable[[Scene, Event], bool]) -> None")]
+    pub fn add_event_handler(slf: &Bound<'_, Self>, f: Py<PyAny>) {
+        let mut viewer = slf.borrow_mut();
+        if let Some(sys) = register_event_system(&viewer.0.scene, slf.as_any(), f) {
+            viewer.0.add_event_system(sys);
+        }
+    }
+    /// Adds a gui window built from python widgets.
+    ///
+    /// # Errors
+    /// Will return an error if the viewer has too many windows from python
+    #[pyo3(text_signature = "($self, window: GuiWindow) -> None")]
+    pub fn add_gui_window(slf: &Bound<'_, Self>, window: Py<PyGuiWindow>) -> PyResult<()> {
+        let mut viewer = slf.borrow_mut();
+        let sys = register_gui_window(&viewer.0.scene, slf.as_any(), window)?;
+        viewer.0.add_gui_system(sys);
+        Ok(())
+    }
 }
@@ -1,12 +1,44 @@
-use crate::{actor::PyActorMut, camera::PyCamera, device::PyDevice, queue::PyQueue, scene::PyScene, texture::PyTexture};
+use crate::{
+    actor::PyActorMut,
+    camera::PyCamera,
+    device::PyDevice,
+    handle::SceneHandle,
+    queue::PyQueue,
+    recorder::PyRecordFormat,
+    scene::PyScene,
+    systems::{register_logic_system, unregister_all},
+    texture::PyTexture,
+};
 
-use gloss_renderer::{camera::Camera, config::Config, plugin_manager::Plugins, scene::Scene, viewer_headless::ViewerHeadless};
+use gloss_renderer::{
+    camera::Camera, config::Config, forward_renderer::render_passes::aov_pass::Aov, plugin_manager::Plugins, scene::Scene,
+    viewer_headless::ViewerHeadless,
+};
+use gloss_utils::convert_enum_from;
 
 use easy_wgpu::texture::Texture;
+use image::{codecs::jpeg::JpegEncoder, ImageFormat};
 use numpy::PyUntypedArray;
-use pyo3::prelude::*;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use std::io::Cursor;
 use wgpu;
 
+#[pyclass(name = "Aov", module = "gloss.types", unsendable, eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PyAov {
+    NormalsWorld = 0,
+    NormalsCam,
+    InstanceId,
+    Uv,
+    MotionVectors,
+    Albedo,
+}
+convert_enum_from!(PyAov, Aov, NormalsWorld, NormalsCam, InstanceId, Uv, MotionVectors, Albedo,);
+
 #[pyclass(name = "ViewerHeadless", module = "gloss", unsendable)] // it has to be unsendable because it does not implement Send: https://pyo3.rs/v0.19.1/class#must-be-send
 pub struct PyViewerHeadless(pub ViewerHeadless);
 impl std::ops::Deref for PyViewerHeadless {
@@ -15,6 +47,19 @@ impl std::ops::Deref for PyViewerHeadless {
         &self.0
     }
 }
+impl PyViewerHeadless {
+    //the handle keeps the python viewer alive so the scene it points to cannot be freed while python still uses it
+    fn scene_handle(slf: &Bound<'_, Self>) -> SceneHandle {
+        //a shared borrow is enough to take the address and doesn't fail while the viewer is borrowed elsewhere
+        let scene: *mut Scene = std::ptr::addr_of!(slf.borrow().0.scene).cast_mut();
+        SceneHandle::new(scene, slf.clone().into_any().unbind())
+    }
+}
+impl Drop for PyViewerHeadless {
+    fn drop(&mut self) {
+        unregister_all(&self.0.scene);
+    }
+}
 #[pymethods]
 impl PyViewerHeadless {
     #[new]
@@ -24,21 +69,39 @@ impl PyViewerHeadless {
         Self(ViewerHeadless::new_with_config(width, height, &Config::new(config_path)))
     }
     #[pyo3(text_signature = "($self, name: str) -> Entity")]
-    pub fn get_or_create_entity(&mut self, name: &str) -> PyActorMut {
-        let scene: &mut Scene = &mut self.0.scene;
-        let entity = scene.get_or_create_entity(name).entity();
-        PyActorMut::new(entity, &mut self.0.scene)
+    pub fn get_or_create_entity(slf: &Bound<'_, Self>, name: &str) -> PyActorMut {
+        let entity = slf.borrow_mut().0.scene.get_or_create_entity(name).entity();
+        PyActorMut::new(entity, Self::scene_handle(slf))
     }
+    /// # Errors
+    /// Will return an error if the component cannot be inserted
     #[pyo3(text_signature = "($self, component: Any) -> None")]
-    pub fn add_resource(&mut self, pycomp: Py<PyAny>) {
-        let mut pyscene = self.get_scene();
-        pyscene.add_resource(pycomp);
+    pub fn add_resource(slf: &Bound<'_, Self>, pycomp: Py<PyAny>) -> PyResult<()> {
+        let mut pyscene = Self::get_scene(slf);
+        pyscene.add_resource(pycomp)
     }
     #[pyo3(text_signature = "($self) -> float")]
     pub fn start_frame(&mut self) -> f32 {
         let dt = self.0.start_frame();
         dt.as_secs_f32()
     }
+    #[pyo3(text_signature = "($self, new_dt: f32)")]
+    pub fn override_dt(&mut self, new_dt: f32) {
+        self.0.override_dt(new_dt);
+    }
+    #[pyo3(signature = (output_path, format=PyRecordFormat::Png, fps=30.0))]
+    #[pyo3(text_signature = "($self, output_path: str, format: RecordFormat = RecordFormat.Png, fps: float = 30.0) -> None")]
+    pub fn start_recording(&mut self, output_path: &str, format: PyRecordFormat, fps: f32) {
+        self.0.start_recording(output_path, format.into(), fps);
+    }
+    #[pyo3(text_signature = "($self) -> None")]
+    pub fn stop_recording(&mut self) {
+        self.0.stop_recording();
+    }
+    #[pyo3(text_signature = "($self) -> bool")]
+    pub fn is_recording(&self) -> bool {
+        self.0.is_recording()
+    }
     #[pyo3(text_signature = "($self) -> Device")]
     pub fn get_device(&mut self) -> PyDevice {
         let obj_ptr: *const wgpu::Device = self.0.gpu.device();
@@ -50,19 +113,24 @@ impl PyViewerHeadless {
         PyQueue::new(obj_ptr)
     }
     #[pyo3(text_signature = "($self) -> Scene")]
-    pub fn get_scene(&mut self) -> PyScene {
-        let obj_ptr: *mut Scene = &mut self.0.scene;
-        PyScene::new(obj_ptr)
+    pub fn get_scene(slf: &Bound<'_, Self>) -> PyScene {
+        PyScene::new(Self::scene_handle(slf))
     }
     #[pyo3(text_signature = "($self) -> Camera")]
-    pub fn get_camera(&mut self) -> PyCamera {
-        let obj_ptr: *mut Camera = &mut self.0.camera;
-        PyCamera::new(obj_ptr, self.get_scene())
+    pub fn get_camera(slf: &Bound<'_, Self>) -> PyCamera {
+        let obj_ptr: *mut Camera = &mut slf.borrow_mut().0.camera;
+        PyCamera::new(obj_ptr, Self::get_scene(slf))
     }
     #[pyo3(text_signature = "($self) -> None")]
     pub fn update(&mut self) {
         self.0.update();
     }
+    /// Renders the frame `nr_samples` times with a different subpixel jitter
+    /// of the camera and averages them into a supersampled image
+    #[pyo3(text_signature = "($self, nr_samples: int) -> None")]
+    pub fn update_supersampled(&mut self, nr_samples: u32) {
+        self.0.update_supersampled(nr_samples);
+    }
     #[pyo3(text_signature = "($self) -> None")]
     pub fn render_next_frame(&mut self) {
         self.start_frame();
@@ -73,6 +141,49 @@ impl PyViewerHeadless {
         let mut last_render = self.get_final_tex();
         last_render.save_to_file(&self.get_device(), &self.get_queue(), path);
     }
+    /// Encodes the last render as "png" or "jpeg" and returns the bytes of
+    /// the file. Useful for streaming frames, for example to a notebook
+    /// widget, without going through the disk
+    ///
+    /// # Errors
+    /// Will return an error if the format is unknown or the encoding fails
+    #[pyo3(signature = (format="png", quality=90))]
+    #[pyo3(text_signature = "($self, format: str = 'png', quality: int = 90) -> bytes")]
+    pub fn encode_last_render<'py>(&self, py: Python<'py>, format: &str, quality: u8) -> PyResult<Bound<'py, PyBytes>> {
+        let img = pollster::block_on(self.0.get_final_tex().download_to_cpu(self.0.gpu.device(), self.0.gpu.queue()));
+        let mut bytes = Vec::new();
+        let res = match format {
+            "png" => img.to_rgba8().write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png),
+            //jpeg has no alpha channel
+            "jpeg" | "jpg" => JpegEncoder::new_with_quality(&mut bytes, quality).encode_image(&img.to_rgb8()),
+            _ => return Err(PyValueError::new_err(format!("unknown image format {format}, expected png or jpeg"))),
+        };
+        res.map_err(|err| PyRuntimeError::new_err(format!("could not encode the render: {err}")))?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+    /// Enables or disables the gpu timestamp queries around every render
+    /// pass. Returns False if the device doesn't support timestamps
+    #[pyo3(signature = (enabled=true, nr_frames_to_average=30))]
+    #[pyo3(text_signature = "($self, enabled: bool = True, nr_frames_to_average: int = 30) -> bool")]
+    pub fn enable_gpu_profiling(&mut self, enabled: bool, nr_frames_to_average: usize) -> bool {
+        let profiler = self.0.gpu.profiler();
+        profiler.set_nr_frames_to_average(nr_frames_to_average);
+        profiler.set_enabled(enabled);
+        profiler.is_supported()
+    }
+    /// Gpu time of every render pass averaged over the last frames as a list of
+    /// `(name, depth, avg_ms, min_ms, max_ms)`. Nested scopes have a higher depth
+    /// than the scope containing them
+    #[pyo3(text_signature = "($self) -> List[Tuple[str, int, float, float, float]]")]
+    pub fn gpu_timings(&self) -> Vec<(String, u32, f32, f32, f32)> {
+        self.0
+            .gpu
+            .profiler()
+            .timings()
+            .into_iter()
+            .map(|t| (t.name, t.depth, t.avg_ms, t.min_ms, t.max_ms))
+            .collect()
+    }
     #[pyo3(text_signature = "($self, camera: Camera) -> None")]
     pub fn render_from_cam(&mut self, cam: &mut PyCamera) {
         self.0.render_from_cam(cam);
@@ -92,6 +203,16 @@ impl PyViewerHeadless {
         let (znear, zfar) = self.0.camera.near_far(&mut self.0.scene);
         self.get_final_depth().depth_linearize(&self.get_device(), &self.get_queue(), znear, zfar)
     }
+    #[pyo3(text_signature = "($self, aov: Aov) -> Optional[Texture]")]
+    pub fn get_aov_tex(&mut self, aov: PyAov) -> Option<PyTexture> {
+        let ptr: *const Texture = self.0.get_aov_tex(aov.into())?;
+        Some(PyTexture::new(ptr))
+    }
+    #[pyo3(text_signature = "($self, aov: Aov) -> Optional[NDArray[np.float32]]")]
+    pub fn get_aov(&mut self, py: Python<'_>, aov: PyAov) -> Option<Py<PyUntypedArray>> {
+        let mut tex = self.get_aov_tex(aov)?;
+        Some(tex.numpy(py, &self.get_device(), &self.get_queue()))
+    }
     #[pyo3(text_signature = "($self) -> int")]
     pub fn get_plugin_list_ptr(&mut self) -> u64 {
         let obj_ptr: *mut Plugins = &mut self.0.plugins;
@@ -111,4 +232,12 @@ impl PyViewerHeadless {
         let v = &mut self.0;
         v.run_manual_plugins();
     }
+    /// Adds a python callable which runs every frame as `f(scene, dt)`
+    #[pyo3(text_signature = "($self, f: Callable[[Scene, float], Optional[bool]]) -> None")]
+    pub fn add_logic_system(slf: &Bound<'_, Self>, f: Py<PyAny>) {
+        let mut viewer = slf.borrow_mut();
+        if let Some(sys) = register_logic_system(&viewer.0.scene, slf.as_any(), f) {
+            viewer.0.add_logic_system(sys);
+        }
+    }
 }
@@ -0,0 +1,85 @@
+"""
+Python handles to entities, scenes and cameras must never dangle. Run with
+`pytest bindings/gloss_py/tests` after installing the wheel
+"""
+
+import gc
+
+import numpy as np
+import pytest
+
+from gloss import StaleHandleError, ViewerHeadless
+from gloss.components import Verts
+
+
+def make_verts():
+    return Verts(np.random.rand(10, 3).astype(np.float32))
+
+
+def test_use_after_despawn_raises():
+    viewer = ViewerHeadless(64, 64)
+    scene = viewer.get_scene()
+    entity = scene.get_or_create_entity("mesh")
+    entity.insert(make_verts())
+    assert entity.is_alive()
+    assert entity.has(Verts)
+
+    scene.remove_entity("mesh")
+    assert not entity.is_alive()
+    with pytest.raises(StaleHandleError):
+        entity.get(Verts)
+    with pytest.raises(StaleHandleError):
+        entity.has(Verts)
+    with pytest.raises(StaleHandleError):
+        entity.insert(make_verts())
+    with pytest.raises(StaleHandleError):
+        entity.remove(Verts)
+    with pytest.raises(StaleHandleError):
+        entity.apply_model_matrix()
+
+
+def test_stale_handle_is_a_runtime_error():
+    viewer = ViewerHeadless(64, 64)
+    entity = viewer.get_or_create_entity("mesh")
+    viewer.get_scene().remove_entity("mesh")
+    with pytest.raises(RuntimeError):
+        entity.get(Verts)
+
+
+def test_despawned_handle_does_not_alias_new_entity():
+    viewer = ViewerHeadless(64, 64)
+    scene = viewer.get_scene()
+    old = scene.get_or_create_entity("mesh")
+    scene.remove_entity("mesh")
+    # the new entity may reuse the slot of the old one but with a new generation
+    new = scene.get_or_create_entity("mesh")
+    new.insert(make_verts())
+    assert new.is_alive()
+    assert not old.is_alive()
+    with pytest.raises(StaleHandleError):
+        old.get(Verts)
+
+
+def test_handles_keep_viewer_alive():
+    viewer = ViewerHeadless(64, 64)
+    scene = viewer.get_scene()
+    entity = viewer.get_or_create_entity("mesh")
+    camera = viewer.get_camera()
+    del viewer
+    gc.collect()
+
+    entity.insert(make_verts())
+    assert entity.get(Verts).numpy().shape == (10, 3)
+    assert scene.get_or_create_entity("mesh").has(Verts)
+    assert len(camera.get_near_far()) == 2
+
+
+def test_query_returns_live_handles():
+    viewer = ViewerHeadless(64, 64)
+    scene = viewer.get_scene()
+    for i in range(3):
+        scene.get_or_create_entity(f"mesh_{i}").insert(make_verts())
+    scene.remove_entity("mesh_1")
+    results = scene.query([Verts])
+    assert len(results) == 2
+    assert all(entity.is_alive() for entity, _verts in results)
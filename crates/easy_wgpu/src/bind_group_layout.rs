@@ -95,6 +95,27 @@ impl BindGroupLayoutBuilder {
         self
     }
 
+    /// Same as ``add_entry_tex`` but allows binding a multisampled texture
+    /// # Panics
+    /// Will panic if the builder was not constructed with ``new()``
+    #[must_use]
+    pub fn add_entry_tex_multisampled(mut self, visibility: wgpu::ShaderStages, sample_type: wgpu::TextureSampleType, multisampled: bool) -> Self {
+        let binding_number = self.last_binding_number;
+        let entry = wgpu::BindGroupLayoutEntry {
+            binding: binding_number, //matches with the @binding in the shader
+            visibility,
+            ty: wgpu::BindingType::Texture {
+                multisampled,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type,
+            },
+            count: None,
+        };
+        self.layout_desc.as_mut().unwrap().entries.push(entry);
+        self.last_binding_number += 1;
+        self
+    }
+
     /// # Panics
     /// Will panic if the builder was not constructed with ``new()``
     #[must_use]
@@ -180,6 +201,31 @@ impl BindGroupLayoutBuilder {
         self
     }
 
+    /// Storage buffer that is read only or read-write in the shader, usually
+    /// for compute passes
+    /// # Panics
+    /// Will panic if the builder was not constructed with ``new()``
+    #[must_use]
+    pub fn add_entry_storage(mut self, visibility: wgpu::ShaderStages, read_only: bool) -> Self {
+        //each entry we add will have sequential binding_indices
+        //this should correspond with the binding in the shader
+        let binding_number = self.last_binding_number;
+        let entry = wgpu::BindGroupLayoutEntry {
+            binding: binding_number, //----- keep in sync with the binding in create_bind_group and also the shader
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        //add
+        self.layout_desc.as_mut().unwrap().entries.push(entry);
+        self.last_binding_number += 1;
+        self
+    }
+
     /// # Panics
     /// Will panic if the builder was not constructed with ``new()``
     pub fn build(&mut self) -> BindGroupLayoutDesc {
@@ -1,3 +1,5 @@
+use crate::gpu_profiler::GpuProfiler;
+
 /// Convenience function for passing around gpu-related data liek device and
 /// queue
 #[derive(Debug)]
@@ -10,17 +12,20 @@ pub struct Gpu {
     queue: wgpu::Queue,
     device: wgpu::Device,
     limits: wgpu::Limits,
+    profiler: GpuProfiler,
 }
 
 impl Gpu {
     pub fn new(adapter: wgpu::Adapter, instance: wgpu::Instance, device: wgpu::Device, queue: wgpu::Queue) -> Self {
         let limits = adapter.limits();
+        let profiler = GpuProfiler::new(&device, &queue);
         Self {
             adapter,
             instance,
             queue,
             device,
             limits,
+            profiler,
         }
     }
 
@@ -43,4 +48,8 @@ impl Gpu {
     pub fn limits(&self) -> &wgpu::Limits {
         &self.limits
     }
+
+    pub fn profiler(&self) -> &GpuProfiler {
+        &self.profiler
+    }
 }
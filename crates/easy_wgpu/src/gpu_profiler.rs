@@ -0,0 +1,382 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+use log::warn;
+
+//two timestamps per scope
+const MAX_QUERIES: u32 = 256;
+//frames whose timestamps are still being read back. More than one so that we never have to wait for the gpu
+const NR_READBACKS: usize = 3;
+
+/// Average gpu time of a scope over the last frames
+#[derive(Clone, Debug)]
+pub struct GpuTiming {
+    pub name: String,
+    /// Nesting level of the scope, 0 for scopes that are not inside another
+    /// one
+    pub depth: u32,
+    pub avg_ms: f32,
+    pub min_ms: f32,
+    pub max_ms: f32,
+}
+
+#[derive(Clone, Debug)]
+struct Scope {
+    name: String,
+    depth: u32,
+    begin: u32,
+    end: Option<u32>,
+}
+
+#[derive(Debug)]
+struct Readback {
+    buffer: wgpu::Buffer,
+    scopes: Vec<Scope>,
+    nr_queries: u32,
+    mapped: Arc<AtomicBool>,
+    in_flight: bool,
+}
+
+#[derive(Debug)]
+struct ProfilerState {
+    enabled: bool,
+    nr_frames_to_average: usize,
+    next_query: u32,
+    //scopes recorded in the current frame in the order in which they begin
+    scopes: Vec<Scope>,
+    //indices into scopes of the ones that have not ended yet. None for scopes that didn't get a query
+    open_scopes: Vec<Option<usize>>,
+    readbacks: Vec<Readback>,
+    //durations of the last frames for every scope
+    history: HashMap<String, VecDeque<f32>>,
+    //layout of the last frame that was read back, used for ordering and nesting of the timings
+    last_layout: Vec<(String, u32)>,
+}
+
+struct ProfilerGpuData {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    timestamp_period: f32,
+    inside_passes: bool,
+}
+
+/// Measures the time that the gpu spends on scopes of work using timestamp
+/// queries. Scopes are recorded in command encoders or inside render passes
+/// and can be nested. The timestamps are read back a few frames later without
+/// stalling the gpu and averaged over the last frames.
+///
+/// The profiler is cheap to clone and all the clones share the same state so
+/// it can be given to objects that record gpu work on their own, like the
+/// [`RenderMipmapGenerator`](crate::mipmap::RenderMipmapGenerator). It starts
+/// disabled and does nothing if the device doesn't support
+/// `TIMESTAMP_QUERY_INSIDE_ENCODERS`.
+#[derive(Clone)]
+pub struct GpuProfiler {
+    gpu_data: Option<Arc<ProfilerGpuData>>,
+    state: Arc<Mutex<ProfilerState>>,
+}
+
+impl std::fmt::Debug for GpuProfiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuProfiler")
+            .field("is_supported", &self.is_supported())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl GpuProfiler {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let features = device.features();
+        let gpu_data = features.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS).then(|| {
+            let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("GpuProfiler queries"),
+                ty: wgpu::QueryType::Timestamp,
+                count: MAX_QUERIES,
+            });
+            let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("GpuProfiler resolve"),
+                size: u64::from(MAX_QUERIES) * 8,
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            Arc::new(ProfilerGpuData {
+                query_set,
+                resolve_buffer,
+                timestamp_period: queue.get_timestamp_period(),
+                inside_passes: features.contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES),
+            })
+        });
+        let readbacks = if gpu_data.is_some() {
+            (0..NR_READBACKS)
+                .map(|_| Readback {
+                    buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("GpuProfiler readback"),
+                        size: u64::from(MAX_QUERIES) * 8,
+                        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),
+                    scopes: Vec::new(),
+                    nr_queries: 0,
+                    mapped: Arc::new(AtomicBool::new(false)),
+                    in_flight: false,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            gpu_data,
+            state: Arc::new(Mutex::new(ProfilerState {
+                enabled: false,
+                nr_frames_to_average: 60,
+                next_query: 0,
+                scopes: Vec::new(),
+                open_scopes: Vec::new(),
+                readbacks,
+                history: HashMap::new(),
+                last_layout: Vec::new(),
+            })),
+        }
+    }
+
+    /// Whether the device supports the timestamp queries needed for profiling
+    pub fn is_supported(&self) -> bool {
+        self.gpu_data.is_some()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_supported() && self.state().enabled
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        let mut state = self.state();
+        if state.enabled != enabled {
+            state.history.clear();
+            state.last_layout.clear();
+        }
+        state.enabled = enabled;
+    }
+
+    /// Number of frames over which the timings are averaged
+    pub fn set_nr_frames_to_average(&self, nr_frames: usize) {
+        self.state().nr_frames_to_average = nr_frames.max(1);
+    }
+
+    /// Reads back the timestamps of previous frames that are ready and starts
+    /// recording a new frame. Has to be called before any scope of the frame
+    pub fn begin_frame(&self, device: &wgpu::Device) {
+        let Some(gpu_data) = &self.gpu_data else {
+            return;
+        };
+        let mut state = self.state();
+        if !state.enabled {
+            return;
+        }
+        let _ = device.poll(wgpu::Maintain::Poll);
+        state.collect_finished(gpu_data.timestamp_period);
+        state.next_query = 0;
+        state.scopes.clear();
+        if !state.open_scopes.is_empty() {
+            warn!("GpuProfiler: {} scopes were not ended in the previous frame", state.open_scopes.len());
+            state.open_scopes.clear();
+        }
+    }
+
+    /// Resolves the timestamps of the frame and schedules them for reading
+    /// back. Has to be called after all the work of the frame was submitted
+    pub fn end_frame(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let Some(gpu_data) = &self.gpu_data else {
+            return;
+        };
+        let mut state = self.state();
+        let nr_queries = state.next_query;
+        if !state.enabled || nr_queries == 0 {
+            return;
+        }
+        //if all the readbacks are still in flight the gpu is far behind and we just skip this frame
+        let scopes = state.scopes.clone();
+        let Some(readback) = state.readbacks.iter_mut().find(|r| !r.in_flight) else {
+            return;
+        };
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("GpuProfiler resolve"),
+        });
+        encoder.resolve_query_set(&gpu_data.query_set, 0..nr_queries, &gpu_data.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&gpu_data.resolve_buffer, 0, &readback.buffer, 0, u64::from(nr_queries) * 8);
+        queue.submit(Some(encoder.finish()));
+
+        readback.scopes = scopes;
+        readback.nr_queries = nr_queries;
+        readback.in_flight = true;
+        readback.mapped.store(false, Ordering::Release);
+        let mapped = readback.mapped.clone();
+        readback.buffer.slice(..).map_async(wgpu::MapMode::Read, move |res| {
+            mapped.store(res.is_ok(), Ordering::Release);
+        });
+    }
+
+    /// Starts a scope by writing a timestamp into the encoder. Every call has
+    /// to be matched by a call to [`GpuProfiler::end_scope`]
+    pub fn begin_scope(&self, name: &str, encoder: &mut wgpu::CommandEncoder) {
+        if let Some((gpu_data, query)) = self.begin_scope_query(name, true) {
+            encoder.write_timestamp(&gpu_data.query_set, query);
+        }
+    }
+
+    pub fn end_scope(&self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some((gpu_data, query)) = self.end_scope_query() {
+            encoder.write_timestamp(&gpu_data.query_set, query);
+        }
+    }
+
+    /// Starts a scope inside a render pass, for example around a single
+    /// pipeline. Does nothing if the device doesn't support
+    /// `TIMESTAMP_QUERY_INSIDE_PASSES`
+    pub fn begin_pass_scope(&self, name: &str, pass: &mut wgpu::RenderPass<'_>) {
+        let inside_passes = self.gpu_data.as_ref().is_some_and(|d| d.inside_passes);
+        if let Some((gpu_data, query)) = self.begin_scope_query(name, inside_passes) {
+            pass.write_timestamp(&gpu_data.query_set, query);
+        }
+    }
+
+    pub fn end_pass_scope(&self, pass: &mut wgpu::RenderPass<'_>) {
+        if let Some((gpu_data, query)) = self.end_scope_query() {
+            pass.write_timestamp(&gpu_data.query_set, query);
+        }
+    }
+
+    /// Starts a scope for work that is not recorded in one of our encoders,
+    /// like `queue.write_buffer`. It submits a small command buffer with the
+    /// timestamp so everything submitted after it falls in the scope
+    pub fn begin_scope_submit(&self, name: &str, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if self.is_enabled() {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(name) });
+            self.begin_scope(name, &mut encoder);
+            queue.submit(Some(encoder.finish()));
+        }
+    }
+
+    pub fn end_scope_submit(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if self.is_enabled() {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+            self.end_scope(&mut encoder);
+            queue.submit(Some(encoder.finish()));
+        }
+    }
+
+    /// Timings averaged over the last frames, in the order in which the scopes
+    /// were recorded. Since the timestamps are read back asynchronously they
+    /// lag a few frames behind
+    pub fn timings(&self) -> Vec<GpuTiming> {
+        let state = self.state();
+        state
+            .last_layout
+            .iter()
+            .filter_map(|(name, depth)| {
+                let history = state.history.get(name)?;
+                if history.is_empty() {
+                    return None;
+                }
+                #[allow(clippy::cast_precision_loss)]
+                let avg_ms = history.iter().sum::<f32>() / history.len() as f32;
+                Some(GpuTiming {
+                    name: name.clone(),
+                    depth: *depth,
+                    avg_ms,
+                    min_ms: history.iter().copied().fold(f32::MAX, f32::min),
+                    max_ms: history.iter().copied().fold(0.0, f32::max),
+                })
+            })
+            .collect()
+    }
+
+    fn state(&self) -> MutexGuard<'_, ProfilerState> {
+        //a panic while holding the lock only leaves us with some wrong timings so we don't propagate the poisoning
+        self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn begin_scope_query(&self, name: &str, supported: bool) -> Option<(&ProfilerGpuData, u32)> {
+        let gpu_data = self.gpu_data.as_deref()?;
+        let mut state = self.state();
+        if !state.enabled {
+            return None;
+        }
+        //the scope is pushed even if it gets no query so that the matching end pops the correct one
+        if !supported || state.next_query + 2 > MAX_QUERIES {
+            state.open_scopes.push(None);
+            return None;
+        }
+        let query = state.next_query;
+        state.next_query += 2;
+        let depth = u32::try_from(state.open_scopes.len()).unwrap_or(u32::MAX);
+        let idx = state.scopes.len();
+        state.scopes.push(Scope {
+            name: name.to_string(),
+            depth,
+            begin: query,
+            end: None,
+        });
+        state.open_scopes.push(Some(idx));
+        Some((gpu_data, query))
+    }
+
+    fn end_scope_query(&self) -> Option<(&ProfilerGpuData, u32)> {
+        let gpu_data = self.gpu_data.as_deref()?;
+        let mut state = self.state();
+        if !state.enabled {
+            return None;
+        }
+        let idx = state.open_scopes.pop()??;
+        let scope = &mut state.scopes[idx];
+        let query = scope.begin + 1;
+        scope.end = Some(query);
+        Some((gpu_data, query))
+    }
+}
+
+impl ProfilerState {
+    fn collect_finished(&mut self, timestamp_period: f32) {
+        let nr_frames_to_average = self.nr_frames_to_average;
+        for readback in &mut self.readbacks {
+            if !readback.in_flight || !readback.mapped.load(Ordering::Acquire) {
+                continue;
+            }
+            {
+                let data = readback.buffer.slice(..u64::from(readback.nr_queries) * 8).get_mapped_range();
+                let timestamps: Vec<u64> = data.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+                //scopes with the same name in one frame, like the mipmaps of several textures, are added together
+                let mut layout: Vec<(String, u32)> = Vec::with_capacity(readback.scopes.len());
+                let mut frame_ms: HashMap<&str, f32> = HashMap::new();
+                for scope in &readback.scopes {
+                    let Some(end) = scope.end else {
+                        continue;
+                    };
+                    let ticks = timestamps[end as usize].saturating_sub(timestamps[scope.begin as usize]);
+                    #[allow(clippy::cast_precision_loss)]
+                    let ms = ticks as f32 * timestamp_period / 1e6;
+                    let total = frame_ms.entry(&scope.name).or_insert_with(|| {
+                        layout.push((scope.name.clone(), scope.depth));
+                        0.0
+                    });
+                    *total += ms;
+                }
+                for (name, ms) in frame_ms {
+                    let history = self.history.entry(name.to_string()).or_default();
+                    history.push_back(ms);
+                    while history.len() > nr_frames_to_average {
+                        history.pop_front();
+                    }
+                }
+                self.last_layout = layout;
+            }
+            readback.buffer.unmap();
+            readback.in_flight = false;
+        }
+    }
+}
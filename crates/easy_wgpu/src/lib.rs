@@ -28,6 +28,7 @@ pub mod bind_group_layout;
 // pub mod egui_renderer;
 pub mod framebuffer;
 pub mod gpu;
+pub mod gpu_profiler;
 pub mod pipeline;
 // pub mod render_pass; //DOES NOT work because we return something that has to
 // live within the current context, also it does not save us much coding space
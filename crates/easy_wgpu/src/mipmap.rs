@@ -1,7 +1,7 @@
 //mostly from
 // https://github.com/jshrake/wgpu-mipmap/blob/main/src/backends/render.rs
 
-use crate::utils::get_mip_extent;
+use crate::{gpu_profiler::GpuProfiler, utils::get_mip_extent};
 use std::collections::HashMap;
 use thiserror::Error;
 use wgpu::{
@@ -18,6 +18,7 @@ pub struct RenderMipmapGenerator {
     sampler: Sampler,
     layout_cache: HashMap<TextureSampleType, BindGroupLayout>,
     pipeline_cache: HashMap<TextureFormat, RenderPipeline>,
+    profiler: Option<GpuProfiler>,
 }
 
 #[allow(clippy::match_same_arms)]
@@ -247,9 +248,17 @@ impl RenderMipmapGenerator {
             sampler,
             layout_cache: render_layout_cache,
             pipeline_cache: render_pipeline_cache,
+            profiler: None,
         }
     }
 
+    /// Measures the gpu time of the mipmap generation with the profiler
+    #[must_use]
+    pub fn with_profiler(mut self, profiler: GpuProfiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
     /// Generate mipmaps from level 0 of `src_texture` to
     /// levels `dst_mip_offset..dst_texture_descriptor.mip_level_count`
     // of `dst_texture`.
@@ -380,7 +389,14 @@ impl RenderMipmapGenerator {
         texture: &Texture,
         texture_descriptor: &TextureDescriptor,
     ) -> Result<(), Error> {
-        self.generate_src_dst(device, encoder, texture, texture, texture_descriptor, texture_descriptor, 0)
+        if let Some(profiler) = &self.profiler {
+            profiler.begin_scope("Mipmaps", encoder);
+        }
+        let res = self.generate_src_dst(device, encoder, texture, texture, texture_descriptor, texture_descriptor, 0);
+        if let Some(profiler) = &self.profiler {
+            profiler.end_scope(encoder);
+        }
+        res
     }
 }
 
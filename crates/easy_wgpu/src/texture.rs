@@ -826,7 +826,7 @@ impl Texture {
         // DynImage::new_luma32f(self.width(), self.height()),     _ => panic!("
         // Texture format not implemented!"), };
 
-        let img: Option<DynImage> = {
+        let img: DynImage = {
             let buffer_slice = output_buffer.slice(..);
 
             // NOTE: We have to create the mapping THEN device.poll() before await
@@ -846,27 +846,44 @@ impl Texture {
             // https://github.com/rerun-io/rerun/blob/93146b6d04f8f494258901c8b892eee0bb31b1a8/crates/re_renderer/src/texture_info.rs#L57
             let data_unpadded = Texture::remove_padding(data.as_bytes(), bytes_per_row_unpadded, bytes_per_row_padded, self.height());
 
-            // let copy_from = data_unpadded.as_bytes();
-            // buffer.copy_from_bytes(self.width(), self.height(), copy_from);
-            let w = self.width();
-            let h = self.height();
-            match self.texture.format() {
-                TextureFormat::Rgba8Unorm => ImageBuffer::from_raw(w, h, data_unpadded.to_vec()).map(DynImage::ImageRgba8),
-                TextureFormat::Bgra8Unorm => {
-                    let bgra_data = data_unpadded.to_vec();
-                    // Convert BGRA to RGBA by swapping channels
-                    let mut rgba_data = bgra_data.clone();
-                    for chunk in rgba_data.chunks_exact_mut(4) {
-                        chunk.swap(0, 2); // Swap B and R
-                    }
-                    ImageBuffer::from_raw(w, h, rgba_data).map(DynImage::ImageRgba8)
+            Texture::img_from_bytes(&data_unpadded, self.width(), self.height(), self.texture.format())
+        };
+        output_buffer.unmap();
+        img
+    }
+
+    /// Creates a [`DynImage`] from the raw bytes of a texture with the given
+    /// format. The bytes are expected to have no row padding, see
+    /// [`Texture::remove_padding`]. Bgra textures get their channels swapped to
+    /// Rgba and Rg32Float textures get padded to Rgb with a zero blue channel.
+    /// # Panics
+    /// Will panic if the format is not implemented or the bytes don't match the
+    /// size of the image
+    pub fn img_from_bytes(data: &[u8], w: u32, h: u32, format: TextureFormat) -> DynImage {
+        let img = match format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => ImageBuffer::from_raw(w, h, data.to_vec()).map(DynImage::ImageRgba8),
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                // Convert BGRA to RGBA by swapping channels
+                let mut rgba_data = data.to_vec();
+                for chunk in rgba_data.chunks_exact_mut(4) {
+                    chunk.swap(0, 2); // Swap B and R
                 }
-                TextureFormat::Rgba32Float => ImageBuffer::from_raw(w, h, numerical::u8_to_f32_vec(&data_unpadded)).map(DynImage::ImageRgba32F),
-                TextureFormat::Depth32Float => ImageBuffer::from_raw(w, h, numerical::u8_to_f32_vec(&data_unpadded)).map(DynImage::ImageLuma32F),
-                x => panic!("Texture format not implemented! {x:?}"),
+                ImageBuffer::from_raw(w, h, rgba_data).map(DynImage::ImageRgba8)
+            }
+            TextureFormat::Rgba32Float => ImageBuffer::from_raw(w, h, numerical::u8_to_f32_vec(data)).map(DynImage::ImageRgba32F),
+            TextureFormat::Depth32Float | TextureFormat::R32Float => {
+                ImageBuffer::from_raw(w, h, numerical::u8_to_f32_vec(data)).map(DynImage::ImageLuma32F)
             }
+            TextureFormat::Rg32Float => {
+                //there is no two channel float image so we pad it to rgb with the last channel set to zero
+                let rgb_data: Vec<f32> = numerical::u8_to_f32_vec(data)
+                    .chunks_exact(2)
+                    .flat_map(|rg| [rg[0], rg[1], 0.0])
+                    .collect();
+                ImageBuffer::from_raw(w, h, rgb_data).map(DynImage::ImageRgb32F)
+            }
+            x => panic!("Texture format not implemented! {x:?}"),
         };
-        output_buffer.unmap();
         img.unwrap()
     }
 
@@ -0,0 +1,75 @@
+// Fragment shader
+//group 0, binding 0 = scene
+//group 0, binding 1 = camera
+//group 0, binding 2 = lights
+//group 0, binding 3 = params
+//group 0, binding 4 = sampler_nearest
+//group 0, binding 5 = sampler_linear
+//group 1, binding 0 Locals
+//group 1, binding 1 diffuse_tex
+
+#import ./types/global_types.wgsl as GlobalTypes
+#import aov_mesh_vert.wgsl as VertShader
+#import ./utils/clip_utils.wgsl as ClipUtils
+
+//group 0
+#import ./bindings/global_binds.wgsl as GlobalBinds
+
+//group 1
+@group(1) @binding(0) var<uniform> locals : VertShader::Locals;
+@group(1) @binding(1) var t_diffuse: texture_2d<f32>;
+
+//the location of each output is the index of the aov in the Aov enum. Outputs without a render target are discarded by the pipeline
+struct AovOutput {
+    @location(0) normals_world: vec4<f32>,
+    @location(1) normals_cam: vec4<f32>,
+    @location(2) instance_id: vec4<f32>,
+    @location(3) uv: vec4<f32>,
+    @location(4) motion_vectors: vec4<f32>,
+    @location(5) albedo: vec4<f32>,
+}
+
+@fragment
+fn fs_main(in: VertShader::VertexOutput) -> AovOutput {
+    var out: AovOutput;
+
+    //the parts cut away by the clip planes are not in the aovs either
+    if locals.clip > 0u && ClipUtils::is_clipped(in.pos_world) {
+        discard;
+    }
+
+    let normal_world = normalize(in.normal_world); //need to normalize because interpolation across triangle might mess things up
+    let normal_cam = normalize((GlobalBinds::camera.view * vec4<f32>(normal_world, 0.0)).xyz);
+    out.normals_world = vec4<f32>(normal_world, 1.0);
+    out.normals_cam = vec4<f32>(normal_cam, 1.0);
+
+    out.instance_id = vec4<f32>(locals.instance_id, 0.0, 0.0, 0.0);
+    out.uv = vec4<f32>(in.uv, 0.0, 0.0);
+
+    //displacement in pixels between where the point is now and where it was in the previous frame, with y going down like the image rows
+    let ndc_cur = in.clip_cur.xy / in.clip_cur.w;
+    let ndc_prev = in.clip_prev.xy / in.clip_prev.w;
+    let motion = (ndc_cur - ndc_prev) * 0.5 * vec2<f32>(GlobalBinds::camera.width, -GlobalBinds::camera.height);
+    out.motion_vectors = vec4<f32>(motion, 0.0, 0.0);
+
+    //same as the albedo of the mesh shader
+    var albedo = vec4<f32>(0.0);
+    if locals.color_type==0{
+        albedo = locals.solid_color;
+    }else if locals.color_type==1{
+        albedo = vec4<f32>(in.color,1.0);
+    }else if locals.color_type==2{
+        let dims_diffuse = vec2<f32>(textureDimensions(t_diffuse));
+        albedo = textureSample(t_diffuse, GlobalBinds::sampler_linear, in.tex_coords);
+        if dims_diffuse.x<=4.0 &&dims_diffuse.y<=4.0 {
+            albedo = locals.solid_color;
+        }
+    }else if locals.color_type==3{
+        albedo = vec4<f32>(in.tex_coords, 0.0, 1.0);
+    }else if locals.color_type==4{
+        albedo = vec4<f32>(normal_world*0.5f+0.5f, 1.0);
+    }
+    out.albedo = albedo;
+
+    return out;
+}
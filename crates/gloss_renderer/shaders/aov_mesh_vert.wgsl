@@ -0,0 +1,67 @@
+// Vertex shader
+//group 0, binding 0 = scene
+//group 0, binding 1 = camera
+//group 0, binding 2 = lights
+//group 0, binding 3 = params
+//group 0, binding 4 = sampler_nearest
+//group 0, binding 5 = sampler_linear
+//group 1, binding 0 Locals
+//group 1, binding 1 diffuse_tex
+#import ./types/global_types.wgsl as GlobalTypes
+
+//group 0
+#import ./bindings/global_binds.wgsl as GlobalBinds
+
+
+struct Locals {
+  model_matrix : mat4x4<f32>,
+  prev_mvp : mat4x4<f32>, //model-view-projection of the previous frame, used for motion vectors
+  solid_color: vec4<f32>,
+  color_type: i32,
+  uv_scale: f32,
+  instance_id: f32,
+  clip: u32, //whether the clip planes cut this mesh
+};
+
+
+//group 1
+@group(1) @binding(0) var<uniform> locals : Locals;
+@group(1) @binding(1) var t_diffuse: texture_2d<f32>;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normals: vec3<f32>,
+    @location(3) colors: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>, //uv as stored in the mesh
+    @location(1) tex_coords: vec2<f32>, //uv used for sampling the textures
+    @location(2) normal_world: vec3<f32>,
+    @location(3) color: vec3<f32>,
+    @location(4) clip_cur: vec4<f32>,
+    @location(5) clip_prev: vec4<f32>,
+    @location(6) pos_world: vec3<f32>,
+}
+
+
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = model.tex_coords;
+    out.tex_coords = vec2<f32>(model.tex_coords.x, 1.0-model.tex_coords.y) * locals.uv_scale;
+    out.normal_world = normalize((locals.model_matrix * vec4<f32>(model.normals, 0.0)).xyz);
+    out.color = model.colors;
+    let pos_world = locals.model_matrix * vec4<f32>(model.position, 1.0);
+    out.pos_world = pos_world.xyz;
+    out.clip_position = GlobalBinds::camera.proj * GlobalBinds::camera.view * pos_world;
+    out.clip_cur = out.clip_position;
+    out.clip_prev = locals.prev_mvp * vec4<f32>(model.position, 1.0);
+
+    return out;
+}
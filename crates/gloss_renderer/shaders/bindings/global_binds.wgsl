@@ -7,4 +7,5 @@
 @group(0) @binding(3) var<uniform> params : GlobalTypes::Params;
 @group(0) @binding(4) var sampler_nearest: sampler;
 @group(0) @binding(5) var sampler_linear: sampler;
-@group(0) @binding(6) var sampler_shadow_map: sampler_comparison;
\ No newline at end of file
+@group(0) @binding(6) var sampler_shadow_map: sampler_comparison;
+@group(0) @binding(7) var<uniform> clip_planes : array<GlobalTypes::ClipPlane,8>;
@@ -0,0 +1,56 @@
+//evaluates the blend shapes and the linear blend skinning of one vertex per invocation and writes the result in the vertex buffer
+// used for rendering. Keep in sync with Geom::apply_blend_shapes and Geom::skin_verts
+
+const MAX_JOINT_INFLUENCES: u32 = 4u;
+
+struct DeformParams {
+    nr_verts: u32,
+    nr_shapes: u32,
+    nr_joints: u32, //zero if the entity has no skinning
+    nr_faces: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: DeformParams;
+//all the vertex data is packed as 3 floats per vertex so we read them as flat arrays instead of vec3 which would be padded to 16 bytes
+@group(0) @binding(1) var<storage, read> rest_verts: array<f32>;
+@group(0) @binding(2) var<storage, read> basis: array<f32>; //offsets of shape k for vertex i start at ((k*nr_verts+i)*3)
+@group(0) @binding(3) var<storage, read> coeffs: array<f32>;
+@group(0) @binding(4) var<storage, read> joints: array<u32>;
+@group(0) @binding(5) var<storage, read> weights: array<f32>;
+@group(0) @binding(6) var<storage, read> joint_transforms: array<mat4x4<f32>>;
+@group(0) @binding(7) var<storage, read_write> out_verts: array<f32>;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let idx = id.x;
+    if idx >= params.nr_verts {
+        return;
+    }
+
+    var p = vec3<f32>(rest_verts[idx * 3u], rest_verts[idx * 3u + 1u], rest_verts[idx * 3u + 2u]);
+
+    //blend shapes
+    for (var k = 0u; k < params.nr_shapes; k++) {
+        let coeff = coeffs[k];
+        if coeff != 0.0 {
+            let offset = (k * params.nr_verts + idx) * 3u;
+            p += coeff * vec3<f32>(basis[offset], basis[offset + 1u], basis[offset + 2u]);
+        }
+    }
+
+    //skinning
+    if params.nr_joints > 0u {
+        var blended = mat4x4<f32>(vec4<f32>(0.0), vec4<f32>(0.0), vec4<f32>(0.0), vec4<f32>(0.0));
+        for (var slot = 0u; slot < MAX_JOINT_INFLUENCES; slot++) {
+            let joint = joints[idx * MAX_JOINT_INFLUENCES + slot];
+            if joint < params.nr_joints {
+                blended += joint_transforms[joint] * weights[idx * MAX_JOINT_INFLUENCES + slot];
+            }
+        }
+        p = (blended * vec4<f32>(p, 1.0)).xyz;
+    }
+
+    out_verts[idx * 3u] = p.x;
+    out_verts[idx * 3u + 1u] = p.y;
+    out_verts[idx * 3u + 2u] = p.z;
+}
@@ -0,0 +1,47 @@
+//recomputes the normals of the deformed vertices as the area weighted average of the normals of the faces around each vertex, the
+// same as Geom::compute_per_vertex_normals with PerVertexNormalsWeightingType::Area. Every vertex loops over its own faces so we don't
+// need atomics to accumulate
+
+struct DeformParams {
+    nr_verts: u32,
+    nr_shapes: u32,
+    nr_joints: u32,
+    nr_faces: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: DeformParams;
+@group(0) @binding(1) var<storage, read> verts: array<f32>;
+@group(0) @binding(2) var<storage, read> faces: array<u32>;
+@group(0) @binding(3) var<storage, read> vert2faces_offsets: array<u32>; //faces of vertex i are vert2faces[offsets[i]..offsets[i+1]]
+@group(0) @binding(4) var<storage, read> vert2faces: array<u32>;
+@group(0) @binding(5) var<storage, read_write> out_normals: array<f32>;
+
+fn vert(idx: u32) -> vec3<f32> {
+    return vec3<f32>(verts[idx * 3u], verts[idx * 3u + 1u], verts[idx * 3u + 2u]);
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let idx = id.x;
+    if idx >= params.nr_verts {
+        return;
+    }
+
+    var normal = vec3<f32>(0.0);
+    for (var i = vert2faces_offsets[idx]; i < vert2faces_offsets[idx + 1u]; i++) {
+        let face = vert2faces[i];
+        let v0 = vert(faces[face * 3u]);
+        let v1 = vert(faces[face * 3u + 1u]);
+        let v2 = vert(faces[face * 3u + 2u]);
+        //the length of the cross product is twice the area so this is already weighted
+        normal += cross(v1 - v0, v2 - v0);
+    }
+    let len = length(normal);
+    if len > 0.0 {
+        normal = normal / len;
+    }
+
+    out_normals[idx * 3u] = normal.x;
+    out_normals[idx * 3u + 1u] = normal.y;
+    out_normals[idx * 3u + 2u] = normal.z;
+}
@@ -0,0 +1,70 @@
+// Fast approximate anti-aliasing (https://developer.download.nvidia.com/assets/gamedev/files/sdk/11/FXAA_WhitePaper.pdf). Finds the
+// direction of the edges from the luma of the neighbouring pixels and blurs along them. The taps are done with loads so that it also
+// works for the Rgba32Float textures which are not filterable
+
+#import ./utils/full_screen_tri_utils.wgsl as Tri
+#import ./utils/tex_utils.wgsl as TexUtils
+
+const EDGE_THRESHOLD_MIN: f32 = 0.0312;
+const EDGE_THRESHOLD_MAX: f32 = 0.125;
+const REDUCE_MIN: f32 = 1.0 / 128.0;
+const REDUCE_MUL: f32 = 1.0 / 8.0;
+const SPAN_MAX: f32 = 8.0;
+
+@group(0) @binding(0) var t_color: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = Tri::full_screen_tri(vertex_index).pos;
+    return out;
+}
+
+fn luma(color: vec4<f32>) -> f32 {
+    return dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+fn load(px: vec2<i32>) -> vec4<f32> {
+    let max_px = vec2<i32>(textureDimensions(t_color)) - 1;
+    return textureLoad(t_color, clamp(px, vec2<i32>(0), max_px), 0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let px = vec2<i32>(floor(in.position.xy));
+    let center = load(px);
+
+    let luma_m = luma(center);
+    let luma_nw = luma(load(px + vec2<i32>(-1, -1)));
+    let luma_ne = luma(load(px + vec2<i32>(1, -1)));
+    let luma_sw = luma(load(px + vec2<i32>(-1, 1)));
+    let luma_se = luma(load(px + vec2<i32>(1, 1)));
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    //flat regions are kept as they are
+    if luma_max - luma_min < max(EDGE_THRESHOLD_MIN, luma_max * EDGE_THRESHOLD_MAX) {
+        return center;
+    }
+
+    //direction along the edge, in pixels
+    var dir = vec2<f32>(-((luma_nw + luma_ne) - (luma_sw + luma_se)), (luma_nw + luma_sw) - (luma_ne + luma_se));
+    let dir_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    let rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+    dir = clamp(dir * rcp_dir_min, vec2<f32>(-SPAN_MAX), vec2<f32>(SPAN_MAX));
+
+    let pos = in.position.xy;
+    let color_a = 0.5 * (TexUtils::texture_load_bilinear(t_color, pos + dir * (1.0 / 3.0 - 0.5)) + TexUtils::texture_load_bilinear(t_color, pos + dir * (2.0 / 3.0 - 0.5)));
+    let color_b = color_a * 0.5 + 0.25 * (TexUtils::texture_load_bilinear(t_color, pos - dir * 0.5) + TexUtils::texture_load_bilinear(t_color, pos + dir * 0.5));
+
+    //the wider blur went past the edge so we keep the narrow one
+    let luma_b = luma(color_b);
+    if luma_b < luma_min || luma_b > luma_max {
+        return color_a;
+    }
+    return color_b;
+}
@@ -15,6 +15,8 @@
 #import ./utils/tex_utils.wgsl as TexUtils
 #import ./utils/tonemap_utils.wgsl as TonemapUtils
 #import ./utils/noise_utils.wgsl as NoiseUtils
+#import ./utils/colormap_utils.wgsl as ColormapUtils
+#import ./utils/clip_utils.wgsl as ClipUtils
 #import ./types/pbr_types.wgsl as PbrTypes
 #import ./pbr/pbr_functions.wgsl as PbrFunc
 
@@ -31,7 +33,17 @@
 @group(2) @binding(3) var t_roughness: texture_2d<f32>;
 
 @fragment
-fn fs_main(in: VertShader::VertexOutput) -> @location(0) vec4<f32> {
+fn fs_main(in: VertShader::VertexOutput, @builtin(front_facing) is_front_facing: bool) -> @location(0) vec4<f32> {
+    //section views, everything on the negative side of the clip planes is cut away. The discard happens at the end so that the texture
+    //sampling stays in uniform control flow
+    let is_clipping = locals.clip > 0u && GlobalBinds::scene.nr_clip_planes > 0u;
+    let is_clipped = is_clipping && ClipUtils::is_clipped(in.pos_world);
+    //the back faces that are visible through the cut are the inside of the mesh so they get the color of the closest capping plane
+    var cap_plane = -1;
+    if is_clipping && !is_front_facing {
+        cap_plane = ClipUtils::closest_cap_plane(in.pos_world);
+    }
+
     //process some of the input
     var normal_world = normalize(in.normal_world); //need to normalize because interpolation across triangle might mess things up
     var tangent_world = normalize(in.tangent_world); //need to normalize because interpolation across triangle might mess things up
@@ -52,6 +64,24 @@ fn fs_main(in: VertShader::VertexOutput) -> @location(0) vec4<f32> {
         albedo = vec4<f32>(in.tex_coords, 0.0, 1.0);
     }else if locals.color_type==4{
         albedo = vec4<f32>(normal_world*0.5f+0.5f, 1.0);
+    }else if locals.color_type==6{
+        albedo = ColormapUtils::scalar_to_color(in.scalar, locals.scalar_min, locals.scalar_max, locals.colormap, locals.scalar_clamp, locals.out_of_range_color);
+        //isolines at equally spaced values inside the range. fwidth keeps them at a constant width in screen space
+        if locals.nr_isolines>0u{
+            let t = ColormapUtils::normalize_scalar(in.scalar, locals.scalar_min, locals.scalar_max) * f32(locals.nr_isolines + 1u);
+            let t_width = max(fwidth(t), 1e-6);
+            let closest_line = round(t);
+            let dist_to_line = abs(t - closest_line) / t_width;
+            var line_factor = 1.0 - clamp(dist_to_line - locals.isoline_width * 0.5 + 0.5, 0.0, 1.0);
+            //the lines at the ends of the range are not drawn so there are exactly nr_isolines of them
+            if closest_line < 1.0 || closest_line > f32(locals.nr_isolines) {
+                line_factor = 0.0;
+            }
+            albedo = mix(albedo, locals.isoline_color, line_factor * locals.isoline_color.a);
+        }
+    }
+    if cap_plane >= 0 {
+        albedo = GlobalBinds::clip_planes[cap_plane].cap_color;
     }
 
 
@@ -60,10 +90,15 @@ fn fs_main(in: VertShader::VertexOutput) -> @location(0) vec4<f32> {
     //https://www.geeks3d.com/20130122/normal-mapping-without-precomputed-tangent-space-vectors/
     let dims_n = vec2<f32>(textureDimensions(t_normal));
     let tangent_finite = tangent_world.x==tangent_world.x; //nans are not equal to any other nan
-    let normal_world_not_perturbed=normal_world;
+    var normal_world_not_perturbed=normal_world;
     if dims_n.x>4.0 &&dims_n.y>4.0 && tangent_finite{ //makes it so we don't run this for the dummy normalmap which is 4x4. TODO However this is a bit of a hacky solution, ideally we would pass a boolean flag
         normal_world = NormalUtils::apply_tbn( normal_world, tangent_world, bitangent_world, t_normal, in.tex_coords, GlobalBinds::sampler_linear );
     }
+    //the cap is lit as if it was the plane itself, facing towards the side that was cut away
+    if cap_plane >= 0 {
+        normal_world = -GlobalBinds::clip_planes[cap_plane].plane.xyz;
+        normal_world_not_perturbed = normal_world;
+    }
 
     //roughness
     let dims_roughness = vec2<f32>(textureDimensions(t_roughness));
@@ -76,7 +111,9 @@ fn fs_main(in: VertShader::VertexOutput) -> @location(0) vec4<f32> {
     // var pbr_input
     var pbr: PbrTypes::PbrInput;
     pbr.material = PbrTypes::standard_material_new();
-    let V = normalize(GlobalBinds::camera.pos_world - in.pos_world); //TOOD this would need to change if the projection is orthographic
+    //with orthographic projection all the view rays are parallel to the camera axis
+    let is_orthographic = GlobalBinds::camera.proj[3].w == 1.0;
+    let V = select(normalize(GlobalBinds::camera.pos_world - in.pos_world), normalize(GlobalBinds::camera.view_inv[2].xyz), is_orthographic);
     pbr.world_position=vec4<f32>(in.pos_world, 1.0);
     pbr.material.perceptual_roughness = roughness;
     pbr.material.base_color = vec4(albedo.xyz, 1.0);
@@ -130,6 +167,10 @@ fn fs_main(in: VertShader::VertexOutput) -> @location(0) vec4<f32> {
     if !(GlobalBinds::params.apply_lighting >0u){
         color_tonemapped_gamma_rgba = albedo;
     }
+
+    if is_clipped {
+        discard;
+    }
     
 
     return color_tonemapped_gamma_rgba;
@@ -25,8 +25,20 @@ struct Locals {
   roughness_black_lvl: f32,
   uv_scale: f32,
   is_floor: u32,
-  pad_b: f32,
-  pad_c: f32
+  //scalar field visualization
+  scalar_min: f32,
+  scalar_max: f32,
+  colormap: u32,
+  scalar_clamp: u32,
+  nr_isolines: u32,
+  isoline_width: f32,
+  isoline_color: vec4<f32>,
+  out_of_range_color: vec4<f32>,
+  clip: u32, //should be bool but that is not host-sharable: https://www.w3.org/TR/WGSL/#host-shareable-types
+  //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
+  pad_b: u32,
+  pad_c: u32,
+  pad_d: u32,
 };
 
 
@@ -41,6 +53,7 @@ struct VertexInput {
     @location(2) normals: vec3<f32>,
     @location(3) tangents: vec4<f32>,
     @location(4) colors: vec3<f32>,
+    @location(5) scalar: f32,
 }
 
 struct VertexOutput {
@@ -54,6 +67,7 @@ struct VertexOutput {
     @location(3) tangent_world: vec3<f32>,
     @location(4) bitangent_world: vec3<f32>,
     @location(5) color: vec3<f32>,
+    @location(6) scalar: f32,
     // @location(6) view_vector: vec3<f32>, // camera pos - vertex pos
     // @location(4) pos_view: vec3<f32>,
     // @location(5) eye_vec: vec3<f32>,
@@ -75,6 +89,7 @@ fn vs_main(
     out.tex_coords = vec2<f32>(model.tex_coords.x, 1.0-model.tex_coords.y) * locals.uv_scale;
     out.normal_world = normalize((locals.model_matrix * vec4<f32>(model.normals, 0.0)).xyz); //will not get affected by translation but it will be affected by scale;
     out.color = model.colors;
+    out.scalar = model.scalar;
     // out.normal_view = normalize((GlobalBinds::camera.view * locals.model_matrix * vec4<f32>(model.normals, 0.0)).xyz); //will not get affected by translation but it will be affected by scale;
     out.clip_position = GlobalBinds::camera.proj * GlobalBinds::camera.view* locals.model_matrix * vec4<f32>(model.position, 1.0);
     // out.view_vector = GlobalBinds::camera.pos_world - pos_world;
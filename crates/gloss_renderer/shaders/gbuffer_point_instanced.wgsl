@@ -10,6 +10,10 @@
 #import ./types/global_types.wgsl as GlobalTypes
 #import ./bindings/global_binds.wgsl as GlobalBinds
 #import ./utils/tonemap_utils.wgsl as TonemapUtils
+#import ./pbr/pbr_lighting.wgsl as PbrLighting
+#import ./pbr/pbr_ambient.wgsl as PbrAmbient
+#import ./utils/colormap_utils.wgsl as ColormapUtils
+#import ./utils/clip_utils.wgsl as ClipUtils
 
 //basically the idea from https://webgpufundamentals.org/webgpu/lessons/webgpu-points.html
 //where we create a quad for every vertex by drawing indexed
@@ -21,7 +25,15 @@ struct Locals {
   point_size: f32,
   is_point_size_in_world_space: u32,
   zbuffer: u32,
-//   pad_d: f32
+  render_mode: u32,
+  has_normals: u32,
+  scalar_min: f32,
+  scalar_max: f32,
+  colormap: u32,
+  scalar_clamp: u32,
+  clip: u32,
+  pad_c: f32,
+  out_of_range_color: vec4<f32>,
 };
 
 //group 2
@@ -30,12 +42,22 @@ struct Locals {
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) colors: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) scalar: f32,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) position: vec3<f32>,
     @location(1) color: vec3<f32>,
+    @location(2) normal_world: vec3<f32>,
+    @location(3) quad_coord: vec2<f32>,
+    @location(4) scalar: f32,
+}
+
+//surfels are disks oriented along the normal of the point, which can only be drawn if the normals were uploaded
+fn is_surfel() -> bool {
+    return locals.render_mode == 1u && locals.has_normals > 0u;
 }
 
 @vertex
@@ -52,14 +74,47 @@ fn vs_main(
         vec2f( 1,  1),
     );
     var out: VertexOutput;
+    let pos = points[v_idx];
+    out.quad_coord = pos;
+    out.color = model.colors;
+    out.scalar = model.scalar;
+
+    if is_surfel() {
+        let pos_world = (locals.model_matrix * vec4<f32>(model.position, 1.0)).xyz;
+        let normal_world = normalize((locals.model_matrix * vec4<f32>(model.normal, 0.0)).xyz);
+        //any vector that is not parallel to the normal works for building the plane of the disk
+        var helper = vec3<f32>(0.0, 1.0, 0.0);
+        if abs(normal_world.y) > 0.9 {
+            helper = vec3<f32>(1.0, 0.0, 0.0);
+        }
+        let tangent = normalize(cross(helper, normal_world));
+        let bitangent = cross(normal_world, tangent);
 
+        //point_size is the diameter of the disk in pixels at the depth of the point or in world units
+        let clip_center = GlobalBinds::camera.proj * GlobalBinds::camera.view * vec4<f32>(pos_world, 1.0);
+        var radius = 0.5 * locals.point_size;
+        if locals.is_point_size_in_world_space == 0u {
+            radius = radius * 2.0 * clip_center.w / (GlobalBinds::camera.proj[1][1] * GlobalBinds::camera.height);
+        }
+        let corner_world = pos_world + (pos.x * tangent + pos.y * bitangent) * radius;
+        var clip_corner = GlobalBinds::camera.proj * GlobalBinds::camera.view * vec4<f32>(corner_world, 1.0);
+        if locals.zbuffer == 0 {
+            clip_corner.z = clip_corner.w;
+        }
+        out.clip_position = clip_corner;
+        out.position = pos_world;
+        out.normal_world = normal_world;
+        return out;
+    }
+
+    //sprites are clipped as a whole depending on where their center is
+    out.position = (locals.model_matrix * vec4<f32>(model.position, 1.0)).xyz;
     let clip_pos = GlobalBinds::camera.proj * GlobalBinds::camera.view* locals.model_matrix * vec4<f32>(model.position, 1.0);
     var clip_pos_ndc = clip_pos / clip_pos.w; 
 
     if locals.zbuffer == 0 {
         clip_pos_ndc = vec4f(clip_pos_ndc.xy, 1, 1);
     }
-    let pos = points[v_idx];
     let resolution = vec2f(GlobalBinds::camera.width, GlobalBinds::camera.height);
     // let clip_offset = vec4f(pos * locals.point_size / resolution / clip_pos.w, 0, 0);
 
@@ -69,19 +124,60 @@ fn vs_main(
     }
     let clip_offset = vec4f(pos * locals.point_size / resolution /w_coord, 0, 0);
     out.clip_position = clip_pos_ndc + clip_offset;
-    out.color = model.colors;
     
     return out;
 }
 
+//pbr lighting of the disk without shadows or environment map
+fn shade_surfel(pos_world: vec3<f32>, normal_world: vec3<f32>, albedo: vec3<f32>) -> vec3<f32> {
+    let is_orthographic = GlobalBinds::camera.proj[3].w == 1.0;
+    let V = select(normalize(GlobalBinds::camera.pos_world - pos_world), normalize(GlobalBinds::camera.view_inv[2].xyz), is_orthographic);
+    //disks are lit from both sides
+    var N = normalize(normal_world);
+    if dot(N, V) < 0.0 {
+        N = -N;
+    }
+    let perceptual_roughness = 0.5;
+    let roughness = PbrLighting::perceptualRoughnessToRoughness(perceptual_roughness);
+    let NdotV = max(dot(N, V), 0.0001);
+    let F0 = vec3<f32>(0.04); //reflectance of 0.5 for a dielectric
+    let R = reflect(-V, N);
+    let f_ab = PbrLighting::F_AB(perceptual_roughness, NdotV);
+
+    var direct_light = vec3<f32>(0.0);
+    for (var l_idx: u32 = 0u; l_idx < GlobalBinds::scene.nr_lights; l_idx++) {
+        let light = GlobalBinds::lights[l_idx];
+        //lights with orthographic projection are directional
+        if light.proj[3].w == 1.0 {
+            direct_light += PbrLighting::directional_light(light, roughness, NdotV, N, V, F0, f_ab, albedo);
+        } else {
+            direct_light += PbrLighting::spot_light(pos_world, light, roughness, NdotV, N, V, R, F0, f_ab, albedo);
+        }
+    }
+    let indirect_light = PbrAmbient::ambient_light(vec4<f32>(pos_world, 1.0), N, V, NdotV, albedo, F0, perceptual_roughness) * GlobalBinds::params.ambient_factor;
+    return direct_light + indirect_light;
+}
+
 // Fragment shader
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if is_surfel() && dot(in.quad_coord, in.quad_coord) > 1.0 {
+        discard;
+    }
+    if locals.clip > 0u && ClipUtils::is_clipped(in.position) {
+        discard;
+    }
+
     var color_linear = vec4<f32>(0.0);
     if locals.color_type==0 {
         color_linear = locals.point_color;
     } else if locals.color_type==1 {
         color_linear = vec4<f32>(in.color,1.0);
+    } else if locals.color_type==2 {
+        color_linear = ColormapUtils::scalar_to_color(in.scalar, locals.scalar_min, locals.scalar_max, locals.colormap, locals.scalar_clamp, locals.out_of_range_color);
+    }
+    if is_surfel() && GlobalBinds::params.apply_lighting > 0u {
+        color_linear = vec4<f32>(shade_surfel(in.position, in.normal_world, color_linear.rgb), color_linear.a);
     }
 
     // Linear pre tonemapping grading
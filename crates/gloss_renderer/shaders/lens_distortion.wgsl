@@ -0,0 +1,31 @@
+#import ./utils/full_screen_tri_utils.wgsl as Tri
+
+@group(0) @binding(0) var t_undistorted: texture_2d<f32>;
+@group(0) @binding(1) var t_map: texture_2d<f32>; //for every output pixel, the uv in the undistorted texture or negative if the pixel sees nothing
+@group(0) @binding(2) var s_linear: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+
+    let pos_uv = Tri::full_screen_tri(vertex_index);
+    out.position=pos_uv.pos;
+    out.tex_coords=pos_uv.uv;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = textureLoad(t_map, vec2<i32>(in.position.xy), 0).xy;
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) {
+        return vec4<f32>(0.0);
+    }
+    //we use the explicit lod because the sampling is in non-uniform control flow
+    return textureSampleLevel(t_undistorted, s_linear, uv, 0.0);
+}
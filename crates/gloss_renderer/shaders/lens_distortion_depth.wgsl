@@ -0,0 +1,32 @@
+// Warps the depth of the undistorted render in the same way as lens_distortion.wgsl warps the color. The undistorted projection maps the
+// view-space z in the same way as the distorted one so the depth along each ray can be copied as is.
+
+#import ./utils/full_screen_tri_utils.wgsl as Tri
+
+//replaced with texture_depth_multisampled_2d when using msaa
+@group(0) @binding(0) var t_undistorted_depth: texture_depth_2d;
+@group(0) @binding(1) var t_map: texture_2d<f32>; //for every output pixel, the uv in the undistorted texture or negative if the pixel sees nothing
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = Tri::full_screen_tri(vertex_index).pos;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @builtin(frag_depth) f32 {
+    let uv = textureLoad(t_map, vec2<i32>(in.position.xy), 0).xy;
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) {
+        //pixels that see nothing keep the cleared depth of the background
+        discard;
+    }
+    //depth can't be interpolated across edges so we take the closest texel, and with msaa its first sample
+    let size = vec2<f32>(textureDimensions(t_undistorted_depth));
+    let px = vec2<i32>(min(uv * size, size - 1.0));
+    return textureLoad(t_undistorted_depth, px, 0);
+}
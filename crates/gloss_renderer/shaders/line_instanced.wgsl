@@ -11,6 +11,8 @@
 #import ./bindings/global_binds.wgsl as GlobalBinds
 #import ./utils/tonemap_utils.wgsl as TonemapUtils
 #import ./utils/num_utils.wgsl as NumUtils
+#import ./utils/colormap_utils.wgsl as ColormapUtils
+#import ./utils/clip_utils.wgsl as ClipUtils
 
 //basically the idea from https://webgpufundamentals.org/webgpu/lessons/webgpu-points.html
 //where we create a quad for every vertex by drawing indexed
@@ -23,9 +25,14 @@ struct Locals {
   zbuffer: u32,
   antialias_edges: u32,
   is_floor: u32,
-  pad_b: f32,
+  scalar_min: f32,
+  scalar_max: f32,
+  colormap: u32,
+  scalar_clamp: u32,
+  clip: u32,
   pad_c: f32,
   pad_d: f32,
+  out_of_range_color: vec4<f32>,
 };
 
 //group 2
@@ -34,6 +41,10 @@ struct Locals {
 struct VertexInput {
     @location(0) ev1: vec3<f32>,
     @location(1) ev2: vec3<f32>,
+    @location(2) color_v1: vec3<f32>,
+    @location(3) color_v2: vec3<f32>,
+    @location(4) scalar_v1: f32,
+    @location(5) scalar_v2: f32,
 }
 
 struct VertexOutput {
@@ -42,6 +53,7 @@ struct VertexOutput {
     @location(1) color: vec3<f32>,
     @location(2) offset_from_edge: f32,
     @location(3) offset_max: f32,
+    @location(4) scalar: f32,
 }
 
 @vertex
@@ -119,6 +131,28 @@ fn vs_main(
     out.offset_from_edge = offset_from_edge_array[v_idx]; 
     out.offset_max = length(offset);
     out.color = locals.line_color.xyz;
+    if locals.color_type==1 {
+        //same order as the points array
+        var colors = array(
+            model.color_v1,
+            model.color_v1,
+            model.color_v2,
+            model.color_v2,
+            model.color_v2,
+            model.color_v1,
+        );
+        out.color = colors[v_idx];
+    }
+    //the scalars are interpolated and only mapped to colors in the fragment shader
+    var scalars = array(
+        model.scalar_v1,
+        model.scalar_v1,
+        model.scalar_v2,
+        model.scalar_v2,
+        model.scalar_v2,
+        model.scalar_v1,
+    );
+    out.scalar = scalars[v_idx];
 
     return out;
 }
@@ -126,11 +160,17 @@ fn vs_main(
 // Fragment shader
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if locals.clip > 0u && ClipUtils::is_clipped(in.position_world) {
+        discard;
+    }
+
     var color_linear = vec4<f32>(0.0);
     if locals.color_type==0 {
         color_linear = locals.line_color;
     } else if locals.color_type==1 {
         color_linear = vec4<f32>(in.color,1.0);
+    } else if locals.color_type==2 {
+        color_linear = ColormapUtils::scalar_to_color(in.scalar, locals.scalar_min, locals.scalar_max, locals.colormap, locals.scalar_clamp, locals.out_of_range_color);
     }
 
     //modify the color towards background when it gets towards the edges of the line
@@ -0,0 +1,44 @@
+// Draws the outline around the highlighted entities by blending their color over the pixels that are outside of the mask but closer to
+// it than the width of the outline
+
+#import ./utils/full_screen_tri_utils.wgsl as Tri
+
+@group(0) @binding(0) var t_mask_color: texture_2d<f32>;
+@group(0) @binding(1) var t_mask_width: texture_2d<f32>;
+@group(0) @binding(2) var t_seeds: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = Tri::full_screen_tri(vertex_index).pos;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let px = vec2<i32>(floor(in.position.xy));
+    //the entity itself is not covered, only its surroundings
+    if textureLoad(t_mask_width, px, 0).r > 0.0 {
+        discard;
+    }
+    let seed = textureLoad(t_seeds, px, 0).xy;
+    if seed.x < 0.0 {
+        discard;
+    }
+
+    //the seed is the center of the closest covered pixel so the edge of the silhouette is half a pixel closer
+    let seed_px = vec2<i32>(seed);
+    let width = textureLoad(t_mask_width, seed_px, 0).r;
+    let dist_to_edge = distance(seed, vec2<f32>(px)) - 0.5;
+    let coverage = clamp(width + 0.5 - dist_to_edge, 0.0, 1.0);
+    if coverage <= 0.0 {
+        discard;
+    }
+
+    let color = textureLoad(t_mask_color, seed_px, 0);
+    return vec4<f32>(color.rgb, color.a * coverage);
+}
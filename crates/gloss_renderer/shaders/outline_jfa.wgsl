@@ -0,0 +1,61 @@
+// Jump flooding (https://www.comp.nus.edu.sg/~tants/jfa.html) over the outline mask. Every pixel ends up with the pixel coordinates of
+// the closest pixel covered by a highlighted entity, or -1 if there is none within reach of the jumps
+
+#import ./utils/full_screen_tri_utils.wgsl as Tri
+
+struct Params {
+    //distance in pixels of the jump. Zero initializes the seeds from the mask
+    step: i32,
+    pad_b: f32,
+    pad_c: f32,
+    pad_d: f32,
+};
+
+@group(0) @binding(0) var t_mask_width: texture_2d<f32>;
+@group(0) @binding(1) var t_seeds: texture_2d<f32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = Tri::full_screen_tri(vertex_index).pos;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec2<f32> {
+    let px = vec2<i32>(floor(in.position.xy));
+
+    if params.step <= 0 {
+        if textureLoad(t_mask_width, px, 0).r > 0.0 {
+            return vec2<f32>(px);
+        }
+        return vec2<f32>(-1.0);
+    }
+
+    let dims = vec2<i32>(textureDimensions(t_seeds));
+    var best = vec2<f32>(-1.0);
+    var best_dist = 3.4e38;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbour = px + vec2<i32>(x, y) * params.step;
+            if any(neighbour < vec2<i32>(0)) || any(neighbour >= dims) {
+                continue;
+            }
+            let seed = textureLoad(t_seeds, neighbour, 0).xy;
+            if seed.x < 0.0 {
+                continue;
+            }
+            let dist = distance(seed, vec2<f32>(px));
+            if dist < best_dist {
+                best = seed;
+                best_dist = dist;
+            }
+        }
+    }
+    return best;
+}
@@ -0,0 +1,90 @@
+// Renders the silhouette of highlighted lines into the outline mask. The quads are built the same way as in line_instanced.wgsl
+//group 0 = per frame uniforms
+//group 1, binding 0 Locals
+
+#import ./types/global_types.wgsl as GlobalTypes
+#import ./bindings/global_binds.wgsl as GlobalBinds
+#import ./utils/clip_utils.wgsl as ClipUtils
+
+//keep in sync with outline_pass.rs
+struct Locals {
+  model_matrix : mat4x4<f32>,
+  color: vec4<f32>,
+  width: f32,
+  size: f32,
+  is_size_in_world_space: u32,
+  clip: u32,
+  is_surfel: u32,
+  pad_b: f32,
+  pad_c: f32,
+  pad_d: f32,
+};
+
+@group(1) @binding(0) var<uniform> locals : Locals;
+
+struct VertexInput {
+    @location(0) ev1: vec3<f32>,
+    @location(1) ev2: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) pos_world: vec3<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) width: f32,
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    @builtin(vertex_index) v_idx: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let p1_world = locals.model_matrix * vec4<f32>(model.ev1, 1.0);
+    let p2_world = locals.model_matrix * vec4<f32>(model.ev2, 1.0);
+    let clip_pos_ev1 = GlobalBinds::camera.proj * GlobalBinds::camera.view * p1_world;
+    let clip_pos_ev2 = GlobalBinds::camera.proj * GlobalBinds::camera.view * p2_world;
+    let w1 = clip_pos_ev1.w;
+    let w2 = clip_pos_ev2.w;
+
+    let resolution = vec2f(GlobalBinds::camera.width, GlobalBinds::camera.height);
+    let thickness = locals.size / resolution;
+    let direction = clip_pos_ev2 / w2 - clip_pos_ev1 / w1;
+    let offset = normalize(vec2<f32>(direction.y, -direction.x)) * thickness;
+
+    var points = array(
+        vec4f(clip_pos_ev1.x + offset.x*w1, clip_pos_ev1.y + offset.y*w1, clip_pos_ev1.z, w1),
+        vec4f(clip_pos_ev1.x - offset.x*w1, clip_pos_ev1.y - offset.y*w1, clip_pos_ev1.z, w1),
+        vec4f(clip_pos_ev2.x + offset.x*w2, clip_pos_ev2.y + offset.y*w2, clip_pos_ev2.z, w2),
+        vec4f(clip_pos_ev2.x + offset.x*w2, clip_pos_ev2.y + offset.y*w2, clip_pos_ev2.z, w2),
+        vec4f(clip_pos_ev2.x - offset.x*w2, clip_pos_ev2.y - offset.y*w2, clip_pos_ev2.z, w2),
+        vec4f(clip_pos_ev1.x - offset.x*w1, clip_pos_ev1.y - offset.y*w1, clip_pos_ev1.z, w1),
+    );
+    var points_world = array(
+        p1_world.xyz,
+        p1_world.xyz,
+        p2_world.xyz,
+        p2_world.xyz,
+        p2_world.xyz,
+        p1_world.xyz,
+    );
+
+    out.clip_position = points[v_idx];
+    out.pos_world = points_world[v_idx];
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    if locals.clip > 0u && ClipUtils::is_clipped(in.pos_world) {
+        discard;
+    }
+
+    var out: FragmentOutput;
+    out.color = locals.color;
+    out.width = locals.width;
+    return out;
+}
@@ -0,0 +1,61 @@
+// Renders the silhouette of highlighted meshes into the outline mask
+//group 0 = per frame uniforms
+//group 1, binding 0 Locals
+
+#import ./types/global_types.wgsl as GlobalTypes
+#import ./bindings/global_binds.wgsl as GlobalBinds
+#import ./utils/clip_utils.wgsl as ClipUtils
+
+//keep in sync with outline_pass.rs
+struct Locals {
+  model_matrix : mat4x4<f32>,
+  color: vec4<f32>,
+  width: f32,
+  size: f32,
+  is_size_in_world_space: u32,
+  clip: u32,
+  is_surfel: u32,
+  pad_b: f32,
+  pad_c: f32,
+  pad_d: f32,
+};
+
+@group(1) @binding(0) var<uniform> locals : Locals;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) pos_world: vec3<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) width: f32,
+}
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    let pos_world = locals.model_matrix * vec4<f32>(model.position, 1.0);
+    out.pos_world = pos_world.xyz;
+    out.clip_position = GlobalBinds::camera.proj * GlobalBinds::camera.view * pos_world;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput, @builtin(front_facing) is_front_facing: bool) -> FragmentOutput {
+    //the parts cut away by the clip planes are not part of the silhouette, except for the back faces that are drawn as caps
+    if locals.clip > 0u && ClipUtils::is_clipped(in.pos_world) {
+        if is_front_facing || ClipUtils::closest_cap_plane(in.pos_world) < 0 {
+            discard;
+        }
+    }
+
+    var out: FragmentOutput;
+    out.color = locals.color;
+    out.width = locals.width;
+    return out;
+}
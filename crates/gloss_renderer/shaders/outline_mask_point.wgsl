@@ -0,0 +1,105 @@
+// Renders the silhouette of highlighted point clouds into the outline mask. The quads are built the same way as in
+// gbuffer_point_instanced.wgsl
+//group 0 = per frame uniforms
+//group 1, binding 0 Locals
+
+#import ./types/global_types.wgsl as GlobalTypes
+#import ./bindings/global_binds.wgsl as GlobalBinds
+#import ./utils/clip_utils.wgsl as ClipUtils
+
+//keep in sync with outline_pass.rs
+struct Locals {
+  model_matrix : mat4x4<f32>,
+  color: vec4<f32>,
+  width: f32,
+  size: f32,
+  is_size_in_world_space: u32,
+  clip: u32,
+  is_surfel: u32,
+  pad_b: f32,
+  pad_c: f32,
+  pad_d: f32,
+};
+
+@group(1) @binding(0) var<uniform> locals : Locals;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) pos_world: vec3<f32>,
+    @location(1) quad_coord: vec2<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) width: f32,
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    @builtin(vertex_index) v_idx: u32,
+) -> VertexOutput {
+    var points = array(
+        vec2f(-1, -1),
+        vec2f( 1, -1),
+        vec2f(-1,  1),
+        vec2f(-1,  1),
+        vec2f( 1, -1),
+        vec2f( 1,  1),
+    );
+    var out: VertexOutput;
+    let pos = points[v_idx];
+    out.quad_coord = pos;
+    let pos_world = (locals.model_matrix * vec4<f32>(model.position, 1.0)).xyz;
+    out.pos_world = pos_world;
+
+    if locals.is_surfel > 0u {
+        let normal_world = normalize((locals.model_matrix * vec4<f32>(model.normal, 0.0)).xyz);
+        var helper = vec3<f32>(0.0, 1.0, 0.0);
+        if abs(normal_world.y) > 0.9 {
+            helper = vec3<f32>(1.0, 0.0, 0.0);
+        }
+        let tangent = normalize(cross(helper, normal_world));
+        let bitangent = cross(normal_world, tangent);
+
+        let clip_center = GlobalBinds::camera.proj * GlobalBinds::camera.view * vec4<f32>(pos_world, 1.0);
+        var radius = 0.5 * locals.size;
+        if locals.is_size_in_world_space == 0u {
+            radius = radius * 2.0 * clip_center.w / (GlobalBinds::camera.proj[1][1] * GlobalBinds::camera.height);
+        }
+        let corner_world = pos_world + (pos.x * tangent + pos.y * bitangent) * radius;
+        out.clip_position = GlobalBinds::camera.proj * GlobalBinds::camera.view * vec4<f32>(corner_world, 1.0);
+        return out;
+    }
+
+    let clip_pos = GlobalBinds::camera.proj * GlobalBinds::camera.view * vec4<f32>(pos_world, 1.0);
+    let clip_pos_ndc = clip_pos / clip_pos.w;
+    let resolution = vec2f(GlobalBinds::camera.width, GlobalBinds::camera.height);
+    var w_coord = 1.0;
+    if locals.is_size_in_world_space > 0u {
+        w_coord = clip_pos.w;
+    }
+    let clip_offset = vec4f(pos * locals.size / resolution / w_coord, 0, 0);
+    out.clip_position = clip_pos_ndc + clip_offset;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    if locals.is_surfel > 0u && dot(in.quad_coord, in.quad_coord) > 1.0 {
+        discard;
+    }
+    if locals.clip > 0u && ClipUtils::is_clipped(in.pos_world) {
+        discard;
+    }
+
+    var out: FragmentOutput;
+    out.color = locals.color;
+    out.width = locals.width;
+    return out;
+}
@@ -56,7 +56,9 @@ fn pbr_input_from_deferred_gbuffer(uv: vec2<f32>, g_albedo: texture_2d<f32>,  g_
     let perceptual_roughness = metalness_perceptual_roughness.y;
     let depth = textureSample(g_depth, GlobalBinds::sampler_nearest, uv).x;
 
-    let V = normalize(GlobalBinds::camera.pos_world - pos_world); //TOOD this would need to change if the projection is orthographic
+    //with orthographic projection all the view rays are parallel to the camera axis
+    let is_orthographic = GlobalBinds::camera.proj[3].w == 1.0;
+    let V = select(normalize(GlobalBinds::camera.pos_world - pos_world), normalize(GlobalBinds::camera.view_inv[2].xyz), is_orthographic);
 
     pbr.world_position=vec4<f32>(pos_world, 1.0);
 
@@ -237,7 +237,13 @@ fn apply_pbr_lighting(
         // else if l_idx==7u && is_shadow_caster { shadow=Shadows::fetch_shadow(light, in.world_position.xyz, ComposeBinds::shadow_map_7, GlobalBinds::sampler_shadow_map);}
 
         //add light contribution
-        let light_contrib = PbrLighting::spot_light(in.world_position.xyz, light, roughness, NdotV, in.N, in.V, R, F0, f_ab, diffuse_color);
+        //lights with orthographic projection are directional
+        var light_contrib = vec3<f32>(0.0);
+        if light.proj[3].w == 1.0 {
+            light_contrib = PbrLighting::directional_light(light, roughness, NdotV, in.N, in.V, F0, f_ab, diffuse_color);
+        } else {
+            light_contrib = PbrLighting::spot_light(in.world_position.xyz, light, roughness, NdotV, in.N, in.V, R, F0, f_ab, diffuse_color);
+        }
         direct_light += light_contrib * shadow;
 
     }
@@ -277,6 +277,31 @@ fn spot_light(
     return point_light * spot_attenuation;
 }
 
+// Light with orthographic projection. All the rays are parallel to the lookat direction and there is no attenuation with distance
+fn directional_light(
+    light: GlobalTypes::Light,
+    roughness: f32,
+    NdotV: f32,
+    N: vec3<f32>,
+    V: vec3<f32>,
+    F0: vec3<f32>,
+    f_ab: vec2<f32>,
+    diffuseColor: vec3<f32>
+) -> vec3<f32> {
+    let L = -light.lookat_dir_world;
+
+    let H = normalize(L + V);
+    let NoL = saturate(dot(N, L));
+    let NoH = saturate(dot(N, H));
+    let LoH = saturate(dot(L, H));
+
+    let diffuse = diffuseColor * Fd_Burley(roughness, NdotV, NoL, LoH);
+    let specularIntensity = 1.0;
+    let specular_light = specular(F0, roughness, H, NdotV, NoL, NoH, LoH, specularIntensity, f_ab);
+
+    return (specular_light + diffuse) * light.intensity * light.color.rgb * NoL;
+}
+
 // fn directional_light(light_id: u32, roughness: f32, NdotV: f32, normal: vec3<f32>, view: vec3<f32>, R: vec3<f32>, F0: vec3<f32>, f_ab: vec2<f32>, diffuseColor: vec3<f32>) -> vec3<f32> {
 //     let light = &view_bindings::lights.directional_lights[light_id];
 
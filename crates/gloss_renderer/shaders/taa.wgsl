@@ -0,0 +1,94 @@
+// Temporal anti-aliasing. The main pass was rendered with a subpixel jitter and here we blend it with the history of the previous frames.
+// The history is reprojected using the depth and the camera of the previous frame and clamped to the colors around the pixel so that
+// disoccluded or moving regions don't leave ghosts behind. When accumulating, the camera doesn't move so the history is read at the same
+// pixel and averaged without any clamping which gives a supersampled image
+
+#import ./utils/full_screen_tri_utils.wgsl as Tri
+#import ./utils/tex_utils.wgsl as TexUtils
+
+struct Params {
+    //inverse of the view-projection of this frame without jitter
+    cur_vp_inv: mat4x4<f32>,
+    //view-projection of the previous frame without jitter
+    prev_vp: mat4x4<f32>,
+    //how much of the history is kept, zero discards it
+    history_weight: f32,
+    //0 for taa, 1 for accumulation
+    mode: u32,
+    pad_c: f32,
+    pad_d: f32,
+};
+
+@group(0) @binding(0) var t_color: texture_2d<f32>;
+@group(0) @binding(1) var t_history: texture_2d<f32>;
+//replaced with texture_depth_multisampled_2d when using msaa
+@group(0) @binding(2) var t_depth: texture_depth_2d;
+@group(0) @binding(3) var<uniform> params: Params;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) history: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = Tri::full_screen_tri(vertex_index).pos;
+    return out;
+}
+
+fn output(color: vec4<f32>) -> FragmentOutput {
+    var out: FragmentOutput;
+    out.color = color;
+    out.history = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    let px = vec2<i32>(floor(in.position.xy));
+    let size = vec2<f32>(textureDimensions(t_color));
+    let max_px = vec2<i32>(size) - 1;
+    let color = textureLoad(t_color, px, 0);
+
+    if params.history_weight <= 0.0 {
+        return output(color);
+    }
+    if params.mode == 1u {
+        return output(mix(color, textureLoad(t_history, px, 0), params.history_weight));
+    }
+
+    //position of this pixel in the previous frame. We stay in homogeneous coordinates since the background has a depth of zero which
+    //with an infinite far plane is a point at infinity
+    let depth = textureLoad(t_depth, px, 0);
+    let uv = in.position.xy / size;
+    let ndc = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let prev_clip = params.prev_vp * (params.cur_vp_inv * ndc);
+    if abs(prev_clip.w) < 1e-7 {
+        return output(color);
+    }
+    let prev_ndc = prev_clip.xy / prev_clip.w;
+    let prev_uv = vec2<f32>(prev_ndc.x * 0.5 + 0.5, 0.5 - prev_ndc.y * 0.5);
+    if any(prev_uv < vec2<f32>(0.0)) || any(prev_uv > vec2<f32>(1.0)) {
+        return output(color);
+    }
+    var history = TexUtils::texture_load_bilinear(t_history, prev_uv * size);
+
+    //clamp the history to the neighbourhood of the pixel
+    var color_min = color;
+    var color_max = color;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbour = textureLoad(t_color, clamp(px + vec2<i32>(x, y), vec2<i32>(0), max_px), 0);
+            color_min = min(color_min, neighbour);
+            color_max = max(color_max, neighbour);
+        }
+    }
+    history = clamp(history, color_min, color_max);
+
+    return output(mix(color, history, params.history_weight));
+}
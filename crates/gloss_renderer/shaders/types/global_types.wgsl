@@ -1,8 +1,8 @@
 struct Scene {
     nr_lights: u32,
     environment_map_smallest_specular_mip_level: u32,
+    nr_clip_planes: u32,
     //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
-    pad_b: u32,
     pad_c: u32,
 }
 struct Camera {
@@ -42,6 +42,15 @@ struct Light {
   pad_c: f32,
   pad_d: f32
 };
+struct ClipPlane {
+  plane: vec4<f32>, //normal in xyz and offset in w so that dot(plane.xyz, pos) + plane.w is the signed distance to the plane
+  cap_color: vec4<f32>,
+  cap: u32, //should be bool but that is not host-sharable: https://www.w3.org/TR/WGSL/#host-shareable-types
+  //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
+  pad_b: u32,
+  pad_c: u32,
+  pad_d: u32
+};
 struct Params {
     ambient_factor: f32,
     environment_factor: f32,
@@ -0,0 +1,33 @@
+#import ../types/global_types.wgsl as GlobalTypes
+#import ../bindings/global_binds.wgsl as GlobalBinds
+
+//signed distance to the plane, negative on the side that is cut away
+fn plane_distance(clip_plane: GlobalTypes::ClipPlane, pos_world: vec3<f32>) -> f32 {
+    return dot(clip_plane.plane.xyz, pos_world) + clip_plane.plane.w;
+}
+
+//true if the position is cut away by any of the clip planes
+fn is_clipped(pos_world: vec3<f32>) -> bool {
+    for (var i: u32 = 0u; i < GlobalBinds::scene.nr_clip_planes; i++) {
+        if plane_distance(GlobalBinds::clip_planes[i], pos_world) < 0.0 {
+            return true;
+        }
+    }
+    return false;
+}
+
+//index of the capping plane closest to the position or -1 if none of the planes caps the cut. Used to color the back faces that become
+//visible through the cut
+fn closest_cap_plane(pos_world: vec3<f32>) -> i32 {
+    var closest = -1;
+    var closest_dist = 0.0;
+    for (var i: u32 = 0u; i < GlobalBinds::scene.nr_clip_planes; i++) {
+        let clip_plane = GlobalBinds::clip_planes[i];
+        let dist = plane_distance(clip_plane, pos_world);
+        if clip_plane.cap > 0u && (closest < 0 || dist < closest_dist) {
+            closest = i32(i);
+            closest_dist = dist;
+        }
+    }
+    return closest;
+}
@@ -0,0 +1,72 @@
+//colormaps used for visualizing scalar fields. Keep in sync with Colormap::sample() on the cpu
+//the polynomial fits for viridis and magma are from https://www.shadertoy.com/view/WlfXRN
+
+fn viridis(t: f32) -> vec3<f32> {
+    let c0 = vec3<f32>(0.2777273272234177, 0.005407344544966578, 0.3340998053353061);
+    let c1 = vec3<f32>(0.1050930431085774, 1.404613529898575, 1.384590162594685);
+    let c2 = vec3<f32>(-0.3308618287255563, 0.214847559468213, 0.09509516302823659);
+    let c3 = vec3<f32>(-4.634230498983486, -5.799100973351585, -19.33244095627987);
+    let c4 = vec3<f32>(6.228269936347081, 14.17993336680509, 56.69055260068105);
+    let c5 = vec3<f32>(4.776384997670288, -13.74514537774601, -65.35303263337234);
+    let c6 = vec3<f32>(-5.435455855934631, 4.645852612178535, 26.3124352495832);
+    return c0 + t * (c1 + t * (c2 + t * (c3 + t * (c4 + t * (c5 + t * c6)))));
+}
+
+fn magma(t: f32) -> vec3<f32> {
+    let c0 = vec3<f32>(-0.002136485053939582, -0.000749655052795221, -0.005386127855323933);
+    let c1 = vec3<f32>(0.2516605407371642, 0.6775232436837668, 2.494026599312351);
+    let c2 = vec3<f32>(8.353717279216625, -3.577719514958484, 0.3144679030132573);
+    let c3 = vec3<f32>(-27.66873308576866, 14.26473078096533, -13.64921318813922);
+    let c4 = vec3<f32>(52.17613981234068, -27.94360607168351, 12.94416944238394);
+    let c5 = vec3<f32>(-50.76852536473588, 29.04658282127291, 4.23415299384598);
+    let c6 = vec3<f32>(18.65570506591883, -11.48977351997711, -5.601961508734096);
+    return c0 + t * (c1 + t * (c2 + t * (c3 + t * (c4 + t * (c5 + t * c6)))));
+}
+
+//diverging map from Moreland interpolated between some of its control points
+fn coolwarm(t: f32) -> vec3<f32> {
+    var stops = array(
+        vec3<f32>(0.230, 0.299, 0.754),
+        vec3<f32>(0.552, 0.690, 0.996),
+        vec3<f32>(0.865, 0.865, 0.865),
+        vec3<f32>(0.958, 0.603, 0.482),
+        vec3<f32>(0.706, 0.016, 0.150),
+    );
+    let pos = t * 4.0;
+    let idx = min(u32(floor(pos)), 3u);
+    let frac = pos - f32(idx);
+    return mix(stops[idx], stops[idx + 1u], frac);
+}
+
+fn jet(t: f32) -> vec3<f32> {
+    return clamp(vec3<f32>(1.5) - abs(4.0 * vec3<f32>(t) - vec3<f32>(3.0, 2.0, 1.0)), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn apply_colormap(t: f32, colormap: u32) -> vec3<f32> {
+    let t_clamped = clamp(t, 0.0, 1.0);
+    var color = vec3<f32>(0.0);
+    if colormap == 0u {
+        color = viridis(t_clamped);
+    } else if colormap == 1u {
+        color = magma(t_clamped);
+    } else if colormap == 2u {
+        color = coolwarm(t_clamped);
+    } else {
+        color = jet(t_clamped);
+    }
+    return clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+//maps the scalar into [0,1] given the range. A range of zero width maps everything to the start of the colormap
+fn normalize_scalar(value: f32, min_val: f32, max_val: f32) -> f32 {
+    return (value - min_val) / max(max_val - min_val, 1e-12);
+}
+
+//color of a scalar value, values outside of the range get the out_of_range_color unless they are clamped
+fn scalar_to_color(value: f32, min_val: f32, max_val: f32, colormap: u32, clamp_range: u32, out_of_range_color: vec4<f32>) -> vec4<f32> {
+    let t = normalize_scalar(value, min_val, max_val);
+    if clamp_range == 0u && (t < 0.0 || t > 1.0) {
+        return out_of_range_color;
+    }
+    return vec4<f32>(apply_colormap(t, colormap), 1.0);
+}
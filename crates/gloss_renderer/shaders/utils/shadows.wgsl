@@ -23,14 +23,16 @@ fn fetch_shadow(light: GlobalTypes::Light,  pos_world: vec3<f32>, normal_world:
     //using https://www.ludicon.com/castano/blog/articles/shadow-mapping-summary-part-1/
     //https://ndotl.wordpress.com/2014/12/19/notes-on-shadow-bias/
     //from normal offset shadows by Daniel Holbert GDC 2011
-    let L = normalize(light.pos_world-pos_world);
+    //orthographic lights are directional so the direction towards the light and the size of the shadow map texels don't depend on the position
+    let is_orthographic = light.proj[3].w == 1.0;
+    let L = select(normalize(light.pos_world-pos_world), -light.lookat_dir_world, is_orthographic);
     let offsets_scale = get_shadow_offsets(normal_world, L);
     let shadowDepthTextureSize =  f32(textureDimensions(shadow_map).x); 
     let oneOverShadowDepthTextureSize = 1.0 / shadowDepthTextureSize;
     let bias_along_normal_dir=light.shadow_bias_normal*oneOverShadowDepthTextureSize;
     //TODO scale the bias also by the distance as mention by "normal offset shadows" by Daniel Holbert GDC 2011
     let pos_view = light.view * vec4<f32>(pos_world, 1.0);
-    let distance_scale= abs(pos_view.z);
+    let distance_scale= select(abs(pos_view.z), 1.0, is_orthographic);
     let fov_factor = 1.0/min(light.proj[0].x, light.proj[1].y);//the higher the fov, the larger the texels are so we need to scale more
     // let pos_world_biased = pos_world.xyz + light.shadow_bias_normal*saturate(1.0-cos_angle)*normal_world;
     let pos_world_biased = pos_world.xyz + bias_along_normal_dir*offsets_scale.x*distance_scale*fov_factor*normal_world;
@@ -39,3 +39,19 @@ fn texture_compare_bilinear(tex: texture_2d<f32>, uv_in: vec2<f32>, reference_z:
 }
 
 
+
+//bilinear interpolation done with loads so it works for unfilterable textures and in non-uniform control flow
+//pos_px is in pixels with the center of the first texel at 0.5. Coordinates outside of the texture are clamped to the edge
+fn texture_load_bilinear(tex: texture_2d<f32>, pos_px: vec2<f32>) -> vec4<f32>{
+    let max_px = vec2<i32>(textureDimensions(tex)) - 1;
+    let p = pos_px - 0.5;
+    let f = fract(p);
+    let tl_px = vec2<i32>(floor(p));
+    let tl = textureLoad(tex, clamp(tl_px, vec2<i32>(0), max_px), 0);
+    let tr = textureLoad(tex, clamp(tl_px + vec2<i32>(1, 0), vec2<i32>(0), max_px), 0);
+    let bl = textureLoad(tex, clamp(tl_px + vec2<i32>(0, 1), vec2<i32>(0), max_px), 0);
+    let br = textureLoad(tex, clamp(tl_px + vec2<i32>(1, 1), vec2<i32>(0), max_px), 0);
+    let tA = mix( tl, tr, f.x );
+    let tB = mix( bl, br, f.x );
+    return mix( tA, tB, f.y );
+}
@@ -2,7 +2,7 @@ use gloss_hecs::{CommandBuffer, Entity};
 use log::{error, warn};
 
 use crate::{
-    components::{Colors, Faces, ModelMatrix, Normals, UVs, Verts},
+    components::{Colors, Faces, LocalModelMatrix, LocalPosLookat, ModelMatrix, Normals, UVs, Verts},
     geom::Geom,
     scene::Scene,
 };
@@ -26,6 +26,10 @@ impl Actor {
         Self { entity }
     }
 
+    /// Bakes the transform of the entity into its vertices and normals so that
+    /// its model matrix becomes the identity. Nothing moves in the world: an
+    /// entity with a parent bakes its full world transform and its children get
+    /// the baked transform moved into their local one
     pub fn apply_model_matrix(
         &mut self,
         scene: &mut Scene,
@@ -34,27 +38,41 @@ impl Actor {
         let mut command_buffer = CommandBuffer::new();
 
         {
-            let Some(model_matrix) = scene.get_comp::<&ModelMatrix>(&self.entity).ok() else {
+            if !scene.world.has::<ModelMatrix>(self.entity).unwrap_or(false) {
                 warn!("No model matrix to apply");
                 return;
-            };
+            }
+            let model_matrix = scene.get_world_matrix(self.entity);
 
             //verts
             if let Ok(verts) = scene.get_comp::<&Verts>(&self.entity) {
-                let new_verts = Geom::transform_verts(&verts.0.to_dmatrix(), &model_matrix.0);
+                let new_verts = Geom::transform_verts(&verts.0.to_dmatrix(), &model_matrix);
                 let new_verts_tensor = DynamicTensorFloat2D::from_dmatrix(&new_verts);
                 command_buffer.insert_one(self.entity, Verts(new_verts_tensor));
             }
 
             //normals
             if let Ok(normals) = scene.get_comp::<&Normals>(&self.entity) {
-                let new_normals = Geom::transform_vectors(&normals.0.to_dmatrix(), &model_matrix.0);
+                let new_normals = Geom::transform_vectors(&normals.0.to_dmatrix(), &model_matrix);
                 let new_normals_tensor = DynamicTensorFloat2D::from_dmatrix(&new_normals);
                 command_buffer.insert_one(self.entity, Normals(new_normals_tensor));
             }
 
-            //model matrix is now identity
+            //model matrix is now identity, for a child this means undoing the transform of the parent
             command_buffer.insert_one(self.entity, ModelMatrix::default());
+            if let Some(parent) = scene.get_parent(self.entity) {
+                command_buffer.insert_one(self.entity, LocalModelMatrix(scene.get_world_matrix(parent).inverse()));
+            }
+
+            //the children were placed relative to the transform we just baked
+            for child in scene.get_children(self.entity) {
+                if let Ok(local) = scene.get_comp::<&LocalModelMatrix>(&child) {
+                    command_buffer.insert_one(child, LocalModelMatrix(model_matrix * local.0));
+                }
+                if let Ok(local) = scene.get_comp::<&LocalPosLookat>(&child) {
+                    command_buffer.insert_one(child, LocalPosLookat(local.0.transformed(&model_matrix)));
+                }
+            }
         }
 
         command_buffer.run_on(&mut scene.world);
@@ -0,0 +1,497 @@
+use gloss_hecs::Entity;
+use na::DMatrix;
+use nalgebra as na;
+
+/// Maximum number of triangles stored in a leaf node
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Intersection of a ray with a triangle of a [`Bvh`]
+#[derive(Clone, Copy, Debug)]
+pub struct BvhHit {
+    pub face: u32,
+    /// Weights of the three vertices of the face at the hit point
+    pub barycentric: na::Vector3<f32>,
+    /// Distance along the ray in units of the ray direction
+    pub t: f32,
+}
+
+/// Point of a [`Bvh`] that is closest to a query point
+#[derive(Clone, Copy, Debug)]
+pub struct BvhClosestPoint {
+    pub face: u32,
+    pub barycentric: na::Vector3<f32>,
+    pub point: na::Point3<f32>,
+    pub distance: f32,
+}
+
+/// Intersection of a ray with the meshes of the scene as returned by
+/// [`crate::scene::Scene::raycast`]. The hit point is at `origin+t*dir`
+#[derive(Clone, Copy, Debug)]
+pub struct Hit {
+    pub entity: Entity,
+    pub face: u32,
+    pub barycentric: na::Vector3<f32>,
+    pub t: f32,
+}
+
+/// Point on the meshes of the scene closest to a query point as returned by
+/// [`crate::scene::Scene::closest_point`]. Position and distance are in world
+/// coordinates
+#[derive(Clone, Copy, Debug)]
+pub struct ClosestPoint {
+    pub entity: Entity,
+    pub face: u32,
+    pub barycentric: na::Vector3<f32>,
+    pub point: na::Point3<f32>,
+    pub distance: f32,
+}
+
+/// Axis aligned box of a node. Leaves index into the triangle list with
+/// `first..first+count`, inner nodes have `count==0` and their children are
+/// at `first` and `first+1`
+#[derive(Clone, Copy)]
+struct BvhNode {
+    min: na::Point3<f32>,
+    max: na::Point3<f32>,
+    first: u32,
+    count: u32,
+}
+impl BvhNode {
+    fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+
+    /// Distance along the ray at which it enters the box, if it does so before
+    /// `t_max`
+    fn ray_entry(&self, origin: &na::Point3<f32>, inv_dir: &na::Vector3<f32>, t_max: f32) -> Option<f32> {
+        let mut t_near = 0.0_f32;
+        let mut t_far = t_max;
+        for i in 0..3 {
+            let t0 = (self.min[i] - origin[i]) * inv_dir[i];
+            let t1 = (self.max[i] - origin[i]) * inv_dir[i];
+            //written so that NaN from 0*inf doesn't shrink the interval
+            t_near = if t0.min(t1) > t_near { t0.min(t1) } else { t_near };
+            t_far = if t0.max(t1) < t_far { t0.max(t1) } else { t_far };
+        }
+        (t_near <= t_far).then_some(t_near)
+    }
+
+    fn distance_squared(&self, point: &na::Point3<f32>) -> f32 {
+        let clamped = point.sup(&self.min).inf(&self.max);
+        (point - clamped).norm_squared()
+    }
+}
+
+/// Bounding volume hierarchy over the triangles of a mesh for ray casting and
+/// closest point queries on the CPU. The tree is built once with median splits
+/// along the longest axis and afterwards it can be refit to new vertex
+/// positions as long as the faces stay the same
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    //faces reordered so that every leaf covers a contiguous range
+    tri_faces: Vec<u32>,
+    verts: Vec<na::Point3<f32>>,
+    faces: Vec<[u32; 3]>,
+}
+
+impl Bvh {
+    /// Builds the hierarchy from vertices of size Nx3 and faces of size Mx3
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(verts: &DMatrix<f32>, faces: &DMatrix<u32>) -> Self {
+        let faces: Vec<[u32; 3]> = faces.row_iter().map(|f| [f[0], f[1], f[2]]).collect();
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            tri_faces: (0..faces.len() as u32).collect(),
+            verts: Self::points_from_matrix(verts),
+            faces,
+        };
+        bvh.build();
+        bvh
+    }
+
+    pub fn nr_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    pub fn nr_verts(&self) -> usize {
+        self.verts.len()
+    }
+
+    /// Updates the boxes for new vertex positions while keeping the structure
+    /// of the tree. Much faster than building it again but the tree gets less
+    /// efficient if the vertices move a lot relative to each other
+    pub fn refit(&mut self, verts: &DMatrix<f32>) {
+        assert_eq!(verts.nrows(), self.verts.len(), "refit needs the same number of vertices");
+        self.verts = Self::points_from_matrix(verts);
+        //children are always stored after their parent so going backwards visits them first
+        for idx in (0..self.nodes.len()).rev() {
+            let node = self.nodes[idx];
+            let (min, max) = if node.is_leaf() {
+                self.triangles_bounds(node.first as usize..(node.first + node.count) as usize)
+            } else {
+                let (left, right) = (self.nodes[node.first as usize], self.nodes[node.first as usize + 1]);
+                (left.min.inf(&right.min), left.max.sup(&right.max))
+            };
+            self.nodes[idx].min = min;
+            self.nodes[idx].max = max;
+        }
+    }
+
+    /// Closest intersection of the ray with the triangles, ignoring the ones
+    /// further than `t_max`. Triangles are hit from both sides and the
+    /// direction doesn't need to be normalized
+    pub fn raycast(&self, origin: &na::Point3<f32>, dir: &na::Vector3<f32>, t_max: f32) -> Option<BvhHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_dir = dir.map(|d| 1.0 / d);
+        let mut closest: Option<BvhHit> = None;
+        let mut t_closest = t_max;
+        let mut stack = vec![0_u32];
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx as usize];
+            if node.ray_entry(origin, &inv_dir, t_closest).is_none() {
+                continue;
+            }
+            if node.is_leaf() {
+                for face in &self.tri_faces[node.first as usize..(node.first + node.count) as usize] {
+                    let [a, b, c] = self.triangle(*face);
+                    if let Some((t, u, v)) = ray_triangle(origin, dir, &a, &b, &c) {
+                        if t < t_closest {
+                            t_closest = t;
+                            closest = Some(BvhHit {
+                                face: *face,
+                                barycentric: na::Vector3::new(1.0 - u - v, u, v),
+                                t,
+                            });
+                        }
+                    }
+                }
+            } else {
+                //visit the nearest child first so the further one can be culled by the hit
+                let (left, right) = (node.first, node.first + 1);
+                let t_left = self.nodes[left as usize].ray_entry(origin, &inv_dir, t_closest);
+                let t_right = self.nodes[right as usize].ray_entry(origin, &inv_dir, t_closest);
+                match (t_left, t_right) {
+                    (Some(tl), Some(tr)) if tl < tr => stack.extend([right, left]),
+                    (Some(_), Some(_)) => stack.extend([left, right]),
+                    (Some(_), None) => stack.push(left),
+                    (None, Some(_)) => stack.push(right),
+                    (None, None) => {}
+                }
+            }
+        }
+        closest
+    }
+
+    /// Point on the surface of the triangles that is closest to the query point
+    pub fn closest_point(&self, point: &na::Point3<f32>) -> Option<BvhClosestPoint> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut closest: Option<BvhClosestPoint> = None;
+        let mut dist2_closest = f32::MAX;
+        let mut stack = vec![0_u32];
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx as usize];
+            if node.distance_squared(point) > dist2_closest {
+                continue;
+            }
+            if node.is_leaf() {
+                for face in &self.tri_faces[node.first as usize..(node.first + node.count) as usize] {
+                    let [a, b, c] = self.triangle(*face);
+                    let barycentric = closest_point_triangle(point, &a, &b, &c);
+                    let p = na::Point3::from(a.coords * barycentric.x + b.coords * barycentric.y + c.coords * barycentric.z);
+                    let dist2 = (p - point).norm_squared();
+                    if dist2 < dist2_closest {
+                        dist2_closest = dist2;
+                        closest = Some(BvhClosestPoint {
+                            face: *face,
+                            barycentric,
+                            point: p,
+                            distance: dist2.sqrt(),
+                        });
+                    }
+                }
+            } else {
+                let (left, right) = (node.first, node.first + 1);
+                let d_left = self.nodes[left as usize].distance_squared(point);
+                let d_right = self.nodes[right as usize].distance_squared(point);
+                if d_left < d_right {
+                    stack.extend([right, left]);
+                } else {
+                    stack.extend([left, right]);
+                }
+            }
+        }
+        closest
+    }
+
+    fn points_from_matrix(verts: &DMatrix<f32>) -> Vec<na::Point3<f32>> {
+        verts.row_iter().map(|v| na::Point3::new(v[0], v[1], v[2])).collect()
+    }
+
+    fn triangle(&self, face: u32) -> [na::Point3<f32>; 3] {
+        self.faces[face as usize].map(|v| self.verts[v as usize])
+    }
+
+    fn centroid(&self, face: u32) -> na::Point3<f32> {
+        let [a, b, c] = self.triangle(face);
+        na::Point3::from((a.coords + b.coords + c.coords) / 3.0)
+    }
+
+    fn triangles_bounds(&self, range: std::ops::Range<usize>) -> (na::Point3<f32>, na::Point3<f32>) {
+        let mut min = na::Point3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = na::Point3::new(f32::MIN, f32::MIN, f32::MIN);
+        for face in &self.tri_faces[range] {
+            for v in self.triangle(*face) {
+                min = min.inf(&v);
+                max = max.sup(&v);
+            }
+        }
+        (min, max)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn build(&mut self) {
+        self.nodes.clear();
+        if self.faces.is_empty() {
+            return;
+        }
+        let (min, max) = self.triangles_bounds(0..self.faces.len());
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            first: 0,
+            count: self.faces.len() as u32,
+        });
+        let mut stack = vec![0_usize];
+        while let Some(idx) = stack.pop() {
+            let node = self.nodes[idx];
+            let count = node.count as usize;
+            if count <= MAX_LEAF_SIZE {
+                continue;
+            }
+            //split at the median centroid along the longest axis of the box
+            let axis = (node.max - node.min).imax();
+            let start = node.first as usize;
+            let mid = count / 2;
+            let mut tri_faces = std::mem::take(&mut self.tri_faces);
+            tri_faces[start..start + count].select_nth_unstable_by(mid, |a, b| self.centroid(*a)[axis].total_cmp(&self.centroid(*b)[axis]));
+            self.tri_faces = tri_faces;
+
+            let idx_left = self.nodes.len();
+            for (first, count) in [(start, mid), (start + mid, count - mid)] {
+                let (min, max) = self.triangles_bounds(first..first + count);
+                self.nodes.push(BvhNode {
+                    min,
+                    max,
+                    first: first as u32,
+                    count: count as u32,
+                });
+            }
+            self.nodes[idx].first = idx_left as u32;
+            self.nodes[idx].count = 0;
+            stack.extend([idx_left, idx_left + 1]);
+        }
+    }
+}
+
+/// Möller–Trumbore intersection. Returns the distance along the ray and the
+/// barycentric coordinates of b and c
+#[allow(clippy::many_single_char_names)]
+fn ray_triangle(
+    origin: &na::Point3<f32>,
+    dir: &na::Vector3<f32>,
+    a: &na::Point3<f32>,
+    b: &na::Point3<f32>,
+    c: &na::Point3<f32>,
+) -> Option<(f32, f32, f32)> {
+    let e1 = b - a;
+    let e2 = c - a;
+    let p = dir.cross(&e2);
+    let det = e1.dot(&p);
+    if det.abs() < f32::EPSILON * e1.norm() * e2.norm() * dir.norm() {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = origin - a;
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&e1);
+    let v = dir.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = e2.dot(&q) * inv_det;
+    (t >= 0.0).then_some((t, u, v))
+}
+
+/// Barycentric coordinates of the point of the triangle closest to p, from
+/// Ericson's Real-Time Collision Detection
+#[allow(clippy::many_single_char_names, clippy::similar_names)]
+fn closest_point_triangle(p: &na::Point3<f32>, a: &na::Point3<f32>, b: &na::Point3<f32>, c: &na::Point3<f32>) -> na::Vector3<f32> {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return na::Vector3::new(1.0, 0.0, 0.0);
+    }
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return na::Vector3::new(0.0, 1.0, 0.0);
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return na::Vector3::new(1.0 - v, v, 0.0);
+    }
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return na::Vector3::new(0.0, 0.0, 1.0);
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return na::Vector3::new(1.0 - w, 0.0, w);
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return na::Vector3::new(0.0, 1.0 - w, w);
+    }
+    //inside the face
+    let denom = 1.0 / (va + vb + vc);
+    let v = vb * denom;
+    let w = vc * denom;
+    na::Vector3::new(1.0 - v - w, v, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::{MeshBvh, ModelMatrix, Verts, VisMesh},
+        geom::Geom,
+        scene::Scene,
+    };
+    use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
+
+    fn brute_force_raycast(verts: &DMatrix<f32>, faces: &DMatrix<u32>, origin: &na::Point3<f32>, dir: &na::Vector3<f32>) -> Option<f32> {
+        let p = |i: u32| na::Point3::new(verts[(i as usize, 0)], verts[(i as usize, 1)], verts[(i as usize, 2)]);
+        faces
+            .row_iter()
+            .filter_map(|f| ray_triangle(origin, dir, &p(f[0]), &p(f[1]), &p(f[2])))
+            .map(|(t, _, _)| t)
+            .min_by(f32::total_cmp)
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn raycast_matches_brute_force() {
+        let verts = DMatrix::<f32>::from_fn(200, 3, |r, c| ((r * 7 + c * 13) % 17) as f32 * 0.1 + (r as f32 * 0.37 + c as f32).sin());
+        let faces = DMatrix::<u32>::from_fn(150, 3, |r, c| ((r * 11 + c * 67) % 200) as u32);
+        let bvh = Bvh::new(&verts, &faces);
+        for i in 0..50 {
+            let angle = i as f32 * 0.4;
+            let origin = na::Point3::new(5.0 * angle.cos(), 0.3 * (i % 5) as f32, 5.0 * angle.sin());
+            let dir = na::Point3::new(0.5, 0.5, 0.5) - origin;
+            let expected = brute_force_raycast(&verts, &faces, &origin, &dir);
+            let hit = bvh.raycast(&origin, &dir, f32::MAX).map(|hit| hit.t);
+            assert_eq!(expected, hit);
+        }
+    }
+
+    #[test]
+    fn raycast_scene() {
+        let mut scene = Scene::new();
+        let cube = scene
+            .get_or_create_entity("cube")
+            .insert_builder(Geom::build_cube(na::Point3::origin()))
+            .entity();
+
+        let hit = scene.raycast(&na::Point3::new(0.3, 0.2, -5.0), &na::Vector3::z()).unwrap();
+        assert_eq!(hit.entity, cube);
+        assert!((hit.t - 4.0).abs() < 1e-5);
+        assert!((hit.barycentric.sum() - 1.0).abs() < 1e-5);
+        assert!(scene.raycast(&na::Point3::new(3.0, 0.0, -5.0), &na::Vector3::z()).is_none());
+
+        //moving and scaling the cube moves the hit without rebuilding the bvh
+        let model_matrix = na::SimilarityMatrix3::new(na::Vector3::new(0.0, 0.0, -2.0), na::Vector3::zeros(), 2.0);
+        scene.world.insert_one(cube, ModelMatrix(model_matrix)).unwrap();
+        let hit = scene.raycast(&na::Point3::new(0.3, 0.2, -5.0), &na::Vector3::z()).unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn refit_after_verts_change() {
+        let mut scene = Scene::new();
+        let cube = scene
+            .get_or_create_entity("cube")
+            .insert_builder(Geom::build_cube(na::Point3::origin()))
+            .entity();
+        scene.update_bvhs();
+        assert!(scene.raycast(&na::Point3::new(1.5, 0.0, -5.0), &na::Vector3::z()).is_none());
+        //simulate the end of a frame
+        scene.mark_changed_bvhs();
+        scene.world.clear_trackers();
+
+        let verts = scene.get_comp::<&Verts>(&cube).unwrap().0.to_dmatrix() * 2.0;
+        scene.world.insert_one(cube, Verts(DynamicTensorFloat2D::from_dmatrix(&verts))).unwrap();
+        scene.mark_changed_bvhs();
+        scene.world.clear_trackers();
+
+        //the outdated bvh is not used even before it gets refit
+        let hit = scene.raycast(&na::Point3::new(1.5, 0.0, -5.0), &na::Vector3::z()).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-5);
+        scene.update_bvhs();
+        assert!(!scene.get_comp::<&MeshBvh>(&cube).unwrap().needs_refit);
+        let hit = scene.raycast(&na::Point3::new(1.5, 0.0, -5.0), &na::Vector3::z()).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hidden_meshes_are_skipped() {
+        let mut scene = Scene::new();
+        let cube = scene
+            .get_or_create_entity("cube")
+            .insert_builder(Geom::build_cube(na::Point3::origin()))
+            .entity();
+        let vis_mesh = VisMesh {
+            show_mesh: false,
+            ..Default::default()
+        };
+        scene.world.insert_one(cube, vis_mesh).unwrap();
+
+        scene.update_bvhs();
+        assert!(scene.get_comp::<&MeshBvh>(&cube).is_err());
+        assert!(scene.raycast(&na::Point3::new(0.3, 0.2, -5.0), &na::Vector3::z()).is_none());
+        assert!(scene.closest_point(&na::Point3::new(4.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn closest_point_scene() {
+        let mut scene = Scene::new();
+        let cube = scene
+            .get_or_create_entity("cube")
+            .insert_builder(Geom::build_cube(na::Point3::new(10.0, 0.0, 0.0)))
+            .entity();
+
+        let cp = scene.closest_point(&na::Point3::new(14.0, 0.5, 0.0)).unwrap();
+        assert_eq!(cp.entity, cube);
+        assert!((cp.distance - 3.0).abs() < 1e-5);
+        assert!((cp.point - na::Point3::new(11.0, 0.5, 0.0)).norm() < 1e-5);
+
+        //corner regions snap to the vertex
+        let cp = scene.closest_point(&na::Point3::new(12.0, 2.0, 2.0)).unwrap();
+        assert!((cp.point - na::Point3::new(11.0, 1.0, 1.0)).norm() < 1e-5);
+    }
+}
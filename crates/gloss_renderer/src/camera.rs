@@ -8,7 +8,10 @@ use winit::{
 };
 
 use crate::{
-    components::{CamController, CamMode, PosLookat, Projection, ProjectionWithIntrinsics, TargetResolution, TargetResolutionUpdate},
+    components::{
+        CamController, CamMode, LensDistortion, PosLookat, Projection, ProjectionOrthographic, ProjectionWithIntrinsics, TargetResolution,
+        TargetResolutionUpdate, UndistortedBounds,
+    },
     scene::Scene,
 };
 use gloss_hecs::Entity;
@@ -59,6 +62,10 @@ impl Camera {
     /// Will panic if the ``Projection`` component does not exist for this
     /// entity
     pub fn proj_matrix(&self, scene: &Scene) -> na::Matrix4<f32> {
+        if let Some(intrinsics) = self.distorted_intrinsics(scene) {
+            let bounds = self.undistorted_bounds(&intrinsics, scene);
+            return intrinsics.undistorted_proj_matrix(&bounds.min, &bounds.max);
+        }
         let proj = scene.get_comp::<&Projection>(&self.entity).unwrap();
         let (width, height) = self.get_target_res(scene);
         proj.proj_matrix(width, height)
@@ -68,6 +75,10 @@ impl Camera {
     /// Will panic if the ``Projection`` component does not exist for this
     /// entity
     pub fn proj_matrix_reverse_z(&self, scene: &Scene) -> na::Matrix4<f32> {
+        if let Some(intrinsics) = self.distorted_intrinsics(scene) {
+            let bounds = self.undistorted_bounds(&intrinsics, scene);
+            return intrinsics.undistorted_proj_matrix_reverse_z(&bounds.min, &bounds.max);
+        }
         let proj = scene.get_comp::<&Projection>(&self.entity).unwrap();
         let (width, height) = self.get_target_res(scene);
         proj.proj_matrix_reverse_z(width, height)
@@ -97,13 +108,17 @@ impl Camera {
     }
 
     /// Projects from 3D world to 2D screen coordinates in the range [0,
-    /// `viewport_width`] and [0, `viewport_height`]
+    /// `viewport_width`] and [0, `viewport_height`]. Works for both
+    /// perspective and orthographic projection matrices. If the camera has
+    /// [`ProjectionWithIntrinsics`] with lens distortion, the x and y are
+    /// distorted in the same way as the rendered image.
     pub fn project(
         &self,
         point_world: na::Point3<f32>,
         view: na::Matrix4<f32>,
         proj: na::Matrix4<f32>,
         viewport_size: na::Vector2<f32>,
+        scene: &Scene,
     ) -> na::Vector3<f32> {
         //get the point from world to screen space
         let p_view = view * point_world.to_homogeneous();
@@ -113,11 +128,21 @@ impl Camera {
         p_proj.x *= viewport_size.x;
         p_proj.y *= viewport_size.y;
 
+        //the depth still follows the projection matrix but the position on screen goes through the lens
+        if let Some(intrinsics) = self.distorted_intrinsics_for_viewport(viewport_size, scene) {
+            let p_normalized = na::Vector2::new(p_view.x / -p_view.z, p_view.y / p_view.z);
+            let p_px = intrinsics.normalized_to_pixel(&p_normalized);
+            p_proj.x = p_px.x;
+            p_proj.y = viewport_size.y - p_px.y;
+        }
+
         p_proj.fixed_rows::<3>(0).clone_owned()
     }
 
     /// Unprojects from 2D screen coordinates in range [0, `viewport_width`] and
-    /// [0, `viewport_height`] to 3D world # Panics
+    /// [0, `viewport_height`] to 3D world. It's the inverse of
+    /// [`Camera::project`] and works for both perspective and orthographic
+    /// projection matrices and for lens distortion. # Panics
     /// Will panic if the proj*view matrix is not invertable
     pub fn unproject(
         &self,
@@ -125,13 +150,26 @@ impl Camera {
         view: na::Matrix4<f32>,
         proj: na::Matrix4<f32>,
         viewport_size: na::Vector2<f32>,
+        scene: &Scene,
     ) -> na::Vector3<f32> {
         let inv = (proj * view).try_inverse().unwrap();
 
+        //from screen to normalized device coordinates, the w stays 1
         let mut tmp = win.to_homogeneous();
         tmp.x /= viewport_size.x;
         tmp.y /= viewport_size.y;
-        tmp = tmp * 2.0 - na::Vector4::<f32>::new(-1.0, -1.0, -1.0, -1.0);
+        tmp.x = tmp.x * 2.0 - 1.0;
+        tmp.y = tmp.y * 2.0 - 1.0;
+        tmp.z = tmp.z * 2.0 - 1.0;
+
+        //undistort the pixel and get the ndc of its ray in the undistorted image of the projection matrix
+        if let Some(intrinsics) = self.distorted_intrinsics_for_viewport(viewport_size, scene) {
+            if let Some(p_normalized) = intrinsics.pixel_to_normalized(&na::Vector2::new(win.x, viewport_size.y - win.y)) {
+                let p_clip = proj * na::Vector4::new(p_normalized.x, -p_normalized.y, -1.0, 1.0);
+                tmp.x = p_clip.x / p_clip.w;
+                tmp.y = p_clip.y / p_clip.w;
+            }
+        }
 
         let mut obj = inv * tmp;
         obj = obj / obj.w;
@@ -141,6 +179,24 @@ impl Camera {
         scene
     }
 
+    /// Intrinsics of the camera if it has lens distortion, scaled from the
+    /// target resolution towards the `viewport_size`
+    #[allow(clippy::cast_precision_loss)]
+    fn distorted_intrinsics_for_viewport(&self, viewport_size: na::Vector2<f32>, scene: &Scene) -> Option<ProjectionWithIntrinsics> {
+        let mut intrinsics = self.distorted_intrinsics(scene)?;
+        if let Ok(res) = scene.get_comp::<&TargetResolution>(&self.entity) {
+            if res.width != 0 && res.height != 0 {
+                let scale_x = viewport_size.x / res.width as f32;
+                let scale_y = viewport_size.y / res.height as f32;
+                intrinsics.fx *= scale_x;
+                intrinsics.cx *= scale_x;
+                intrinsics.fy *= scale_y;
+                intrinsics.cy *= scale_y;
+            }
+        }
+        Some(intrinsics)
+    }
+
     /// Handle the event of touching with a finger
     /// # Panics
     /// Will panic if the ``CamController`` component does not exist for this
@@ -346,15 +402,22 @@ impl Camera {
             } else if cam_control.mouse_mode == CamMode::Translation && cam_control.prev_mouse_pos_valid {
                 let view = pos_lookat.view_matrix();
 
-                let coord = self.project(pos_lookat.lookat, view, proj, viewport_size);
+                let coord = self.project(pos_lookat.lookat, view, proj, viewport_size, scene);
                 let down_mouse_z = coord.z;
 
-                let pos1 = self.unproject(na::Point3::<f32>::new(x, viewport_size.y - y, down_mouse_z), view, proj, viewport_size);
+                let pos1 = self.unproject(
+                    na::Point3::<f32>::new(x, viewport_size.y - y, down_mouse_z),
+                    view,
+                    proj,
+                    viewport_size,
+                    scene,
+                );
                 let pos0 = self.unproject(
                     na::Point3::<f32>::new(cam_control.prev_mouse.x, viewport_size.y - cam_control.prev_mouse.y, down_mouse_z),
                     view,
                     proj,
                     viewport_size,
+                    scene,
                 );
                 let diff = pos1 - pos0;
                 // diff.array()*=speed_multiplier;
@@ -373,6 +436,7 @@ impl Camera {
     pub fn process_mouse_scroll(&mut self, delta: &MouseScrollDelta, scene: &mut Scene) {
         let mut pos_lookat = scene.get_comp::<&mut PosLookat>(&self.entity).unwrap();
         let cam_control = scene.get_comp::<&mut CamController>(&self.entity).unwrap();
+        let mut projection = scene.get_comp::<&mut Projection>(&self.entity).ok();
 
         let scroll = match delta {
             // I'm assuming a line is about 100 pixels
@@ -383,6 +447,12 @@ impl Camera {
 
         let mut s = if scroll > 0.0 { 0.1 } else { -0.1 };
 
+        //moving an orthographic camera doesn't change the size of the objects so instead we zoom by changing the size of the view volume
+        if let Some(Projection::Orthographic(proj)) = projection.as_deref_mut() {
+            proj.height *= 1.0 - s;
+            return;
+        }
+
         if let Some(max_dist) = cam_control.limit_max_dist {
             let cur_dist = pos_lookat.dist_lookat();
             if cur_dist > max_dist && s < 0.0 {
@@ -394,6 +464,71 @@ impl Camera {
         pos_lookat.dolly(s);
     }
 
+    /// Switches between perspective and orthographic projection. The new
+    /// projection shows the objects at the lookat point with the same size as
+    /// the previous one. Cameras with projection from intrinsics are left
+    /// untouched.
+    /// # Panics
+    /// Will panic if the ``Projection`` component does not exist for this
+    /// entity
+    pub fn set_orthographic(&mut self, orthographic: bool, scene: &mut Scene) {
+        let dist = scene
+            .get_comp::<&PosLookat>(&self.entity)
+            .map_or(PosLookat::default().dist_lookat(), |pos_lookat| pos_lookat.dist_lookat());
+        let mut proj = scene.get_comp::<&mut Projection>(&self.entity).unwrap();
+        let new_proj = match (&*proj, orthographic) {
+            (Projection::WithFov(proj_fov), true) => Projection::Orthographic(ProjectionOrthographic::from_perspective(proj_fov, dist)),
+            (Projection::Orthographic(proj_ortho), false) => Projection::WithFov(proj_ortho.to_perspective(dist)),
+            _ => return,
+        };
+        *proj = new_proj;
+    }
+
+    /// # Panics
+    /// Will panic if the ``Projection`` component does not exist for this
+    /// entity
+    pub fn is_orthographic(&self, scene: &Scene) -> bool {
+        scene.get_comp::<&Projection>(&self.entity).unwrap().is_orthographic()
+    }
+
+    /// Returns the intrinsics of the camera if it uses
+    /// [`ProjectionWithIntrinsics`] with lens distortion
+    pub fn distorted_intrinsics(&self, scene: &Scene) -> Option<ProjectionWithIntrinsics> {
+        match &*scene.get_comp::<&Projection>(&self.entity).ok()? {
+            Projection::WithIntrinsics(proj) if proj.distortion.is_some() => Some(proj.clone()),
+            _ => None,
+        }
+    }
+
+    /// Bounds of the undistorted image of a camera with lens distortion, see
+    /// [`ProjectionWithIntrinsics::undistorted_bounds`]. Uses the cached
+    /// [`UndistortedBounds`] if they are still valid and computes them
+    /// otherwise
+    /// # Panics
+    /// Will panic if the ``TargetResolution`` component does not exist for
+    /// this entity
+    pub fn undistorted_bounds(&self, intrinsics: &ProjectionWithIntrinsics, scene: &Scene) -> UndistortedBounds {
+        let (width, height) = self.get_target_res(scene);
+        match scene.get_comp::<&UndistortedBounds>(&self.entity) {
+            Ok(bounds) if bounds.is_valid_for(intrinsics, width, height) => (*bounds).clone(),
+            _ => UndistortedBounds::new(intrinsics, width, height),
+        }
+    }
+
+    /// Sets the lens distortion of a camera which uses
+    /// [`ProjectionWithIntrinsics`]. Has no effect for other projections.
+    /// # Panics
+    /// Will panic if the ``Projection`` component does not exist for this
+    /// entity
+    pub fn set_lens_distortion(&mut self, distortion: Option<LensDistortion>, scene: &mut Scene) {
+        let mut proj = scene.get_comp::<&mut Projection>(&self.entity).unwrap();
+        if let Projection::WithIntrinsics(ref mut proj) = *proj {
+            proj.distortion = distortion;
+        } else {
+            warn!("Lens distortion is only supported for cameras with intrinsics");
+        }
+    }
+
     /// Resizing the window means that the projection matrix of the camera has
     /// to change accordingly so as to not squish the scene # Panics
     /// Will panic if the ``Projection`` component does not exist for this
@@ -401,8 +536,10 @@ impl Camera {
     pub fn set_aspect_ratio(&mut self, val: f32, scene: &mut Scene) {
         //extract data
         let mut proj = scene.get_comp::<&mut Projection>(&self.entity).unwrap();
-        if let Projection::WithFov(ref mut proj) = *proj {
-            proj.aspect_ratio = val;
+        match *proj {
+            Projection::WithFov(ref mut proj) => proj.aspect_ratio = val,
+            Projection::Orthographic(ref mut proj) => proj.aspect_ratio = val,
+            Projection::WithIntrinsics(_) => {}
         }
     }
 
@@ -410,8 +547,10 @@ impl Camera {
     /// to change accordingly so as to not squish the scene
     pub fn set_aspect_ratio_maybe(&mut self, val: f32, scene: &mut Scene) {
         if let Ok(mut proj) = scene.get_comp::<&mut Projection>(&self.entity) {
-            if let Projection::WithFov(ref mut proj) = *proj {
-                proj.aspect_ratio = val;
+            match *proj {
+                Projection::WithFov(ref mut proj) => proj.aspect_ratio = val,
+                Projection::Orthographic(ref mut proj) => proj.aspect_ratio = val,
+                Projection::WithIntrinsics(_) => {}
             }
         } else if scene.nr_renderables() != 0 {
             //if the nr of renderabled is 0 then might not have a projection matrix and
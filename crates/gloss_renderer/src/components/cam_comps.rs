@@ -1,5 +1,6 @@
 // use gloss_hecs::Bundle;
 
+use gloss_hecs::Entity;
 use std::collections::HashMap;
 use winit::event::Touch;
 
@@ -36,10 +37,12 @@ pub struct PosLookat {
 }
 
 // #[derive(Default)]
+#[derive(Clone)]
 pub enum Projection {
     // #[default]
     WithFov(ProjectionWithFov),
     WithIntrinsics(ProjectionWithIntrinsics),
+    Orthographic(ProjectionOrthographic),
 }
 
 /// Component usually used on camera on lights. Defines a projection matrix
@@ -61,6 +64,32 @@ pub struct ProjectionWithIntrinsics {
     // pub width: f32,
     pub near: f32,
     pub far: f32,
+    /// When set, the scene is rendered at a larger undistorted resolution
+    /// and then warped so that it matches the images of the real lens
+    pub distortion: Option<LensDistortion>,
+}
+
+/// Lens distortion of a [`ProjectionWithIntrinsics`]. The coefficients follow
+/// the conventions of `OpenCV` so the values from a calibration can be used
+/// directly. The distortion is applied on the normalized image coordinates,
+/// with x going right and y going down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LensDistortion {
+    /// Radial and tangential distortion as in `cv::projectPoints`
+    BrownConrady { k1: f32, k2: f32, k3: f32, p1: f32, p2: f32 },
+    /// Equidistant fisheye distortion as in `cv::fisheye::projectPoints`
+    KannalaBrandt { k1: f32, k2: f32, k3: f32, k4: f32 },
+}
+
+/// Parallel projection without perspective foreshortening. Useful for
+/// technical views of an object and for directional lights. The visible volume
+/// is `height` world units tall and `height * aspect_ratio` wide.
+#[derive(Clone)]
+pub struct ProjectionOrthographic {
+    pub aspect_ratio: f32,
+    pub height: f32,
+    pub near: f32,
+    pub far: f32,
 }
 
 /// Component usually used on camera, allows to keep track of camera state while
@@ -77,6 +106,51 @@ pub struct CamController {
                                                  * to an "active" or touching finger */
 }
 
+/// Component that turns a camera into a view which is drawn inside a rectangle
+/// of the output. The rectangle is in normalized coordinates with the origin
+/// at the top-left corner, so a view covering the left half of the window is
+/// `Viewport::new(0.0, 0.0, 0.5, 1.0)`. When at least one camera has a
+/// viewport, only the cameras with viewports are rendered. Cameras with
+/// [`LensDistortion`] are warped inside their viewport as well.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Component for per-view overrides of the render config. Only has an effect
+/// on cameras that also have a [`Viewport`]
+#[derive(Clone, Debug, Default)]
+pub struct ViewOverrides {
+    pub bg_color: Option<na::Vector4<f32>>,
+    pub wireframe_only: bool,
+}
+
+/// Resource that selects which view receives the mouse and touch input. If
+/// it's missing or points to a camera without a [`Viewport`], the input goes
+/// to the main camera
+#[derive(Clone, Copy, Debug)]
+pub struct InputView {
+    pub entity: Entity,
+}
+
+/// Everything that determines the undistorted image of a camera with lens
+/// distortion: the intrinsics and the size of the distorted image
+pub type DistortionKey = (f32, f32, f32, f32, Option<LensDistortion>, u32, u32);
+
+/// Component that caches the [`ProjectionWithIntrinsics::undistorted_bounds`]
+/// of a camera with lens distortion since computing them inverts the
+/// distortion along the whole border of the image. It's added and kept up to
+/// date by [`crate::scene::Scene::update_undistorted_bounds`]
+#[derive(Clone, Debug)]
+pub struct UndistortedBounds {
+    pub min: na::Vector2<f32>,
+    pub max: na::Vector2<f32>,
+    key: DistortionKey,
+}
+
 //implementations
 //PosLookAt
 impl Default for PosLookat {
@@ -116,6 +190,17 @@ impl PosLookat {
         }
     }
 
+    /// Returns the position, lookat and up vector moved by a transform. The
+    /// scale only affects the distance between position and lookat
+    #[must_use]
+    pub fn transformed(&self, tf: &na::SimilarityMatrix3<f32>) -> Self {
+        Self {
+            position: tf * self.position,
+            lookat: tf * self.lookat,
+            up: tf.isometry.rotation * self.up,
+        }
+    }
+
     /// Get view matrix as a mat4x4. View matrix maps from world to camera
     /// coordinates
     pub fn view_matrix(&self) -> na::Matrix4<f32> {
@@ -235,6 +320,7 @@ impl Projection {
         match self {
             Projection::WithFov(proj) => proj.proj_matrix(),
             Projection::WithIntrinsics(proj) => proj.proj_matrix(width, height),
+            Projection::Orthographic(proj) => proj.proj_matrix(),
         }
     }
     /// # Panics
@@ -244,26 +330,33 @@ impl Projection {
         match self {
             Projection::WithFov(proj) => proj.proj_matrix_reverse_z(),
             Projection::WithIntrinsics(proj) => proj.proj_matrix_reverse_z(width, height),
+            Projection::Orthographic(proj) => proj.proj_matrix_reverse_z(),
         }
     }
     pub fn near_far(&self) -> (f32, f32) {
         match self {
             Projection::WithFov(proj) => (proj.near, proj.far),
             Projection::WithIntrinsics(proj) => (proj.near, proj.far),
+            Projection::Orthographic(proj) => (proj.near, proj.far),
         }
     }
     pub fn set_near(&mut self, val: f32) {
         match self {
             Projection::WithFov(ref mut proj) => proj.near = val,
             Projection::WithIntrinsics(ref mut proj) => proj.near = val,
+            Projection::Orthographic(ref mut proj) => proj.near = val,
         }
     }
     pub fn set_far(&mut self, val: f32) {
         match self {
             Projection::WithFov(ref mut proj) => proj.far = val,
             Projection::WithIntrinsics(ref mut proj) => proj.far = val,
+            Projection::Orthographic(ref mut proj) => proj.far = val,
         }
     }
+    pub fn is_orthographic(&self) -> bool {
+        matches!(self, Projection::Orthographic(_))
+    }
 }
 
 //ProjectionWithFov
@@ -317,11 +410,71 @@ impl ProjectionWithFov {
     }
 }
 
+//ProjectionOrthographic
+impl Default for ProjectionOrthographic {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: 1.6,
+            height: 2.0,
+            near: 0.01,
+            far: 100.0,
+        }
+    }
+}
+impl ProjectionOrthographic {
+    /// Creates an orthographic projection that shows objects at distance
+    /// `dist` with the same size as the perspective projection `proj`
+    pub fn from_perspective(proj: &ProjectionWithFov, dist: f32) -> Self {
+        Self {
+            aspect_ratio: proj.aspect_ratio,
+            height: 2.0 * dist * (0.5 * proj.fovy).tan(),
+            near: proj.near,
+            far: proj.far,
+        }
+    }
+
+    /// Creates a perspective projection which, at distance `dist`, shows
+    /// objects with the same size as this orthographic projection
+    pub fn to_perspective(&self, dist: f32) -> ProjectionWithFov {
+        ProjectionWithFov {
+            aspect_ratio: self.aspect_ratio,
+            fovy: 2.0 * (0.5 * self.height / dist).atan(),
+            near: self.near,
+            far: self.far,
+        }
+    }
+
+    // right hand orthographic projection with a depth range of 0 to 1
+    pub fn proj_matrix(&self) -> na::Matrix4<f32> {
+        let half_height = 0.5 * self.height;
+        let half_width = half_height * self.aspect_ratio;
+        glm::ortho_rh_zo(-half_width, half_width, -half_height, half_height, self.near, self.far)
+    }
+
+    pub fn proj_matrix_reverse_z(&self) -> na::Matrix4<f32> {
+        let mat = self.proj_matrix();
+
+        let mut depth_remap = glm::TMat4::identity();
+        depth_remap[(2, 2)] = -1.0;
+        depth_remap[(2, 3)] = 1.0;
+
+        depth_remap * mat
+    }
+}
+
 impl ProjectionWithIntrinsics {
+    /// Projection matrix towards an image of size `width` x `height`. With
+    /// distortion this is the matrix of the undistorted image that covers the
+    /// whole distorted one, see [`ProjectionWithIntrinsics::undistorted_bounds`]
     #[allow(clippy::cast_precision_loss)]
     pub fn proj_matrix(&self, width: u32, height: u32) -> na::Matrix4<f32> {
         let mut projection_matrix = na::Matrix4::<f32>::zeros();
 
+        if self.distortion.is_some() {
+            let (min, max) = self.undistorted_bounds(width, height);
+            return self.undistorted_proj_matrix(&min, &max);
+        }
+
         // Calculate the projection matrix with the given fx, fy, and normalised cx, cy
         projection_matrix[(0, 0)] = 2.0 * self.fx / width as f32;
         projection_matrix[(1, 1)] = 2.0 * self.fy / height as f32;
@@ -348,7 +501,209 @@ impl ProjectionWithIntrinsics {
         // mat[(2, 3)] *= -1.0;
         // mat
     }
+
+    /// Projection matrix of the undistorted image with the given bounds in
+    /// normalized coordinates, see [`ProjectionWithIntrinsics::undistorted_bounds`]
+    pub fn undistorted_proj_matrix(&self, min: &na::Vector2<f32>, max: &na::Vector2<f32>) -> na::Matrix4<f32> {
+        let mut projection_matrix = na::Matrix4::<f32>::zeros();
+        let size = max - min;
+        projection_matrix[(0, 0)] = 2.0 / size.x;
+        projection_matrix[(1, 1)] = 2.0 / size.y;
+        projection_matrix[(0, 2)] = 1.0 + 2.0 * min.x / size.x;
+        projection_matrix[(1, 2)] = -1.0 - 2.0 * min.y / size.y;
+        projection_matrix[(2, 2)] = -self.far / (self.far - self.near);
+        projection_matrix[(2, 3)] = -self.far * self.near / (self.far - self.near);
+        projection_matrix[(3, 2)] = -1.0;
+        projection_matrix
+    }
+
+    pub fn undistorted_proj_matrix_reverse_z(&self, min: &na::Vector2<f32>, max: &na::Vector2<f32>) -> na::Matrix4<f32> {
+        let mat = self.undistorted_proj_matrix(min, max);
+
+        let mut depth_remap = glm::TMat4::identity();
+        depth_remap[(2, 2)] = -1.0;
+        depth_remap[(2, 3)] = 1.0;
+
+        depth_remap * mat
+    }
+
+    /// Maps normalized image coordinates (x right, y down, at unit distance in
+    /// front of the camera) to pixels, applying the distortion if there is any
+    pub fn normalized_to_pixel(&self, p: &na::Vector2<f32>) -> na::Vector2<f32> {
+        let p_distorted = self.distortion.map_or(*p, |distortion| distortion.distort(p));
+        na::Vector2::new(self.fx * p_distorted.x + self.cx, self.fy * p_distorted.y + self.cy)
+    }
+
+    /// Inverse of [`ProjectionWithIntrinsics::normalized_to_pixel`]. Returns
+    /// None if the distortion cannot be inverted at this pixel
+    pub fn pixel_to_normalized(&self, px: &na::Vector2<f32>) -> Option<na::Vector2<f32>> {
+        let p_distorted = na::Vector2::new((px.x - self.cx) / self.fx, (px.y - self.cy) / self.fy);
+        match self.distortion {
+            Some(distortion) => distortion.undistort(&p_distorted),
+            None => Some(p_distorted),
+        }
+    }
+
+    pub fn distortion_key(&self, width: u32, height: u32) -> DistortionKey {
+        (self.fx, self.fy, self.cx, self.cy, self.distortion, width, height)
+    }
+
+    /// Bounding box (min, max) in normalized coordinates of the rays that are
+    /// seen by an image of size `width` x `height`. Computed by undistorting
+    /// the border of the image which works for both barrel and pincushion
+    /// distortion.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn undistorted_bounds(&self, width: u32, height: u32) -> (na::Vector2<f32>, na::Vector2<f32>) {
+        //very wide fisheye lenses can see rays which are almost parallel to the image plane so we clamp the bounds
+        const MAX_EXTENT: f32 = 10.0;
+        const NR_SAMPLES_PER_EDGE: usize = 64;
+
+        let (w, h) = (width as f32, height as f32);
+        let mut min = na::Vector2::repeat(f32::MAX);
+        let mut max = na::Vector2::repeat(f32::MIN);
+        for i in 0..=NR_SAMPLES_PER_EDGE {
+            let t = i as f32 / NR_SAMPLES_PER_EDGE as f32;
+            let border = [
+                na::Vector2::new(t * w, 0.0),
+                na::Vector2::new(t * w, h),
+                na::Vector2::new(0.0, t * h),
+                na::Vector2::new(w, t * h),
+            ];
+            for p in border.iter().filter_map(|px| self.pixel_to_normalized(px)) {
+                min = min.inf(&p);
+                max = max.sup(&p);
+            }
+        }
+        (min.sup(&na::Vector2::repeat(-MAX_EXTENT)), max.inf(&na::Vector2::repeat(MAX_EXTENT)))
+    }
+
+    /// Resolution of the undistorted image which has the same pixel density as
+    /// the distorted one at the principal point. Gets scaled down so that no
+    /// side is larger than `max_size`.
+    pub fn undistorted_res(&self, width: u32, height: u32, max_size: u32) -> (u32, u32) {
+        let (min, max) = self.undistorted_bounds(width, height);
+        self.undistorted_res_for_bounds(&min, &max, max_size)
+    }
+
+    /// Same as [`ProjectionWithIntrinsics::undistorted_res`] but with bounds
+    /// that were already computed
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn undistorted_res_for_bounds(&self, min: &na::Vector2<f32>, max: &na::Vector2<f32>, max_size: u32) -> (u32, u32) {
+        let size = max - min;
+        let mut res = na::Vector2::new(size.x * self.fx, size.y * self.fy);
+        let scale = (max_size as f32 / res.max()).min(1.0);
+        res *= scale;
+        ((res.x.ceil() as u32).max(1), (res.y.ceil() as u32).max(1))
+    }
 }
+
+//UndistortedBounds
+impl UndistortedBounds {
+    pub fn new(intrinsics: &ProjectionWithIntrinsics, width: u32, height: u32) -> Self {
+        let (min, max) = intrinsics.undistorted_bounds(width, height);
+        Self {
+            min,
+            max,
+            key: intrinsics.distortion_key(width, height),
+        }
+    }
+
+    /// Whether the bounds were computed for these intrinsics and image size
+    pub fn is_valid_for(&self, intrinsics: &ProjectionWithIntrinsics, width: u32, height: u32) -> bool {
+        self.key == intrinsics.distortion_key(width, height)
+    }
+}
+
+//LensDistortion
+impl LensDistortion {
+    /// Distorts a point in normalized image coordinates
+    pub fn distort(&self, p: &na::Vector2<f32>) -> na::Vector2<f32> {
+        match *self {
+            LensDistortion::BrownConrady { k1, k2, k3, p1, p2 } => {
+                let (x, y) = (p.x, p.y);
+                let r2 = x * x + y * y;
+                let radial = 1.0 + r2 * (k1 + r2 * (k2 + r2 * k3));
+                let dx = 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+                let dy = p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+                na::Vector2::new(x * radial + dx, y * radial + dy)
+            }
+            LensDistortion::KannalaBrandt { k1, k2, k3, k4 } => {
+                let r = p.norm();
+                if r < 1e-8 {
+                    return *p;
+                }
+                let theta = r.atan();
+                let theta2 = theta * theta;
+                let theta_d = theta * (1.0 + theta2 * (k1 + theta2 * (k2 + theta2 * (k3 + theta2 * k4))));
+                p * (theta_d / r)
+            }
+        }
+    }
+
+    /// Inverse of [`LensDistortion::distort`] computed with Newton iterations.
+    /// Returns None if it doesn't converge, which can happen for points
+    /// outside of the range where the distortion is invertible.
+    pub fn undistort(&self, p: &na::Vector2<f32>) -> Option<na::Vector2<f32>> {
+        const MAX_ITERS: usize = 20;
+        const EPS: f32 = 1e-6;
+        const H: f32 = 1e-3; //step for the finite differences of the jacobian
+
+        let mut u = *p;
+        for _ in 0..MAX_ITERS {
+            let residual = self.distort(&u) - p;
+            if residual.norm() < EPS {
+                return Some(u);
+            }
+            let dx = (self.distort(&(u + na::Vector2::new(H, 0.0))) - self.distort(&(u - na::Vector2::new(H, 0.0)))) / (2.0 * H);
+            let dy = (self.distort(&(u + na::Vector2::new(0.0, H))) - self.distort(&(u - na::Vector2::new(0.0, H)))) / (2.0 * H);
+            let jacobian_inv = na::Matrix2::from_columns(&[dx, dy]).try_inverse()?;
+            u -= jacobian_inv * residual;
+        }
+        //the last iterations might just be bouncing around the solution due to the float precision
+        ((self.distort(&u) - p).norm() < EPS * 100.0).then_some(u)
+    }
+}
+//Viewport
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Rectangle in pixels as (x, y, width, height) for an output of size
+    /// `out_width` x `out_height`. The corners are rounded independently so
+    /// that views which share an edge tile the output without gaps.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_pixels(&self, out_width: u32, out_height: u32) -> (u32, u32, u32, u32) {
+        let to_px = |v: f32, size: u32| (v.clamp(0.0, 1.0) * size as f32).round() as u32;
+        let x_start = to_px(self.x, out_width).min(out_width.saturating_sub(1));
+        let y_start = to_px(self.y, out_height).min(out_height.saturating_sub(1));
+        let x_end = to_px(self.x + self.width, out_width).max(x_start + 1);
+        let y_end = to_px(self.y + self.height, out_height).max(y_start + 1);
+        (x_start, y_start, x_end - x_start, y_end - y_start)
+    }
+
+    /// Checks if a pixel position lies inside the viewport
+    #[allow(clippy::cast_precision_loss)]
+    pub fn contains(&self, px: f32, py: f32, out_width: u32, out_height: u32) -> bool {
+        let (x, y, w, h) = self.to_pixels(out_width, out_height);
+        px >= x as f32 && px < (x + w) as f32 && py >= y as f32 && py < (y + h) as f32
+    }
+}
+
 //CamController
 impl Default for CamController {
     fn default() -> Self {
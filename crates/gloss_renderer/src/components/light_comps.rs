@@ -4,6 +4,7 @@ extern crate nalgebra as na;
 extern crate nalgebra_glm as glm;
 
 /// Component usually added on lights. Defines properties of the light emitter.
+#[derive(Clone)]
 pub struct LightEmit {
     pub color: na::Vector3<f32>,
     pub intensity: f32,
@@ -40,6 +41,7 @@ impl Default for LightEmit {
 
 /// Component added to a Light to indicate that it will cast a shadow with a
 /// certain resolution
+#[derive(Clone)]
 pub struct ShadowCaster {
     /// Resolution of the shadow map. Shadow map is always a square texture.
     pub shadow_res: u32,
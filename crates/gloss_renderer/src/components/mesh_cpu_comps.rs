@@ -8,7 +8,7 @@ extern crate nalgebra as na;
 use gloss_img::DynImage;
 use gloss_utils::{
     io::FileLoader,
-    tensor::{DynamicTensorFloat2D, DynamicTensorInt2D},
+    tensor::{DynamicMatrixOps, DynamicTensorFloat2D, DynamicTensorInt2D, DynamicTensorOps},
 };
 use image::ImageReader;
 use na::DMatrix;
@@ -26,6 +26,16 @@ pub struct ConfigChanges {
 pub enum PointColorType {
     Solid = 0,
     PerVert,
+    Scalar,
+}
+
+/// Defines how every point of a point cloud is drawn. Surfels are disks
+/// oriented along the [`Normals`] and lit like meshes. Points without normals
+/// are always drawn as sprites
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointRenderMode {
+    Sprite = 0,
+    Surfel,
 }
 
 /// Defines the color type an entity which is displayed as a point cloud
@@ -33,6 +43,7 @@ pub enum PointColorType {
 pub enum LineColorType {
     Solid = 0,
     PerVert,
+    Scalar,
 }
 
 /// Defines the color type an entity which is displayed as a mesh
@@ -44,6 +55,91 @@ pub enum MeshColorType {
     UV,
     Normal,
     NormalViewCoords,
+    Scalar,
+}
+
+/// Colormaps used to draw a [`Scalars`] field. They are evaluated on the GPU
+/// so the same polynomial fits are used by [`Colormap::sample`] on the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Viridis = 0,
+    Magma,
+    Coolwarm,
+    Jet,
+}
+impl Colormap {
+    pub const ALL: [Colormap; 4] = [Colormap::Viridis, Colormap::Magma, Colormap::Coolwarm, Colormap::Jet];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colormap::Viridis => "Viridis",
+            Colormap::Magma => "Magma",
+            Colormap::Coolwarm => "Coolwarm",
+            Colormap::Jet => "Jet",
+        }
+    }
+
+    /// Color at `t` which is clamped to [0,1]. Keep in sync with
+    /// `colormap_utils.wgsl`
+    pub fn sample(self, t: f32) -> na::Vector3<f32> {
+        let t = t.clamp(0.0, 1.0);
+        let color = match self {
+            //polynomial fits from https://www.shadertoy.com/view/WlfXRN
+            Colormap::Viridis => Self::poly6(
+                t,
+                &[
+                    [0.277_727_33, 0.005_407_344_5, 0.334_099_8],
+                    [0.105_093_04, 1.404_613_5, 1.384_590_2],
+                    [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+                    [-4.634_230_5, -5.799_101, -19.332_441],
+                    [6.228_27, 14.179_933, 56.690_55],
+                    [4.776_385, -13.745_145, -65.353_03],
+                    [-5.435_456, 4.645_852_6, 26.312_435],
+                ],
+            ),
+            Colormap::Magma => Self::poly6(
+                t,
+                &[
+                    [-0.002_136_485, -0.000_749_655, -0.005_386_128],
+                    [0.251_660_54, 0.677_523_24, 2.494_026_6],
+                    [8.353_717, -3.577_719_5, 0.314_467_9],
+                    [-27.668_733, 14.264_731, -13.649_213],
+                    [52.176_14, -27.943_606, 12.944_169],
+                    [-50.768_524, 29.046_583, 4.234_153],
+                    [18.655_705, -11.489_774, -5.601_961_5],
+                ],
+            ),
+            //diverging map from Moreland, interpolated between a few of its control points
+            Colormap::Coolwarm => {
+                const STOPS: [[f32; 3]; 5] = [
+                    [0.230, 0.299, 0.754],
+                    [0.552, 0.690, 0.996],
+                    [0.865, 0.865, 0.865],
+                    [0.958, 0.603, 0.482],
+                    [0.706, 0.016, 0.150],
+                ];
+                let pos = t * 4.0;
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let idx = (pos.floor() as usize).min(3);
+                #[allow(clippy::cast_precision_loss)]
+                let frac = pos - idx as f32;
+                let a = na::Vector3::from(STOPS[idx]);
+                let b = na::Vector3::from(STOPS[idx + 1]);
+                a.lerp(&b, frac)
+            }
+            Colormap::Jet => na::Vector3::new(
+                (1.5 - (4.0 * t - 3.0).abs()).clamp(0.0, 1.0),
+                (1.5 - (4.0 * t - 2.0).abs()).clamp(0.0, 1.0),
+                (1.5 - (4.0 * t - 1.0).abs()).clamp(0.0, 1.0),
+            ),
+        };
+        color.map(|c| c.clamp(0.0, 1.0))
+    }
+
+    fn poly6(t: f32, coeffs: &[[f32; 3]; 7]) -> na::Vector3<f32> {
+        //horner evaluation starting from the highest degree
+        coeffs.iter().rev().fold(na::Vector3::zeros(), |acc, c| acc * t + na::Vector3::from(*c))
+    }
 }
 
 /// Component for visualization options of lines
@@ -94,12 +190,32 @@ pub struct VisPoints {
     pub point_size: f32,
     pub is_point_size_in_world_space: bool,
     pub color_type: PointColorType,
+    pub render_mode: PointRenderMode,
     pub zbuffer: bool,
     //if this components was added automatically by the renderer this will be set to true. This is useful to know since when we upload textures to
     // gpu the vis_mesh.color_type will be set to Texture but this should happen ONLY if the VisMesh was added automatically. If the used adds this
     // component manually then we shouldn't override his VisMesh.colortype.
     pub added_automatically: bool,
 }
+/// Component for visualization options of a [`Scalars`] field. These are
+/// uploaded every frame together with the other per-entity options so changing
+/// the range or the colormap doesn't re-upload any vertex data
+#[derive(Clone)]
+pub struct VisScalars {
+    pub colormap: Colormap,
+    pub min: f32,
+    pub max: f32,
+    //values outside of [min,max] get the color of the closest end of the colormap, otherwise they are drawn with the out_of_range_color
+    pub clamp: bool,
+    pub out_of_range_color: na::Vector4<f32>,
+    pub nr_isolines: u32, //isolines are evenly spaced between min and max and only drawn on meshes
+    pub isoline_width: f32,
+    pub isoline_color: na::Vector4<f32>,
+    pub show_colorbar: bool,
+    //if this components was added automatically by the renderer this will be set to true. The range is then set from the values of the
+    // scalars when they are first uploaded
+    pub added_automatically: bool,
+}
 /// Component for visualization options of meshes
 #[derive(Clone)]
 pub struct VisMesh {
@@ -161,11 +277,40 @@ impl Default for VisPoints {
             point_size: 1.0,
             is_point_size_in_world_space: false,
             color_type: PointColorType::Solid,
+            render_mode: PointRenderMode::Sprite,
             zbuffer: true,
             added_automatically: false,
         }
     }
 }
+impl Default for VisScalars {
+    fn default() -> VisScalars {
+        VisScalars {
+            colormap: Colormap::Viridis,
+            min: 0.0,
+            max: 1.0,
+            clamp: true,
+            out_of_range_color: na::Vector4::<f32>::new(0.5, 0.5, 0.5, 1.0),
+            nr_isolines: 0,
+            isoline_width: 1.0,
+            isoline_color: na::Vector4::<f32>::new(0.0, 0.0, 0.0, 1.0),
+            show_colorbar: true,
+            added_automatically: false,
+        }
+    }
+}
+impl VisScalars {
+    /// Sets the range to the minimum and maximum of the scalars
+    #[must_use]
+    pub fn with_range_of(mut self, scalars: &Scalars) -> Self {
+        let mat = scalars.0.to_dmatrix();
+        if !mat.is_empty() {
+            self.min = mat.min();
+            self.max = mat.max();
+        }
+        self
+    }
+}
 impl Default for VisMesh {
     fn default() -> VisMesh {
         VisMesh {
@@ -269,6 +414,93 @@ pub struct Tangents(pub DynamicTensorFloat2D);
 #[derive(Clone)]
 pub struct Colors(pub DynamicTensorFloat2D);
 
+/// Component that represents a per vertex scalar field as a Nx1 matrix, for
+/// example the distance of every vertex to a scan. It is drawn through the
+/// colormap of [`VisScalars`] when the color type of the entity is `Scalar`
+#[derive(Clone)]
+pub struct Scalars(pub DynamicTensorFloat2D);
+
+/// Maximum number of joints that can influence a vertex with [`SkinWeights`]
+pub const MAX_JOINT_INFLUENCES: usize = 4;
+
+/// Component that represents morph targets of a mesh. The basis holds K shapes
+/// of per vertex offsets from the rest pose in [`Verts`] and is stored as a
+/// (K*N)x3 matrix where rows k*N..(k+1)*N are the offsets of shape k. The basis
+/// is uploaded once and blended on the GPU with the [`BlendShapeCoeffs`] so
+/// only the coefficients are uploaded when they change.
+///
+/// The deformed vertices only exist on the GPU so the CPU queries like
+/// [`crate::scene::Scene::raycast`], [`crate::scene::Scene::get_bounding_points`]
+/// and [`crate::geom::Geom::mesh_distance_entities`] see the rest pose. Use
+/// [`crate::geom::Geom::apply_blend_shapes`] to get the deformed vertices on
+/// the CPU
+#[derive(Clone)]
+pub struct BlendShapes(pub DynamicTensorFloat2D);
+impl BlendShapes {
+    /// Builds the basis from a (N*3)xK matrix where every column is a shape
+    /// flattened as x0,y0,z0,x1,... which is the layout used by SMPL
+    pub fn from_flattened(basis: &DMatrix<f32>) -> Self {
+        assert_eq!(basis.nrows() % 3, 0, "the flattened basis should have 3 rows per vertex");
+        let nr_verts = basis.nrows() / 3;
+        let nr_shapes = basis.ncols();
+        let stacked = DMatrix::<f32>::from_fn(nr_shapes * nr_verts, 3, |r, c| basis[((r % nr_verts) * 3 + c, r / nr_verts)]);
+        Self(DynamicTensorFloat2D::from_dmatrix(&stacked))
+    }
+
+    /// Number of shapes in the basis for a mesh with `nr_verts` vertices
+    pub fn nr_shapes(&self, nr_verts: usize) -> usize {
+        self.0.nrows().checked_div(nr_verts).unwrap_or(0)
+    }
+}
+
+/// Component that holds one weight for every shape of [`BlendShapes`]. Shapes
+/// without a coefficient get a weight of zero
+#[derive(Clone, Default)]
+pub struct BlendShapeCoeffs(pub Vec<f32>);
+
+/// Component for linear blend skinning. Every vertex is influenced by up to
+/// [`MAX_JOINT_INFLUENCES`] joints, stored as Nx4 matrices of joint indices
+/// and weights. Unused influences should have a weight of zero.
+///
+/// Like with [`BlendShapes`], the skinned vertices only exist on the GPU and
+/// the CPU queries see the rest pose. Use [`crate::geom::Geom::skin_verts`] to
+/// get the skinned vertices on the CPU
+#[derive(Clone)]
+pub struct SkinWeights {
+    pub joints: DynamicTensorInt2D,
+    pub weights: DynamicTensorFloat2D,
+}
+impl SkinWeights {
+    /// Builds the weights from a dense `NxJ` matrix with a weight for every joint,
+    /// as used by SMPL. Only the largest [`MAX_JOINT_INFLUENCES`] weights of
+    /// every vertex are kept and they are normalized to sum up to one
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_dense(weights: &DMatrix<f32>) -> Self {
+        let mut joints_out = DMatrix::<u32>::zeros(weights.nrows(), MAX_JOINT_INFLUENCES);
+        let mut weights_out = DMatrix::<f32>::zeros(weights.nrows(), MAX_JOINT_INFLUENCES);
+        for (idx, row) in weights.row_iter().enumerate() {
+            let mut sorted: Vec<(usize, f32)> = row.iter().copied().enumerate().collect();
+            sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+            sorted.truncate(MAX_JOINT_INFLUENCES);
+            let sum: f32 = sorted.iter().map(|(_, w)| w).sum();
+            for (slot, (joint, w)) in sorted.iter().enumerate() {
+                joints_out[(idx, slot)] = *joint as u32;
+                weights_out[(idx, slot)] = if sum > 0.0 { w / sum } else { 0.0 };
+            }
+        }
+        Self {
+            joints: DynamicTensorInt2D::from_dmatrix(&joints_out),
+            weights: DynamicTensorFloat2D::from_dmatrix(&weights_out),
+        }
+    }
+}
+
+/// Component that holds the transform of every joint used by [`SkinWeights`].
+/// Each one maps a point of the rest pose, after the blend shapes are applied,
+/// to the current pose and is expressed in object coordinates
+#[derive(Clone, Default)]
+pub struct JointTransforms(pub Vec<na::Matrix4<f32>>);
+
 #[derive(Clone)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct ImgConfig {
@@ -413,6 +645,7 @@ impl NormalImg {
 }
 
 /// Component which represents a metalness img.
+#[derive(Clone)]
 pub struct MetalnessImg {
     pub generic_img: GenericImg,
 }
@@ -440,6 +673,7 @@ impl MetalnessImg {
 
 /// Component which represents a roughness img. Assumes it is stored as
 /// perceptual roughness/
+#[derive(Clone)]
 pub struct RoughnessImg {
     pub generic_img: GenericImg,
 }
@@ -501,6 +735,7 @@ impl RoughnessImg {
 /// the GGX distribution.
 ///
 /// `KhronosGroup` also has several prefiltered environment maps that can be found [here](https://github.com/KhronosGroup/glTF-Sample-Environments)
+#[derive(Clone)]
 pub struct EnvironmentMap {
     pub diffuse_path: String,
     pub specular_path: String,
@@ -87,6 +87,42 @@ impl EdgesV2GPU {
     }
 }
 
+/// Per vertex colors of the first vertex of every edge. Used for drawing lines
+/// with [`LineColorType::PerVert`](crate::components::LineColorType::PerVert)
+#[derive(Debug)]
+pub struct EdgesColorsV1GPU {
+    pub buf: wgpu::Buffer,
+    pub nr_vertices: u32,
+}
+impl EdgesColorsV1GPU {
+    pub fn vertex_buffer_layout_instanced<const SHADER_LOCATION: u32>() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: (3 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![
+            SHADER_LOCATION => Float32x3
+            ],
+        }
+    }
+}
+/// Per vertex colors of the second vertex of every edge
+#[derive(Debug)]
+pub struct EdgesColorsV2GPU {
+    pub buf: wgpu::Buffer,
+    pub nr_vertices: u32,
+}
+impl EdgesColorsV2GPU {
+    pub fn vertex_buffer_layout_instanced<const SHADER_LOCATION: u32>() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: (3 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![
+            SHADER_LOCATION => Float32x3
+            ],
+        }
+    }
+}
+
 pub struct UVsGPU {
     pub buf: wgpu::Buffer,
     pub nr_vertices: u32,
@@ -117,6 +153,15 @@ impl NormalsGPU {
             ],
         }
     }
+    pub fn vertex_buffer_layout_instanced<const SHADER_LOCATION: u32>() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: (3 * std::mem::size_of::<f32>()) as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![
+            SHADER_LOCATION => Float32x3
+            ],
+        }
+    }
 }
 
 pub struct TangentsGPU {
@@ -162,6 +207,55 @@ impl ColorsGPU {
         }
     }
 }
+/// Per vertex scalars with one f32 per vertex. Mapped to colors in the
+/// shaders through the colormap of [`VisScalars`](crate::components::VisScalars)
+pub struct ScalarsGPU {
+    pub buf: wgpu::Buffer,
+    pub nr_vertices: u32,
+}
+impl ScalarsGPU {
+    pub fn vertex_buffer_layout<const SHADER_LOCATION: u32>() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<f32>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![
+            SHADER_LOCATION => Float32
+            ],
+        }
+    }
+    pub fn vertex_buffer_layout_instanced<const SHADER_LOCATION: u32>() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<f32>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![
+            SHADER_LOCATION => Float32
+            ],
+        }
+    }
+}
+/// Scalars of the first vertex of every edge. Used for drawing lines with
+/// [`LineColorType::Scalar`](crate::components::LineColorType::Scalar)
+#[derive(Debug)]
+pub struct EdgesScalarsV1GPU {
+    pub buf: wgpu::Buffer,
+    pub nr_vertices: u32,
+}
+impl EdgesScalarsV1GPU {
+    pub fn vertex_buffer_layout_instanced<const SHADER_LOCATION: u32>() -> wgpu::VertexBufferLayout<'static> {
+        ScalarsGPU::vertex_buffer_layout_instanced::<SHADER_LOCATION>()
+    }
+}
+/// Scalars of the second vertex of every edge
+#[derive(Debug)]
+pub struct EdgesScalarsV2GPU {
+    pub buf: wgpu::Buffer,
+    pub nr_vertices: u32,
+}
+impl EdgesScalarsV2GPU {
+    pub fn vertex_buffer_layout_instanced<const SHADER_LOCATION: u32>() -> wgpu::VertexBufferLayout<'static> {
+        ScalarsGPU::vertex_buffer_layout_instanced::<SHADER_LOCATION>()
+    }
+}
 #[derive(Debug)]
 pub struct EdgesGPU {
     pub buf: wgpu::Buffer,
@@ -276,6 +370,31 @@ impl EnvironmentMapGpu {
     }
 }
 
+/// Buffers used by the deform pass to evaluate
+/// [`BlendShapes`](crate::components::BlendShapes) and
+/// [`SkinWeights`](crate::components::SkinWeights) on the GPU. It's added
+/// automatically and the result is written directly into the [`VertsGPU`] and
+/// [`NormalsGPU`] of the entity. Buffers of components that the entity doesn't
+/// have are small placeholders
+pub struct DeformGPU {
+    pub params: wgpu::Buffer,
+    pub rest_verts: wgpu::Buffer,
+    pub basis: wgpu::Buffer,
+    pub coeffs: wgpu::Buffer,
+    pub joints: wgpu::Buffer,
+    pub weights: wgpu::Buffer,
+    pub joint_transforms: wgpu::Buffer,
+    pub faces: wgpu::Buffer,
+    pub vert2faces_offsets: wgpu::Buffer,
+    pub vert2faces: wgpu::Buffer,
+    pub nr_verts: u32,
+    pub nr_shapes: u32,
+    pub nr_joints: u32,
+    pub nr_faces: u32,
+    //set when any of the inputs changed and the deformation needs to be evaluated again
+    pub(crate) dirty: bool,
+}
+
 //implement some atributes for the vertex atributes so we can use them in a
 // generic function
 pub trait GpuAtrib {
@@ -307,6 +426,46 @@ impl GpuAtrib for EdgesV2GPU {
         Self { buf, nr_vertices }
     }
 }
+impl GpuAtrib for EdgesColorsV1GPU {
+    fn data_ref(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+    fn new_from(buf: wgpu::Buffer, nr_vertices: u32) -> Self {
+        Self { buf, nr_vertices }
+    }
+}
+impl GpuAtrib for EdgesColorsV2GPU {
+    fn data_ref(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+    fn new_from(buf: wgpu::Buffer, nr_vertices: u32) -> Self {
+        Self { buf, nr_vertices }
+    }
+}
+impl GpuAtrib for ScalarsGPU {
+    fn data_ref(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+    fn new_from(buf: wgpu::Buffer, nr_vertices: u32) -> Self {
+        Self { buf, nr_vertices }
+    }
+}
+impl GpuAtrib for EdgesScalarsV1GPU {
+    fn data_ref(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+    fn new_from(buf: wgpu::Buffer, nr_vertices: u32) -> Self {
+        Self { buf, nr_vertices }
+    }
+}
+impl GpuAtrib for EdgesScalarsV2GPU {
+    fn data_ref(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+    fn new_from(buf: wgpu::Buffer, nr_vertices: u32) -> Self {
+        Self { buf, nr_vertices }
+    }
+}
 impl GpuAtrib for EdgesGPU {
     fn data_ref(&self) -> &wgpu::Buffer {
         &self.buf
@@ -374,6 +533,28 @@ unsafe impl Sync for EdgesV1GPU {}
 unsafe impl Send for EdgesV2GPU {}
 #[cfg(target_arch = "wasm32")]
 unsafe impl Sync for EdgesV2GPU {}
+//edgescolorsgpu
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for EdgesColorsV1GPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for EdgesColorsV1GPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for EdgesColorsV2GPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for EdgesColorsV2GPU {}
+//scalarsgpu
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for ScalarsGPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for ScalarsGPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for EdgesScalarsV1GPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for EdgesScalarsV1GPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for EdgesScalarsV2GPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for EdgesScalarsV2GPU {}
 //edgesgpu
 #[cfg(target_arch = "wasm32")]
 unsafe impl Send for EdgesGPU {}
@@ -389,6 +570,11 @@ unsafe impl Sync for FacesGPU {}
 unsafe impl Send for UVsGPU {}
 #[cfg(target_arch = "wasm32")]
 unsafe impl Sync for UVsGPU {}
+//deformgpu
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for DeformGPU {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for DeformGPU {}
 //normalsgpu
 #[cfg(target_arch = "wasm32")]
 unsafe impl Send for NormalsGPU {}
@@ -1,5 +1,10 @@
+use crate::{bvh::Bvh, components::PosLookat};
+use gloss_hecs::Entity;
+use nalgebra as na;
+
 /// Each entity is identifies with an unique name. This component stores this
 /// name.
+#[derive(Clone)]
 pub struct Name(pub String);
 
 /// Faces from the obj file are reindexed when reading them
@@ -9,3 +14,52 @@ pub struct Name(pub String);
 /// final_index->original_index Therefore `faces_original_index`[0]=4 and
 /// `faces_original_index`[1]=2
 pub struct FacesOriginalIndex(pub Vec<u32>);
+
+/// Bounding volume hierarchy over the triangles of a mesh in object
+/// coordinates. It's created by [`crate::scene::Scene::update_bvhs`]
+/// and gets refit or rebuilt when the [`crate::components::Verts`] or
+/// [`crate::components::Faces`] change
+pub struct MeshBvh {
+    pub bvh: Bvh,
+    pub(crate) needs_refit: bool,
+    pub(crate) needs_rebuild: bool,
+}
+impl MeshBvh {
+    pub fn new(bvh: Bvh) -> Self {
+        Self {
+            bvh,
+            needs_refit: false,
+            needs_rebuild: false,
+        }
+    }
+}
+
+/// Makes the transform of an entity relative to another one. The world
+/// transform of the child is recomputed every frame by
+/// [`crate::scene::Scene::propagate_transforms`] from the world transform of
+/// the parent and the [`LocalModelMatrix`] or [`LocalPosLookat`] of the child
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Parent(pub Entity);
+
+/// Entities that have this one as their [`Parent`]. It's kept in sync with the
+/// [`Parent`] components so it only needs to be read
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Children(pub Vec<Entity>);
+
+/// Transform from the object coordinates of an entity with a [`Parent`] to the
+/// object coordinates of the parent. If it's missing, it gets computed from
+/// the current [`crate::components::ModelMatrix`] so that the child keeps its
+/// pose in the world when it's attached. Writing to the `ModelMatrix` of a
+/// child also updates it so that both stay consistent
+#[derive(Clone)]
+pub struct LocalModelMatrix(pub na::SimilarityMatrix3<f32>);
+impl Default for LocalModelMatrix {
+    fn default() -> Self {
+        Self(na::SimilarityMatrix3::<f32>::identity())
+    }
+}
+
+/// Same as [`LocalModelMatrix`] but for cameras and lights which are placed
+/// with a [`PosLookat`] instead of a model matrix
+#[derive(Clone)]
+pub struct LocalPosLookat(pub PosLookat);
@@ -1,6 +1,109 @@
+extern crate nalgebra as na;
+
 /// Identifies a mesh that will be rendered to the screen
 pub struct Renderable;
 
 /// Identifies a mesh that has been modified in some way and we need to update
 /// the shadow map
 pub struct ShadowMapDirty;
+
+/// Plane that cuts away all the geometry on its negative side, the side
+/// opposite to where the normal points. Every entity with this component is a
+/// clip plane and at most
+/// [`MAX_NUM_CLIP_PLANES`](crate::forward_renderer::render_passes::upload_pass::MAX_NUM_CLIP_PLANES)
+/// of them are used in a frame. Meshes, points and lines are all cut, except
+/// for entities that have [`NoClip`]. The cut is also applied to the aovs and
+/// to the outlines but not to the shadow maps, so the parts that are cut away
+/// still cast shadows
+#[derive(Clone, Debug)]
+pub struct ClipPlane {
+    pub point: na::Point3<f32>,
+    /// Direction towards the half-space that is kept. Doesn't need to be
+    /// normalized
+    pub normal: na::Vector3<f32>,
+    pub enabled: bool,
+    /// Fills the cut surface of meshes with the `cap_color` so they look solid
+    /// instead of hollow. This works by drawing the back faces that become
+    /// visible through the cut so it only looks right for closed meshes
+    pub cap: bool,
+    pub cap_color: na::Vector4<f32>,
+}
+impl Default for ClipPlane {
+    fn default() -> Self {
+        Self::new(na::Point3::origin(), na::Vector3::new(1.0, 0.0, 0.0))
+    }
+}
+impl ClipPlane {
+    pub fn new(point: na::Point3<f32>, normal: na::Vector3<f32>) -> Self {
+        Self {
+            point,
+            normal,
+            enabled: true,
+            cap: false,
+            cap_color: na::Vector4::new(0.8, 0.3, 0.3, 1.0),
+        }
+    }
+
+    #[must_use]
+    pub fn with_cap(mut self, cap_color: na::Vector4<f32>) -> Self {
+        self.cap = true;
+        self.cap_color = cap_color;
+        self
+    }
+
+    /// Normalized normal of the plane. Degenerate normals default to the x
+    /// axis
+    pub fn unit_normal(&self) -> na::Vector3<f32> {
+        self.normal.try_normalize(1e-8).unwrap_or_else(na::Vector3::x)
+    }
+
+    /// Plane equation as (nx, ny, nz, d) so that `n.dot(p) + d` is the signed
+    /// distance of a point to the plane
+    pub fn equation(&self) -> na::Vector4<f32> {
+        let n = self.unit_normal();
+        na::Vector4::new(n.x, n.y, n.z, -n.dot(&self.point.coords))
+    }
+
+    pub fn signed_distance(&self, point: &na::Point3<f32>) -> f32 {
+        self.unit_normal().dot(&(point - self.point))
+    }
+
+    /// True if the point is on the side of the plane that is cut away
+    pub fn clips(&self, point: &na::Point3<f32>) -> bool {
+        self.enabled && self.signed_distance(point) < 0.0
+    }
+}
+
+/// Entities with this component are not cut by any [`ClipPlane`]. The floor
+/// is never cut
+#[derive(Clone, Copy)]
+pub struct NoClip;
+
+/// Draws a screen-space outline around the silhouette of the entity, on top
+/// of everything else so it stays visible even when the entity is occluded.
+/// Works for meshes, points and lines, whichever of them are shown
+#[derive(Clone, Debug)]
+pub struct Highlight {
+    /// Color of the outline, the alpha is used for blending it over the
+    /// rendered image
+    pub color: na::Vector4<f32>,
+    /// Width of the outline in pixels
+    pub width: f32,
+    /// Set by the GUI on the highlight of the selected entity so that it can
+    /// remove it again once the selection changes
+    pub from_selection: bool,
+}
+impl Default for Highlight {
+    fn default() -> Self {
+        Self::new(na::Vector4::new(1.0, 0.6, 0.1, 1.0), 3.0)
+    }
+}
+impl Highlight {
+    pub fn new(color: na::Vector4<f32>, width: f32) -> Self {
+        Self {
+            color,
+            width,
+            from_selection: false,
+        }
+    }
+}
@@ -13,6 +13,8 @@ pub struct Config {
     pub core: CoreConfig,
     pub render: RenderConfig,
     pub scene: SceneConfig,
+    pub recorder: RecorderConfig,
+    pub aov: AovConfig,
     is_concrete: Option<bool>, // Some configs are set to "auto", when they are made concrete, this bool gets set to true
     is_consumed: Option<bool>, // Using the config to create a scene will set this to true so that we don't rerun it
 }
@@ -54,8 +56,31 @@ pub struct RenderConfig {
     pub exposure: f32,
     pub shadow_filter_method: ShadowFilteringMethod,
     pub msaa_nr_samples: u32,
+    pub antialiasing: AntiAliasing,
+    pub taa_history_weight: f32,
     pub preallocated_staging_buffer_bytes: u32,
     pub offscreen_color_float_tex: bool,
+    pub wireframe_only: bool,
+}
+
+#[derive(Clone, serde::Deserialize, Debug)]
+#[allow(unused)]
+pub struct RecorderConfig {
+    pub output_path: String,
+    pub format: RecordFormat,
+    pub fps: f32,
+}
+
+#[derive(Clone, serde::Deserialize, Debug)]
+#[allow(unused)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct AovConfig {
+    pub normals_world: bool,
+    pub normals_cam: bool,
+    pub instance_id: bool,
+    pub uv: bool,
+    pub motion_vectors: bool,
+    pub albedo: bool,
 }
 
 #[derive(Clone, serde::Deserialize, Debug)]
@@ -110,6 +135,26 @@ pub enum ShadowFilteringMethod {
     Castano13,
 }
 
+/// Anti-aliasing applied as a post process after the main pass. Can be
+/// combined with MSAA.
+#[derive(Debug, Copy, Clone, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AntiAliasing {
+    None = 0,
+    /// Fast approximate anti-aliasing which blurs along the edges it finds in
+    /// the rendered image.
+    ///
+    /// Deterministic and cheap but softens thin details.
+    Fxaa,
+    /// Temporal anti-aliasing which jitters the camera by a subpixel offset
+    /// every frame and blends with the history of the previous frames,
+    /// reprojected using the depth and the movement of the camera.
+    ///
+    /// Converges to a supersampled image when the view doesn't change but can
+    /// leave trails behind moving objects.
+    Taa,
+}
+
 #[derive(Debug, Copy, Clone, serde::Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum FloorType {
@@ -124,6 +169,19 @@ pub enum FloorTexture {
     Checkerboard,
 }
 
+#[derive(Debug, Copy, Clone, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordFormat {
+    /// Numbered sequence of 8 bit PNG images.
+    Png = 0,
+    /// Numbered sequence of 32 bit float EXR images. Most useful together
+    /// with `offscreen_color_float_tex`. Needs the `record-exr` feature.
+    Exr,
+    /// Single uncompressed Y4M video file which can be encoded later with
+    /// ffmpeg.
+    Y4m,
+}
+
 #[derive(Debug, Copy, Clone, serde::Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
@@ -0,0 +1,367 @@
+use crate::{
+    camera::Camera,
+    components::Projection,
+    config::{AntiAliasing, RenderConfig},
+    forward_renderer::renderer::OffscreenTarget,
+    scene::Scene,
+};
+use easy_wgpu::{
+    bind_group::{BindGroupBuilder, BindGroupDesc, BindGroupWrapper},
+    bind_group_layout::{BindGroupLayoutBuilder, BindGroupLayoutDesc},
+    buffer::Buffer,
+    framebuffer::FrameBuffer,
+    gpu::Gpu,
+    pipeline::RenderPipelineDescBuilder,
+    texture::{TexParams, Texture},
+};
+use log::debug;
+use nalgebra as na;
+
+//shaders
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/fxaa.wgsl")]
+mod fxaa_shader_code {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/taa.wgsl")]
+mod taa_shader_code {}
+
+//float so that the accumulation of many frames doesn't lose precision
+const HISTORY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+//number of jitter offsets that taa cycles through
+const TAA_NR_SAMPLES: u32 = 16;
+
+/// Post process anti-aliasing which runs after the main pass. The main pass
+/// renders towards [`OffscreenTarget::AntiAliasingInput`] and this pass
+/// writes the anti-aliased image towards the output. Depending on
+/// [`RenderConfig::antialiasing`] it either does FXAA or TAA. Independently
+/// of the config, it can also accumulate a number of jittered frames of a
+/// static scene into a supersampled image, see
+/// [`AntiAliasingPass::begin_accumulation`].
+pub struct AntiAliasingPass {
+    //ping-pong textures with the result of the previous frames, allocated only for taa and accumulation
+    history: Option<[Texture; 2]>,
+    //index of the history that the next run reads from
+    history_idx: usize,
+    history_valid: bool,
+    prev_vp: Option<na::Matrix4<f32>>,
+    frame_idx: u32,
+    //number of frames accumulated so far, if accumulating
+    accumulation: Option<u32>,
+    fxaa_pipeline: wgpu::RenderPipeline,
+    taa_pipeline: wgpu::RenderPipeline,
+    params_uniform: Buffer,
+    fxaa_layout: wgpu::BindGroupLayout,
+    fxaa_bind_group: Option<BindGroupWrapper>,
+    taa_layout: wgpu::BindGroupLayout,
+    //index i reads the history from the texture i
+    taa_bind_groups: [Option<BindGroupWrapper>; 2],
+}
+
+impl AntiAliasingPass {
+    pub fn new(gpu: &Gpu, params: &RenderConfig, color_target_format: wgpu::TextureFormat) -> Self {
+        //wasm likes everything to be 16 bytes aligned
+        const_assert!(std::mem::size_of::<TaaParams>() % 16 == 0);
+
+        let usage = wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM;
+        let params_uniform = Buffer::new_empty(gpu.device(), usage, Some("taa_params_buffer"), std::mem::size_of::<TaaParams>());
+
+        let fxaa_layout_desc = Self::fxaa_layout_desc();
+        let fxaa_layout = fxaa_layout_desc.clone().into_bind_group_layout(gpu.device());
+        let fxaa_pipeline = RenderPipelineDescBuilder::new()
+            .label("fxaa")
+            .shader_code(fxaa_shader_code::SOURCE)
+            .shader_label("fxaa_shader")
+            .add_bind_group_layout_desc(fxaa_layout_desc)
+            .add_render_target(wgpu::ColorTargetState {
+                format: color_target_format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .depth_state(None)
+            .multisample(wgpu::MultisampleState::default())
+            .build_pipeline(gpu.device());
+
+        //with msaa the depth is multisampled and we read only its first sample
+        let multisampled = params.msaa_nr_samples > 1;
+        let taa_shader = if multisampled {
+            taa_shader_code::SOURCE.replace("texture_depth_2d", "texture_depth_multisampled_2d")
+        } else {
+            taa_shader_code::SOURCE.to_string()
+        };
+        let taa_layout_desc = Self::taa_layout_desc(multisampled);
+        let taa_layout = taa_layout_desc.clone().into_bind_group_layout(gpu.device());
+        let taa_pipeline = RenderPipelineDescBuilder::new()
+            .label("taa")
+            .shader_code(&taa_shader)
+            .shader_label("taa_shader")
+            .add_bind_group_layout_desc(taa_layout_desc)
+            .add_render_target(wgpu::ColorTargetState {
+                format: color_target_format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .add_render_target(wgpu::ColorTargetState {
+                format: HISTORY_FORMAT,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .depth_state(None)
+            .multisample(wgpu::MultisampleState::default())
+            .build_pipeline(gpu.device());
+
+        Self {
+            history: None,
+            history_idx: 0,
+            history_valid: false,
+            prev_vp: None,
+            frame_idx: 0,
+            accumulation: None,
+            fxaa_pipeline,
+            taa_pipeline,
+            params_uniform,
+            fxaa_layout,
+            fxaa_bind_group: None,
+            taa_layout,
+            taa_bind_groups: [None, None],
+        }
+    }
+
+    /// Whether the main pass should render towards
+    /// [`OffscreenTarget::AntiAliasingInput`] so that this pass can run
+    pub fn is_enabled(&self, params: &RenderConfig) -> bool {
+        params.antialiasing != AntiAliasing::None || self.accumulation.is_some()
+    }
+
+    /// Subpixel offset in pixels by which the camera of the next main pass
+    /// should be shifted. Zero unless using TAA or accumulating.
+    pub fn jitter(&self, params: &RenderConfig) -> na::Vector2<f32> {
+        let sample_idx = match (self.accumulation, params.antialiasing) {
+            (Some(nr_accumulated), _) => nr_accumulated,
+            (None, AntiAliasing::Taa) => self.frame_idx % TAA_NR_SAMPLES,
+            _ => return na::Vector2::zeros(),
+        };
+        //the halton sequence starts at 1 since index 0 is always 0
+        na::Vector2::new(halton(sample_idx + 1, 2) - 0.5, halton(sample_idx + 1, 3) - 0.5)
+    }
+
+    /// Starts averaging every frame that is rendered into a supersampled
+    /// image, each with a different jitter. The scene and the camera should
+    /// not change until [`AntiAliasingPass::end_accumulation`].
+    pub fn begin_accumulation(&mut self) {
+        self.accumulation = Some(0);
+    }
+
+    /// Stops the accumulation and goes back to the anti-aliasing from the
+    /// config. Returns the number of frames that were accumulated.
+    pub fn end_accumulation(&mut self) -> u32 {
+        //the history now has the accumulated image which taa should not reproject
+        self.history_valid = false;
+        self.accumulation.take().unwrap_or(0)
+    }
+
+    /// Reads [`OffscreenTarget::AntiAliasingInput`] and writes the
+    /// anti-aliased image towards `out_view`.
+    pub fn run(
+        &mut self,
+        gpu: &Gpu,
+        camera: &Camera,
+        scene: &Scene,
+        params: &RenderConfig,
+        framebuffer: &FrameBuffer<OffscreenTarget>,
+        out_view: &wgpu::TextureView,
+    ) {
+        let input = framebuffer.get(OffscreenTarget::AntiAliasingInput).unwrap();
+        let depth = framebuffer.get(OffscreenTarget::Depth).unwrap();
+
+        let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("AntiAliasingPass Encoder"),
+        });
+        gpu.profiler().begin_scope("AntiAliasingPass", &mut encoder);
+
+        let is_fxaa = self.accumulation.is_none() && params.antialiasing == AntiAliasing::Fxaa;
+        if is_fxaa {
+            self.history_valid = false;
+            self.update_fxaa_bind_group(gpu, input);
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Fxaa Pass"),
+                color_attachments: &[Some(Self::attachment(out_view))],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.fxaa_pipeline);
+            render_pass.set_bind_group(0, self.fxaa_bind_group.as_ref().unwrap().bg(), &[]);
+            render_pass.draw(0..3, 0..1);
+        } else {
+            self.update_history(gpu, framebuffer.width, framebuffer.height);
+            self.update_taa_params(gpu, camera, scene, params);
+            self.update_taa_bind_groups(gpu, input, depth);
+            let history = self.history.as_ref().unwrap();
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Taa Pass"),
+                color_attachments: &[
+                    Some(Self::attachment(out_view)),
+                    Some(Self::attachment(&history[1 - self.history_idx].view)),
+                ],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.taa_pipeline);
+            render_pass.set_bind_group(0, self.taa_bind_groups[self.history_idx].as_ref().unwrap().bg(), &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        gpu.profiler().end_scope(&mut encoder);
+        gpu.queue().submit(Some(encoder.finish()));
+
+        //the next frame reads the history we just wrote
+        if !is_fxaa {
+            self.history_idx = 1 - self.history_idx;
+        }
+        if let Some(nr_accumulated) = self.accumulation.as_mut() {
+            *nr_accumulated += 1;
+        }
+        self.frame_idx = self.frame_idx.wrapping_add(1);
+    }
+
+    /// Allocates the history or resizes it to the size of the output. The
+    /// history is only valid once it has been written at the current size.
+    fn update_history(&mut self, gpu: &Gpu, width: u32, height: u32) {
+        match &mut self.history {
+            Some(history) if history[0].width() == width && history[0].height() == height => {}
+            Some(history) => {
+                for tex in history.iter_mut() {
+                    tex.resize(gpu.device(), width, height);
+                }
+                self.history_valid = false;
+            }
+            None => {
+                debug!("creating anti-aliasing history");
+                let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+                let tex = || Texture::new(gpu.device(), width, height, HISTORY_FORMAT, usage, TexParams::default());
+                self.history = Some([tex(), tex()]);
+                self.history_valid = false;
+            }
+        }
+    }
+
+    /// Uploads the matrices for the reprojection and how much of the history
+    /// to keep. Marks the history as valid since it will be written by this
+    /// frame.
+    fn update_taa_params(&mut self, gpu: &Gpu, camera: &Camera, scene: &Scene, params: &RenderConfig) {
+        //the matrices are without the jitter since it's only there to move the samples within the pixel
+        let vp = if scene.world.has::<Projection>(camera.entity).unwrap_or(false) {
+            camera.proj_matrix_reverse_z(scene) * camera.view_matrix(scene)
+        } else {
+            na::Matrix4::identity()
+        };
+        let prev_vp = self.prev_vp.unwrap_or(vp);
+        self.prev_vp = Some(vp);
+
+        #[allow(clippy::cast_precision_loss)]
+        let (history_weight, mode) = match self.accumulation {
+            //running mean of all the frames accumulated so far
+            Some(nr_accumulated) => (nr_accumulated as f32 / (nr_accumulated as f32 + 1.0), 1),
+            None if self.history_valid => (params.taa_history_weight.clamp(0.0, 1.0), 0),
+            None => (0.0, 0),
+        };
+        self.history_valid = true;
+
+        let taa_params = TaaParams {
+            cur_vp_inv: vp.try_inverse().unwrap_or_else(na::Matrix4::identity),
+            prev_vp,
+            history_weight,
+            mode,
+            pad_c: 0.0,
+            pad_d: 0.0,
+        };
+        self.params_uniform.push_cpu_chunk_packed(&taa_params);
+        self.params_uniform.upload_from_cpu_chunks(gpu.queue());
+        self.params_uniform.reset_chunks_offset();
+    }
+
+    /// Recreates the bind group towards the input in case it was resized
+    fn update_fxaa_bind_group(&mut self, gpu: &Gpu, input: &Texture) {
+        let entries = BindGroupBuilder::new().add_entry_tex(input).build_entries();
+        if self.fxaa_bind_group.as_ref().map_or(true, |b| b.is_stale(&entries)) {
+            debug!("fxaa bind group is stale, recreating");
+            self.fxaa_bind_group = Some(BindGroupDesc::new("fxaa_bg", entries).into_bind_group_wrapper(gpu.device(), &self.fxaa_layout));
+        }
+    }
+
+    /// Recreates the bind groups towards the input, the history and the depth
+    /// in case they were resized
+    fn update_taa_bind_groups(&mut self, gpu: &Gpu, input: &Texture, depth: &Texture) {
+        let history = self.history.as_ref().unwrap();
+        for (read_idx, bind_group) in self.taa_bind_groups.iter_mut().enumerate() {
+            let entries = BindGroupBuilder::new()
+                .add_entry_tex(input)
+                .add_entry_tex(&history[read_idx])
+                .add_entry_tex(depth)
+                .add_entry_buf(&self.params_uniform.buffer)
+                .build_entries();
+            if bind_group.as_ref().map_or(true, |b| b.is_stale(&entries)) {
+                debug!("taa bind group is stale, recreating");
+                *bind_group = Some(BindGroupDesc::new("taa_bg", entries).into_bind_group_wrapper(gpu.device(), &self.taa_layout));
+            }
+        }
+    }
+
+    fn attachment(view: &wgpu::TextureView) -> wgpu::RenderPassColorAttachment<'_> {
+        wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: wgpu::StoreOp::Store,
+            },
+        }
+    }
+
+    fn fxaa_layout_desc() -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("fxaa_layout")
+            //input color
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            .build()
+    }
+
+    fn taa_layout_desc(multisampled_depth: bool) -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("taa_layout")
+            //input color
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            //history
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            //depth
+            .add_entry_tex_multisampled(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Depth, multisampled_depth)
+            //params
+            .add_entry_uniform(wgpu::ShaderStages::FRAGMENT, false, None)
+            .build()
+    }
+}
+
+/// Element `index` of the low discrepancy Halton sequence in the given base,
+/// in the range [0, 1)
+#[allow(clippy::cast_precision_loss)]
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Keep in sync with the shader `taa.wgsl`
+#[repr(C)]
+#[derive(Clone, Copy, encase::ShaderType)]
+struct TaaParams {
+    cur_vp_inv: na::Matrix4<f32>,
+    prev_vp: na::Matrix4<f32>,
+    history_weight: f32,
+    mode: u32,
+    pad_c: f32,
+    pad_d: f32,
+}
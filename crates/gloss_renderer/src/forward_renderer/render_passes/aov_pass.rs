@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+
+use crate::{
+    camera::Camera,
+    components::{ColorsGPU, DiffuseTex, FacesGPU, ModelMatrix, Name, NoClip, NormalsGPU, Projection, Renderable, UVsGPU, VertsGPU, VisMesh},
+    config::AovConfig,
+    forward_renderer::bind_group_collection::BindGroupCollection,
+    scene::Scene,
+};
+use easy_wgpu::{
+    bind_group::{BindGroupBuilder, BindGroupWrapper},
+    bind_group_layout::{BindGroupLayoutBuilder, BindGroupLayoutDesc},
+    buffer::Buffer,
+    gpu::Gpu,
+    pipeline::RenderPipelineDescBuilder,
+    texture::{TexParams, Texture},
+};
+use enum_map::{Enum, EnumMap};
+use gloss_hecs::Entity;
+use gloss_utils::numerical::align;
+use log::{debug, error};
+use nalgebra as na;
+
+use super::upload_pass::PerFrameUniforms;
+
+//shaders
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/aov_mesh_vert.wgsl")]
+mod vert_shader_code {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/aov_mesh_frag.wgsl")]
+mod frag_shader_code {}
+
+/// Arbitrary output variables that can be rendered in addition to the color
+/// and depth. The order determines the output location in the shader.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    /// Interpolated world space normal in rgb and 1.0 in alpha for pixels
+    /// covered by a mesh
+    NormalsWorld,
+    /// Same as [`Aov::NormalsWorld`] but in the coordinate frame of the camera
+    NormalsCam,
+    /// `Entity::id()` of the mesh covering the pixel or -1.0 for the
+    /// background
+    InstanceId,
+    /// UV coordinates as stored in the mesh
+    Uv,
+    /// Displacement in pixels of the surface point since the previous frame,
+    /// with y going down like the image rows. Only the movement of the
+    /// camera and the model matrices is taken into account, not the
+    /// deformation of the vertices.
+    MotionVectors,
+    /// Color of the mesh before any lighting or tonemapping
+    Albedo,
+}
+impl Aov {
+    pub fn format(self) -> wgpu::TextureFormat {
+        match self {
+            Aov::NormalsWorld | Aov::NormalsCam | Aov::Albedo => wgpu::TextureFormat::Rgba32Float,
+            Aov::InstanceId => wgpu::TextureFormat::R32Float,
+            Aov::Uv | Aov::MotionVectors => wgpu::TextureFormat::Rg32Float,
+        }
+    }
+
+    fn clear_color(self) -> wgpu::Color {
+        match self {
+            Aov::InstanceId => wgpu::Color {
+                r: -1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0,
+            },
+            _ => wgpu::Color::TRANSPARENT,
+        }
+    }
+
+    pub fn is_enabled(self, config: &AovConfig) -> bool {
+        match self {
+            Aov::NormalsWorld => config.normals_world,
+            Aov::NormalsCam => config.normals_cam,
+            Aov::InstanceId => config.instance_id,
+            Aov::Uv => config.uv,
+            Aov::MotionVectors => config.motion_vectors,
+            Aov::Albedo => config.albedo,
+        }
+    }
+
+    pub fn all_enabled(config: &AovConfig) -> Vec<Aov> {
+        (0..Aov::LENGTH).map(Aov::from_usize).filter(|aov| aov.is_enabled(config)).collect()
+    }
+}
+
+/// A pipeline that writes only a subset of the aovs. Needed because the
+/// device limits how many bytes per pixel a single render pass can write to.
+struct AovSubPass {
+    aovs: Vec<Aov>,
+    render_pipeline: wgpu::RenderPipeline,
+}
+
+/// Renders the meshes of the scene a second time towards the enabled
+/// [`Aov`] targets. The targets are not multisampled so that values like the
+/// instance id don't get blended at the edges of the meshes. The aovs are
+/// only rendered for the main camera, not for the views, and not for cameras
+/// with lens distortion since they would not line up with the warped image.
+pub struct AovPass {
+    targets: EnumMap<Aov, Option<Texture>>,
+    depth: Option<Texture>,
+    sub_passes: Vec<AovSubPass>,
+    locals_uniform: Buffer, // a uniform buffer that we suballocate for the locals of every mesh
+    locals_bind_groups: LocalsBindGroups,
+    //state of the previous frame needed for the motion vectors
+    prev_vp: Option<na::Matrix4<f32>>,
+    prev_model_matrices: HashMap<Entity, na::Matrix4<f32>>,
+    //so that we complain only once about a distorted camera instead of every frame
+    reported_distortion: bool,
+}
+
+impl AovPass {
+    pub fn new(gpu: &Gpu) -> Self {
+        //wasm likes everything to be 16 bytes aligned
+        const_assert!(std::mem::size_of::<Locals>() % 16 == 0);
+
+        let size_bytes = 0x10000;
+        let usage = wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM;
+        let locals_uniform = Buffer::new_empty(gpu.device(), usage, Some("aov_local_buffer"), size_bytes);
+
+        Self {
+            targets: EnumMap::default(),
+            depth: None,
+            sub_passes: Vec::new(),
+            locals_uniform,
+            locals_bind_groups: LocalsBindGroups::new(gpu),
+            prev_vp: None,
+            prev_model_matrices: HashMap::new(),
+            reported_distortion: false,
+        }
+    }
+
+    /// Texture of the aov rendered in the last frame. Returns None if the aov
+    /// is not enabled in the config or if the camera has lens distortion.
+    pub fn tex(&self, aov: Aov) -> Option<&Texture> {
+        self.targets[aov].as_ref()
+    }
+
+    pub fn run(&mut self, gpu: &Gpu, per_frame_uniforms: &PerFrameUniforms, camera: &Camera, scene: &Scene, config: &AovConfig) {
+        let aovs = Aov::all_enabled(config);
+        if aovs.is_empty() {
+            //release the targets if the aovs got disabled
+            self.release_targets();
+            return;
+        }
+
+        //the aovs are rendered at the undistorted resolution without the warp so they would not line up with the final image
+        if camera.distorted_intrinsics(scene).is_some() {
+            if !self.reported_distortion {
+                error!("Aovs are not supported for cameras with lens distortion, they will not be rendered");
+                self.reported_distortion = true;
+            }
+            self.release_targets();
+            return;
+        }
+        self.reported_distortion = false;
+
+        let (width, height) = camera.get_target_res(scene);
+        self.update_targets(gpu, &aovs, width, height);
+        if self.sub_passes.iter().flat_map(|sub_pass| sub_pass.aovs.iter()).ne(aovs.iter()) {
+            self.sub_passes = Self::create_sub_passes(gpu, &aovs);
+        }
+
+        let mut query = scene
+            .world
+            .query::<(&VertsGPU, &FacesGPU, &UVsGPU, &NormalsGPU, &ColorsGPU, &DiffuseTex, &VisMesh, &Name)>()
+            .with::<&Renderable>();
+
+        self.update_locals(gpu, camera, scene);
+
+        let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("AovPass Encoder"),
+        });
+        for sub_pass in &self.sub_passes {
+            let mut color_attachments = vec![None; Aov::LENGTH];
+            for aov in &sub_pass.aovs {
+                color_attachments[aov.into_usize()] = Some(wgpu::RenderPassColorAttachment {
+                    view: &self.targets[*aov].as_ref().unwrap().view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(aov.clear_color()),
+                        store: wgpu::StoreOp::Store,
+                    },
+                });
+            }
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Aov Pass"),
+                color_attachments: &color_attachments,
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth.as_ref().unwrap().view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&sub_pass.render_pipeline);
+            render_pass.set_bind_group(0, &per_frame_uniforms.bind_group, &[]);
+
+            for (_id, (verts, faces, uvs, normals, colors, _diffuse_tex, vis_mesh, name)) in query.iter() {
+                if !vis_mesh.show_mesh {
+                    continue;
+                }
+
+                //local bindings
+                let (local_bg, offset) = &self.locals_bind_groups.mesh2local_bind[&name.0];
+                render_pass.set_bind_group(1, local_bg.bg(), &[*offset]);
+
+                render_pass.set_vertex_buffer(0, verts.buf.slice(..));
+                render_pass.set_vertex_buffer(1, uvs.buf.slice(..));
+                render_pass.set_vertex_buffer(2, normals.buf.slice(..));
+                render_pass.set_vertex_buffer(3, colors.buf.slice(..));
+                render_pass.set_index_buffer(faces.buf.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..faces.nr_triangles * 3, 0, 0..1);
+            }
+        }
+        gpu.queue().submit(Some(encoder.finish()));
+    }
+
+    /// Creates, resizes or releases the textures so that only the enabled aovs
+    /// have a target of the correct size
+    fn update_targets(&mut self, gpu: &Gpu, aovs: &[Aov], width: u32, height: u32) {
+        for (aov, target) in &mut self.targets {
+            if !aovs.contains(&aov) {
+                *target = None;
+                continue;
+            }
+            match target {
+                Some(tex) if tex.width() == width && tex.height() == height => {}
+                Some(tex) => tex.resize(gpu.device(), width, height),
+                None => {
+                    debug!("creating aov target {aov:?}");
+                    *target = Some(Texture::new(
+                        gpu.device(),
+                        width,
+                        height,
+                        aov.format(),
+                        //copy_src so we can download it to cpu and texture_binding so it can be shown in a gui or sampled by other passes
+                        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::TEXTURE_BINDING,
+                        TexParams::default(),
+                    ));
+                }
+            }
+        }
+
+        match &mut self.depth {
+            Some(tex) if tex.width() == width && tex.height() == height => {}
+            Some(tex) => tex.resize(gpu.device(), width, height),
+            None => {
+                self.depth = Some(Texture::new(
+                    gpu.device(),
+                    width,
+                    height,
+                    wgpu::TextureFormat::Depth32Float,
+                    wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    TexParams::default(),
+                ));
+            }
+        }
+    }
+
+    /// Groups the aovs so that each group fits in the bytes per sample that a
+    /// render pass can write
+    fn create_sub_passes(gpu: &Gpu, aovs: &[Aov]) -> Vec<AovSubPass> {
+        let max_bytes = gpu.device().limits().max_color_attachment_bytes_per_sample;
+
+        let mut groups: Vec<Vec<Aov>> = Vec::new();
+        let mut group_bytes = 0;
+        for aov in aovs {
+            let format = aov.format();
+            let cost = format.target_pixel_byte_cost().unwrap();
+            let alignment = format.target_component_alignment().unwrap();
+            let bytes_with_aov = align(group_bytes, alignment) + cost;
+            match groups.last_mut() {
+                Some(group) if bytes_with_aov <= max_bytes => {
+                    group.push(*aov);
+                    group_bytes = bytes_with_aov;
+                }
+                _ => {
+                    groups.push(vec![*aov]);
+                    group_bytes = cost;
+                }
+            }
+        }
+        debug!("aovs are rendered in {} sub passes", groups.len());
+
+        groups
+            .into_iter()
+            .map(|aovs| {
+                let mut desc = RenderPipelineDescBuilder::new()
+                    .label("aov_pipeline")
+                    .shader_code_vert(vert_shader_code::SOURCE)
+                    .shader_code_frag(frag_shader_code::SOURCE)
+                    .shader_label("aov_shader")
+                    .add_bind_group_layout_desc(PerFrameUniforms::build_layout_desc())
+                    .add_bind_group_layout_desc(LocalsBindGroups::build_layout_desc())
+                    .add_vertex_buffer_layout(VertsGPU::vertex_buffer_layout::<0>())
+                    .add_vertex_buffer_layout(UVsGPU::vertex_buffer_layout::<1>())
+                    .add_vertex_buffer_layout(NormalsGPU::vertex_buffer_layout::<2>())
+                    .add_vertex_buffer_layout(ColorsGPU::vertex_buffer_layout::<3>())
+                    .depth_state(Some(wgpu::DepthStencilState {
+                        format: wgpu::TextureFormat::Depth32Float,
+                        depth_write_enabled: true,
+                        depth_compare: wgpu::CompareFunction::Greater,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }))
+                    .multisample(wgpu::MultisampleState::default())
+                    .build_desc();
+                //one target for every location in the shader, the ones which are not in this group are left empty
+                desc.render_targets = (0..Aov::LENGTH)
+                    .map(Aov::from_usize)
+                    .map(|aov| {
+                        aovs.contains(&aov).then(|| wgpu::ColorTargetState {
+                            format: aov.format(),
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })
+                    })
+                    .collect();
+                let render_pipeline = desc.into_render_pipeline(gpu.device());
+                AovSubPass { aovs, render_pipeline }
+            })
+            .collect()
+    }
+
+    /// Frees the targets and the state of the previous frame, for when the
+    /// aovs got disabled or cannot be rendered
+    fn release_targets(&mut self) {
+        if self.depth.is_some() {
+            self.targets = EnumMap::default();
+            self.depth = None;
+            self.sub_passes.clear();
+            self.prev_vp = None;
+            self.prev_model_matrices.clear();
+        }
+    }
+
+    /// update the local information that need to be sent to the gpu for each
+    /// mesh like te model matrix. Also stores the matrices of this frame so
+    /// that the next one can compute motion vectors.
+    fn update_locals(&mut self, gpu: &Gpu, camera: &Camera, scene: &Scene) {
+        let vp = if scene.world.has::<Projection>(camera.entity).unwrap_or(false) {
+            camera.proj_matrix_reverse_z(scene) * camera.view_matrix(scene)
+        } else {
+            na::Matrix4::identity()
+        };
+        let prev_vp = self.prev_vp.unwrap_or(vp);
+
+        let mut model_matrices = HashMap::new();
+        self.locals_uniform.reset_chunks_offset_if_necessary();
+        let mut query = scene.world.query::<(&VisMesh, &Name)>().with::<(&Renderable, &VertsGPU, &DiffuseTex)>();
+        for (entity, (vis_mesh, name)) in query.iter() {
+            let model_matrix = scene
+                .get_comp::<&ModelMatrix>(&entity)
+                .map_or_else(|_| na::Matrix4::identity(), |m| m.0.to_homogeneous());
+            let prev_model_matrix = self.prev_model_matrices.get(&entity).copied().unwrap_or(model_matrix);
+            model_matrices.insert(entity, model_matrix);
+            let is_floor = scene.get_floor().map_or(false, |floor| floor.entity == entity);
+            let clip = u32::from(!is_floor && !scene.world.has::<NoClip>(entity).unwrap_or(false));
+
+            #[allow(clippy::cast_precision_loss)]
+            let locals = Locals {
+                model_matrix,
+                prev_mvp: prev_vp * prev_model_matrix,
+                solid_color: vis_mesh.solid_color,
+                color_type: vis_mesh.color_type as i32,
+                uv_scale: vis_mesh.uv_scale,
+                instance_id: entity.id() as f32,
+                clip,
+            };
+            let offset_in_ubo = self.locals_uniform.push_cpu_chunk_aligned::<Locals>(&locals);
+
+            //chekc if we need to recreate bind group (for example when the diffuse texture has changed)
+            self.locals_bind_groups
+                .update_bind_group(entity, gpu, &name.0, &self.locals_uniform, offset_in_ubo, scene);
+        }
+        self.locals_uniform.upload_from_cpu_chunks(gpu.queue()); //important to upload everything to gpu at the end
+
+        self.prev_vp = Some(vp);
+        self.prev_model_matrices = model_matrices;
+    }
+}
+
+/// Keep in sync with shader `aov_mesh_vert.wgsl`
+#[repr(C)]
+#[derive(Clone, Copy, encase::ShaderType)]
+struct Locals {
+    model_matrix: nalgebra::Matrix4<f32>,
+    prev_mvp: nalgebra::Matrix4<f32>,
+    solid_color: nalgebra::Vector4<f32>,
+    color_type: i32,
+    uv_scale: f32,
+    instance_id: f32,
+    clip: u32,
+}
+
+struct LocalsBindGroups {
+    layout: wgpu::BindGroupLayout,
+    pub mesh2local_bind: HashMap<String, (BindGroupWrapper, u32)>,
+}
+impl BindGroupCollection for LocalsBindGroups {
+    fn new(gpu: &Gpu) -> Self {
+        Self {
+            layout: Self::build_layout_desc().into_bind_group_layout(gpu.device()),
+            mesh2local_bind: HashMap::default(),
+        }
+    }
+
+    //keep as associated function so we can call it in the pipeline creation
+    // without and object
+    fn build_layout_desc() -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("aov_pass_locals_layout")
+            //locals
+            .add_entry_uniform(
+                wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                true,
+                wgpu::BufferSize::new(u64::from(align(u32::try_from(std::mem::size_of::<Locals>()).unwrap(), 256))),
+            )
+            //diffuse tex
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: true })
+            .build()
+    }
+
+    fn update_bind_group(&mut self, entity: Entity, gpu: &Gpu, mesh_name: &str, ubo: &Buffer, offset_in_ubo: u32, scene: &Scene) {
+        let diffuse_tex = &scene.get_comp::<&DiffuseTex>(&entity).unwrap().0;
+
+        let entries = BindGroupBuilder::new()
+            .add_entry_buf_chunk::<Locals>(&ubo.buffer)
+            .add_entry_tex(diffuse_tex)
+            .build_entries();
+
+        self.update_if_stale(mesh_name, entries, offset_in_ubo, gpu);
+    }
+
+    fn get_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+    fn get_mut_entity2binds(&mut self) -> &mut HashMap<String, (BindGroupWrapper, u32)> {
+        &mut self.mesh2local_bind
+    }
+}
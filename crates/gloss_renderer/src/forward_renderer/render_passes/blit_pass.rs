@@ -61,9 +61,34 @@ impl BlitPass {
     /// Will panic if the `input_bind_group` is not created. It should be
     /// created automatically when doing ``run()`` by the `update_bind_group()`
     pub fn run(&mut self, gpu: &Gpu, src_texture: &Texture, out_view: &wgpu::TextureView) {
+        self.run_inner(gpu, src_texture, out_view, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), None);
+    }
+
+    /// Copies the texture only inside the rectangle (x, y, width, height) of
+    /// the `out_view`, given in pixels. The rest of the `out_view` is kept
+    /// unless `clear` is true in which case it's set to transparent.
+    pub fn run_to_viewport(&mut self, gpu: &Gpu, src_texture: &Texture, out_view: &wgpu::TextureView, rect: (u32, u32, u32, u32), clear: bool) {
+        let load = if clear {
+            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+        } else {
+            wgpu::LoadOp::Load
+        };
+        self.run_inner(gpu, src_texture, out_view, load, Some(rect));
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn run_inner(
+        &mut self,
+        gpu: &Gpu,
+        src_texture: &Texture,
+        out_view: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+        rect: Option<(u32, u32, u32, u32)>,
+    ) {
         let mut encoder = gpu
             .device()
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Blit Encoder") });
+        gpu.profiler().begin_scope("BlitPass", &mut encoder);
 
         {
             //update the bind group in case the input_texture changed
@@ -76,7 +101,7 @@ impl BlitPass {
                         view: out_view,
                         resolve_target: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            load,
                             store: wgpu::StoreOp::Store,
                         },
                     })],
@@ -87,6 +112,10 @@ impl BlitPass {
 
                 render_pass.set_pipeline(&self.render_pipeline);
 
+                if let Some((x, y, width, height)) = rect {
+                    render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+                }
+
                 render_pass.set_bind_group(0, self.input_bind_group.as_ref().unwrap().bg(), &[]);
 
                 //draw a quad
@@ -94,6 +123,7 @@ impl BlitPass {
             }
         }
 
+        gpu.profiler().end_scope(&mut encoder);
         gpu.queue().submit(Some(encoder.finish()));
     }
 
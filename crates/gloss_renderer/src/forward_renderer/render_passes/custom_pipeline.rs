@@ -0,0 +1,84 @@
+use crate::{config::RenderConfig, scene::Scene};
+
+use easy_wgpu::gpu::Gpu;
+
+use super::upload_pass::PerFrameUniforms;
+
+/// Where a custom pipeline is drawn inside the main pass relative to the
+/// built-in mesh, point and line pipelines. Runners with the same order are
+/// drawn in the order in which they were registered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PipelineOrder {
+    /// Drawn before any of the scene geometry, useful for backgrounds
+    BeforeOpaque,
+    /// Drawn after the meshes, points and lines so it is depth tested against
+    /// them
+    #[default]
+    AfterOpaque,
+    /// Drawn last. The depth buffer is still bound so pipelines that should
+    /// appear on top of everything need to use `CompareFunction::Always`
+    Overlay,
+}
+
+/// Formats of the targets of the main pass. The pipelines of a
+/// [`CustomPipelineRunner`] need to be created with these otherwise wgpu will
+/// refuse to draw them.
+#[derive(Clone, Copy, Debug)]
+pub struct PipelineTargets {
+    pub color_format: wgpu::TextureFormat,
+    pub depth_format: wgpu::TextureFormat,
+    pub sample_count: u32,
+}
+
+/// Everything that a custom pipeline can use while drawing in the main pass
+pub struct CustomPipelineContext<'a> {
+    /// Camera, lights and render params. Its `bind_group` has the layout from
+    /// [`PerFrameUniforms::build_layout_desc`] and is usually bound at group 0
+    pub per_frame_uniforms: &'a PerFrameUniforms,
+    /// Environment maps and shadow maps as used by the mesh pipeline with the
+    /// layout from
+    /// [`MeshPipeline::input_layout_desc`](super::mesh_pipeline::MeshPipeline).
+    /// None if there are no meshes to draw.
+    pub shading_bind_group: Option<&'a wgpu::BindGroup>,
+    pub render_params: &'a RenderConfig,
+    pub targets: PipelineTargets,
+}
+
+/// A user defined pipeline which is drawn together with the rest of the scene
+/// in the main pass, for example a heat-map shader or a hair renderer. Unlike
+/// [`PipelineRunner`](super::pipeline_runner::PipelineRunner) this trait can
+/// be used as a trait object so any number of runners can be registered at
+/// runtime through [`RenderPasses::add_pipeline_runner`](crate::forward_renderer::RenderPasses::add_pipeline_runner)
+/// or, from a plugin which only has access to the scene, through
+/// [`PendingPipelineRunners::register`].
+pub trait CustomPipelineRunner: Send + Sync {
+    /// Unique name of the runner. Used to remove it and as the label of its
+    /// gpu timings
+    fn name(&self) -> &str;
+    fn order(&self) -> PipelineOrder {
+        PipelineOrder::AfterOpaque
+    }
+    /// Called every frame before the render pass starts. This is the place to
+    /// lazily create the pipeline using `context.targets` and to upload any
+    /// per-entity data.
+    fn prepare(&mut self, gpu: &Gpu, context: &CustomPipelineContext, scene: &Scene);
+    /// Records the draw calls into the main render pass
+    fn run(&self, render_pass: &mut wgpu::RenderPass<'_>, context: &CustomPipelineContext, scene: &Scene);
+}
+
+/// Resource through which pipeline runners can be registered when there is no
+/// access to the renderer, like in the logic systems of a plugin. The main
+/// pass takes them out of the scene at the start of the next frame.
+#[derive(Default)]
+pub struct PendingPipelineRunners(pub Vec<Box<dyn CustomPipelineRunner>>);
+impl PendingPipelineRunners {
+    /// # Panics
+    /// Will panic if the entity that holds the resources has not been created
+    pub fn register(scene: &mut Scene, runner: Box<dyn CustomPipelineRunner>) {
+        if let Ok(mut pending) = scene.get_resource::<&mut PendingPipelineRunners>() {
+            pending.0.push(runner);
+            return;
+        }
+        scene.add_resource(PendingPipelineRunners(vec![runner]));
+    }
+}
@@ -0,0 +1,498 @@
+use crate::{
+    components::{
+        BlendShapeCoeffs, BlendShapes, DeformGPU, Faces, JointTransforms, Normals, NormalsGPU, Renderable, SkinWeights, Verts, VertsGPU,
+        MAX_JOINT_INFLUENCES,
+    },
+    geom::{Geom, PerVertexNormalsWeightingType},
+    scene::Scene,
+};
+use easy_wgpu::{
+    bind_group::BindGroupBuilder,
+    bind_group_layout::{BindGroupLayoutBuilder, BindGroupLayoutDesc},
+    gpu::Gpu,
+};
+use gloss_hecs::{Changed, CommandBuffer, Entity};
+use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorOps};
+use log::warn;
+use wgpu::util::DeviceExt;
+
+//shaders
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/deform.wgsl")]
+mod deform_shader {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/deform_normals.wgsl")]
+mod normals_shader {}
+
+const WORKGROUP_SIZE: u32 = 64;
+//wgpu doesn't allow binding empty buffers so the inputs an entity doesn't have get one of this size
+const PLACEHOLDER_BYTES: usize = 16;
+
+/// Part of the upload pass that evaluates [`BlendShapes`] and linear blend
+/// skinning with [`SkinWeights`] and [`JointTransforms`] in a compute shader.
+/// The rest pose, basis and weights are uploaded once so every frame only the
+/// coefficients and the joint transforms are uploaded. The deformed vertices
+/// and normals are written into [`VertsGPU`] and [`NormalsGPU`] while
+/// [`Verts`] and [`Normals`] keep the rest pose. On devices without compute
+/// shaders the same is evaluated on the CPU with [`Geom::apply_blend_shapes`]
+/// and [`Geom::skin_verts`]
+pub struct DeformPass {
+    //none if the device doesn't support compute shaders
+    pipelines: Option<(wgpu::ComputePipeline, wgpu::ComputePipeline)>,
+    deform_layout: wgpu::BindGroupLayout,
+    normals_layout: wgpu::BindGroupLayout,
+    command_buffer: CommandBuffer,
+}
+
+impl DeformPass {
+    pub fn new(gpu: &Gpu) -> Self {
+        let deform_layout = Self::deform_layout_desc().into_bind_group_layout(gpu.device());
+        let normals_layout = Self::normals_layout_desc().into_bind_group_layout(gpu.device());
+
+        let supports_compute = gpu
+            .adapter()
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
+        let pipelines = if supports_compute {
+            Some((
+                Self::create_pipeline(gpu, "deform", deform_shader::SOURCE, &deform_layout),
+                Self::create_pipeline(gpu, "deform_normals", normals_shader::SOURCE, &normals_layout),
+            ))
+        } else {
+            warn!("The device doesn't support compute shaders so blend shapes and skinning will be evaluated on the CPU");
+            None
+        };
+
+        Self {
+            pipelines,
+            deform_layout,
+            normals_layout,
+            command_buffer: CommandBuffer::new(),
+        }
+    }
+
+    pub fn run(&mut self, gpu: &Gpu, scene: &mut Scene) {
+        self.add_or_remove_deform_gpu(gpu, scene);
+        Self::upload_changed(gpu, scene);
+        if self.pipelines.is_some() {
+            self.evaluate_gpu(gpu, scene);
+        } else {
+            Self::evaluate_cpu(gpu, scene);
+        }
+    }
+
+    /// Adds the [`DeformGPU`] together with vertex and normal buffers that can
+    /// be written from a compute shader to the entities that have blend shapes
+    /// or skinning, and removes it from the ones that don't have them anymore
+    #[allow(clippy::type_complexity)]
+    fn add_or_remove_deform_gpu(&mut self, gpu: &Gpu, scene: &mut Scene) {
+        let mut removed: Vec<Entity> = Vec::new();
+        {
+            let mut query = scene
+                .world
+                .query::<(
+                    &Verts,
+                    Option<&Faces>,
+                    Option<&BlendShapes>,
+                    Option<&BlendShapeCoeffs>,
+                    Option<&SkinWeights>,
+                    Option<&JointTransforms>,
+                    Option<&DeformGPU>,
+                )>()
+                .with::<&Renderable>();
+            for (entity, (verts, faces, blend_shapes, coeffs, skin, transforms, deform_gpu)) in query.iter() {
+                let is_deformed = blend_shapes.is_some() || skin.is_some();
+                let nr_verts = u32::try_from(verts.0.nrows()).unwrap();
+                if !is_deformed {
+                    if deform_gpu.is_some() {
+                        removed.push(entity);
+                    }
+                    continue;
+                }
+                if deform_gpu.map_or(false, |d| d.nr_verts == nr_verts) {
+                    continue;
+                }
+
+                let mut deform_gpu = Self::create_deform_gpu(gpu, nr_verts);
+                Self::upload_rest_verts(gpu, &mut deform_gpu, verts);
+                if let Some(blend_shapes) = blend_shapes {
+                    Self::upload_blend_shapes(gpu, &mut deform_gpu, blend_shapes);
+                }
+                if let Some(coeffs) = coeffs {
+                    Self::upload_coeffs(gpu, &mut deform_gpu, coeffs);
+                }
+                if let Some(skin) = skin {
+                    Self::upload_skin_weights(gpu, &mut deform_gpu, skin);
+                }
+                if let Some(transforms) = transforms {
+                    Self::upload_joint_transforms(gpu, &mut deform_gpu, transforms);
+                }
+                if let Some(faces) = faces {
+                    Self::upload_faces(gpu, &mut deform_gpu, faces);
+                }
+
+                //the output buffers need to be storage buffers so we create them here instead of in the upload of the verts and normals
+                let out_usage =
+                    wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC;
+                let out_size = u64::from(nr_verts) * 3 * std::mem::size_of::<f32>() as u64;
+                let create_out = |label: &str| {
+                    gpu.device().create_buffer(&wgpu::BufferDescriptor {
+                        label: Some(label),
+                        size: out_size.max(PLACEHOLDER_BYTES as u64),
+                        usage: out_usage,
+                        mapped_at_creation: false,
+                    })
+                };
+                self.command_buffer.insert_one(
+                    entity,
+                    VertsGPU {
+                        buf: create_out("deformed_verts"),
+                        nr_vertices: nr_verts,
+                    },
+                );
+                if faces.is_some() {
+                    self.command_buffer.insert_one(
+                        entity,
+                        NormalsGPU {
+                            buf: create_out("deformed_normals"),
+                            nr_vertices: nr_verts,
+                        },
+                    );
+                }
+                self.command_buffer.insert_one(entity, deform_gpu);
+            }
+        }
+        for entity in &removed {
+            self.command_buffer.remove_one::<DeformGPU>(*entity);
+        }
+        self.command_buffer.run_on(&mut scene.world);
+
+        //the vertex buffers still hold the deformed mesh so we mark the rest pose as changed in order for the upload pass to upload it again
+        for entity in removed {
+            if let Ok(mut verts) = scene.get_comp::<&mut Verts>(&entity) {
+                let _ = &mut *verts;
+            }
+            if let Ok(mut normals) = scene.get_comp::<&mut Normals>(&entity) {
+                let _ = &mut *normals;
+            }
+        }
+    }
+
+    /// Uploads only the inputs that changed since the last frame
+    fn upload_changed(gpu: &Gpu, scene: &mut Scene) {
+        for (_entity, (verts, mut deform_gpu, changed)) in scene.world.query_mut::<(&Verts, &mut DeformGPU, Changed<Verts>)>() {
+            if changed {
+                Self::upload_rest_verts(gpu, &mut deform_gpu, verts);
+            }
+        }
+        for (_entity, (blend_shapes, mut deform_gpu, changed)) in scene.world.query_mut::<(&BlendShapes, &mut DeformGPU, Changed<BlendShapes>)>() {
+            if changed {
+                Self::upload_blend_shapes(gpu, &mut deform_gpu, blend_shapes);
+            }
+        }
+        //the coefficients are padded to the number of shapes so they also need to be uploaded again if the basis changed
+        for (_entity, (coeffs, mut deform_gpu, changed)) in scene.world.query_mut::<(&BlendShapeCoeffs, &mut DeformGPU, Changed<BlendShapeCoeffs>)>()
+        {
+            if changed || deform_gpu.coeffs.size() != Self::padded_size(deform_gpu.nr_shapes as usize * std::mem::size_of::<f32>()) {
+                Self::upload_coeffs(gpu, &mut deform_gpu, coeffs);
+            }
+        }
+        for (_entity, (skin, mut deform_gpu, changed)) in scene.world.query_mut::<(&SkinWeights, &mut DeformGPU, Changed<SkinWeights>)>() {
+            if changed {
+                Self::upload_skin_weights(gpu, &mut deform_gpu, skin);
+            }
+        }
+        for (_entity, (transforms, mut deform_gpu, changed)) in
+            scene.world.query_mut::<(&JointTransforms, &mut DeformGPU, Changed<JointTransforms>)>()
+        {
+            if changed {
+                Self::upload_joint_transforms(gpu, &mut deform_gpu, transforms);
+            }
+        }
+        for (_entity, (faces, mut deform_gpu, changed)) in scene.world.query_mut::<(&Faces, &mut DeformGPU, Changed<Faces>)>() {
+            if changed {
+                Self::upload_faces(gpu, &mut deform_gpu, faces);
+            }
+        }
+    }
+
+    fn evaluate_gpu(&self, gpu: &Gpu, scene: &mut Scene) {
+        //most frames nothing changes so we don't even create an encoder
+        if !scene.world.query_mut::<&DeformGPU>().into_iter().any(|(_, d)| d.dirty) {
+            return;
+        }
+        let (deform_pipeline, normals_pipeline) = self.pipelines.as_ref().unwrap();
+        let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Deform Encoder"),
+        });
+        gpu.profiler().begin_scope("DeformPass", &mut encoder);
+
+        for (_entity, (mut deform_gpu, mut verts_gpu, mut normals_gpu, skin)) in
+            scene
+                .world
+                .query_mut::<(&mut DeformGPU, &mut VertsGPU, Option<&mut NormalsGPU>, Option<&SkinWeights>)>()
+        {
+            if !deform_gpu.dirty {
+                continue;
+            }
+            deform_gpu.dirty = false;
+            Self::upload_params(gpu, &deform_gpu, skin.is_some());
+            //goes through deref_mut so that the shadow maps see the vertices as changed
+            let verts_gpu: &mut VertsGPU = &mut verts_gpu;
+
+            let deform_bind_group = BindGroupBuilder::new()
+                .label("deform_bind_group")
+                .add_entry_buf(&deform_gpu.params)
+                .add_entry_buf(&deform_gpu.rest_verts)
+                .add_entry_buf(&deform_gpu.basis)
+                .add_entry_buf(&deform_gpu.coeffs)
+                .add_entry_buf(&deform_gpu.joints)
+                .add_entry_buf(&deform_gpu.weights)
+                .add_entry_buf(&deform_gpu.joint_transforms)
+                .add_entry_buf(&verts_gpu.buf)
+                .build_bind_group(gpu.device(), &self.deform_layout);
+            let normals_bind_group = match normals_gpu.as_deref_mut() {
+                Some(normals_gpu) if deform_gpu.nr_faces > 0 => Some(
+                    BindGroupBuilder::new()
+                        .label("deform_normals_bind_group")
+                        .add_entry_buf(&deform_gpu.params)
+                        .add_entry_buf(&verts_gpu.buf)
+                        .add_entry_buf(&deform_gpu.faces)
+                        .add_entry_buf(&deform_gpu.vert2faces_offsets)
+                        .add_entry_buf(&deform_gpu.vert2faces)
+                        .add_entry_buf(&normals_gpu.buf)
+                        .build_bind_group(gpu.device(), &self.normals_layout),
+                ),
+                _ => None,
+            };
+
+            let nr_workgroups = (deform_gpu.nr_verts + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Deform Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(deform_pipeline);
+            compute_pass.set_bind_group(0, &deform_bind_group, &[]);
+            compute_pass.dispatch_workgroups(nr_workgroups, 1, 1);
+            //normals need all the deformed vertices so they run after the whole first dispatch
+            if let Some(normals_bind_group) = &normals_bind_group {
+                compute_pass.set_pipeline(normals_pipeline);
+                compute_pass.set_bind_group(0, normals_bind_group, &[]);
+                compute_pass.dispatch_workgroups(nr_workgroups, 1, 1);
+            }
+        }
+
+        gpu.profiler().end_scope(&mut encoder);
+        gpu.queue().submit(Some(encoder.finish()));
+    }
+
+    /// Fallback for devices without compute shaders
+    fn evaluate_cpu(gpu: &Gpu, scene: &mut Scene) {
+        for (_entity, (mut deform_gpu, mut verts_gpu, mut normals_gpu, verts, faces, blend_shapes, coeffs, skin, transforms)) in
+            scene.world.query_mut::<(
+                &mut DeformGPU,
+                &mut VertsGPU,
+                Option<&mut NormalsGPU>,
+                &Verts,
+                Option<&Faces>,
+                Option<&BlendShapes>,
+                Option<&BlendShapeCoeffs>,
+                Option<&SkinWeights>,
+                Option<&JointTransforms>,
+            )>()
+        {
+            if !deform_gpu.dirty {
+                continue;
+            }
+            deform_gpu.dirty = false;
+            let mut deformed = verts.0.to_dmatrix();
+            if let (Some(blend_shapes), Some(coeffs)) = (blend_shapes, coeffs) {
+                deformed = Geom::apply_blend_shapes(&deformed, &blend_shapes.0.to_dmatrix(), &coeffs.0);
+            }
+            if let Some(skin) = skin {
+                let transforms = transforms.map_or(&[][..], |t| t.0.as_slice());
+                deformed = Geom::skin_verts(&deformed, &skin.joints.to_dmatrix(), &skin.weights.to_dmatrix(), transforms);
+            }
+            let verts_gpu: &mut VertsGPU = &mut verts_gpu;
+            gpu.queue()
+                .write_buffer(&verts_gpu.buf, 0, bytemuck::cast_slice(deformed.transpose().as_slice()));
+            if let (Some(normals_gpu), Some(faces)) = (normals_gpu.as_deref_mut(), faces) {
+                let normals = Geom::compute_per_vertex_normals(&deformed, &faces.0.to_dmatrix(), &PerVertexNormalsWeightingType::Area);
+                gpu.queue()
+                    .write_buffer(&normals_gpu.buf, 0, bytemuck::cast_slice(normals.transpose().as_slice()));
+            }
+        }
+    }
+
+    fn create_deform_gpu(gpu: &Gpu, nr_verts: u32) -> DeformGPU {
+        let placeholder = |label: &str| Self::create_storage(gpu, label, &[]);
+        DeformGPU {
+            params: gpu.device().create_buffer(&wgpu::BufferDescriptor {
+                label: Some("deform_params"),
+                size: 4 * std::mem::size_of::<u32>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            rest_verts: placeholder("deform_rest_verts"),
+            basis: placeholder("deform_basis"),
+            coeffs: placeholder("deform_coeffs"),
+            joints: placeholder("deform_joints"),
+            weights: placeholder("deform_weights"),
+            joint_transforms: placeholder("deform_joint_transforms"),
+            faces: placeholder("deform_faces"),
+            vert2faces_offsets: placeholder("deform_vert2faces_offsets"),
+            vert2faces: placeholder("deform_vert2faces"),
+            nr_verts,
+            nr_shapes: 0,
+            nr_joints: 0,
+            nr_faces: 0,
+            dirty: true,
+        }
+    }
+
+    fn upload_rest_verts(gpu: &Gpu, deform_gpu: &mut DeformGPU, verts: &Verts) {
+        Self::write_storage(gpu, &mut deform_gpu.rest_verts, "deform_rest_verts", &verts.0.as_bytes());
+        deform_gpu.dirty = true;
+    }
+
+    fn upload_blend_shapes(gpu: &Gpu, deform_gpu: &mut DeformGPU, blend_shapes: &BlendShapes) {
+        Self::write_storage(gpu, &mut deform_gpu.basis, "deform_basis", &blend_shapes.0.as_bytes());
+        deform_gpu.nr_shapes = u32::try_from(blend_shapes.nr_shapes(deform_gpu.nr_verts as usize)).unwrap();
+        deform_gpu.dirty = true;
+    }
+
+    fn upload_coeffs(gpu: &Gpu, deform_gpu: &mut DeformGPU, coeffs: &BlendShapeCoeffs) {
+        let mut padded = coeffs.0.clone();
+        padded.resize(deform_gpu.nr_shapes as usize, 0.0);
+        Self::write_storage(gpu, &mut deform_gpu.coeffs, "deform_coeffs", bytemuck::cast_slice(&padded));
+        deform_gpu.dirty = true;
+    }
+
+    fn upload_skin_weights(gpu: &Gpu, deform_gpu: &mut DeformGPU, skin: &SkinWeights) {
+        assert_eq!(
+            skin.joints.shape(),
+            (deform_gpu.nr_verts as usize, MAX_JOINT_INFLUENCES),
+            "SkinWeights need {MAX_JOINT_INFLUENCES} joints per vertex"
+        );
+        Self::write_storage(gpu, &mut deform_gpu.joints, "deform_joints", &skin.joints.as_bytes());
+        Self::write_storage(gpu, &mut deform_gpu.weights, "deform_weights", &skin.weights.as_bytes());
+        deform_gpu.dirty = true;
+    }
+
+    fn upload_joint_transforms(gpu: &Gpu, deform_gpu: &mut DeformGPU, transforms: &JointTransforms) {
+        //nalgebra matrices are column major like the ones in wgsl
+        let data: Vec<f32> = transforms.0.iter().flat_map(|m| m.as_slice().to_vec()).collect();
+        Self::write_storage(
+            gpu,
+            &mut deform_gpu.joint_transforms,
+            "deform_joint_transforms",
+            bytemuck::cast_slice(&data),
+        );
+        deform_gpu.nr_joints = u32::try_from(transforms.0.len()).unwrap();
+        deform_gpu.dirty = true;
+    }
+
+    /// Uploads the faces together with the list of faces around every vertex
+    /// which is used to compute the normals without atomics
+    fn upload_faces(gpu: &Gpu, deform_gpu: &mut DeformGPU, faces: &Faces) {
+        let faces = faces.0.to_dmatrix();
+        let nr_verts = deform_gpu.nr_verts as usize;
+        let mut offsets = vec![0_u32; nr_verts + 1];
+        for v in faces.iter() {
+            offsets[*v as usize + 1] += 1;
+        }
+        for i in 0..nr_verts {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut cursor = offsets.clone();
+        let mut vert2faces = vec![0_u32; offsets[nr_verts] as usize];
+        for (idx_face, face) in faces.row_iter().enumerate() {
+            for v in face.iter() {
+                vert2faces[cursor[*v as usize] as usize] = u32::try_from(idx_face).unwrap();
+                cursor[*v as usize] += 1;
+            }
+        }
+        let faces_flat: Vec<u32> = faces.transpose().as_slice().to_vec();
+        Self::write_storage(gpu, &mut deform_gpu.faces, "deform_faces", bytemuck::cast_slice(&faces_flat));
+        Self::write_storage(
+            gpu,
+            &mut deform_gpu.vert2faces_offsets,
+            "deform_vert2faces_offsets",
+            bytemuck::cast_slice(&offsets),
+        );
+        Self::write_storage(gpu, &mut deform_gpu.vert2faces, "deform_vert2faces", bytemuck::cast_slice(&vert2faces));
+        deform_gpu.nr_faces = u32::try_from(faces.nrows()).unwrap();
+        deform_gpu.dirty = true;
+    }
+
+    fn upload_params(gpu: &Gpu, deform_gpu: &DeformGPU, has_skinning: bool) {
+        let nr_joints = if has_skinning { deform_gpu.nr_joints } else { 0 };
+        let params = [deform_gpu.nr_verts, deform_gpu.nr_shapes, nr_joints, deform_gpu.nr_faces];
+        gpu.queue().write_buffer(&deform_gpu.params, 0, bytemuck::cast_slice(&params));
+    }
+
+    fn padded_size(nr_bytes: usize) -> u64 {
+        nr_bytes.max(PLACEHOLDER_BYTES) as u64
+    }
+
+    fn create_storage(gpu: &Gpu, label: &str, bytes: &[u8]) -> wgpu::Buffer {
+        let mut contents = bytes.to_vec();
+        contents.resize(contents.len().max(PLACEHOLDER_BYTES), 0);
+        gpu.device().create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: &contents,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    /// Writes into the buffer if it has the same size and otherwise replaces it
+    /// with a new one
+    fn write_storage(gpu: &Gpu, buf: &mut wgpu::Buffer, label: &str, bytes: &[u8]) {
+        if buf.size() == Self::padded_size(bytes.len()) && !bytes.is_empty() {
+            gpu.queue().write_buffer(buf, 0, bytes);
+        } else {
+            *buf = Self::create_storage(gpu, label, bytes);
+        }
+    }
+
+    fn create_pipeline(gpu: &Gpu, label: &str, source: &str, layout: &wgpu::BindGroupLayout) -> wgpu::ComputePipeline {
+        let module = gpu.device().create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipeline_layout = gpu.device().create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts: &[layout],
+            push_constant_ranges: &[],
+        });
+        gpu.device().create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: "cs_main",
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        })
+    }
+
+    fn deform_layout_desc() -> BindGroupLayoutDesc {
+        let mut builder = BindGroupLayoutBuilder::new()
+            .label("deform_layout")
+            .add_entry_uniform(wgpu::ShaderStages::COMPUTE, false, None);
+        //rest verts, basis, coeffs, joints, weights and joint transforms
+        for _ in 0..6 {
+            builder = builder.add_entry_storage(wgpu::ShaderStages::COMPUTE, true);
+        }
+        builder.add_entry_storage(wgpu::ShaderStages::COMPUTE, false).build()
+    }
+
+    fn normals_layout_desc() -> BindGroupLayoutDesc {
+        let mut builder = BindGroupLayoutBuilder::new()
+            .label("deform_normals_layout")
+            .add_entry_uniform(wgpu::ShaderStages::COMPUTE, false, None);
+        //deformed verts, faces, offsets and faces around every vertex
+        for _ in 0..4 {
+            builder = builder.add_entry_storage(wgpu::ShaderStages::COMPUTE, true);
+        }
+        builder.add_entry_storage(wgpu::ShaderStages::COMPUTE, false).build()
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{
+    components::{DistortionKey, ProjectionWithIntrinsics, UndistortedBounds},
+    config::RenderConfig,
+    forward_renderer::renderer::{OffscreenTarget, RenderData},
+};
+use easy_wgpu::{
+    bind_group::{BindGroupBuilder, BindGroupDesc, BindGroupWrapper},
+    bind_group_layout::{BindGroupLayoutBuilder, BindGroupLayoutDesc},
+    gpu::Gpu,
+    pipeline::RenderPipelineDescBuilder,
+    texture::{TexParams, Texture},
+};
+use log::debug;
+use nalgebra as na;
+
+//shaders
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/lens_distortion.wgsl")]
+mod shader_code {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/lens_distortion_depth.wgsl")]
+mod depth_shader_code {}
+
+/// Pass used for cameras with lens distortion. The scene is rendered into an
+/// undistorted target that covers all the rays seen by the distorted image and
+/// this pass warps it towards the output. The warp uses a map computed on the
+/// CPU with the same functions as [`Camera::project`](crate::camera::Camera::project)
+/// so that the rendered pixels agree with the projected points. The depth is
+/// warped with the same map so the output depth matches the distorted image.
+pub struct DistortionPass {
+    render_pipeline: wgpu::RenderPipeline,
+    depth_pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    input_layout: wgpu::BindGroupLayout,
+    input_bind_group: Option<BindGroupWrapper>,
+    depth_layout: wgpu::BindGroupLayout,
+    depth_bind_group: Option<BindGroupWrapper>,
+    surface_format: Option<wgpu::TextureFormat>,
+    //created lazily since most cameras don't have distortion
+    undistorted: Option<RenderData>,
+    map: Option<Texture>,
+    map_key: Option<DistortionKey>,
+}
+
+impl DistortionPass {
+    pub fn new(gpu: &Gpu, params: &RenderConfig, surface_format: Option<wgpu::TextureFormat>, out_format: wgpu::TextureFormat) -> Self {
+        let input_layout_desc = Self::input_layout_desc();
+        let input_layout = input_layout_desc.clone().into_bind_group_layout(gpu.device());
+
+        //render pipeline
+        let render_pipeline = RenderPipelineDescBuilder::new()
+            .label("lens distortion pipeline")
+            .shader_code(shader_code::SOURCE)
+            .shader_label("lens_distortion_shader")
+            .add_bind_group_layout_desc(input_layout_desc)
+            .add_render_target(wgpu::ColorTargetState {
+                format: out_format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .depth_state(None)
+            .multisample(wgpu::MultisampleState::default())
+            .build_pipeline(gpu.device());
+
+        //the depth target is multisampled with msaa so the depth pipeline writes all its samples
+        let multisampled = params.msaa_nr_samples > 1;
+        let depth_shader = if multisampled {
+            depth_shader_code::SOURCE.replace("texture_depth_2d", "texture_depth_multisampled_2d")
+        } else {
+            depth_shader_code::SOURCE.to_string()
+        };
+        let depth_layout_desc = Self::depth_layout_desc(multisampled);
+        let depth_layout = depth_layout_desc.clone().into_bind_group_layout(gpu.device());
+        let depth_pipeline = RenderPipelineDescBuilder::new()
+            .label("lens distortion depth pipeline")
+            .shader_code(&depth_shader)
+            .shader_label("lens_distortion_depth_shader")
+            .add_bind_group_layout_desc(depth_layout_desc)
+            .depth_state(Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }))
+            .multisample(wgpu::MultisampleState {
+                count: params.msaa_nr_samples,
+                ..Default::default()
+            })
+            .build_pipeline(gpu.device());
+
+        //sampler
+        let sampler = gpu.device().create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            render_pipeline,
+            depth_pipeline,
+            sampler,
+            input_layout,
+            input_bind_group: None,
+            depth_layout,
+            depth_bind_group: None,
+            surface_format,
+            undistorted: None,
+            map: None,
+            map_key: None,
+        }
+    }
+
+    /// Makes sure that the undistorted target and the distortion map match the
+    /// intrinsics and the output size of `width` x `height`. The `bounds` are
+    /// the ones of [`Camera::undistorted_bounds`](crate::camera::Camera::undistorted_bounds)
+    pub fn prepare(
+        &mut self,
+        gpu: &Gpu,
+        params: &RenderConfig,
+        intrinsics: &ProjectionWithIntrinsics,
+        bounds: &UndistortedBounds,
+        width: u32,
+        height: u32,
+    ) {
+        let (undistorted_width, undistorted_height) =
+            intrinsics.undistorted_res_for_bounds(&bounds.min, &bounds.max, gpu.limits().max_texture_dimension_2d);
+        let surface_format = self.surface_format;
+        let undistorted = self.undistorted.get_or_insert_with(|| RenderData::new(gpu, params, surface_format));
+        if undistorted.framebuffer.width != undistorted_width || undistorted.framebuffer.height != undistorted_height {
+            debug!("resizing undistorted framebuffer to {undistorted_width}, {undistorted_height}");
+            undistorted.framebuffer.resize(gpu.device(), undistorted_width, undistorted_height);
+        }
+
+        let key = intrinsics.distortion_key(width, height);
+        if self.map_key != Some(key) {
+            self.map = Some(Self::create_map(gpu, intrinsics, bounds, width, height));
+            self.map_key = Some(key);
+        }
+    }
+
+    /// Target in which the scene has to be rendered before running the pass
+    /// # Panics
+    /// Will panic if [`DistortionPass::prepare`] was not called before
+    pub fn undistorted_data(&self) -> &RenderData {
+        self.undistorted.as_ref().expect("The distortion pass needs to be prepared first")
+    }
+
+    /// Warps the undistorted render towards the `out_view` and its depth
+    /// towards `out_depth_view`. With msaa the main pass only keeps the depth
+    /// when using TAA, so otherwise the warped depth is undefined just like
+    /// the depth of cameras without distortion.
+    /// # Panics
+    /// Will panic if [`DistortionPass::prepare`] was not called before
+    pub fn run(&mut self, gpu: &Gpu, out_view: &wgpu::TextureView, out_depth_view: &wgpu::TextureView) {
+        let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Lens Distortion Encoder"),
+        });
+
+        {
+            //update the bind group in case the undistorted texture or the map changed
+            self.update_input_bind_group(gpu);
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Lens Distortion Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: out_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_bind_group(0, self.input_bind_group.as_ref().unwrap().bg(), &[]);
+
+            //draw a quad
+            render_pass.draw(0..4, 0..1);
+        }
+
+        {
+            self.update_depth_bind_group(gpu);
+
+            //the pixels that see nothing keep the depth of the background
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Lens Distortion Depth Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: out_depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.depth_pipeline);
+            render_pass.set_bind_group(0, self.depth_bind_group.as_ref().unwrap().bg(), &[]);
+
+            //draw a quad
+            render_pass.draw(0..4, 0..1);
+        }
+
+        gpu.queue().submit(Some(encoder.finish()));
+    }
+
+    /// For every output pixel stores the uv towards the undistorted image or
+    /// -1 if the distortion cannot be inverted at that pixel
+    #[allow(clippy::cast_precision_loss)]
+    fn create_map(gpu: &Gpu, intrinsics: &ProjectionWithIntrinsics, bounds: &UndistortedBounds, width: u32, height: u32) -> Texture {
+        debug!("computing lens distortion map of size {width}, {height}");
+        let (min, max) = (bounds.min, bounds.max);
+        let size = max - min;
+
+        let mut map_data: Vec<f32> = Vec::with_capacity((width * height * 2) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let px = na::Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let uv = intrinsics
+                    .pixel_to_normalized(&px)
+                    .map_or(na::Vector2::repeat(-1.0), |p| (p - min).component_div(&size));
+                map_data.push(uv.x);
+                map_data.push(uv.y);
+            }
+        }
+
+        let map = Texture::new(
+            gpu.device(),
+            width,
+            height,
+            wgpu::TextureFormat::Rg32Float,
+            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            TexParams::default(),
+        );
+        gpu.queue().write_texture(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture: &map.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            bytemuck::cast_slice(&map_data),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 2 * 4),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        map
+    }
+
+    fn input_layout_desc() -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("lens_distortion_layout")
+            //undistorted texture
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: true })
+            //distortion map
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            //sampler
+            .add_entry_sampler(wgpu::ShaderStages::FRAGMENT, wgpu::SamplerBindingType::Filtering)
+            .build()
+    }
+
+    fn depth_layout_desc(multisampled: bool) -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("lens_distortion_depth_layout")
+            //undistorted depth
+            .add_entry_tex_multisampled(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Depth, multisampled)
+            //distortion map
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            .build()
+    }
+
+    fn update_input_bind_group(&mut self, gpu: &Gpu) {
+        let undistorted_tex = self.undistorted.as_ref().unwrap().framebuffer.get(OffscreenTarget::Color).unwrap();
+        let entries = BindGroupBuilder::new()
+            .add_entry_tex(undistorted_tex)
+            .add_entry_tex(self.map.as_ref().unwrap())
+            .add_entry_sampler(&self.sampler)
+            .build_entries();
+        let stale = self.input_bind_group.as_ref().map_or(true, |b| b.is_stale(&entries)); //returns true if the bg has not been created or if stale
+        if stale {
+            debug!("lens distortion bind group is stale, recreating");
+            self.input_bind_group = Some(BindGroupDesc::new("lens_distortion_bg", entries).into_bind_group_wrapper(gpu.device(), &self.input_layout));
+        }
+    }
+
+    fn update_depth_bind_group(&mut self, gpu: &Gpu) {
+        let undistorted_depth = self.undistorted.as_ref().unwrap().framebuffer.get(OffscreenTarget::Depth).unwrap();
+        let entries = BindGroupBuilder::new()
+            .add_entry_tex(undistorted_depth)
+            .add_entry_tex(self.map.as_ref().unwrap())
+            .build_entries();
+        let stale = self.depth_bind_group.as_ref().map_or(true, |b| b.is_stale(&entries));
+        if stale {
+            debug!("lens distortion depth bind group is stale, recreating");
+            self.depth_bind_group =
+                Some(BindGroupDesc::new("lens_distortion_depth_bg", entries).into_bind_group_wrapper(gpu.device(), &self.depth_layout));
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
 use crate::{
-    components::{EdgesV1GPU, EdgesV2GPU, ModelMatrix, Name, Renderable, VisLines},
+    components::{
+        EdgesColorsV1GPU, EdgesColorsV2GPU, EdgesScalarsV1GPU, EdgesScalarsV2GPU, EdgesV1GPU, EdgesV2GPU, ModelMatrix, Name, NoClip, Renderable,
+        VisLines, VisScalars,
+    },
     config::RenderConfig,
     forward_renderer::{bind_group_collection::BindGroupCollection, locals::LocalEntData},
     scene::Scene,
@@ -55,6 +58,10 @@ impl LinePipeline {
             // .add_vertex_buffer_layout(ColorsGPU::vertex_buffer_layout_instanced::<1>())
             .add_vertex_buffer_layout(EdgesV1GPU::vertex_buffer_layout_instanced::<0>())
             .add_vertex_buffer_layout(EdgesV2GPU::vertex_buffer_layout_instanced::<1>())
+            .add_vertex_buffer_layout(EdgesColorsV1GPU::vertex_buffer_layout_instanced::<2>())
+            .add_vertex_buffer_layout(EdgesColorsV2GPU::vertex_buffer_layout_instanced::<3>())
+            .add_vertex_buffer_layout(EdgesScalarsV1GPU::vertex_buffer_layout_instanced::<4>())
+            .add_vertex_buffer_layout(EdgesScalarsV2GPU::vertex_buffer_layout_instanced::<5>())
             .add_render_target(wgpu::ColorTargetState {
                 format: color_target_format,
                 blend: None,
@@ -91,7 +98,16 @@ impl LinePipeline {
 }
 
 impl PipelineRunner for LinePipeline {
-    type QueryItems<'a> = (&'a EdgesV1GPU, &'a EdgesV2GPU, &'a VisLines, &'a Name);
+    type QueryItems<'a> = (
+        &'a EdgesV1GPU,
+        &'a EdgesV2GPU,
+        Option<&'a EdgesColorsV1GPU>,
+        Option<&'a EdgesColorsV2GPU>,
+        Option<&'a EdgesScalarsV1GPU>,
+        Option<&'a EdgesScalarsV2GPU>,
+        &'a VisLines,
+        &'a Name,
+    );
     type QueryState<'a> = gloss_hecs::QueryBorrow<'a, gloss_hecs::With<Self::QueryItems<'a>, &'a Renderable>>;
 
     fn query_state(scene: &Scene) -> Self::QueryState<'_> {
@@ -102,12 +118,12 @@ impl PipelineRunner for LinePipeline {
         self.update_locals(gpu, scene);
         Self::query_state(scene)
     }
-    fn run<'r>(
-        &'r mut self,
-        render_pass: &mut wgpu::RenderPass<'r>,
-        per_frame_uniforms: &'r PerFrameUniforms,
+    fn run(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        per_frame_uniforms: &PerFrameUniforms,
         _render_params: &RenderConfig,
-        query_state: &'r mut Self::QueryState<'_>,
+        query_state: &mut Self::QueryState<'_>,
     ) {
         //completely skip this if there are no entities to draw
         if query_state.iter().count() == 0 {
@@ -120,7 +136,7 @@ impl PipelineRunner for LinePipeline {
 
         // No need for the input binding because we don't use shadow maps during point
         // rendering
-        for (_id, (ev1, ev2, vis_lines, name)) in query_state.iter() {
+        for (_id, (ev1, ev2, ec1, ec2, es1, es2, vis_lines, name)) in query_state.iter() {
             if !vis_lines.show_lines {
                 continue;
             }
@@ -129,6 +145,12 @@ impl PipelineRunner for LinePipeline {
             render_pass.set_bind_group(1, local_bg.bg(), &[*offset]);
             render_pass.set_vertex_buffer(0, ev1.buf.slice(..));
             render_pass.set_vertex_buffer(1, ev2.buf.slice(..));
+            //entities without per vertex colors still need something bound, so we bind the positions which have the same layout but are
+            // ignored by the shader
+            render_pass.set_vertex_buffer(2, ec1.map_or(&ev1.buf, |c| &c.buf).slice(..));
+            render_pass.set_vertex_buffer(3, ec2.map_or(&ev2.buf, |c| &c.buf).slice(..));
+            render_pass.set_vertex_buffer(4, es1.map_or(&ev1.buf, |s| &s.buf).slice(..));
+            render_pass.set_vertex_buffer(5, es2.map_or(&ev2.buf, |s| &s.buf).slice(..));
             render_pass.draw(0..6, 0..ev1.nr_vertices);
         }
     }
@@ -157,10 +179,15 @@ struct Locals {
     zbuffer: u32,
     antialias_edges: u32,
     is_floor: u32,
+    scalar_min: f32,
+    scalar_max: f32,
+    colormap: u32,
+    scalar_clamp: u32,
+    clip: u32,
     //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
-    pad_b: f32,
     pad_c: f32,
     pad_d: f32,
+    out_of_range_color: nalgebra::Vector4<f32>,
 }
 impl LocalEntData for Locals {
     fn new(entity: Entity, scene: &Scene) -> Self {
@@ -173,6 +200,9 @@ impl LocalEntData for Locals {
             false
         };
         let is_floor = u32::from(is_floor);
+        let vis_scalars = scene
+            .get_comp::<&VisScalars>(&entity)
+            .map_or_else(|_| VisScalars::default(), |v| (*v).clone());
         Locals {
             model_matrix,
             color_type,
@@ -181,9 +211,15 @@ impl LocalEntData for Locals {
             zbuffer: u32::from(vis_lines.zbuffer),
             antialias_edges: u32::from(vis_lines.antialias_edges),
             is_floor,
-            pad_b: 0.0,
+            scalar_min: vis_scalars.min,
+            scalar_max: vis_scalars.max,
+            colormap: vis_scalars.colormap as u32,
+            scalar_clamp: u32::from(vis_scalars.clamp),
+            //the floor grid is never cut
+            clip: u32::from(is_floor == 0 && !scene.world.has::<NoClip>(entity).unwrap_or(false)),
             pad_c: 0.0,
             pad_d: 0.0,
+            out_of_range_color: vis_scalars.out_of_range_color,
         }
     }
 }
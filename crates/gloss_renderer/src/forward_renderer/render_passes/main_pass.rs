@@ -1,4 +1,4 @@
-use crate::config::RenderConfig;
+use crate::config::{AntiAliasing, RenderConfig};
 use log::trace;
 use nalgebra as na;
 
@@ -6,16 +6,25 @@ use crate::scene::Scene;
 
 use easy_wgpu::gpu::Gpu;
 
-use super::{line_pipeline::LinePipeline, mesh_pipeline::MeshPipeline, point_pipeline::PointPipeline, upload_pass::PerFrameUniforms};
+use super::{
+    custom_pipeline::{CustomPipelineContext, CustomPipelineRunner, PendingPipelineRunners, PipelineOrder, PipelineTargets},
+    line_pipeline::LinePipeline,
+    mesh_pipeline::MeshPipeline,
+    point_pipeline::PointPipeline,
+    upload_pass::PerFrameUniforms,
+};
 
 use crate::forward_renderer::{render_passes::pipeline_runner::PipelineRunner, renderer::OffscreenTarget};
-use easy_wgpu::framebuffer::FrameBuffer;
+use easy_wgpu::{framebuffer::FrameBuffer, gpu_profiler::GpuProfiler};
 
 /// Render all the meshes from the scene
 pub struct MainPass {
     mesh_pipeline: MeshPipeline,
     point_pipeline: PointPipeline,
     line_pipeline: LinePipeline,
+    //user pipelines, kept sorted by their order
+    custom_runners: Vec<Box<dyn CustomPipelineRunner>>,
+    targets: PipelineTargets,
 }
 
 impl MainPass {
@@ -23,10 +32,66 @@ impl MainPass {
         let mesh_pipeline = MeshPipeline::new(gpu, params, color_target_format, depth_target_format);
         let point_pipeline = PointPipeline::new(gpu, params, color_target_format, depth_target_format);
         let line_pipeline = LinePipeline::new(gpu, params, color_target_format, depth_target_format);
+        let targets = PipelineTargets {
+            color_format: color_target_format,
+            depth_format: depth_target_format,
+            sample_count: params.msaa_nr_samples,
+        };
         Self {
             mesh_pipeline,
             point_pipeline,
             line_pipeline,
+            custom_runners: Vec::new(),
+            targets,
+        }
+    }
+
+    /// Adds a pipeline that will be drawn every frame at the position given by
+    /// its [`PipelineOrder`]. A runner with the same name as an existing one
+    /// replaces it.
+    pub fn add_pipeline_runner(&mut self, runner: Box<dyn CustomPipelineRunner>) {
+        self.remove_pipeline_runner(runner.name());
+        //insert after all the runners with the same or earlier order so that registration order is kept
+        let idx = self.custom_runners.partition_point(|r| r.order() <= runner.order());
+        self.custom_runners.insert(idx, runner);
+    }
+
+    pub fn remove_pipeline_runner(&mut self, name: &str) -> Option<Box<dyn CustomPipelineRunner>> {
+        let idx = self.custom_runners.iter().position(|r| r.name() == name)?;
+        Some(self.custom_runners.remove(idx))
+    }
+
+    pub fn pipeline_runner_names(&self) -> Vec<String> {
+        self.custom_runners.iter().map(|r| r.name().to_string()).collect()
+    }
+
+    pub fn pipeline_targets(&self) -> PipelineTargets {
+        self.targets
+    }
+
+    fn custom_context<'a>(&'a self, per_frame_uniforms: &'a PerFrameUniforms, render_params: &'a RenderConfig) -> CustomPipelineContext<'a> {
+        CustomPipelineContext {
+            per_frame_uniforms,
+            shading_bind_group: self.mesh_pipeline.input_bind_group(),
+            render_params,
+            targets: self.targets,
+        }
+    }
+
+    fn run_custom_pipelines(
+        &self,
+        order: PipelineOrder,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        per_frame_uniforms: &PerFrameUniforms,
+        render_params: &RenderConfig,
+        scene: &Scene,
+        profiler: &GpuProfiler,
+    ) {
+        let context = self.custom_context(per_frame_uniforms, render_params);
+        for runner in self.custom_runners.iter().filter(|r| r.order() == order) {
+            profiler.begin_pass_scope(runner.name(), render_pass);
+            runner.run(render_pass, &context, scene);
+            profiler.end_pass_scope(render_pass);
         }
     }
 
@@ -45,6 +110,13 @@ impl MainPass {
     ) {
         self.begin_pass();
 
+        //runners registered from plugins through the scene
+        if let Ok(pending) = scene.remove_resource::<PendingPipelineRunners>() {
+            for runner in pending.0 {
+                self.add_pipeline_runner(runner);
+            }
+        }
+
         //tonemap bg color
         let aces = gloss_utils::tonemap::AcesFitted::new();
         let bg_color_vec = render_params.bg_color.fixed_rows::<3>(0).clone_owned();
@@ -58,11 +130,21 @@ impl MainPass {
         let mut line_query = self.line_pipeline.prepare(gpu, per_frame_uniforms, scene);
         let mut mesh_query = self.mesh_pipeline.prepare(gpu, per_frame_uniforms, scene);
         let mut point_query = self.point_pipeline.prepare(gpu, per_frame_uniforms, scene);
+        //the mesh pipeline is prepared first so the custom pipelines can use its shading bind group. The runners are taken out while they
+        // prepare since the context borrows the rest of the pass
+        let mut custom_runners = std::mem::take(&mut self.custom_runners);
+        let context = self.custom_context(per_frame_uniforms, render_params);
+        for runner in &mut custom_runners {
+            runner.prepare(gpu, &context, scene);
+        }
+        self.custom_runners = custom_runners;
 
         //do the actual rendering now
         let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("MainPass Encoder"),
         });
+        let profiler = gpu.profiler();
+        profiler.begin_scope("MainPass", &mut encoder);
         {
             //check if the depth of color need clearing
             let color_clear_op = wgpu::LoadOp::Clear(wgpu::Color {
@@ -85,6 +167,13 @@ impl MainPass {
                                                 // just use them for resolving
             }
 
+            //the temporal anti-aliasing reprojects using the depth so it needs to be kept even with msaa
+            let depth_store = if render_params.antialiasing == AntiAliasing::Taa {
+                wgpu::StoreOp::Store
+            } else {
+                store
+            };
+
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Main Pass"),
                 color_attachments: &[
@@ -99,7 +188,7 @@ impl MainPass {
                     view: &offscreen_fb.get(OffscreenTarget::Depth).unwrap().view,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(0.0),
-                        store,
+                        store: depth_store,
                     }),
                     stencil_ops: None,
                 }),
@@ -108,14 +197,48 @@ impl MainPass {
             });
 
             //Use the piplines to render to the render targets we specified
+            self.run_custom_pipelines(
+                PipelineOrder::BeforeOpaque,
+                &mut render_pass,
+                per_frame_uniforms,
+                render_params,
+                scene,
+                profiler,
+            );
+
+            profiler.begin_pass_scope("MeshPipeline", &mut render_pass);
             self.mesh_pipeline
                 .run(&mut render_pass, per_frame_uniforms, render_params, &mut mesh_query);
+            profiler.end_pass_scope(&mut render_pass);
 
+            profiler.begin_pass_scope("PointPipeline", &mut render_pass);
             self.point_pipeline
                 .run(&mut render_pass, per_frame_uniforms, render_params, &mut point_query);
+            profiler.end_pass_scope(&mut render_pass);
+
+            profiler.begin_pass_scope("LinePipeline", &mut render_pass);
             self.line_pipeline
                 .run(&mut render_pass, per_frame_uniforms, render_params, &mut line_query);
+            profiler.end_pass_scope(&mut render_pass);
+
+            self.run_custom_pipelines(
+                PipelineOrder::AfterOpaque,
+                &mut render_pass,
+                per_frame_uniforms,
+                render_params,
+                scene,
+                profiler,
+            );
+            self.run_custom_pipelines(
+                PipelineOrder::Overlay,
+                &mut render_pass,
+                per_frame_uniforms,
+                render_params,
+                scene,
+                profiler,
+            );
         }
+        profiler.end_scope(&mut encoder);
         gpu.queue().submit(Some(encoder.finish()));
 
         self.end_pass();
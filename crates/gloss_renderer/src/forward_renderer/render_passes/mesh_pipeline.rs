@@ -2,8 +2,8 @@ use std::collections::HashMap;
 
 use crate::{
     components::{
-        ColorsGPU, DiffuseTex, EnvironmentMapGpu, FacesGPU, ModelMatrix, Name, NormalTex, NormalsGPU, Renderable, RoughnessTex, ShadowCaster,
-        ShadowMap, TangentsGPU, UVsGPU, VertsGPU, VisMesh,
+        ColorsGPU, DiffuseTex, EnvironmentMapGpu, FacesGPU, ModelMatrix, Name, NoClip, NormalTex, NormalsGPU, Renderable, RoughnessTex, ScalarsGPU,
+        ShadowCaster, ShadowMap, TangentsGPU, UVsGPU, VertsGPU, VisMesh, VisScalars,
     },
     config::RenderConfig,
     forward_renderer::{bind_group_collection::BindGroupCollection, locals::LocalEntData},
@@ -44,6 +44,7 @@ mod frag_shader_code {}
 /// Render all the meshes from the scene to the `GBuffer`
 pub struct MeshPipeline {
     render_pipeline: wgpu::RenderPipeline,
+    render_pipeline_wireframe: Option<wgpu::RenderPipeline>, //none if the device doesn't support POLYGON_MODE_LINE
     _empty_group: wgpu::BindGroup,
     locals_uniform: Buffer, // a uniform buffer that we suballocate for the locals of every mesh
     locals_bind_groups: LocalsBindGroups,
@@ -73,7 +74,7 @@ impl MeshPipeline {
         let input_layout = input_layout_desc.clone().into_bind_group_layout(gpu.device());
 
         //render pipeline
-        let render_pipeline = RenderPipelineDescBuilder::new()
+        let pipeline_desc = RenderPipelineDescBuilder::new()
             .label("mesh_pipeline")
             //Code has to be sparated between vert and frag because we use derivatives with dpdx in frag shader and that fails to compiles on wasm when in the same file as the vert shader: https://github.com/gfx-rs/wgpu/issues/4368
             .shader_code_vert(vert_shader_code::SOURCE)
@@ -87,6 +88,7 @@ impl MeshPipeline {
             .add_vertex_buffer_layout(NormalsGPU::vertex_buffer_layout::<2>())
             .add_vertex_buffer_layout(TangentsGPU::vertex_buffer_layout::<3>())
             .add_vertex_buffer_layout(ColorsGPU::vertex_buffer_layout::<4>())
+            .add_vertex_buffer_layout(ScalarsGPU::vertex_buffer_layout::<5>())
             .add_render_target(wgpu::ColorTargetState {
                 format: color_target_format,
                 blend: None,
@@ -103,7 +105,18 @@ impl MeshPipeline {
                 count: params.msaa_nr_samples,
                 ..Default::default()
             })
-            .build_pipeline(gpu.device());
+            .build_desc();
+
+        //same pipeline but rasterizing only the edges of the triangles, used for views that are set to wireframe_only
+        let render_pipeline_wireframe = if gpu.device().features().contains(wgpu::Features::POLYGON_MODE_LINE) {
+            let mut desc = pipeline_desc.clone();
+            desc.label = Some(String::from("mesh_pipeline_wireframe"));
+            desc.primitive.polygon_mode = wgpu::PolygonMode::Line;
+            Some(desc.into_render_pipeline(gpu.device()))
+        } else {
+            None
+        };
+        let render_pipeline = pipeline_desc.into_render_pipeline(gpu.device());
 
         let empty_group = create_empty_group(gpu.device());
 
@@ -139,6 +152,7 @@ impl MeshPipeline {
 
         Self {
             render_pipeline,
+            render_pipeline_wireframe,
             _empty_group: empty_group,
             locals_uniform,
             locals_bind_groups,
@@ -149,6 +163,13 @@ impl MeshPipeline {
             passthrough_light,
         }
     }
+
+    /// Bind group with the environment maps and the shadow maps of the lights
+    /// as described by [`MeshPipeline::input_layout_desc`]. It is None until
+    /// the pipeline has been prepared for the first time
+    pub fn input_bind_group(&self) -> Option<&wgpu::BindGroup> {
+        self.input_bind_group.as_ref().map(BindGroupWrapper::bg)
+    }
 }
 impl PipelineRunner for MeshPipeline {
     type QueryItems<'a> = (
@@ -163,6 +184,7 @@ impl PipelineRunner for MeshPipeline {
         &'a RoughnessTex,
         &'a VisMesh,
         &'a Name,
+        Option<&'a ScalarsGPU>,
     );
     type QueryState<'a> = gloss_hecs::QueryBorrow<'a, gloss_hecs::With<Self::QueryItems<'a>, &'a Renderable>>;
 
@@ -184,26 +206,33 @@ impl PipelineRunner for MeshPipeline {
     /// # Panics
     /// Will panic if the input bind groups are not created
     #[allow(clippy::too_many_lines)]
-    fn run<'r>(
-        &'r mut self,
-        render_pass: &mut wgpu::RenderPass<'r>,
-        per_frame_uniforms: &'r PerFrameUniforms,
-        _render_params: &RenderConfig,
-        query_state: &'r mut Self::QueryState<'_>,
+    fn run(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        per_frame_uniforms: &PerFrameUniforms,
+        render_params: &RenderConfig,
+        query_state: &mut Self::QueryState<'_>,
     ) {
         //completely skip this if there are no entities to draw
         if query_state.iter().count() == 0 {
             return;
         }
 
-        render_pass.set_pipeline(&self.render_pipeline);
+        //if line rasterization is not supported we just fall back to drawing the full mesh
+        let pipeline = match (&self.render_pipeline_wireframe, render_params.wireframe_only) {
+            (Some(pipeline_wireframe), true) => pipeline_wireframe,
+            _ => &self.render_pipeline,
+        };
+        render_pass.set_pipeline(pipeline);
 
         //global binding
         render_pass.set_bind_group(0, &per_frame_uniforms.bind_group, &[]);
         //input binding
         render_pass.set_bind_group(1, self.input_bind_group.as_ref().unwrap().bg(), &[]);
 
-        for (_id, (verts, faces, uvs, normals, tangents, colors, _diffuse_tex, _normal_tex, _roughness_tex, vis_mesh, name)) in query_state.iter() {
+        for (_id, (verts, faces, uvs, normals, tangents, colors, _diffuse_tex, _normal_tex, _roughness_tex, vis_mesh, name, scalars)) in
+            query_state.iter()
+        {
             if !vis_mesh.show_mesh {
                 continue;
             }
@@ -217,6 +246,8 @@ impl PipelineRunner for MeshPipeline {
             render_pass.set_vertex_buffer(2, normals.buf.slice(..));
             render_pass.set_vertex_buffer(3, tangents.buf.slice(..));
             render_pass.set_vertex_buffer(4, colors.buf.slice(..));
+            //meshes without scalars still need something bound, the colors are big enough and are ignored by the shader
+            render_pass.set_vertex_buffer(5, scalars.map_or(&colors.buf, |s| &s.buf).slice(..));
             render_pass.set_index_buffer(faces.buf.slice(..), wgpu::IndexFormat::Uint32);
             render_pass.draw_indexed(0..faces.nr_triangles * 3, 0, 0..1);
         }
@@ -307,10 +338,19 @@ struct Locals {
     roughness_black_lvl: f32,
     uv_scale: f32,
     is_floor: u32,
+    scalar_min: f32,
+    scalar_max: f32,
+    colormap: u32,
+    scalar_clamp: u32,
+    nr_isolines: u32,
+    isoline_width: f32,
+    isoline_color: nalgebra::Vector4<f32>,
+    out_of_range_color: nalgebra::Vector4<f32>,
+    clip: u32,
     //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
-    // pad_b: f32,
-    pad_c: f32,
-    pad_d: f32,
+    pad_b: u32,
+    pad_c: u32,
+    pad_d: u32,
 }
 impl LocalEntData for Locals {
     fn new(entity: Entity, scene: &Scene) -> Self {
@@ -323,6 +363,10 @@ impl LocalEntData for Locals {
             false
         };
         let is_floor = u32::from(is_floor);
+        //the range and colormap are only uniforms so changing them doesn't require uploading the scalars again
+        let vis_scalars = scene
+            .get_comp::<&VisScalars>(&entity)
+            .map_or_else(|_| VisScalars::default(), |v| (*v).clone());
         Locals {
             model_matrix,
             color_type,
@@ -332,8 +376,19 @@ impl LocalEntData for Locals {
             roughness_black_lvl: vis_mesh.roughness_black_lvl,
             uv_scale: vis_mesh.uv_scale,
             is_floor,
-            pad_c: 0.0,
-            pad_d: 0.0,
+            scalar_min: vis_scalars.min,
+            scalar_max: vis_scalars.max,
+            colormap: vis_scalars.colormap as u32,
+            scalar_clamp: u32::from(vis_scalars.clamp),
+            nr_isolines: vis_scalars.nr_isolines,
+            isoline_width: vis_scalars.isoline_width,
+            isoline_color: vis_scalars.isoline_color,
+            out_of_range_color: vis_scalars.out_of_range_color,
+            //the floor is never cut
+            clip: u32::from(is_floor == 0 && !scene.world.has::<NoClip>(entity).unwrap_or(false)),
+            pad_b: 0,
+            pad_c: 0,
+            pad_d: 0,
         }
     }
 }
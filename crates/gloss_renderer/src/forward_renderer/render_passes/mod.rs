@@ -1,9 +1,15 @@
+pub mod antialiasing_pass;
+pub mod aov_pass;
 pub mod blit_pass;
 // pub mod compose_pass;
+pub mod custom_pipeline;
 pub mod debug_pass;
+pub mod deform_pass;
+pub mod distortion_pass;
 pub mod line_pipeline;
 pub mod main_pass;
 pub mod mesh_pipeline;
+pub mod outline_pass;
 pub mod pipeline_runner;
 pub mod point_pipeline;
 pub mod prepass;
@@ -0,0 +1,549 @@
+use crate::{
+    components::{
+        EdgesV1GPU, EdgesV2GPU, FacesGPU, Highlight, ModelMatrix, NoClip, NormalsGPU, PointRenderMode, Renderable, VertsGPU, VisLines, VisMesh,
+        VisPoints,
+    },
+    scene::Scene,
+};
+use easy_wgpu::{
+    bind_group::{BindGroupBuilder, BindGroupDesc, BindGroupWrapper},
+    bind_group_layout::{BindGroupLayoutBuilder, BindGroupLayoutDesc},
+    buffer::Buffer,
+    gpu::Gpu,
+    pipeline::RenderPipelineDescBuilder,
+    texture::{TexParams, Texture},
+};
+use gloss_hecs::Entity;
+use gloss_utils::numerical::align;
+use log::debug;
+use nalgebra as na;
+
+use super::upload_pass::PerFrameUniforms;
+
+//shaders
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/outline_mask_mesh.wgsl")]
+mod mask_mesh_shader_code {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/outline_mask_point.wgsl")]
+mod mask_point_shader_code {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/outline_mask_line.wgsl")]
+mod mask_line_shader_code {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/outline_jfa.wgsl")]
+mod jfa_shader_code {}
+#[include_wgsl_oil::include_wgsl_oil("../../../shaders/outline_composite.wgsl")]
+mod composite_shader_code {}
+
+const MASK_COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const MASK_WIDTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R16Float;
+const SEEDS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Float;
+
+/// Which of the representations of an entity is drawn into the mask
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MaskPrimitive {
+    Mesh,
+    Points,
+    Lines,
+}
+
+/// Textures that the outline is computed in. They have the size of the
+/// output and are only allocated once something gets highlighted.
+struct OutlineTargets {
+    mask_color: Texture,
+    mask_width: Texture,
+    //ping-pong textures for the jump flooding
+    seeds: [Texture; 2],
+}
+impl OutlineTargets {
+    fn new(gpu: &Gpu, width: u32, height: u32) -> Self {
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        let tex = |format| Texture::new(gpu.device(), width, height, format, usage, TexParams::default());
+        Self {
+            mask_color: tex(MASK_COLOR_FORMAT),
+            mask_width: tex(MASK_WIDTH_FORMAT),
+            seeds: [tex(SEEDS_FORMAT), tex(SEEDS_FORMAT)],
+        }
+    }
+
+    fn resize(&mut self, gpu: &Gpu, width: u32, height: u32) {
+        for tex in [&mut self.mask_color, &mut self.mask_width].into_iter().chain(self.seeds.iter_mut()) {
+            tex.resize(gpu.device(), width, height);
+        }
+    }
+}
+
+/// Draws a screen-space outline around every entity that has a
+/// [`Highlight`]. The silhouettes of the highlighted meshes, points and lines
+/// are rendered into a mask, a jump flooding pass finds for every pixel the
+/// closest covered pixel and finally the pixels within the width of the
+/// outline get the color of the highlight blended on top of the output.
+/// Since it never reads the output it can draw directly towards the surface.
+pub struct OutlinePass {
+    targets: Option<OutlineTargets>,
+    mesh_pipeline: wgpu::RenderPipeline,
+    point_pipeline: wgpu::RenderPipeline,
+    line_pipeline: wgpu::RenderPipeline,
+    jfa_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    locals_uniform: Buffer, // a uniform buffer that we suballocate for the locals of every highlighted entity
+    locals_bind_group: BindGroupWrapper,
+    jfa_uniform: Buffer, //one chunk for the step of every jump flooding iteration
+    jfa_layout: wgpu::BindGroupLayout,
+    //index i reads the seeds from the texture i
+    jfa_bind_groups: [Option<BindGroupWrapper>; 2],
+    composite_layout: wgpu::BindGroupLayout,
+    composite_bind_group: Option<BindGroupWrapper>,
+}
+
+impl OutlinePass {
+    pub fn new(gpu: &Gpu, color_target_format: wgpu::TextureFormat) -> Self {
+        //wasm likes everything to be 16 bytes aligned
+        const_assert!(std::mem::size_of::<Locals>() % 16 == 0);
+        const_assert!(std::mem::size_of::<JfaParams>() % 16 == 0);
+
+        let usage = wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM;
+        let locals_uniform = Buffer::new_empty(gpu.device(), usage, Some("outline_local_buffer"), 0x10000);
+        let jfa_uniform = Buffer::new_empty(gpu.device(), usage, Some("outline_jfa_buffer"), 0x2000);
+
+        //the locals have no textures so a single bind group with a dynamic offset serves all the entities
+        let locals_layout = Self::locals_layout_desc().into_bind_group_layout(gpu.device());
+        let entries = BindGroupBuilder::new()
+            .add_entry_buf_chunk::<Locals>(&locals_uniform.buffer)
+            .build_entries();
+        let locals_bind_group = BindGroupDesc::new("outline_locals_bg", entries).into_bind_group_wrapper(gpu.device(), &locals_layout);
+
+        //the mask has the color of the highlight in one target and its width in the other
+        let mask_pipeline = |label: &str, shader: &str, vertex_layouts: Vec<wgpu::VertexBufferLayout<'static>>| {
+            let mut builder = RenderPipelineDescBuilder::new()
+                .label(label)
+                .shader_code(shader)
+                .shader_label(label)
+                .add_bind_group_layout_desc(PerFrameUniforms::build_layout_desc())
+                .add_bind_group_layout_desc(Self::locals_layout_desc())
+                .add_render_target(wgpu::ColorTargetState {
+                    format: MASK_COLOR_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+                .add_render_target(wgpu::ColorTargetState {
+                    format: MASK_WIDTH_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+                .depth_state(None)
+                .multisample(wgpu::MultisampleState::default());
+            for layout in vertex_layouts {
+                builder = builder.add_vertex_buffer_layout(layout);
+            }
+            builder.build_pipeline(gpu.device())
+        };
+        let mesh_pipeline = mask_pipeline(
+            "outline_mask_mesh",
+            mask_mesh_shader_code::SOURCE,
+            vec![VertsGPU::vertex_buffer_layout::<0>()],
+        );
+        let point_pipeline = mask_pipeline(
+            "outline_mask_point",
+            mask_point_shader_code::SOURCE,
+            vec![
+                VertsGPU::vertex_buffer_layout_instanced::<0>(),
+                NormalsGPU::vertex_buffer_layout_instanced::<1>(),
+            ],
+        );
+        let line_pipeline = mask_pipeline(
+            "outline_mask_line",
+            mask_line_shader_code::SOURCE,
+            vec![
+                EdgesV1GPU::vertex_buffer_layout_instanced::<0>(),
+                EdgesV2GPU::vertex_buffer_layout_instanced::<1>(),
+            ],
+        );
+
+        let jfa_layout_desc = Self::jfa_layout_desc();
+        let jfa_layout = jfa_layout_desc.clone().into_bind_group_layout(gpu.device());
+        let jfa_pipeline = RenderPipelineDescBuilder::new()
+            .label("outline_jfa")
+            .shader_code(jfa_shader_code::SOURCE)
+            .shader_label("outline_jfa_shader")
+            .add_bind_group_layout_desc(jfa_layout_desc)
+            .add_render_target(wgpu::ColorTargetState {
+                format: SEEDS_FORMAT,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .depth_state(None)
+            .multisample(wgpu::MultisampleState::default())
+            .build_pipeline(gpu.device());
+
+        let composite_layout_desc = Self::composite_layout_desc();
+        let composite_layout = composite_layout_desc.clone().into_bind_group_layout(gpu.device());
+        let composite_pipeline = RenderPipelineDescBuilder::new()
+            .label("outline_composite")
+            .shader_code(composite_shader_code::SOURCE)
+            .shader_label("outline_composite_shader")
+            .add_bind_group_layout_desc(composite_layout_desc)
+            .add_render_target(wgpu::ColorTargetState {
+                format: color_target_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .depth_state(None)
+            .multisample(wgpu::MultisampleState::default())
+            .build_pipeline(gpu.device());
+
+        Self {
+            targets: None,
+            mesh_pipeline,
+            point_pipeline,
+            line_pipeline,
+            jfa_pipeline,
+            composite_pipeline,
+            locals_uniform,
+            locals_bind_group,
+            jfa_uniform,
+            jfa_layout,
+            jfa_bind_groups: [None, None],
+            composite_layout,
+            composite_bind_group: None,
+        }
+    }
+
+    /// Draws the outlines of the highlighted entities on top of `out_view`
+    /// which has a size of `width` x `height`. Does nothing if no entity has a
+    /// [`Highlight`].
+    #[allow(clippy::too_many_lines)]
+    pub fn run(&mut self, gpu: &Gpu, per_frame_uniforms: &PerFrameUniforms, scene: &Scene, out_view: &wgpu::TextureView, width: u32, height: u32) {
+        let draws = self.update_locals(gpu, scene);
+        if draws.is_empty() {
+            return;
+        }
+
+        match &mut self.targets {
+            Some(targets) if targets.mask_color.width() == width && targets.mask_color.height() == height => {}
+            Some(targets) => targets.resize(gpu, width, height),
+            None => {
+                debug!("creating outline targets");
+                self.targets = Some(OutlineTargets::new(gpu, width, height));
+            }
+        }
+
+        //jump in steps of halving size, starting from the first power of two that covers the widest outline
+        #[allow(clippy::cast_precision_loss)]
+        let max_width = draws.iter().map(|draw| draw.width).fold(0.0, f32::max).min(width.max(height) as f32);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let first_step = (max_width.ceil() as u32 + 1).next_power_of_two();
+        let steps: Vec<u32> = std::iter::successors(Some(first_step), |step| (*step > 1).then_some(step / 2)).collect();
+        let jfa_offsets = self.update_jfa_params(gpu, &steps);
+        self.update_bind_groups(gpu, jfa_offsets.len());
+        let targets = self.targets.as_ref().unwrap();
+
+        let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("OutlinePass Encoder"),
+        });
+        gpu.profiler().begin_scope("OutlinePass", &mut encoder);
+
+        //silhouettes of the highlighted entities
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Outline Mask Pass"),
+                color_attachments: &[
+                    Some(Self::clear_attachment(&targets.mask_color)),
+                    Some(Self::clear_attachment(&targets.mask_width)),
+                ],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_bind_group(0, &per_frame_uniforms.bind_group, &[]);
+
+            for draw in &draws {
+                render_pass.set_bind_group(1, self.locals_bind_group.bg(), &[draw.offset]);
+                match draw.primitive {
+                    MaskPrimitive::Mesh => {
+                        let (Ok(verts), Ok(faces)) = (scene.get_comp::<&VertsGPU>(&draw.entity), scene.get_comp::<&FacesGPU>(&draw.entity)) else {
+                            continue;
+                        };
+                        render_pass.set_pipeline(&self.mesh_pipeline);
+                        render_pass.set_vertex_buffer(0, verts.buf.slice(..));
+                        render_pass.set_index_buffer(faces.buf.slice(..), wgpu::IndexFormat::Uint32);
+                        render_pass.draw_indexed(0..faces.nr_triangles * 3, 0, 0..1);
+                    }
+                    MaskPrimitive::Points => {
+                        let Ok(verts) = scene.get_comp::<&VertsGPU>(&draw.entity) else {
+                            continue;
+                        };
+                        let normals = scene.get_comp::<&NormalsGPU>(&draw.entity).ok();
+                        render_pass.set_pipeline(&self.point_pipeline);
+                        render_pass.set_vertex_buffer(0, verts.buf.slice(..));
+                        //sprites ignore the normals so any buffer with the same layout works
+                        render_pass.set_vertex_buffer(1, normals.as_ref().map_or(&verts.buf, |n| &n.buf).slice(..));
+                        render_pass.draw(0..6, 0..verts.nr_vertices);
+                    }
+                    MaskPrimitive::Lines => {
+                        let (Ok(ev1), Ok(ev2)) = (scene.get_comp::<&EdgesV1GPU>(&draw.entity), scene.get_comp::<&EdgesV2GPU>(&draw.entity)) else {
+                            continue;
+                        };
+                        render_pass.set_pipeline(&self.line_pipeline);
+                        render_pass.set_vertex_buffer(0, ev1.buf.slice(..));
+                        render_pass.set_vertex_buffer(1, ev2.buf.slice(..));
+                        render_pass.draw(0..6, 0..ev1.nr_vertices);
+                    }
+                }
+            }
+        }
+
+        //jump flooding, the first iteration writes the seeds from the mask into seeds[0] and every step after it reads from one of the
+        // textures and writes to the other
+        for (idx, offset) in jfa_offsets.iter().enumerate() {
+            let read_idx = if idx == 0 { 1 } else { (idx - 1) % 2 };
+            let write_idx = 1 - read_idx;
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Outline Jfa Pass"),
+                color_attachments: &[Some(Self::clear_attachment(&targets.seeds[write_idx]))],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.jfa_pipeline);
+            render_pass.set_bind_group(0, self.jfa_bind_groups[read_idx].as_ref().unwrap().bg(), &[*offset]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        //blend the outline on top of the output
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Outline Composite Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: out_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.composite_pipeline);
+            render_pass.set_bind_group(0, self.composite_bind_group.as_ref().unwrap().bg(), &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        gpu.profiler().end_scope(&mut encoder);
+        gpu.queue().submit(Some(encoder.finish()));
+    }
+
+    /// Uploads the locals of every highlighted representation that is shown
+    /// and returns what needs to be drawn into the mask
+    fn update_locals(&mut self, gpu: &Gpu, scene: &Scene) -> Vec<MaskDraw> {
+        let mut draws = Vec::new();
+        self.locals_uniform.reset_chunks_offset_if_necessary();
+
+        let mut query = scene
+            .world
+            .query::<(&Highlight, Option<&VisMesh>, Option<&VisPoints>, Option<&VisLines>)>()
+            .with::<&Renderable>();
+        for (entity, (highlight, vis_mesh, vis_points, vis_lines)) in query.iter() {
+            if highlight.width <= 0.0 {
+                continue;
+            }
+            let model_matrix = scene
+                .get_comp::<&ModelMatrix>(&entity)
+                .map_or_else(|_| na::Matrix4::identity(), |m| m.0.to_homogeneous());
+            let is_floor = scene.get_floor().map_or(false, |floor| floor.entity == entity);
+            let clip = u32::from(!is_floor && !scene.world.has::<NoClip>(entity).unwrap_or(false));
+            let locals = Locals {
+                model_matrix,
+                color: highlight.color,
+                width: highlight.width,
+                size: 0.0,
+                is_size_in_world_space: 0,
+                clip,
+                is_surfel: 0,
+                pad_b: 0.0,
+                pad_c: 0.0,
+                pad_d: 0.0,
+            };
+
+            let has = |primitive: MaskPrimitive| match primitive {
+                MaskPrimitive::Mesh => scene.world.has::<FacesGPU>(entity).unwrap_or(false) && scene.world.has::<VertsGPU>(entity).unwrap_or(false),
+                MaskPrimitive::Points => scene.world.has::<VertsGPU>(entity).unwrap_or(false),
+                MaskPrimitive::Lines => scene.world.has::<EdgesV1GPU>(entity).unwrap_or(false),
+            };
+            let mut push = |primitive: MaskPrimitive, locals: Locals| {
+                let offset = self.locals_uniform.push_cpu_chunk_aligned::<Locals>(&locals);
+                draws.push(MaskDraw {
+                    entity,
+                    primitive,
+                    offset,
+                    width: highlight.width,
+                });
+            };
+
+            if vis_mesh.map_or(false, |v| v.show_mesh) && has(MaskPrimitive::Mesh) {
+                push(MaskPrimitive::Mesh, locals);
+            }
+            if let Some(vis_points) = vis_points.filter(|v| v.show_points && has(MaskPrimitive::Points)) {
+                let has_normals = scene.world.has::<NormalsGPU>(entity).unwrap_or(false);
+                push(
+                    MaskPrimitive::Points,
+                    Locals {
+                        size: vis_points.point_size,
+                        is_size_in_world_space: u32::from(vis_points.is_point_size_in_world_space),
+                        is_surfel: u32::from(vis_points.render_mode == PointRenderMode::Surfel && has_normals),
+                        ..locals
+                    },
+                );
+            }
+            if let Some(vis_lines) = vis_lines.filter(|v| v.show_lines && has(MaskPrimitive::Lines)) {
+                push(
+                    MaskPrimitive::Lines,
+                    Locals {
+                        size: vis_lines.line_width,
+                        ..locals
+                    },
+                );
+            }
+        }
+        self.locals_uniform.upload_from_cpu_chunks(gpu.queue()); //important to upload everything to gpu at the end
+
+        draws
+    }
+
+    /// Uploads the initialization of the seeds followed by every jump size.
+    /// Returns the offset in the uniform buffer of each iteration.
+    fn update_jfa_params(&mut self, gpu: &Gpu, steps: &[u32]) -> Vec<u32> {
+        self.jfa_uniform.reset_chunks_offset();
+        let offsets = std::iter::once(0)
+            .chain(steps.iter().copied())
+            .map(|step| {
+                let params = JfaParams {
+                    step: i32::try_from(step).unwrap(),
+                    pad_b: 0.0,
+                    pad_c: 0.0,
+                    pad_d: 0.0,
+                };
+                self.jfa_uniform.push_cpu_chunk_aligned::<JfaParams>(&params)
+            })
+            .collect();
+        self.jfa_uniform.upload_from_cpu_chunks(gpu.queue());
+        offsets
+    }
+
+    /// Recreates the bind groups towards the targets in case they were
+    /// resized. The composite reads the seeds from whichever texture the last
+    /// jump wrote to.
+    fn update_bind_groups(&mut self, gpu: &Gpu, nr_iterations: usize) {
+        let targets = self.targets.as_ref().unwrap();
+
+        for (read_idx, bind_group) in self.jfa_bind_groups.iter_mut().enumerate() {
+            let entries = BindGroupBuilder::new()
+                .add_entry_tex(&targets.mask_width)
+                .add_entry_tex(&targets.seeds[read_idx])
+                .add_entry_buf_chunk::<JfaParams>(&self.jfa_uniform.buffer)
+                .build_entries();
+            if bind_group.as_ref().map_or(true, |b| b.is_stale(&entries)) {
+                debug!("outline jfa bind group is stale, recreating");
+                *bind_group = Some(BindGroupDesc::new("outline_jfa_bg", entries).into_bind_group_wrapper(gpu.device(), &self.jfa_layout));
+            }
+        }
+
+        //the init writes to seeds[0] and then every step flips it
+        let entries = BindGroupBuilder::new()
+            .add_entry_tex(&targets.mask_color)
+            .add_entry_tex(&targets.mask_width)
+            .add_entry_tex(&targets.seeds[(nr_iterations - 1) % 2])
+            .build_entries();
+        if self.composite_bind_group.as_ref().map_or(true, |b| b.is_stale(&entries)) {
+            debug!("outline composite bind group is stale, recreating");
+            self.composite_bind_group =
+                Some(BindGroupDesc::new("outline_composite_bg", entries).into_bind_group_wrapper(gpu.device(), &self.composite_layout));
+        }
+    }
+
+    fn clear_attachment(tex: &Texture) -> wgpu::RenderPassColorAttachment<'_> {
+        wgpu::RenderPassColorAttachment {
+            view: &tex.view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: wgpu::StoreOp::Store,
+            },
+        }
+    }
+
+    fn locals_layout_desc() -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("outline_locals_layout")
+            .add_entry_uniform(
+                wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                true,
+                wgpu::BufferSize::new(u64::from(align(u32::try_from(std::mem::size_of::<Locals>()).unwrap(), 256))),
+            )
+            .build()
+    }
+
+    fn jfa_layout_desc() -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("outline_jfa_layout")
+            //mask width
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            //seeds of the previous iteration
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            //params
+            .add_entry_uniform(
+                wgpu::ShaderStages::FRAGMENT,
+                true,
+                wgpu::BufferSize::new(u64::from(align(u32::try_from(std::mem::size_of::<JfaParams>()).unwrap(), 256))),
+            )
+            .build()
+    }
+
+    fn composite_layout_desc() -> BindGroupLayoutDesc {
+        BindGroupLayoutBuilder::new()
+            .label("outline_composite_layout")
+            //mask color
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            //mask width
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            //seeds
+            .add_entry_tex(wgpu::ShaderStages::FRAGMENT, wgpu::TextureSampleType::Float { filterable: false })
+            .build()
+    }
+}
+
+/// One representation of a highlighted entity drawn into the mask
+struct MaskDraw {
+    entity: Entity,
+    primitive: MaskPrimitive,
+    offset: u32,
+    width: f32,
+}
+
+/// Keep in sync with the shaders `outline_mask_*.wgsl`
+#[repr(C)]
+#[derive(Clone, Copy, encase::ShaderType)]
+struct Locals {
+    model_matrix: nalgebra::Matrix4<f32>,
+    color: nalgebra::Vector4<f32>,
+    width: f32,
+    //point size or line width
+    size: f32,
+    is_size_in_world_space: u32,
+    clip: u32,
+    is_surfel: u32,
+    //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
+    pad_b: f32,
+    pad_c: f32,
+    pad_d: f32,
+}
+
+/// Keep in sync with shader `outline_jfa.wgsl`
+#[repr(C)]
+#[derive(Clone, Copy, encase::ShaderType)]
+struct JfaParams {
+    step: i32,
+    //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
+    pad_b: f32,
+    pad_c: f32,
+    pad_d: f32,
+}
@@ -20,12 +20,12 @@ pub trait PipelineRunner {
 
     fn query_state(scene: &Scene) -> Self::QueryState<'_>;
     fn prepare<'a>(&mut self, gpu: &Gpu, per_frame_uniforms: &PerFrameUniforms, scene: &'a Scene) -> Self::QueryState<'a>;
-    fn run<'r>(
-        &'r mut self,
-        render_pass: &mut wgpu::RenderPass<'r>,
-        per_frame_uniforms: &'r PerFrameUniforms,
+    fn run(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        per_frame_uniforms: &PerFrameUniforms,
         _render_params: &RenderConfig,
-        query_state: &'r mut Self::QueryState<'_>,
+        query_state: &mut Self::QueryState<'_>,
     );
     fn begin_pass(&mut self);
     fn input_layout_desc() -> BindGroupLayoutDesc {
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    components::{ColorsGPU, ModelMatrix, Name, Renderable, VertsGPU, VisPoints},
+    components::{ColorsGPU, ModelMatrix, Name, NoClip, NormalsGPU, Renderable, ScalarsGPU, VertsGPU, VisPoints, VisScalars},
     config::RenderConfig,
     forward_renderer::{bind_group_collection::BindGroupCollection, locals::LocalEntData},
     scene::Scene,
@@ -53,6 +53,8 @@ impl PointPipeline {
             .add_bind_group_layout_desc(LocalsBindGroups::build_layout_desc())
             .add_vertex_buffer_layout(VertsGPU::vertex_buffer_layout_instanced::<0>())
             .add_vertex_buffer_layout(ColorsGPU::vertex_buffer_layout_instanced::<1>())
+            .add_vertex_buffer_layout(NormalsGPU::vertex_buffer_layout_instanced::<2>())
+            .add_vertex_buffer_layout(ScalarsGPU::vertex_buffer_layout_instanced::<3>())
             .add_render_target(wgpu::ColorTargetState {
                 format: color_target_format,
                 blend: None,
@@ -89,7 +91,14 @@ impl PointPipeline {
 }
 
 impl PipelineRunner for PointPipeline {
-    type QueryItems<'a> = (&'a VertsGPU, &'a ColorsGPU, &'a VisPoints, &'a Name);
+    type QueryItems<'a> = (
+        &'a VertsGPU,
+        &'a ColorsGPU,
+        Option<&'a NormalsGPU>,
+        Option<&'a ScalarsGPU>,
+        &'a VisPoints,
+        &'a Name,
+    );
     type QueryState<'a> = gloss_hecs::QueryBorrow<'a, gloss_hecs::With<Self::QueryItems<'a>, &'a Renderable>>;
 
     fn query_state(scene: &Scene) -> Self::QueryState<'_> {
@@ -102,12 +111,12 @@ impl PipelineRunner for PointPipeline {
         Self::query_state(scene)
     }
 
-    fn run<'r>(
-        &'r mut self,
-        render_pass: &mut wgpu::RenderPass<'r>,
-        per_frame_uniforms: &'r PerFrameUniforms,
+    fn run(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        per_frame_uniforms: &PerFrameUniforms,
         _render_params: &RenderConfig,
-        query_state: &'r mut Self::QueryState<'_>,
+        query_state: &mut Self::QueryState<'_>,
     ) {
         //completely skip this if there are no entities to draw
         if query_state.iter().count() == 0 {
@@ -121,7 +130,7 @@ impl PipelineRunner for PointPipeline {
         //No need for the input binding because we don't use shadow maps during point
         // rendering
 
-        for (_id, (verts, colors, vis_points, name)) in query_state.iter() {
+        for (_id, (verts, colors, normals, scalars, vis_points, name)) in query_state.iter() {
             if !vis_points.show_points {
                 continue;
             }
@@ -131,6 +140,9 @@ impl PipelineRunner for PointPipeline {
             render_pass.set_bind_group(1, local_bg.bg(), &[*offset]);
             render_pass.set_vertex_buffer(0, verts.buf.slice(..));
             render_pass.set_vertex_buffer(1, colors.buf.slice(..));
+            //point clouds without normals can only be drawn as sprites so the shader ignores this buffer
+            render_pass.set_vertex_buffer(2, normals.map_or(&verts.buf, |n| &n.buf).slice(..));
+            render_pass.set_vertex_buffer(3, scalars.map_or(&verts.buf, |s| &s.buf).slice(..));
             render_pass.draw(0..6, 0..verts.nr_vertices);
         }
     }
@@ -158,16 +170,25 @@ struct Locals {
     point_size: f32,
     is_point_size_in_world_space: u32,
     zbuffer: u32,
+    render_mode: u32,
+    has_normals: u32,
+    scalar_min: f32,
+    scalar_max: f32,
+    colormap: u32,
+    scalar_clamp: u32,
+    clip: u32,
     //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
-    // pad_b: f32,
-    // pad_c: f32,
-    // pad_d: f32,
+    pad_c: f32,
+    out_of_range_color: nalgebra::Vector4<f32>,
 }
 impl LocalEntData for Locals {
     fn new(entity: Entity, scene: &Scene) -> Self {
         let model_matrix = scene.get_comp::<&ModelMatrix>(&entity).unwrap().0.to_homogeneous();
         let vis_points = scene.get_comp::<&VisPoints>(&entity).unwrap();
         let color_type = vis_points.color_type as i32;
+        let vis_scalars = scene
+            .get_comp::<&VisScalars>(&entity)
+            .map_or_else(|_| VisScalars::default(), |v| (*v).clone());
         Locals {
             model_matrix,
             color_type,
@@ -175,7 +196,15 @@ impl LocalEntData for Locals {
             point_size: vis_points.point_size,
             is_point_size_in_world_space: u32::from(vis_points.is_point_size_in_world_space),
             zbuffer: u32::from(vis_points.zbuffer),
-            // pad_d: 0.0,
+            render_mode: vis_points.render_mode as u32,
+            has_normals: u32::from(scene.get_comp::<&NormalsGPU>(&entity).is_ok()),
+            scalar_min: vis_scalars.min,
+            scalar_max: vis_scalars.max,
+            colormap: vis_scalars.colormap as u32,
+            scalar_clamp: u32::from(vis_scalars.clamp),
+            clip: u32::from(!scene.world.has::<NoClip>(entity).unwrap_or(false)),
+            pad_c: 0.0,
+            out_of_range_color: vis_scalars.out_of_range_color,
         }
     }
 }
@@ -3,8 +3,8 @@ extern crate nalgebra as na;
 use crate::{
     camera::Camera,
     components::{
-        Colors, DiffuseTex, Edges, EnvironmentMapGpu, Faces, LightEmit, ModelMatrix, Name, NormalTex, Normals, Renderable, RoughnessTex,
-        ShadowCaster, ShadowMap, Tangents, UVs, Verts, VisLines, VisMesh, VisNormals, VisPoints, VisWireframe,
+        Colors, DiffuseTex, Edges, EnvironmentMapGpu, Faces, LightEmit, ModelMatrix, Name, NormalTex, Normals, Renderable, RoughnessTex, Scalars,
+        ShadowCaster, ShadowMap, Tangents, UVs, Verts, VisLines, VisMesh, VisNormals, VisPoints, VisScalars, VisWireframe,
     },
     config::Config,
     geom::{Geom, PerVertexNormalsWeightingType},
@@ -60,6 +60,7 @@ impl PrePass {
         self.add_vis_normals(scene);
         self.add_vis_points(scene);
         self.add_vis_mesh(scene);
+        self.add_vis_scalars(scene);
         self.command_buffer.run_on(&mut scene.world); //in order to actually
                                                       // create the model matrix
                                                       // so that adding lights
@@ -85,6 +86,9 @@ impl PrePass {
         //this adds a lot of components automatically if they are needed
         self.add_auto_components(gpu, scene);
 
+        //children get their world transform from their parents, this needs the model matrices that were just added
+        scene.propagate_transforms();
+
         //if we have objects in the scene, make the config objects that were set to
         // auto, to a value that is concrete
         if !config.is_concrete() && scene.nr_renderables() != 0 {
@@ -106,6 +110,9 @@ impl PrePass {
         // }
         self.add_shadow_maps(scene, gpu);
 
+        //the projection of cameras with lens distortion needs the bounds of their undistorted image
+        scene.update_undistorted_bounds();
+
         // if !scene.has_floor() && scene.get_renderables(false).len() != 0 {
         //     scene.add_floor();
         // }
@@ -214,6 +221,20 @@ impl PrePass {
         }
     }
 
+    fn add_vis_scalars(&mut self, scene: &mut Scene) {
+        let mut query = scene.world.query::<&Scalars>().without::<&VisScalars>();
+        for (entity, scalars) in query.iter() {
+            self.command_buffer.insert_one(
+                entity,
+                VisScalars {
+                    added_automatically: true,
+                    ..Default::default()
+                }
+                .with_range_of(scalars),
+            );
+        }
+    }
+
     fn add_model_matrix(&mut self, scene: &mut Scene) {
         let mut query = scene.world.query::<()>().with::<&Renderable>().without::<&ModelMatrix>();
         for (entity, _comp) in query.iter() {
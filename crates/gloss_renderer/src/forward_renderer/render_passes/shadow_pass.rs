@@ -92,6 +92,8 @@ impl ShadowPass {
             let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Shadow pass encoder"),
             });
+            //the scopes of all the lights are added together
+            gpu.profiler().begin_scope("ShadowPass", &mut encoder);
             {
                 let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("Shadow Pass"),
@@ -148,6 +150,7 @@ impl ShadowPass {
 
                 //TODO render points
             }
+            gpu.profiler().end_scope(&mut encoder);
             debug!("shadow encoder");
             gpu.queue().submit(Some(encoder.finish()));
         }
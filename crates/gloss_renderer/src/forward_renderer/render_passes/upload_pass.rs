@@ -7,11 +7,14 @@ extern crate nalgebra as na;
 use crate::{
     camera::Camera,
     components::{
-        Colors, ColorsGPU, DiffuseImg, DiffuseTex, Edges, EdgesV1, EdgesV1GPU, EdgesV2, EdgesV2GPU, EnvironmentMap, EnvironmentMapGpu, Faces,
-        FacesGPU, GpuAtrib, LightEmit, MeshColorType, Name, NormalImg, NormalTex, Normals, NormalsGPU, PosLookat, Projection, ProjectionWithFov,
-        Renderable, RoughnessImg, RoughnessTex, ShadowCaster, Tangents, TangentsGPU, UVs, UVsGPU, Verts, VertsGPU, VisMesh,
+        ClipPlane, Colors, ColorsGPU, DeformGPU, DiffuseImg, DiffuseTex, Edges, EdgesColorsV1GPU, EdgesColorsV2GPU, EdgesScalarsV1GPU,
+        EdgesScalarsV2GPU, EdgesV1, EdgesV1GPU, EdgesV2, EdgesV2GPU, EnvironmentMap, EnvironmentMapGpu, Faces, FacesGPU, GpuAtrib, LightEmit,
+        LineColorType, MeshColorType, Name, NormalImg, NormalTex, Normals, NormalsGPU, PointColorType, PosLookat, Projection, ProjectionWithFov,
+        Renderable, RoughnessImg, RoughnessTex, Scalars, ScalarsGPU, ShadowCaster, Tangents, TangentsGPU, UVs, UVsGPU, Verts, VertsGPU, VisLines,
+        VisMesh, VisPoints,
     },
     config::RenderConfig,
+    forward_renderer::render_passes::deform_pass::DeformPass,
     scene::Scene,
 };
 
@@ -35,6 +38,7 @@ use encase;
 
 pub const MAX_NUM_LIGHTS: usize = 20; //lower than 20 causes wasm to throw error because the uniform is too small..
 pub const MAX_NUM_SHADOWS: usize = 3; //HAS to be lower than MAX_NUM_LIGHTS.
+pub const MAX_NUM_CLIP_PLANES: usize = 8; //keep in sync with the size of the clip_planes array in global_binds.wgsl
 
 pub fn index_vertices_from_edges(matrix: &na::DMatrix<f32>, v_indices: &na::DMatrix<u32>, col_id: usize) -> na::DMatrix<f32> {
     let index_slice = v_indices.column(col_id).into_owned();
@@ -58,6 +62,9 @@ pub struct UploadPass {
     //the local stuff that changes from mesh to mesh is allocated by each pass, because each pass might need something different from the mesh
     pub command_buffer: CommandBuffer, //defer insertions and deletion of scene entities for whenever we apply this command buffer
     pub staging_buffer: Option<Buffer>,
+    deform_pass: DeformPass,
+    //subpixel offset of the projection in pixels, used for the temporal anti-aliasing
+    jitter: na::Vector2<f32>,
 }
 
 impl UploadPass {
@@ -67,6 +74,7 @@ impl UploadPass {
         const_assert!(std::mem::size_of::<PerFrameCamCPU>() % 16 == 0);
         const_assert!(std::mem::size_of::<PerFrameLightCPU>() % 16 == 0);
         const_assert!(std::mem::size_of::<PerFrameParamsCPU>() % 16 == 0);
+        const_assert!(std::mem::size_of::<PerFrameClipPlaneCPU>() % 16 == 0);
 
         let per_frame_uniforms = PerFrameUniforms::new(gpu);
 
@@ -85,14 +93,17 @@ impl UploadPass {
         //     }
         // }
 
-        let mipmapper = Some(RenderMipmapGenerator::new_with_format_hints(
-            gpu.device(),
-            &[
-                wgpu::TextureFormat::Rgba8Unorm,     //for normal maps
-                wgpu::TextureFormat::Rgba8UnormSrgb, //for diffuse maps
-                wgpu::TextureFormat::R8Unorm,        //for roughness maps
-            ],
-        ));
+        let mipmapper = Some(
+            RenderMipmapGenerator::new_with_format_hints(
+                gpu.device(),
+                &[
+                    wgpu::TextureFormat::Rgba8Unorm,     //for normal maps
+                    wgpu::TextureFormat::Rgba8UnormSrgb, //for diffuse maps
+                    wgpu::TextureFormat::R8Unorm,        //for roughness maps
+                ],
+            )
+            .with_profiler(gpu.profiler().clone()),
+        );
 
         let command_buffer = CommandBuffer::new();
 
@@ -111,15 +122,25 @@ impl UploadPass {
             None
         };
 
+        let deform_pass = DeformPass::new(gpu);
+
         Self {
             per_frame_uniforms,
             mipmapper,
             command_buffer,
             staging_buffer,
+            deform_pass,
+            jitter: na::Vector2::zeros(),
         }
     }
 
     pub fn run(&mut self, gpu: &Gpu, camera: &Camera, scene: &mut Scene, render_params: &RenderConfig) -> &PerFrameUniforms {
+        //most of the uploads are queue writes which are not in any encoder of ours so the scope is submitted on its own
+        gpu.profiler().begin_scope_submit("UploadPass", gpu.device(), gpu.queue());
+
+        //blend shapes and skinning go first so that the upload of the vertices and normals knows which entities to skip
+        self.deform_pass.run(gpu, scene);
+
         //upload each component (all of these are needed for the mesh)
         self.upload_v(gpu, scene);
         self.upload_e(gpu, scene);
@@ -128,13 +149,24 @@ impl UploadPass {
         self.upload_nv(gpu, scene);
         self.upload_t(gpu, scene);
         self.upload_c(gpu, scene);
+        self.upload_ec(gpu, scene);
+        self.upload_s(gpu, scene);
+        self.upload_es(gpu, scene);
         self.upload_textures(gpu, scene);
 
         self.upload_scene(gpu, scene);
         self.upload_cam(gpu, camera, scene);
         self.upload_lights(gpu, scene);
+        self.upload_clip_planes(gpu, scene);
         self.upload_params(gpu, scene, render_params);
 
+        gpu.profiler().end_scope_submit(gpu.device(), gpu.queue());
+
+        &self.per_frame_uniforms
+    }
+
+    /// Uniforms uploaded during the last [`UploadPass::run`]
+    pub fn per_frame_uniforms(&self) -> &PerFrameUniforms {
         &self.per_frame_uniforms
     }
 
@@ -186,12 +218,13 @@ impl UploadPass {
     fn upload_v(&mut self, gpu: &Gpu, scene: &mut Scene) {
         let query = scene
             .world
-            .query_mut::<(&Verts, Option<&mut VertsGPU>, Changed<Verts>)>()
+            .query_mut::<(&Verts, Option<&mut VertsGPU>, Option<&DeformGPU>, Changed<Verts>)>()
             .with::<&Renderable>();
         let usage = wgpu::BufferUsages::VERTEX;
 
-        for (ent, (verts, mut verts_gpu, changed_verts)) in query {
-            if changed_verts {
+        for (ent, (verts, mut verts_gpu, deform_gpu, changed_verts)) in query {
+            //deformed entities get their vertex buffer written by the deform pass
+            if changed_verts && deform_gpu.is_none() {
                 self.upload_dynamic_vertex_atrib(ent, &verts.0, verts_gpu.as_deref_mut(), gpu, usage, "verts");
             }
         }
@@ -229,6 +262,35 @@ impl UploadPass {
         self.command_buffer.run_on(&mut scene.world);
     }
 
+    fn upload_ec(&mut self, gpu: &Gpu, scene: &mut Scene) {
+        let query = scene
+            .world
+            .query_mut::<(
+                &Colors,
+                &Edges,
+                Option<&mut EdgesColorsV1GPU>,
+                Option<&mut EdgesColorsV2GPU>,
+                Changed<Colors>,
+                Changed<Edges>,
+            )>()
+            .with::<&Renderable>();
+
+        let usage = wgpu::BufferUsages::VERTEX;
+        for (ent, (colors, edges, mut edges_colors_v1_gpu, mut edges_colors_v2_gpu, changed_colors, changed_edges)) in query {
+            if changed_colors || changed_edges {
+                let colors_v1_mat = index_vertices_from_edges(&colors.0.to_dmatrix(), &edges.0.to_dmatrix(), 0);
+                let colors_v2_mat = index_vertices_from_edges(&colors.0.to_dmatrix(), &edges.0.to_dmatrix(), 1);
+
+                let colors_v1_tensor = DynamicTensorFloat2D::from_dmatrix(&colors_v1_mat);
+                let colors_v2_tensor = DynamicTensorFloat2D::from_dmatrix(&colors_v2_mat);
+
+                self.upload_dynamic_vertex_atrib(ent, &colors_v1_tensor, edges_colors_v1_gpu.as_deref_mut(), gpu, usage, "edges_colors_v1");
+                self.upload_dynamic_vertex_atrib(ent, &colors_v2_tensor, edges_colors_v2_gpu.as_deref_mut(), gpu, usage, "edges_colors_v2");
+            }
+        }
+        self.command_buffer.run_on(&mut scene.world);
+    }
+
     fn upload_f(&mut self, gpu: &Gpu, scene: &mut Scene) {
         let query = scene
             .world
@@ -256,11 +318,12 @@ impl UploadPass {
     fn upload_nv(&mut self, gpu: &Gpu, scene: &mut Scene) {
         let query = scene
             .world
-            .query_mut::<(&Normals, Option<&mut NormalsGPU>, Changed<Normals>)>()
+            .query_mut::<(&Normals, Option<&mut NormalsGPU>, Option<&DeformGPU>, Changed<Normals>)>()
             .with::<&Renderable>();
         let usage = wgpu::BufferUsages::VERTEX;
-        for (ent, (normals, mut normals_gpu, changed_normals)) in query {
-            if changed_normals {
+        for (ent, (normals, mut normals_gpu, deform_gpu, changed_normals)) in query {
+            //normals of deformed meshes are recomputed by the deform pass
+            if changed_normals && deform_gpu.map_or(true, |d| d.nr_faces == 0) {
                 self.upload_dynamic_vertex_atrib(ent, &normals.0, normals_gpu.as_deref_mut(), gpu, usage, "normals");
             }
         }
@@ -295,6 +358,86 @@ impl UploadPass {
         self.command_buffer.run_on(&mut scene.world);
     }
 
+    fn upload_s(&mut self, gpu: &Gpu, scene: &mut Scene) {
+        let mut new_entities = Vec::new();
+        let query = scene
+            .world
+            .query_mut::<(&Scalars, Option<&mut ScalarsGPU>, Changed<Scalars>)>()
+            .with::<&Renderable>();
+        let usage = wgpu::BufferUsages::VERTEX;
+        for (ent, (scalars, mut scalars_gpu, changed_scalars)) in query {
+            if changed_scalars {
+                if scalars_gpu.is_none() {
+                    new_entities.push(ent);
+                }
+                self.upload_dynamic_vertex_atrib(ent, &scalars.0, scalars_gpu.as_deref_mut(), gpu, usage, "scalars");
+            }
+        }
+        self.command_buffer.run_on(&mut scene.world);
+
+        //the first time we see the scalars we color the entity with them, unless the user chose the visualization options themselves
+        for entity in new_entities {
+            if let Ok(mut vis_mesh) = scene.get_comp::<&mut VisMesh>(&entity) {
+                if vis_mesh.added_automatically {
+                    vis_mesh.color_type = MeshColorType::Scalar;
+                }
+            }
+            if let Ok(mut vis_points) = scene.get_comp::<&mut VisPoints>(&entity) {
+                if vis_points.added_automatically {
+                    vis_points.color_type = PointColorType::Scalar;
+                }
+            }
+            if let Ok(mut vis_lines) = scene.get_comp::<&mut VisLines>(&entity) {
+                if vis_lines.added_automatically {
+                    vis_lines.color_type = LineColorType::Scalar;
+                }
+            }
+        }
+    }
+
+    fn upload_es(&mut self, gpu: &Gpu, scene: &mut Scene) {
+        let query = scene
+            .world
+            .query_mut::<(
+                &Scalars,
+                &Edges,
+                Option<&mut EdgesScalarsV1GPU>,
+                Option<&mut EdgesScalarsV2GPU>,
+                Changed<Scalars>,
+                Changed<Edges>,
+            )>()
+            .with::<&Renderable>();
+
+        let usage = wgpu::BufferUsages::VERTEX;
+        for (ent, (scalars, edges, mut edges_scalars_v1_gpu, mut edges_scalars_v2_gpu, changed_scalars, changed_edges)) in query {
+            if changed_scalars || changed_edges {
+                let scalars_v1_mat = index_vertices_from_edges(&scalars.0.to_dmatrix(), &edges.0.to_dmatrix(), 0);
+                let scalars_v2_mat = index_vertices_from_edges(&scalars.0.to_dmatrix(), &edges.0.to_dmatrix(), 1);
+
+                let scalars_v1_tensor = DynamicTensorFloat2D::from_dmatrix(&scalars_v1_mat);
+                let scalars_v2_tensor = DynamicTensorFloat2D::from_dmatrix(&scalars_v2_mat);
+
+                self.upload_dynamic_vertex_atrib(
+                    ent,
+                    &scalars_v1_tensor,
+                    edges_scalars_v1_gpu.as_deref_mut(),
+                    gpu,
+                    usage,
+                    "edges_scalars_v1",
+                );
+                self.upload_dynamic_vertex_atrib(
+                    ent,
+                    &scalars_v2_tensor,
+                    edges_scalars_v2_gpu.as_deref_mut(),
+                    gpu,
+                    usage,
+                    "edges_scalars_v2",
+                );
+            }
+        }
+        self.command_buffer.run_on(&mut scene.world);
+    }
+
     fn upload_diffuse_tex(&mut self, gpu: &Gpu, scene: &mut Scene) {
         let mut modified_entities = Vec::new();
         {
@@ -576,13 +719,14 @@ impl UploadPass {
 
     fn upload_scene(&mut self, gpu: &Gpu, scene: &mut Scene) {
         let entities_lights = scene.get_lights(false);
+        let nr_clip_planes = scene.world.query_mut::<&ClipPlane>().into_iter().filter(|(_, p)| p.enabled).count();
         let env_map = scene.get_resource::<&EnvironmentMapGpu>().unwrap();
         let environment_map_smallest_specular_mip_level = env_map.specular_tex.texture.mip_level_count() - 1;
 
         let per_frame_scene_data = PerFrameSceneCPU {
             nr_lights: u32::try_from(entities_lights.len()).unwrap(),
             environment_map_smallest_specular_mip_level,
-            pad_1: 0,
+            nr_clip_planes: u32::try_from(nr_clip_planes.min(MAX_NUM_CLIP_PLANES)).unwrap(),
             pad_2: 0,
         };
 
@@ -591,7 +735,15 @@ impl UploadPass {
         self.per_frame_uniforms.scene_buf.reset_chunks_offset();
     }
 
-    fn upload_cam(&mut self, gpu: &Gpu, camera: &Camera, scene: &mut Scene) {
+    /// Sets the offset in pixels, with y going down, by which the projection of
+    /// the camera is shifted from the next upload on
+    pub fn set_jitter(&mut self, jitter: na::Vector2<f32>) {
+        self.jitter = jitter;
+    }
+
+    /// Uploads only the camera. Useful for the passes after the main one which
+    /// need the camera without the jitter
+    pub fn upload_cam(&mut self, gpu: &Gpu, camera: &Camera, scene: &mut Scene) {
         let pos_lookat = if let Ok(pos_lookat) = scene.world.get::<&mut PosLookat>(camera.entity) {
             pos_lookat.clone()
         } else {
@@ -616,7 +768,15 @@ impl UploadPass {
             (near, far) = (proj.near, proj.far);
         }
         let (width, height) = camera.get_target_res(scene);
+        //cameras with lens distortion are rendered at a larger undistorted resolution
+        let (width, height) = camera.distorted_intrinsics(scene).map_or((width, height), |intrinsics| {
+            let bounds = camera.undistorted_bounds(&intrinsics, scene);
+            intrinsics.undistorted_res_for_bounds(&bounds.min, &bounds.max, gpu.limits().max_texture_dimension_2d)
+        });
         let aspect_ratio = width as f32 / height as f32;
+        //shift in ndc applied after the projection so it works for both perspective and orthographic cameras
+        let jitter_ndc = na::Vector3::new(2.0 * self.jitter.x / width as f32, -2.0 * self.jitter.y / height as f32, 0.0);
+        let proj_matrix = na::Matrix4::new_translation(&jitter_ndc) * proj_matrix;
         let proj_inv_matrix = proj_matrix.try_inverse().unwrap();
 
         let vp_matrix = proj_matrix * view_matrix;
@@ -652,6 +812,7 @@ impl UploadPass {
             let view_matrix = pos_lookat.view_matrix();
             let proj_matrix = match *proj {
                 Projection::WithFov(ref proj) => proj.proj_matrix_reverse_z(),
+                Projection::Orthographic(ref proj) => proj.proj_matrix_reverse_z(),
                 Projection::WithIntrinsics(_) => {
                     panic!("We don't deal with light that have projection as intrinsics")
                 }
@@ -733,6 +894,43 @@ impl UploadPass {
         self.per_frame_uniforms.lights_buf.reset_chunks_offset();
     }
 
+    fn upload_clip_planes(&mut self, gpu: &Gpu, scene: &mut Scene) {
+        let mut clip_planes: Vec<(Entity, ClipPlane)> = scene
+            .world
+            .query_mut::<&ClipPlane>()
+            .into_iter()
+            .filter(|(_, p)| p.enabled)
+            .map(|(entity, p)| (entity, p.clone()))
+            .collect();
+        if clip_planes.is_empty() {
+            return;
+        }
+        //sort so that the same planes are kept every frame when there are too many of them
+        clip_planes.sort_by_key(|(entity, _)| *entity);
+        if clip_planes.len() > MAX_NUM_CLIP_PLANES {
+            warn!(
+                "there are {} enabled clip planes but only the first {MAX_NUM_CLIP_PLANES} are used",
+                clip_planes.len()
+            );
+        }
+
+        for (_, clip_plane) in clip_planes.iter().take(MAX_NUM_CLIP_PLANES) {
+            let per_frame_clip_plane_data = PerFrameClipPlaneCPU {
+                plane: clip_plane.equation(),
+                cap_color: clip_plane.cap_color,
+                cap: u32::from(clip_plane.cap),
+                pad_b: 0,
+                pad_c: 0,
+                pad_d: 0,
+            };
+            //push packed because we will expose it as an array inside the shader
+            self.per_frame_uniforms.clip_planes_buf.push_cpu_chunk_packed(&per_frame_clip_plane_data);
+        }
+
+        self.per_frame_uniforms.clip_planes_buf.upload_from_cpu_chunks(gpu.queue());
+        self.per_frame_uniforms.clip_planes_buf.reset_chunks_offset();
+    }
+
     fn upload_params(&mut self, gpu: &Gpu, _scene: &mut Scene, render_params: &RenderConfig) {
         let per_frame_params_data = PerFrameParamsCPU {
             ambient_factor: render_params.ambient_factor,
@@ -763,8 +961,8 @@ impl UploadPass {
 struct PerFrameSceneCPU {
     nr_lights: u32,
     environment_map_smallest_specular_mip_level: u32,
+    nr_clip_planes: u32,
     //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
-    pad_1: u32,
     pad_2: u32,
 }
 /// Contains camera data that will be sent to the GPU once a frame.
@@ -835,15 +1033,29 @@ struct PerFrameParamsCPU {
     pad_c: f32,
     pad_d: f32,
 }
+/// Contains the data of one clip plane that will be sent to the GPU once a
+/// frame.
+#[repr(C)]
+#[derive(Clone, Copy, encase::ShaderType)]
+struct PerFrameClipPlaneCPU {
+    plane: na::Vector4<f32>, //normal in xyz and offset in w
+    cap_color: na::Vector4<f32>,
+    cap: u32, //should be bool but that is not host-sharable: https://www.w3.org/TR/WGSL/#host-shareable-types
+    //wasm needs padding to 16 bytes https://github.com/gfx-rs/wgpu/issues/2932
+    pad_b: u32,
+    pad_c: u32,
+    pad_d: u32,
+}
 
 /// All the buffers that are the same for all meshes. Contains things like
 /// camera parameters, lights, and global setting.
 #[non_exhaustive]
 pub struct PerFrameUniforms {
-    scene_buf: Buffer,  //group 0, binding 0
-    cam_buf: Buffer,    //group 0, binding 1
-    lights_buf: Buffer, //group 0, binding 2
-    params_buf: Buffer, //group 0, binding 3
+    scene_buf: Buffer,       //group 0, binding 0
+    cam_buf: Buffer,         //group 0, binding 1
+    lights_buf: Buffer,      //group 0, binding 2
+    params_buf: Buffer,      //group 0, binding 3
+    clip_planes_buf: Buffer, //group 0, binding 7
 
     #[allow(dead_code)]
     //storing the samplers is not needed since the bind group consumes them but it makes things more explicit
@@ -888,6 +1100,13 @@ impl PerFrameUniforms {
             Some("global_params_uniform"),
             align_usz(std::mem::size_of::<PerFrameParamsCPU>(), 256),
         );
+        //allocate space for MAX_NUM_CLIP_PLANES clip planes
+        let clip_planes_buf = Buffer::new_empty(
+            gpu.device(),
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            Some("global_clip_planes_uniform"),
+            align_usz(MAX_NUM_CLIP_PLANES * std::mem::size_of::<PerFrameClipPlaneCPU>(), 256),
+        );
 
         //samplers for nearest and linear
         let sampler_nearest = gpu.device().create_sampler(&wgpu::SamplerDescriptor {
@@ -928,6 +1147,7 @@ impl PerFrameUniforms {
             .add_entry_sampler(&sampler_nearest)
             .add_entry_sampler(&sampler_linear)
             .add_entry_sampler(&sampler_comparison)
+            .add_entry_buf(&clip_planes_buf.buffer)
             .build_bind_group(gpu.device(), &layout);
 
         Self {
@@ -938,6 +1158,7 @@ impl PerFrameUniforms {
             sampler_nearest,
             sampler_linear,
             sampler_comparison,
+            clip_planes_buf,
             bind_group,
             light2idx_ubo: HashMap::new(),
             idx_ubo2light: Vec::new(),
@@ -984,6 +1205,15 @@ impl PerFrameUniforms {
             .add_entry_sampler(wgpu::ShaderStages::FRAGMENT, wgpu::SamplerBindingType::NonFiltering)
             .add_entry_sampler(wgpu::ShaderStages::FRAGMENT, wgpu::SamplerBindingType::Filtering)
             .add_entry_sampler(wgpu::ShaderStages::FRAGMENT, wgpu::SamplerBindingType::Comparison)
+            //clip planes
+            .add_entry_uniform(
+                wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                false,
+                wgpu::BufferSize::new(u64::from(align(
+                    u32::try_from(MAX_NUM_CLIP_PLANES * std::mem::size_of::<PerFrameClipPlaneCPU>()).unwrap(),
+                    256,
+                ))),
+            )
             .build()
     }
 }
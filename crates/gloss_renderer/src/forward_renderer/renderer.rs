@@ -1,9 +1,21 @@
+use std::collections::HashMap;
+
 use crate::{
-    components::ConfigChanges,
+    components::{ConfigChanges, TargetResolution, ViewOverrides, Viewport},
     config::{Config, RenderConfig},
 };
 
-use crate::forward_renderer::render_passes::{prepass::PrePass, shadow_pass::ShadowPass, upload_pass::UploadPass};
+use crate::forward_renderer::render_passes::{
+    antialiasing_pass::AntiAliasingPass,
+    aov_pass::{Aov, AovPass},
+    blit_pass::BlitPass,
+    custom_pipeline::{CustomPipelineRunner, PipelineTargets},
+    distortion_pass::DistortionPass,
+    outline_pass::OutlinePass,
+    prepass::PrePass,
+    shadow_pass::ShadowPass,
+    upload_pass::UploadPass,
+};
 
 use crate::{camera::Camera, scene::Scene};
 
@@ -14,7 +26,9 @@ use easy_wgpu::{
 };
 
 use enum_map::Enum;
+use gloss_hecs::Entity;
 use log::debug;
+use nalgebra as na;
 
 use super::main_pass::MainPass;
 
@@ -24,6 +38,7 @@ pub enum OffscreenTarget {
     Color,     //for drawing to offscreen
     MSAAColor, //useful for drawing during MSAA and then resolving to another view
     Depth,
+    AntiAliasingInput, //the main pass renders here when there is post process anti-aliasing
 }
 
 ///  Contains long-living objects that will stay alive for the whole duration of
@@ -35,10 +50,11 @@ impl RenderData {
     pub fn new(gpu: &Gpu, params: &RenderConfig, surface_format: Option<wgpu::TextureFormat>) -> Self {
         //create long-living objects
         let frambuffer_builder = FrameBufferBuilder::<OffscreenTarget>::new(128, 128);
+        //texture_binding because the temporal anti-aliasing reprojects using the depth
         let depth_texture_usage = if cfg!(target_arch = "wasm32") {
-            wgpu::TextureUsages::RENDER_ATTACHMENT
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
         } else {
-            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC
             // because for headless rendering we want to download it to cpu
         };
 
@@ -52,8 +68,9 @@ impl RenderData {
                 gpu.device(),
                 OffscreenTarget::Color,
                 surface_format.unwrap_or(offscreen_color_format),
-                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC, /* because for headless rendering we want to download it
-                                                                                         * to cpu */
+                //copy_src because for headless rendering and recording we want to download it to cpu, texture_binding so that we can blit it to
+                // screen
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::TEXTURE_BINDING,
                 TexParams::default(),
             )
             .add_render_target(
@@ -76,39 +93,144 @@ impl RenderData {
                     ..Default::default()
                 },
             )
+            .add_render_target(
+                gpu.device(),
+                OffscreenTarget::AntiAliasingInput,
+                surface_format.unwrap_or(offscreen_color_format),
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                TexParams::default(),
+            )
             .build(gpu.device());
 
         Self { framebuffer }
     }
 }
 
+/// Offscreen targets of a camera which is rendered as a separate view. Each
+/// view has its own blit pass so that the bind group towards its texture
+/// doesn't need to be recreated every time we compose the views. Likewise for
+/// the distortion pass of views whose camera has lens distortion.
+pub struct ViewData {
+    pub data: RenderData,
+    compose_pass: BlitPass,                  //copies the view into its viewport of the final output
+    distortion_pass: Option<DistortionPass>, //created the first time the camera of the view has lens distortion
+}
+impl ViewData {
+    pub fn new(gpu: &Gpu, params: &RenderConfig, surface_format: Option<wgpu::TextureFormat>, out_format: wgpu::TextureFormat) -> Self {
+        let data = RenderData::new(gpu, params, surface_format);
+        let compose_pass = BlitPass::new(gpu, &out_format);
+        Self {
+            data,
+            compose_pass,
+            distortion_pass: None,
+        }
+    }
+
+    fn resize_if_necesary(&mut self, width: u32, height: u32, gpu: &Gpu) {
+        if self.data.framebuffer.width != width || self.data.framebuffer.height != height {
+            debug!("resizing view framebuffer to {width}, {height}");
+            self.data.framebuffer.resize(gpu.device(), width, height);
+        }
+    }
+}
+
 pub struct RenderPasses {
     pub upload_pass: UploadPass, //uploads from CPU to GPU everything that we need globally like settings, camera parameters, lights, etc.
     shadow_pass: ShadowPass,     //renders depth maps towards all lights
     main_pass: MainPass,
+    pub antialiasing_pass: AntiAliasingPass, //fxaa or taa after the main pass, also accumulates jittered frames for supersampling
+    aov_pass: AovPass,                       //renders the meshes again towards the enabled aov targets
+    outline_pass: OutlinePass,               //draws the outline of the highlighted entities on top of the output
 }
 impl RenderPasses {
     pub fn new(gpu: &Gpu, params: &RenderConfig, color_target_format: wgpu::TextureFormat, depth_target_format: wgpu::TextureFormat) -> Self {
         let upload_pass = UploadPass::new(gpu, params);
         let shadow_pass = ShadowPass::new(gpu);
         let main_pass = MainPass::new(gpu, params, color_target_format, depth_target_format);
+        let antialiasing_pass = AntiAliasingPass::new(gpu, params, color_target_format);
+        let aov_pass = AovPass::new(gpu);
+        let outline_pass = OutlinePass::new(gpu, color_target_format);
         Self {
             upload_pass,
             shadow_pass,
             main_pass,
+            antialiasing_pass,
+            aov_pass,
+            outline_pass,
         }
     }
 
     #[allow(clippy::too_many_arguments)]
     pub fn run(&mut self, out_view: &wgpu::TextureView, data: &RenderData, gpu: &Gpu, camera: &mut Camera, scene: &mut Scene, config: &mut Config) {
+        //the anti-aliasing is only done for the main camera since the history of taa belongs to a single camera
+        self.upload_pass.set_jitter(self.antialiasing_pass.jitter(&config.render));
+        if self.antialiasing_pass.is_enabled(&config.render) {
+            let input_view = &data.framebuffer.get(OffscreenTarget::AntiAliasingInput).unwrap().view;
+            self.run_with_params(input_view, data, gpu, camera, scene, &config.render, true);
+            self.antialiasing_pass
+                .run(gpu, camera, scene, &config.render, &data.framebuffer, out_view);
+
+            //the passes after this one draw on top of the anti-aliased image so they need the camera without the jitter
+            self.upload_pass.set_jitter(na::Vector2::zeros());
+            self.upload_pass.upload_cam(gpu, camera, scene);
+        } else {
+            self.run_with_params(out_view, data, gpu, camera, scene, &config.render, true);
+        }
+
+        //the aovs are only rendered for the main camera and not for the views
+        self.aov_pass.run(gpu, self.upload_pass.per_frame_uniforms(), camera, scene, &config.aov);
+
+        //same for the outlines of the highlighted entities
+        let (width, height) = (data.framebuffer.width, data.framebuffer.height);
+        self.outline_pass
+            .run(gpu, self.upload_pass.per_frame_uniforms(), scene, out_view, width, height);
+    }
+
+    /// Adds a user pipeline which is drawn in the main pass together with the
+    /// meshes, points and lines. See [`CustomPipelineRunner`]
+    pub fn add_pipeline_runner(&mut self, runner: Box<dyn CustomPipelineRunner>) {
+        self.main_pass.add_pipeline_runner(runner);
+    }
+
+    pub fn remove_pipeline_runner(&mut self, name: &str) -> Option<Box<dyn CustomPipelineRunner>> {
+        self.main_pass.remove_pipeline_runner(name)
+    }
+
+    pub fn pipeline_runner_names(&self) -> Vec<String> {
+        self.main_pass.pipeline_runner_names()
+    }
+
+    /// Formats that the pipelines of a [`CustomPipelineRunner`] have to be
+    /// created with
+    pub fn pipeline_targets(&self) -> PipelineTargets {
+        self.main_pass.pipeline_targets()
+    }
+
+    /// Same as [`RenderPasses::run`] but with explicit render params, which
+    /// allows for per-view overrides. The shadow maps don't depend on the
+    /// camera so `run_shadows` can be set to false if they were already
+    /// rendered in this frame.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run_with_params(
+        &mut self,
+        out_view: &wgpu::TextureView,
+        data: &RenderData,
+        gpu: &Gpu,
+        camera: &mut Camera,
+        scene: &mut Scene,
+        render_params: &RenderConfig,
+        run_shadows: bool,
+    ) {
         //update ubos
-        let global_uniforms = self.upload_pass.run(gpu, camera, scene, &config.render);
+        let global_uniforms = self.upload_pass.run(gpu, camera, scene, render_params);
 
         //render all the geoemtry towards shadow maps
-        self.shadow_pass.run(gpu, global_uniforms, scene);
+        if run_shadows {
+            self.shadow_pass.run(gpu, global_uniforms, scene);
+        }
 
         self.main_pass
-            .run(gpu, global_uniforms, &data.framebuffer, out_view, scene, &config.render);
+            .run(gpu, global_uniforms, &data.framebuffer, out_view, scene, render_params);
     }
 }
 
@@ -123,6 +245,11 @@ pub struct Renderer {
     //passes
     prepass: PrePass,
     pub passes: RenderPasses,
+    //cameras with a Viewport are rendered each to their own targets and then composed together
+    views: HashMap<Entity, ViewData>,
+    surface_format: Option<wgpu::TextureFormat>,
+    //cameras with lens distortion are rendered to an undistorted target and then warped
+    distortion_pass: DistortionPass,
 }
 
 impl Renderer {
@@ -139,8 +266,16 @@ impl Renderer {
         let depth_target_format = data.framebuffer.get(OffscreenTarget::Depth).unwrap().texture.format();
 
         let passes = RenderPasses::new(gpu, params, color_target_format, depth_target_format);
+        let distortion_pass = DistortionPass::new(gpu, params, surface_format, color_target_format);
 
-        Self { data, prepass, passes }
+        Self {
+            data,
+            prepass,
+            passes,
+            views: HashMap::new(),
+            surface_format,
+            distortion_pass,
+        }
     }
 
     /// Calls the full rendering functionality and writes the final image to a
@@ -161,7 +296,16 @@ impl Renderer {
     ) {
         self.begin_frame(gpu, camera, scene, config);
 
-        self.passes.run(out_view, &self.data, gpu, camera, scene, config);
+        Self::run_passes(
+            &mut self.passes,
+            &mut self.distortion_pass,
+            out_view,
+            &self.data,
+            gpu,
+            camera,
+            scene,
+            config,
+        );
 
         self.end_frame(scene);
     }
@@ -188,11 +332,176 @@ impl Renderer {
         self.begin_frame(gpu, camera, scene, config);
 
         let out_view = &self.data.framebuffer.get(OffscreenTarget::Color).unwrap().view;
-        self.passes.run(out_view, &self.data, gpu, camera, scene, config);
+        Self::run_passes(
+            &mut self.passes,
+            &mut self.distortion_pass,
+            out_view,
+            &self.data,
+            gpu,
+            camera,
+            scene,
+            config,
+        );
+
+        self.end_frame(scene);
+    }
+
+    /// Renders every camera that has a [`Viewport`] and composes them into
+    /// `out_view`. The `camera` is the main camera whose target resolution
+    /// defines the size of the output. Cameras can have a [`ViewOverrides`]
+    /// to change the background or to draw only the wireframe.
+    ///
+    /// # Panics
+    /// This function may panic if the GPU or camera is not available.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_views_to_view(
+        &mut self,
+        out_view: &wgpu::TextureView,
+        gpu: &Gpu,
+        camera: &mut Camera,
+        scene: &mut Scene,
+        config: &mut Config,
+        _dt: core::time::Duration,
+    ) {
+        self.begin_frame(gpu, camera, scene, config);
+
+        let (out_width, out_height) = camera.get_target_res(scene);
+        let views = self.render_views(gpu, scene, config, out_width, out_height);
+        Self::compose_views(&mut self.views, &views, gpu, scene, out_view, out_width, out_height);
+
+        self.end_frame(scene);
+    }
+
+    /// Same as [`Renderer::render_views_to_view`] but composes the views into
+    /// the internal texture which can be recovered using
+    /// [`Renderer::rendered_tex`]. The texture of each view can be recovered
+    /// with [`Renderer::view_tex`].
+    ///
+    /// # Panics
+    /// This function may panic if the GPU or camera is not available.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_views_to_texture(&mut self, gpu: &Gpu, camera: &mut Camera, scene: &mut Scene, config: &mut Config, _dt: core::time::Duration) {
+        self.begin_frame(gpu, camera, scene, config);
+
+        let (out_width, out_height) = camera.get_target_res(scene);
+        let views = self.render_views(gpu, scene, config, out_width, out_height);
+        let out_view = &self.data.framebuffer.get(OffscreenTarget::Color).unwrap().view;
+        Self::compose_views(&mut self.views, &views, gpu, scene, out_view, out_width, out_height);
 
         self.end_frame(scene);
     }
 
+    /// Runs all the passes towards `out_view`. Cameras with lens distortion
+    /// are first rendered at a larger undistorted resolution which then gets
+    /// warped towards `out_view`, together with their depth towards the depth
+    /// of `data`.
+    #[allow(clippy::too_many_arguments)]
+    fn run_passes(
+        passes: &mut RenderPasses,
+        distortion_pass: &mut DistortionPass,
+        out_view: &wgpu::TextureView,
+        data: &RenderData,
+        gpu: &Gpu,
+        camera: &mut Camera,
+        scene: &mut Scene,
+        config: &mut Config,
+    ) {
+        let Some(intrinsics) = camera.distorted_intrinsics(scene) else {
+            passes.run(out_view, data, gpu, camera, scene, config);
+            return;
+        };
+
+        let (width, height) = camera.get_target_res(scene);
+        let bounds = camera.undistorted_bounds(&intrinsics, scene);
+        distortion_pass.prepare(gpu, &config.render, &intrinsics, &bounds, width, height);
+        let undistorted = distortion_pass.undistorted_data();
+        let undistorted_view = &undistorted.framebuffer.get(OffscreenTarget::Color).unwrap().view;
+        passes.run(undistorted_view, undistorted, gpu, camera, scene, config);
+        let out_depth_view = &data.framebuffer.get(OffscreenTarget::Depth).unwrap().view;
+        distortion_pass.run(gpu, out_view, out_depth_view);
+    }
+
+    /// Renders each view towards its own framebuffer. Returns the views in
+    /// the order in which they should be composed. Views whose camera has lens
+    /// distortion are warped with their own [`DistortionPass`], so they match
+    /// [`Camera::project`] just like the main camera.
+    fn render_views(&mut self, gpu: &Gpu, scene: &mut Scene, config: &Config, out_width: u32, out_height: u32) -> Vec<Entity> {
+        let views = scene.get_views(true);
+
+        //forget the framebuffers of cameras that are not views anymore
+        self.views.retain(|entity, _| views.contains(entity));
+
+        let out_format = self.data.framebuffer.get(OffscreenTarget::Color).unwrap().texture.format();
+        for (idx, entity) in views.iter().enumerate() {
+            let viewport = *scene.get_comp::<&Viewport>(entity).unwrap();
+            let (_, _, width, height) = viewport.to_pixels(out_width, out_height);
+
+            //the camera of each view renders at the size of its viewport
+            scene.insert_if_doesnt_exist::<TargetResolution>(*entity);
+            let mut view_cam = Camera::from_entity(*entity);
+            view_cam.set_target_res(width, height, scene);
+            scene.update_undistorted_bounds();
+
+            let mut render_params = config.render.clone();
+            if let Ok(overrides) = scene.get_comp::<&ViewOverrides>(entity) {
+                if let Some(bg_color) = overrides.bg_color {
+                    render_params.bg_color = bg_color;
+                }
+                render_params.wireframe_only |= overrides.wireframe_only;
+            }
+
+            let view = self
+                .views
+                .entry(*entity)
+                .or_insert_with(|| ViewData::new(gpu, &config.render, self.surface_format, out_format));
+            view.resize_if_necesary(width, height, gpu);
+
+            //views with lens distortion are warped in the same way as the main camera
+            let view_out = &view.data.framebuffer.get(OffscreenTarget::Color).unwrap().view;
+            if let Some(intrinsics) = view_cam.distorted_intrinsics(scene) {
+                let color_format = view.data.framebuffer.get(OffscreenTarget::Color).unwrap().texture.format();
+                let surface_format = self.surface_format;
+                let distortion_pass = view
+                    .distortion_pass
+                    .get_or_insert_with(|| DistortionPass::new(gpu, &config.render, surface_format, color_format));
+                let bounds = view_cam.undistorted_bounds(&intrinsics, scene);
+                distortion_pass.prepare(gpu, &render_params, &intrinsics, &bounds, width, height);
+                let undistorted = distortion_pass.undistorted_data();
+                let undistorted_view = &undistorted.framebuffer.get(OffscreenTarget::Color).unwrap().view;
+                self.passes
+                    .run_with_params(undistorted_view, undistorted, gpu, &mut view_cam, scene, &render_params, idx == 0);
+                let view_depth = &view.data.framebuffer.get(OffscreenTarget::Depth).unwrap().view;
+                distortion_pass.run(gpu, view_out, view_depth);
+            } else {
+                self.passes
+                    .run_with_params(view_out, &view.data, gpu, &mut view_cam, scene, &render_params, idx == 0);
+            }
+        }
+
+        views
+    }
+
+    fn compose_views(
+        views_data: &mut HashMap<Entity, ViewData>,
+        views: &[Entity],
+        gpu: &Gpu,
+        scene: &Scene,
+        out_view: &wgpu::TextureView,
+        out_width: u32,
+        out_height: u32,
+    ) {
+        for (idx, entity) in views.iter().enumerate() {
+            let Some(view) = views_data.get_mut(entity) else {
+                continue;
+            };
+            let viewport = *scene.get_comp::<&Viewport>(entity).unwrap();
+            let rect = viewport.to_pixels(out_width, out_height);
+            //the first view clears the output so that the regions not covered by any viewport don't contain stale data
+            let src_tex = view.data.framebuffer.get(OffscreenTarget::Color).unwrap();
+            view.compose_pass.run_to_viewport(gpu, src_tex, out_view, rect, idx == 0);
+        }
+    }
+
     fn prepare_for_rendering(&mut self, gpu: &Gpu, camera: &mut Camera, scene: &mut Scene, config: &mut Config) {
         //modify config if needed
         if let Ok(delta) = scene.get_resource::<&ConfigChanges>() {
@@ -212,7 +521,9 @@ impl Renderer {
 
     fn end_frame(&self, scene: &mut Scene) {
         //if we do manual ecs without the bevy system, we need to call clear trackers
-        // so that the changed flag gets cleared for the next frame
+        // so that the changed flag gets cleared for the next frame. The bvhs are
+        // only updated when queried so they need to remember the changes
+        scene.mark_changed_bvhs();
         scene.world.clear_trackers();
     }
 
@@ -234,6 +545,19 @@ impl Renderer {
     pub fn depth_buffer(&self) -> &Texture {
         self.data.framebuffer.get(OffscreenTarget::Depth).unwrap()
     }
+    /// Texture of an [`Aov`] rendered together with the final image. Returns
+    /// None if the aov is not enabled in the [`AovConfig`](crate::config::AovConfig)
+    /// or if the camera has lens distortion.
+    pub fn aov_tex(&self, aov: Aov) -> Option<&Texture> {
+        self.passes.aov_pass.tex(aov)
+    }
+    /// Texture of a camera that was rendered as a view in
+    /// [`Renderer::render_views_to_view`] or
+    /// [`Renderer::render_views_to_texture`]. Returns None if the camera is not
+    /// a view.
+    pub fn view_tex(&self, entity: Entity) -> Option<&Texture> {
+        self.views.get(&entity).and_then(|view| view.data.framebuffer.get(OffscreenTarget::Color))
+    }
 
     fn resize_if_necesary(&mut self, width: u32, height: u32, gpu: &Gpu) {
         if self.data.framebuffer.width != width || self.data.framebuffer.height != height {
@@ -1,8 +1,13 @@
 #![allow(clippy::missing_panics_doc)] //a lot of operations require inserting or removing component from a entity but
                                       // the entity will for sure exists so it will never panic
 
-use crate::components::{Colors, Edges};
-use gloss_hecs::EntityBuilder;
+use crate::{
+    bvh::Bvh,
+    components::{Colors, Edges, LineColorType, Tangents, VisLines},
+    kdtree::KdTree,
+    scene::Scene,
+};
+use gloss_hecs::{Entity, EntityBuilder};
 use gloss_img::DynImage;
 use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D, DynamicTensorInt2D};
 use image::{GenericImageView, Pixel};
@@ -18,7 +23,11 @@ use itertools::izip;
 use log::{error, info, warn};
 use na::DMatrix;
 use obj_exporter::{Geometry, ObjSet, Object, Primitive, Shape, TVertex, Vertex};
-use std::{ops::AddAssign, path::Path};
+use std::{
+    collections::{BinaryHeap, HashMap, HashSet},
+    ops::AddAssign,
+    path::Path,
+};
 use tobj;
 
 use ply_rs::{
@@ -45,12 +54,60 @@ pub enum SplatType {
     Sum,
 }
 
+/// Neighbourhood of every point used when processing point clouds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointNeighbourhood {
+    /// The k nearest points
+    Knn(usize),
+    /// All points within a radius
+    Radius(f32),
+}
+
 #[derive(PartialEq)]
 pub enum IndirRemovalPolicy {
     RemoveInvalidRows,
     RemoveInvalidCols,
 }
 
+/// Point of a profile which is revolved around the Y axis to create the
+/// primitives. The normal is given in the plane of the profile as (radial, y)
+struct ProfilePoint {
+    radius: f32,
+    y: f32,
+    normal: na::Vector2<f32>,
+}
+impl ProfilePoint {
+    fn new(radius: f32, y: f32, normal_radial: f32, normal_y: f32) -> Self {
+        Self {
+            radius,
+            y,
+            normal: na::Vector2::new(normal_radial, normal_y),
+        }
+    }
+
+    fn distance(&self, other: &Self) -> f32 {
+        na::Vector2::new(self.radius - other.radius, self.y - other.y).norm()
+    }
+
+    /// Profile of a cone cut at both ends, optionally closed by discs at the
+    /// ends with a non zero radius
+    fn truncated_cone(radius_bottom: f32, radius_top: f32, y_bottom: f32, y_top: f32, capped: bool) -> Vec<Self> {
+        let side = na::Vector2::new(y_top - y_bottom, radius_bottom - radius_top);
+        let mut profile = Vec::new();
+        if capped && radius_top > 0.0 {
+            profile.push(Self::new(0.0, y_top, 0.0, 1.0));
+            profile.push(Self::new(radius_top, y_top, 0.0, 1.0));
+        }
+        profile.push(Self::new(radius_top, y_top, side.x, side.y));
+        profile.push(Self::new(radius_bottom, y_bottom, side.x, side.y));
+        if capped && radius_bottom > 0.0 {
+            profile.push(Self::new(radius_bottom, y_bottom, 0.0, -1.0));
+            profile.push(Self::new(0.0, y_bottom, 0.0, -1.0));
+        }
+        profile
+    }
+}
+
 /// Geom contains functionality related to mesh-like entities, like reading from
 /// files, or fixing and processing mesh data. Contains mostly static functions
 /// so that in the future this class can be broken into it's own crate for
@@ -383,6 +440,405 @@ impl Geom {
         builder
     }
 
+    /// Creates a UV sphere made of `nr_rings` rings from pole to pole and
+    /// `nr_segments` segments around the Y axis. The mesh has normals, UVs and
+    /// tangents where U goes around the Y axis and V goes from the bottom pole
+    /// to the top one
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn build_uv_sphere(center: na::Point3<f32>, radius: f32, nr_rings: u32, nr_segments: u32) -> EntityBuilder {
+        let nr_rings = nr_rings.max(2);
+        let profile: Vec<ProfilePoint> = (0..=nr_rings)
+            .map(|i| {
+                let theta = f32::consts::PI * i as f32 / nr_rings as f32;
+                ProfilePoint::new(radius * theta.sin(), radius * theta.cos(), theta.sin(), theta.cos())
+            })
+            .collect();
+        Self::build_lathe(&profile, nr_segments, Self::translation_matrix(center))
+    }
+
+    /// Creates an icosphere by subdividing an icosahedron `nr_subdivisions`
+    /// times. The triangles are more uniform than the ones of
+    /// [`Geom::build_uv_sphere`]. UVs use the same spherical mapping as the UV
+    /// sphere with the vertices along the seam and at the poles duplicated
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::many_single_char_names)]
+    #[allow(clippy::too_many_lines)]
+    pub fn build_ico_sphere(center: na::Point3<f32>, radius: f32, nr_subdivisions: u32) -> EntityBuilder {
+        let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+        let mut dirs: Vec<na::Vector3<f32>> = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .iter()
+        .map(|v| na::Vector3::from(*v).normalize())
+        .collect();
+        let mut faces: Vec<[u32; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        //each subdivision splits a triangle in 4 and pushes the new vertices onto the sphere
+        for _ in 0..nr_subdivisions {
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, dirs: &mut Vec<na::Vector3<f32>>| -> u32 {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    dirs.push((dirs[a as usize] + dirs[b as usize]).normalize());
+                    (dirs.len() - 1) as u32
+                })
+            };
+            let mut new_faces = Vec::with_capacity(faces.len() * 4);
+            for [a, b, c] in faces {
+                let ab = midpoint(a, b, &mut dirs);
+                let bc = midpoint(b, c, &mut dirs);
+                let ca = midpoint(c, a, &mut dirs);
+                new_faces.extend([[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+            }
+            faces = new_faces;
+        }
+
+        let mut uvs: Vec<na::Vector2<f32>> = dirs.iter().map(Self::sphere_uv).collect();
+        let is_pole = |dir: &na::Vector3<f32>| dir.x.abs() < 1e-6 && dir.z.abs() < 1e-6;
+
+        //triangles crossing the seam would interpolate U over the whole texture so we
+        // duplicate their vertices that are on the low side of the seam
+        let mut seam_duplicates: HashMap<u32, u32> = HashMap::new();
+        for face in &mut faces {
+            let us: Vec<f32> = face
+                .iter()
+                .filter(|idx| !is_pole(&dirs[**idx as usize]))
+                .map(|idx| uvs[*idx as usize].x)
+                .collect();
+            let spread = us.iter().fold(0.0_f32, |acc, u| acc.max(*u)) - us.iter().fold(1.0_f32, |acc, u| acc.min(*u));
+            if spread < 0.5 {
+                continue;
+            }
+            for idx in face.iter_mut() {
+                if uvs[*idx as usize].x < 0.5 && !is_pole(&dirs[*idx as usize]) {
+                    *idx = *seam_duplicates.entry(*idx).or_insert_with(|| {
+                        dirs.push(dirs[*idx as usize]);
+                        uvs.push(uvs[*idx as usize] + na::Vector2::new(1.0, 0.0));
+                        (dirs.len() - 1) as u32
+                    });
+                }
+            }
+        }
+
+        //U is not defined at the poles so every triangle touching them gets its own
+        // pole vertex with the U of the rest of the triangle
+        for face in &mut faces {
+            for corner in 0..3 {
+                let idx = face[corner] as usize;
+                if !is_pole(&dirs[idx]) {
+                    continue;
+                }
+                let u = (uvs[face[(corner + 1) % 3] as usize].x + uvs[face[(corner + 2) % 3] as usize].x) / 2.0;
+                dirs.push(dirs[idx]);
+                uvs.push(na::Vector2::new(u, uvs[idx].y));
+                face[corner] = (dirs.len() - 1) as u32;
+            }
+        }
+
+        let verts: Vec<na::Vector3<f32>> = dirs.iter().map(|d| d * radius).collect();
+        let tangents: Vec<na::Vector4<f32>> = dirs
+            .iter()
+            .map(|d| {
+                //direction of increasing U which is the same as for the uv sphere
+                let t = na::Vector3::new(d.z, 0.0, -d.x).try_normalize(1e-6).unwrap_or(na::Vector3::x());
+                na::Vector4::new(t.x, t.y, t.z, 1.0)
+            })
+            .collect();
+
+        Self::build_from_surface(&verts, &faces, &dirs, &uvs, &tangents, Self::translation_matrix(center))
+    }
+
+    /// Creates a cylinder with the axis along Y which is centered at `center`.
+    /// If `capped` is true, the top and bottom discs are also added
+    #[must_use]
+    pub fn build_cylinder(center: na::Point3<f32>, radius: f32, height: f32, nr_segments: u32, capped: bool) -> EntityBuilder {
+        let profile = ProfilePoint::truncated_cone(radius, radius, -height / 2.0, height / 2.0, capped);
+        Self::build_lathe(&profile, nr_segments, Self::translation_matrix(center))
+    }
+
+    /// Creates a cone with the axis along Y which is centered at `center`. The
+    /// base is at the bottom and the apex at the top. If `capped` is true, the
+    /// base disc is also added
+    #[must_use]
+    pub fn build_cone(center: na::Point3<f32>, radius: f32, height: f32, nr_segments: u32, capped: bool) -> EntityBuilder {
+        let profile = ProfilePoint::truncated_cone(radius, 0.0, -height / 2.0, height / 2.0, capped);
+        Self::build_lathe(&profile, nr_segments, Self::translation_matrix(center))
+    }
+
+    /// Creates a capsule with the axis along Y which is centered at `center`.
+    /// The `height` is the total height including the two hemispheres so it
+    /// is at least `2*radius`. Each hemisphere is made of `nr_rings` rings
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn build_capsule(center: na::Point3<f32>, radius: f32, height: f32, nr_rings: u32, nr_segments: u32) -> EntityBuilder {
+        let nr_rings = nr_rings.max(1);
+        let half_cylinder = (height / 2.0 - radius).max(0.0);
+        let hemisphere = |offset_y: f32, theta_start: f32| {
+            (0..=nr_rings).map(move |i| {
+                let theta = theta_start + f32::consts::FRAC_PI_2 * i as f32 / nr_rings as f32;
+                ProfilePoint::new(radius * theta.sin(), offset_y + radius * theta.cos(), theta.sin(), theta.cos())
+            })
+        };
+        //the segment between the two equators is the cylindrical part
+        let profile: Vec<ProfilePoint> = hemisphere(half_cylinder, 0.0)
+            .chain(hemisphere(-half_cylinder, f32::consts::FRAC_PI_2))
+            .collect();
+        Self::build_lathe(&profile, nr_segments, Self::translation_matrix(center))
+    }
+
+    /// Creates a torus lying in the XZ plane. The `major_radius` is the
+    /// distance from the center to the middle of the tube and the
+    /// `minor_radius` is the radius of the tube. The tube is made of `nr_rings`
+    /// rings
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn build_torus(center: na::Point3<f32>, major_radius: f32, minor_radius: f32, nr_rings: u32, nr_segments: u32) -> EntityBuilder {
+        let nr_rings = nr_rings.max(3);
+        //starts at the top of the tube and goes outwards so that the faces point outside
+        let profile: Vec<ProfilePoint> = (0..=nr_rings)
+            .map(|i| {
+                let alpha = f32::consts::FRAC_PI_2 - 2.0 * f32::consts::PI * i as f32 / nr_rings as f32;
+                ProfilePoint::new(
+                    major_radius + minor_radius * alpha.cos(),
+                    minor_radius * alpha.sin(),
+                    alpha.cos(),
+                    alpha.sin(),
+                )
+            })
+            .collect();
+        Self::build_lathe(&profile, nr_segments, Self::translation_matrix(center))
+    }
+
+    /// Creates an arrow going from `start` to `end` made of a cylindrical shaft
+    /// and a conical head. The head length is capped to the length of the
+    /// arrow. The mesh is built along Y and oriented with the model matrix
+    #[must_use]
+    pub fn build_arrow(
+        start: na::Point3<f32>,
+        end: na::Point3<f32>,
+        shaft_radius: f32,
+        head_radius: f32,
+        head_length: f32,
+        nr_segments: u32,
+    ) -> EntityBuilder {
+        let dir = end - start;
+        let length = dir.norm();
+        let head_length = head_length.min(length);
+        let shaft_length = length - head_length;
+        let head_side = na::Vector2::new(head_length, head_radius);
+
+        let profile = vec![
+            //head
+            ProfilePoint::new(0.0, length, head_side.x, head_side.y),
+            ProfilePoint::new(head_radius, shaft_length, head_side.x, head_side.y),
+            //ring between the head and the shaft
+            ProfilePoint::new(head_radius, shaft_length, 0.0, -1.0),
+            ProfilePoint::new(shaft_radius, shaft_length, 0.0, -1.0),
+            //shaft
+            ProfilePoint::new(shaft_radius, shaft_length, 1.0, 0.0),
+            ProfilePoint::new(shaft_radius, 0.0, 1.0, 0.0),
+            //bottom cap
+            ProfilePoint::new(shaft_radius, 0.0, 0.0, -1.0),
+            ProfilePoint::new(0.0, 0.0, 0.0, -1.0),
+        ];
+
+        //rotate Y towards the direction of the arrow
+        let rotation = if length < 1e-6 {
+            na::UnitQuaternion::identity()
+        } else {
+            na::UnitQuaternion::rotation_between(&na::Vector3::y(), &dir)
+                .unwrap_or_else(|| na::UnitQuaternion::from_axis_angle(&na::Vector3::x_axis(), f32::consts::PI))
+        };
+        let model_matrix = na::SimilarityMatrix3::from_parts(na::Translation3::from(start.coords), rotation.to_rotation_matrix(), 1.0);
+
+        Self::build_lathe(&profile, nr_segments, model_matrix)
+    }
+
+    /// Creates a coordinate axes triad as lines of length `size` colored red,
+    /// green and blue for the X, Y and Z axis respectively
+    #[must_use]
+    pub fn build_axes(center: na::Point3<f32>, size: f32, line_width: f32) -> EntityBuilder {
+        //every axis has its own origin vertex so that it can have its own color
+        let verts = DMatrix::<f32>::from_row_slice(
+            6,
+            3,
+            &[
+                0.0, 0.0, 0.0, //
+                size, 0.0, 0.0, //
+                0.0, 0.0, 0.0, //
+                0.0, size, 0.0, //
+                0.0, 0.0, 0.0, //
+                0.0, 0.0, size, //
+            ],
+        );
+        let colors = DMatrix::<f32>::from_row_slice(
+            6,
+            3,
+            &[
+                1.0, 0.0, 0.0, //
+                1.0, 0.0, 0.0, //
+                0.0, 1.0, 0.0, //
+                0.0, 1.0, 0.0, //
+                0.0, 0.0, 1.0, //
+                0.0, 0.0, 1.0, //
+            ],
+        );
+        let edges = DMatrix::<u32>::from_row_slice(
+            3,
+            2,
+            &[
+                0, 1, //
+                2, 3, //
+                4, 5, //
+            ],
+        );
+
+        let mut builder = EntityBuilder::new();
+        builder
+            .add(Verts(DynamicTensorFloat2D::from_dmatrix(&verts)))
+            .add(Edges(DynamicTensorInt2D::from_dmatrix(&edges)))
+            .add(Colors(DynamicTensorFloat2D::from_dmatrix(&colors)))
+            .add(ModelMatrix(Self::translation_matrix(center)))
+            .add(VisLines {
+                show_lines: true,
+                line_width,
+                color_type: LineColorType::PerVert,
+                ..Default::default()
+            });
+
+        builder
+    }
+
+    /// Revolves the profile around the Y axis with `nr_segments` segments. The
+    /// profile goes from the top of the surface towards the bottom so that the
+    /// faces point outwards. Consecutive points at the same position are used
+    /// for hard edges and points with zero radius for poles and apexes
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::many_single_char_names)]
+    fn build_lathe(profile: &[ProfilePoint], nr_segments: u32, model_matrix: na::SimilarityMatrix3<f32>) -> EntityBuilder {
+        let nr_segments = nr_segments.max(3);
+        let nr_cols = nr_segments + 1; //the seam is duplicated so it can have both U=0 and U=1
+
+        //V goes along the length of the profile
+        let mut lengths = vec![0.0];
+        for pair in profile.windows(2) {
+            lengths.push(lengths.last().unwrap() + pair[0].distance(&pair[1]));
+        }
+        let total_length = lengths.last().copied().unwrap_or(0.0).max(1e-6);
+
+        let mut verts = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut tangents = Vec::new();
+        for (point, length) in profile.iter().zip(lengths.iter()) {
+            for j in 0..nr_cols {
+                let u = j as f32 / nr_segments as f32;
+                let (sin, cos) = (2.0 * f32::consts::PI * u).sin_cos();
+                verts.push(na::Vector3::new(point.radius * sin, point.y, point.radius * cos));
+                normals.push(na::Vector3::new(point.normal.x * sin, point.normal.y, point.normal.x * cos).normalize());
+                uvs.push(na::Vector2::new(u, 1.0 - length / total_length));
+                tangents.push(na::Vector4::new(cos, 0.0, -sin, 1.0));
+            }
+        }
+
+        let mut faces = Vec::new();
+        for (k, pair) in profile.windows(2).enumerate() {
+            if pair[0].distance(&pair[1]) < 1e-6 {
+                continue;
+            }
+            let k = k as u32;
+            for j in 0..nr_segments {
+                let a = k * nr_cols + j;
+                let b = a + 1;
+                let c = a + nr_cols;
+                let d = c + 1;
+                //skip the triangles that collapse at a pole
+                if pair[1].radius > 1e-6 {
+                    faces.push([c, d, b]);
+                }
+                if pair[0].radius > 1e-6 {
+                    faces.push([c, b, a]);
+                }
+            }
+        }
+
+        Self::build_from_surface(&verts, &faces, &normals, &uvs, &tangents, model_matrix)
+    }
+
+    fn build_from_surface(
+        verts: &[na::Vector3<f32>],
+        faces: &[[u32; 3]],
+        normals: &[na::Vector3<f32>],
+        uvs: &[na::Vector2<f32>],
+        tangents: &[na::Vector4<f32>],
+        model_matrix: na::SimilarityMatrix3<f32>,
+    ) -> EntityBuilder {
+        let verts = DMatrix::<f32>::from_row_iterator(verts.len(), 3, verts.iter().flat_map(|v| [v.x, v.y, v.z]));
+        let faces = DMatrix::<u32>::from_row_iterator(faces.len(), 3, faces.iter().flatten().copied());
+        let normals = DMatrix::<f32>::from_row_iterator(normals.len(), 3, normals.iter().flat_map(|v| [v.x, v.y, v.z]));
+        let uvs = DMatrix::<f32>::from_row_iterator(uvs.len(), 2, uvs.iter().flat_map(|v| [v.x, v.y]));
+        let tangents = DMatrix::<f32>::from_row_iterator(tangents.len(), 4, tangents.iter().flat_map(|v| [v.x, v.y, v.z, v.w]));
+
+        let mut builder = EntityBuilder::new();
+        builder
+            .add(Verts(DynamicTensorFloat2D::from_dmatrix(&verts)))
+            .add(Faces(DynamicTensorInt2D::from_dmatrix(&faces)))
+            .add(Normals(DynamicTensorFloat2D::from_dmatrix(&normals)))
+            .add(UVs(DynamicTensorFloat2D::from_dmatrix(&uvs)))
+            .add(Tangents(DynamicTensorFloat2D::from_dmatrix(&tangents)))
+            .add(ModelMatrix(model_matrix));
+
+        builder
+    }
+
+    fn translation_matrix(center: na::Point3<f32>) -> na::SimilarityMatrix3<f32> {
+        let mut model_matrix = na::SimilarityMatrix3::<f32>::identity();
+        model_matrix.append_translation_mut(&center.coords.into());
+        model_matrix
+    }
+
+    /// Spherical mapping of a unit direction with the same convention as
+    /// [`Geom::build_uv_sphere`]
+    fn sphere_uv(dir: &na::Vector3<f32>) -> na::Vector2<f32> {
+        let u = (dir.x.atan2(dir.z) / (2.0 * f32::consts::PI)).rem_euclid(1.0);
+        let v = 1.0 - dir.y.clamp(-1.0, 1.0).acos() / f32::consts::PI;
+        na::Vector2::new(u, v)
+    }
+
     pub fn build_from_file(path: &str) -> EntityBuilder {
         //get filetype
         let filetype = match Path::new(path).extension() {
@@ -1687,3 +2143,859 @@ impl Geom {
         (verts, edges)
     }
 }
+
+/// Per vertex attributes of an entity that are carried through the mesh
+/// processing
+#[derive(Clone, Copy)]
+enum ProcessedAttrib {
+    UVs,
+    Colors,
+}
+
+/// Candidate edge collapse for the quadric error decimation. Ordered by
+/// reversed cost so that the [`BinaryHeap`] pops the cheapest collapse first
+struct CollapseCandidate {
+    cost: f64,
+    v_keep: usize,
+    v_remove: usize,
+    //versions of the two vertices when the candidate was created. If any of them changed since then the candidate is stale
+    version_keep: u32,
+    version_remove: u32,
+    position: na::Vector3<f64>,
+}
+impl PartialEq for CollapseCandidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost.total_cmp(&other.cost).is_eq()
+    }
+}
+impl Eq for CollapseCandidate {}
+impl PartialOrd for CollapseCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for CollapseCandidate {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+//mesh processing that changes the vertices or the topology of the mesh
+impl Geom {
+    /// Decimates the mesh with quadric error edge collapses until it has at
+    /// most `target_nr_faces` faces. The `attribs` are per vertex matrices like
+    /// UVs or colors which are interpolated along the collapsed edges. Boundary
+    /// edges, which include UV seams where the vertices are duplicated, are
+    /// preserved and collapses that would flip a face or make the mesh
+    /// non-manifold are skipped, so the target might not be reached.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::too_many_lines)]
+    pub fn decimate(
+        verts: &DMatrix<f32>,
+        faces: &DMatrix<u32>,
+        target_nr_faces: usize,
+        attribs: &[&DMatrix<f32>],
+    ) -> (DMatrix<f32>, DMatrix<u32>, Vec<DMatrix<f32>>) {
+        //boundary planes get a large weight so that the boundary barely moves
+        const BOUNDARY_WEIGHT: f64 = 1000.0;
+
+        let mut positions: Vec<na::Vector3<f64>> = verts.row_iter().map(|v| na::Vector3::new(v[0], v[1], v[2]).cast::<f64>()).collect();
+        let mut faces: Vec<[usize; 3]> = faces.row_iter().map(|f| [f[0] as usize, f[1] as usize, f[2] as usize]).collect();
+        let mut attribs: Vec<DMatrix<f32>> = attribs.iter().map(|a| (*a).clone()).collect();
+        let nr_verts = positions.len();
+
+        let mut face_alive = vec![true; faces.len()];
+        let mut vert_alive = vec![true; nr_verts];
+        let mut versions = vec![0_u32; nr_verts];
+        let mut vert2faces: Vec<Vec<usize>> = vec![Vec::new(); nr_verts];
+        for (idx_face, face) in faces.iter().enumerate() {
+            for &v in face {
+                vert2faces[v].push(idx_face);
+            }
+        }
+
+        //quadrics of the planes of the faces weighted by area
+        let mut quadrics = vec![na::Matrix4::<f64>::zeros(); nr_verts];
+        let mut edge_count: HashMap<(usize, usize), u32> = HashMap::new();
+        for face in &faces {
+            let [a, b, c] = face.map(|v| positions[v]);
+            let normal = (b - a).cross(&(c - a));
+            let double_area = normal.norm();
+            if double_area > 0.0 {
+                let normal = normal / double_area;
+                let plane = na::Vector4::new(normal.x, normal.y, normal.z, -normal.dot(&a));
+                let quadric = plane * plane.transpose() * (double_area / 2.0);
+                for &v in face {
+                    quadrics[v] += quadric;
+                }
+            }
+            for i in 0..3 {
+                let (v0, v1) = (face[i], face[(i + 1) % 3]);
+                *edge_count.entry((v0.min(v1), v0.max(v1))).or_insert(0) += 1;
+            }
+        }
+
+        //boundary edges get a plane perpendicular to their face
+        for face in &faces {
+            let [a, b, c] = face.map(|v| positions[v]);
+            let face_normal = (b - a).cross(&(c - a));
+            for i in 0..3 {
+                let (v0, v1) = (face[i], face[(i + 1) % 3]);
+                if edge_count[&(v0.min(v1), v0.max(v1))] != 1 {
+                    continue;
+                }
+                let edge = positions[v1] - positions[v0];
+                let Some(normal) = edge.cross(&face_normal).try_normalize(1e-12) else {
+                    continue;
+                };
+                let plane = na::Vector4::new(normal.x, normal.y, normal.z, -normal.dot(&positions[v0]));
+                let quadric = plane * plane.transpose() * (BOUNDARY_WEIGHT * edge.norm_squared());
+                quadrics[v0] += quadric;
+                quadrics[v1] += quadric;
+            }
+        }
+
+        let candidate = |v_keep: usize, v_remove: usize, positions: &[na::Vector3<f64>], quadrics: &[na::Matrix4<f64>], versions: &[u32]| {
+            let quadric = quadrics[v_keep] + quadrics[v_remove];
+            let error = |p: &na::Vector3<f64>| {
+                let p = p.push(1.0);
+                (p.transpose() * quadric * p)[0].max(0.0)
+            };
+            //the optimal position minimizes the error, if the quadric is singular we pick the best out of the endpoints and the midpoint
+            let mut system = quadric;
+            system.set_row(3, &na::RowVector4::new(0.0, 0.0, 0.0, 1.0));
+            let optimal = system
+                .try_inverse()
+                .filter(|_| system.determinant().abs() > 1e-12)
+                .map(|inv| (inv * na::Vector4::new(0.0, 0.0, 0.0, 1.0)).xyz());
+            let position = optimal.unwrap_or_else(|| {
+                let midpoint = (positions[v_keep] + positions[v_remove]) / 2.0;
+                [positions[v_keep], positions[v_remove], midpoint]
+                    .into_iter()
+                    .min_by(|p0, p1| error(p0).total_cmp(&error(p1)))
+                    .unwrap()
+            });
+            CollapseCandidate {
+                cost: error(&position),
+                v_keep,
+                v_remove,
+                version_keep: versions[v_keep],
+                version_remove: versions[v_remove],
+                position,
+            }
+        };
+
+        let mut heap: BinaryHeap<CollapseCandidate> = edge_count
+            .keys()
+            .map(|&(v0, v1)| candidate(v0, v1, &positions, &quadrics, &versions))
+            .collect();
+
+        let mut nr_faces_alive = faces.len();
+        while nr_faces_alive > target_nr_faces {
+            let Some(collapse) = heap.pop() else {
+                break;
+            };
+            let (v_keep, v_remove) = (collapse.v_keep, collapse.v_remove);
+            if !vert_alive[v_keep]
+                || !vert_alive[v_remove]
+                || versions[v_keep] != collapse.version_keep
+                || versions[v_remove] != collapse.version_remove
+            {
+                continue;
+            }
+
+            //link condition, the vertices can only share the neighbours that are across the faces of the edge
+            let neighbours = |v: usize| -> HashSet<usize> {
+                vert2faces[v]
+                    .iter()
+                    .filter(|f| face_alive[**f])
+                    .flat_map(|f| faces[*f])
+                    .filter(|n| *n != v)
+                    .collect()
+            };
+            let nr_shared_faces = vert2faces[v_keep]
+                .iter()
+                .filter(|f| face_alive[**f] && faces[**f].contains(&v_remove))
+                .count();
+            if neighbours(v_keep).intersection(&neighbours(v_remove)).count() != nr_shared_faces {
+                continue;
+            }
+
+            //the faces that stay must not flip
+            let flips = vert2faces[v_keep].iter().chain(vert2faces[v_remove].iter()).any(|&f| {
+                if !face_alive[f] || (faces[f].contains(&v_keep) && faces[f].contains(&v_remove)) {
+                    return false;
+                }
+                let old = faces[f].map(|v| positions[v]);
+                let new = faces[f].map(|v| if v == v_keep || v == v_remove { collapse.position } else { positions[v] });
+                let normal_old = (old[1] - old[0]).cross(&(old[2] - old[0]));
+                let normal_new = (new[1] - new[0]).cross(&(new[2] - new[0]));
+                normal_old.norm() > 0.0 && normal_old.dot(&normal_new) <= 0.0
+            });
+            if flips {
+                continue;
+            }
+
+            //interpolate the attributes by projecting the new position onto the edge
+            let edge = positions[v_remove] - positions[v_keep];
+            let t = if edge.norm_squared() > 0.0 {
+                ((collapse.position - positions[v_keep]).dot(&edge) / edge.norm_squared()).clamp(0.0, 1.0) as f32
+            } else {
+                0.0
+            };
+            for attrib in &mut attribs {
+                let row = attrib.row(v_keep) * (1.0 - t) + attrib.row(v_remove) * t;
+                attrib.set_row(v_keep, &row);
+            }
+
+            //collapse
+            positions[v_keep] = collapse.position;
+            let quadric_remove = quadrics[v_remove];
+            quadrics[v_keep] += quadric_remove;
+            vert_alive[v_remove] = false;
+            versions[v_keep] += 1;
+            versions[v_remove] += 1;
+            for f in std::mem::take(&mut vert2faces[v_remove]) {
+                if !face_alive[f] {
+                    continue;
+                }
+                if faces[f].contains(&v_keep) {
+                    face_alive[f] = false;
+                    nr_faces_alive -= 1;
+                } else {
+                    for v in &mut faces[f] {
+                        if *v == v_remove {
+                            *v = v_keep;
+                        }
+                    }
+                    vert2faces[v_keep].push(f);
+                }
+            }
+            vert2faces[v_keep].retain(|f| face_alive[*f]);
+
+            //the neighbourhood of the kept vertex changed so we recompute its collapses
+            let mut new_neighbours: Vec<usize> = vert2faces[v_keep].iter().flat_map(|f| faces[*f]).filter(|n| *n != v_keep).collect();
+            new_neighbours.sort_unstable();
+            new_neighbours.dedup();
+            for n in new_neighbours {
+                heap.push(candidate(v_keep, n, &positions, &quadrics, &versions));
+            }
+        }
+
+        //compact the vertices that are still used
+        let mut old2new = vec![u32::MAX; nr_verts];
+        let mut new2old = Vec::new();
+        let mut faces_out = Vec::new();
+        for face in faces.iter().zip(face_alive.iter()).filter(|(_, alive)| **alive).map(|(face, _)| face) {
+            for &v in face {
+                if old2new[v] == u32::MAX {
+                    old2new[v] = new2old.len() as u32;
+                    new2old.push(v);
+                }
+                faces_out.push(old2new[v]);
+            }
+        }
+
+        let verts_out = DMatrix::<f32>::from_row_iterator(
+            new2old.len(),
+            3,
+            new2old.iter().flat_map(|v| {
+                let p = positions[*v];
+                [p.x as f32, p.y as f32, p.z as f32]
+            }),
+        );
+        let faces_out = DMatrix::<u32>::from_row_slice(faces_out.len() / 3, 3, &faces_out);
+        let attribs_out = attribs.iter().map(|a| a.select_rows(new2old.iter())).collect();
+
+        (verts_out, faces_out, attribs_out)
+    }
+
+    /// Applies `nr_iters` iterations of Loop subdivision, where each one splits
+    /// every triangle in four. The `attribs` are per vertex matrices like UVs
+    /// or colors which are linearly interpolated on the new vertices so that
+    /// UV islands keep their shape.
+    pub fn subdivide_loop(
+        verts: &DMatrix<f32>,
+        faces: &DMatrix<u32>,
+        nr_iters: u32,
+        attribs: &[&DMatrix<f32>],
+    ) -> (DMatrix<f32>, DMatrix<u32>, Vec<DMatrix<f32>>) {
+        let mut verts = verts.clone();
+        let mut faces = faces.clone();
+        let mut attribs: Vec<DMatrix<f32>> = attribs.iter().map(|a| (*a).clone()).collect();
+        for _ in 0..nr_iters {
+            (verts, faces, attribs) = Self::subdivide_loop_once(&verts, &faces, &attribs);
+        }
+        (verts, faces, attribs)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    fn subdivide_loop_once(verts: &DMatrix<f32>, faces: &DMatrix<u32>, attribs: &[DMatrix<f32>]) -> (DMatrix<f32>, DMatrix<u32>, Vec<DMatrix<f32>>) {
+        let nr_verts = verts.nrows();
+        let positions: Vec<na::Vector3<f32>> = verts.row_iter().map(|v| na::Vector3::new(v[0], v[1], v[2])).collect();
+
+        //every edge gets a new vertex, we also store the vertices opposite to it
+        let mut edge2idx: HashMap<(u32, u32), usize> = HashMap::new();
+        let mut edges: Vec<((u32, u32), Vec<u32>)> = Vec::new();
+        let mut edges_of_faces = Vec::with_capacity(faces.nrows());
+        for face in faces.row_iter() {
+            let mut face_edges = [0_u32; 3];
+            for i in 0..3 {
+                let (v0, v1, opposite) = (face[i], face[(i + 1) % 3], face[(i + 2) % 3]);
+                let key = (v0.min(v1), v0.max(v1));
+                let idx_edge = *edge2idx.entry(key).or_insert_with(|| {
+                    edges.push((key, Vec::new()));
+                    edges.len() - 1
+                });
+                edges[idx_edge].1.push(opposite);
+                face_edges[i] = (nr_verts + idx_edge) as u32;
+            }
+            edges_of_faces.push(face_edges);
+        }
+
+        let mut neighbours: Vec<Vec<u32>> = vec![Vec::new(); nr_verts];
+        let mut boundary_neighbours: Vec<Vec<u32>> = vec![Vec::new(); nr_verts];
+        for ((v0, v1), opposites) in &edges {
+            neighbours[*v0 as usize].push(*v1);
+            neighbours[*v1 as usize].push(*v0);
+            if opposites.len() == 1 {
+                boundary_neighbours[*v0 as usize].push(*v1);
+                boundary_neighbours[*v1 as usize].push(*v0);
+            }
+        }
+
+        //even vertices
+        let mut new_positions: Vec<na::Vector3<f32>> = Vec::with_capacity(nr_verts + edges.len());
+        for (idx, p) in positions.iter().enumerate() {
+            let sum = |nbrs: &[u32]| nbrs.iter().map(|n| positions[*n as usize]).sum::<na::Vector3<f32>>();
+            let new_p = match (boundary_neighbours[idx].len(), neighbours[idx].len()) {
+                (2, _) => p * 0.75 + sum(&boundary_neighbours[idx]) * 0.125,
+                //non-manifold or isolated vertices are kept in place
+                (0, 0) | (1 | 3.., _) => *p,
+                (0, n) => {
+                    let beta = if n == 3 { 3.0 / 16.0 } else { 3.0 / (8.0 * n as f32) };
+                    p * (1.0 - n as f32 * beta) + sum(&neighbours[idx]) * beta
+                }
+            };
+            new_positions.push(new_p);
+        }
+        //odd vertices
+        for ((v0, v1), opposites) in &edges {
+            let (p0, p1) = (positions[*v0 as usize], positions[*v1 as usize]);
+            let new_p = if let [o0, o1] = opposites[..] {
+                (p0 + p1) * 0.375 + (positions[o0 as usize] + positions[o1 as usize]) * 0.125
+            } else {
+                (p0 + p1) * 0.5
+            };
+            new_positions.push(new_p);
+        }
+
+        let attribs_out = attribs
+            .iter()
+            .map(|attrib| {
+                let mut out = attrib.clone().resize_vertically(nr_verts + edges.len(), 0.0);
+                for (idx_edge, ((v0, v1), _)) in edges.iter().enumerate() {
+                    let row = (attrib.row(*v0 as usize) + attrib.row(*v1 as usize)) * 0.5;
+                    out.set_row(nr_verts + idx_edge, &row);
+                }
+                out
+            })
+            .collect();
+
+        let mut faces_out = Vec::with_capacity(faces.nrows() * 4 * 3);
+        for (face, [e01, e12, e20]) in faces.row_iter().zip(edges_of_faces.iter().copied()) {
+            faces_out.extend([face[0], e01, e20, face[1], e12, e01, face[2], e20, e12, e01, e12, e20]);
+        }
+
+        let verts_out = DMatrix::<f32>::from_row_iterator(new_positions.len(), 3, new_positions.iter().flat_map(|p| [p.x, p.y, p.z]));
+        let faces_out = DMatrix::<u32>::from_row_slice(faces_out.len() / 3, 3, &faces_out);
+        (verts_out, faces_out, attribs_out)
+    }
+
+    /// Laplacian smoothing which moves every vertex by `lambda` towards the
+    /// average of its neighbours for `nr_iters` iterations. Vertices at the
+    /// same position, like the ones duplicated along UV seams, are moved
+    /// together and boundary vertices are only smoothed along the boundary.
+    /// The mesh shrinks with more iterations, see [`Geom::smooth_taubin`] for
+    /// a version that keeps the volume.
+    pub fn smooth_laplacian(verts: &DMatrix<f32>, faces: &DMatrix<u32>, nr_iters: u32, lambda: f32) -> DMatrix<f32> {
+        Self::smooth(verts, faces, (0..nr_iters).map(|_| lambda))
+    }
+
+    /// Taubin smoothing which alternates a Laplacian step with a positive
+    /// `lambda` and one with a negative `mu` so that the mesh doesn't shrink.
+    /// `mu` should be slightly larger in magnitude than `lambda`, for example
+    /// `lambda=0.5` and `mu=-0.53`
+    pub fn smooth_taubin(verts: &DMatrix<f32>, faces: &DMatrix<u32>, nr_iters: u32, lambda: f32, mu: f32) -> DMatrix<f32> {
+        Self::smooth(verts, faces, (0..nr_iters).flat_map(|_| [lambda, mu]))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn smooth(verts: &DMatrix<f32>, faces: &DMatrix<u32>, factors: impl Iterator<Item = f32>) -> DMatrix<f32> {
+        //weld vertices at the same position so that seams stay closed
+        let mut pos2welded: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut welded_positions: Vec<na::Vector3<f32>> = Vec::new();
+        let vert2welded: Vec<usize> = verts
+            .row_iter()
+            .map(|v| {
+                *pos2welded.entry([v[0].to_bits(), v[1].to_bits(), v[2].to_bits()]).or_insert_with(|| {
+                    welded_positions.push(na::Vector3::new(v[0], v[1], v[2]));
+                    welded_positions.len() - 1
+                })
+            })
+            .collect();
+
+        let mut edge_count: HashMap<(usize, usize), u32> = HashMap::new();
+        for face in faces.row_iter() {
+            for i in 0..3 {
+                let (v0, v1) = (vert2welded[face[i] as usize], vert2welded[face[(i + 1) % 3] as usize]);
+                if v0 != v1 {
+                    *edge_count.entry((v0.min(v1), v0.max(v1))).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); welded_positions.len()];
+        let mut boundary_neighbours: Vec<Vec<usize>> = vec![Vec::new(); welded_positions.len()];
+        for (&(v0, v1), &count) in &edge_count {
+            neighbours[v0].push(v1);
+            neighbours[v1].push(v0);
+            if count == 1 {
+                boundary_neighbours[v0].push(v1);
+                boundary_neighbours[v1].push(v0);
+            }
+        }
+
+        for factor in factors {
+            welded_positions = welded_positions
+                .iter()
+                .enumerate()
+                .map(|(idx, p)| {
+                    let nbrs = if boundary_neighbours[idx].is_empty() {
+                        &neighbours[idx]
+                    } else {
+                        &boundary_neighbours[idx]
+                    };
+                    if nbrs.is_empty() {
+                        return *p;
+                    }
+                    let avg = nbrs.iter().map(|n| welded_positions[*n]).sum::<na::Vector3<f32>>() / nbrs.len() as f32;
+                    p + (avg - p) * factor
+                })
+                .collect();
+        }
+
+        DMatrix::<f32>::from_row_iterator(
+            verts.nrows(),
+            3,
+            vert2welded.iter().flat_map(|w| {
+                let p = welded_positions[*w];
+                [p.x, p.y, p.z]
+            }),
+        )
+    }
+
+    /// Decimates the mesh of the entity with [`Geom::decimate`] and returns a
+    /// new builder with its vertices, faces and the UVs and colors if present
+    pub fn build_decimated(entity: Entity, scene: &Scene, target_nr_faces: usize) -> EntityBuilder {
+        let (verts, faces, attribs, model_matrix) = Self::processing_inputs(entity, scene);
+        let attribs_refs: Vec<&DMatrix<f32>> = attribs.iter().map(|(_, a)| a).collect();
+        let (verts, faces, attribs_out) = Self::decimate(&verts, &faces, target_nr_faces, &attribs_refs);
+        Self::build_from_processed(&verts, &faces, attribs.iter().map(|(kind, _)| *kind).zip(attribs_out), model_matrix)
+    }
+
+    /// Subdivides the mesh of the entity with [`Geom::subdivide_loop`] and
+    /// returns a new builder with its vertices, faces and the UVs and colors if
+    /// present
+    pub fn build_subdivided(entity: Entity, scene: &Scene, nr_iters: u32) -> EntityBuilder {
+        let (verts, faces, attribs, model_matrix) = Self::processing_inputs(entity, scene);
+        let attribs_refs: Vec<&DMatrix<f32>> = attribs.iter().map(|(_, a)| a).collect();
+        let (verts, faces, attribs_out) = Self::subdivide_loop(&verts, &faces, nr_iters, &attribs_refs);
+        Self::build_from_processed(&verts, &faces, attribs.iter().map(|(kind, _)| *kind).zip(attribs_out), model_matrix)
+    }
+
+    /// Smooths the mesh of the entity and returns a new builder with the
+    /// smoothed vertices, the faces and the UVs and colors if present. Uses
+    /// [`Geom::smooth_taubin`] if `mu` is set and [`Geom::smooth_laplacian`]
+    /// otherwise
+    pub fn build_smoothed(entity: Entity, scene: &Scene, nr_iters: u32, lambda: f32, mu: Option<f32>) -> EntityBuilder {
+        let (verts, faces, attribs, model_matrix) = Self::processing_inputs(entity, scene);
+        let verts = match mu {
+            Some(mu) => Self::smooth_taubin(&verts, &faces, nr_iters, lambda, mu),
+            None => Self::smooth_laplacian(&verts, &faces, nr_iters, lambda),
+        };
+        Self::build_from_processed(&verts, &faces, attribs.into_iter(), model_matrix)
+    }
+
+    /// Gets the vertices, faces, the per vertex UVs and colors and the model
+    /// matrix of a mesh entity
+    #[allow(clippy::type_complexity)]
+    fn processing_inputs(
+        entity: Entity,
+        scene: &Scene,
+    ) -> (
+        DMatrix<f32>,
+        DMatrix<u32>,
+        Vec<(ProcessedAttrib, DMatrix<f32>)>,
+        Option<na::SimilarityMatrix3<f32>>,
+    ) {
+        let verts = scene.get_comp::<&Verts>(&entity).expect("The entity needs Verts").0.to_dmatrix();
+        let faces = scene.get_comp::<&Faces>(&entity).expect("The entity needs Faces").0.to_dmatrix();
+        let mut attribs = Vec::new();
+        if let Ok(uvs) = scene.get_comp::<&UVs>(&entity) {
+            attribs.push((ProcessedAttrib::UVs, uvs.0.to_dmatrix()));
+        }
+        if let Ok(colors) = scene.get_comp::<&Colors>(&entity) {
+            attribs.push((ProcessedAttrib::Colors, colors.0.to_dmatrix()));
+        }
+        //only per vertex attributes can be carried through
+        attribs.retain(|(_, a)| a.nrows() == verts.nrows());
+        let model_matrix = scene.get_comp::<&ModelMatrix>(&entity).ok().map(|mm| mm.0);
+        (verts, faces, attribs, model_matrix)
+    }
+
+    fn build_from_processed(
+        verts: &DMatrix<f32>,
+        faces: &DMatrix<u32>,
+        attribs: impl Iterator<Item = (ProcessedAttrib, DMatrix<f32>)>,
+        model_matrix: Option<na::SimilarityMatrix3<f32>>,
+    ) -> EntityBuilder {
+        let mut builder = EntityBuilder::new();
+        builder
+            .add(Verts(DynamicTensorFloat2D::from_dmatrix(verts)))
+            .add(Faces(DynamicTensorInt2D::from_dmatrix(faces)));
+        for (kind, attrib) in attribs {
+            let tensor = DynamicTensorFloat2D::from_dmatrix(&attrib);
+            match kind {
+                ProcessedAttrib::UVs => builder.add(UVs(tensor)),
+                ProcessedAttrib::Colors => builder.add(Colors(tensor)),
+            };
+        }
+        if let Some(model_matrix) = model_matrix {
+            builder.add(ModelMatrix(model_matrix));
+        }
+        builder
+    }
+}
+
+//point cloud processing
+impl Geom {
+    /// Estimates a normal for every point of a point cloud as the direction of
+    /// least variance of its neighbourhood. Normals are flipped to point
+    /// towards the viewpoint, which is usually the position of the scanner.
+    /// Points with less than 3 neighbours get a normal pointing at the
+    /// viewpoint
+    pub fn estimate_point_normals(verts: &DMatrix<f32>, neighbourhood: PointNeighbourhood, viewpoint: &na::Point3<f32>) -> DMatrix<f32> {
+        let tree = KdTree::new(verts);
+        let point = |idx: usize| na::Point3::new(verts[(idx, 0)], verts[(idx, 1)], verts[(idx, 2)]);
+        let mut normals = DMatrix::<f32>::zeros(verts.nrows(), 3);
+        for idx in 0..verts.nrows() {
+            let p = point(idx);
+            let neighbours: Vec<u32> = match neighbourhood {
+                PointNeighbourhood::Knn(k) => tree.knn(&p, k).into_iter().map(|(n, _)| n).collect(),
+                PointNeighbourhood::Radius(radius) => tree.within_radius(&p, radius),
+            };
+            let to_viewpoint = viewpoint - p;
+            let mut normal = if neighbours.len() < 3 {
+                to_viewpoint
+            } else {
+                Self::direction_least_variance(neighbours.iter().map(|n| point(*n as usize)))
+            };
+            if normal.dot(&to_viewpoint) < 0.0 {
+                normal = -normal;
+            }
+            normals.row_mut(idx).copy_from(&normal.normalize().transpose());
+        }
+        normals
+    }
+
+    /// Replaces all the points that fall in the same cell of a grid with
+    /// `voxel_size` by their average. The per-point attributes, like colors or
+    /// normals, are averaged in the same way so normals need to be normalized
+    /// again afterwards
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn voxel_downsample(verts: &DMatrix<f32>, voxel_size: f32, attribs: &[&DMatrix<f32>]) -> (DMatrix<f32>, Vec<DMatrix<f32>>) {
+        if verts.nrows() == 0 {
+            return (verts.clone(), attribs.iter().map(|a| (*a).clone()).collect());
+        }
+        let mut voxel2idx: HashMap<[i64; 3], usize> = HashMap::new();
+        let orig2downsampled: Vec<u32> = verts
+            .row_iter()
+            .map(|v| {
+                let voxel = [0, 1, 2].map(|i| (v[i] / voxel_size).floor() as i64);
+                let nr_voxels = voxel2idx.len();
+                *voxel2idx.entry(voxel).or_insert(nr_voxels) as u32
+            })
+            .collect();
+        let verts_out = Self::splat_rows(verts, &orig2downsampled, &SplatType::Avg);
+        let attribs_out = attribs.iter().map(|a| Self::splat_rows(a, &orig2downsampled, &SplatType::Avg)).collect();
+        (verts_out, attribs_out)
+    }
+
+    /// Finds outliers as the points whose mean distance to their
+    /// `nr_neighbours` nearest points is larger than the average over the
+    /// whole cloud by more than `std_ratio` standard deviations. Returns a mask
+    /// which is true for the points to keep and can be used with
+    /// [`Geom::filter_rows`]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn statistical_outlier_mask(verts: &DMatrix<f32>, nr_neighbours: usize, std_ratio: f32) -> Vec<bool> {
+        let tree = KdTree::new(verts);
+        //the closest point is always the point itself so we ask for one more
+        let mean_dists: Vec<f32> = verts
+            .row_iter()
+            .map(|v| {
+                let knn = tree.knn(&na::Point3::new(v[0], v[1], v[2]), nr_neighbours + 1);
+                let dists: Vec<f32> = knn.iter().skip(1).map(|(_, d2)| d2.sqrt()).collect();
+                if dists.is_empty() {
+                    0.0
+                } else {
+                    dists.iter().sum::<f32>() / dists.len() as f32
+                }
+            })
+            .collect();
+        if mean_dists.is_empty() {
+            return Vec::new();
+        }
+        let mean = mean_dists.iter().sum::<f32>() / mean_dists.len() as f32;
+        let std = (mean_dists.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / mean_dists.len() as f32).sqrt();
+        let threshold = mean + std_ratio * std;
+        mean_dists.iter().map(|d| *d <= threshold).collect()
+    }
+
+    /// Eigenvector of the smallest eigenvalue of the covariance of the points
+    #[allow(clippy::cast_precision_loss)]
+    fn direction_least_variance(points: impl Iterator<Item = na::Point3<f32>> + Clone) -> na::Vector3<f32> {
+        let nr_points = points.clone().count() as f32;
+        let centroid = points.clone().fold(na::Vector3::zeros(), |acc, p| acc + p.coords) / nr_points;
+        let covariance = points.fold(na::Matrix3::zeros(), |acc, p| {
+            let d = p.coords - centroid;
+            acc + d * d.transpose()
+        }) / nr_points;
+        let eigen = covariance.symmetric_eigen();
+        eigen.eigenvectors.column(eigen.eigenvalues.imin()).into_owned()
+    }
+}
+
+/// Statistics of the distances computed by [`Geom::mesh_distance`]. They are
+/// all computed over the absolute distances so `hausdorff` is the one-sided
+/// Hausdorff distance from the source to the target
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MeshDistanceStats {
+    pub hausdorff: f32,
+    pub mean: f32,
+    pub rms: f32,
+    //signed extremes, useful for setting a symmetric range on a diverging colormap
+    pub min_signed: f32,
+    pub max_signed: f32,
+}
+
+//comparison between meshes
+impl Geom {
+    /// Signed distance from every vertex of the source to the closest point on
+    /// the surface of the target. The distance is positive for vertices on the
+    /// side the target normals point to, which is the outside for a
+    /// consistently oriented mesh. Returns a Nx1 matrix together with
+    /// statistics over the absolute distances
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mesh_distance(src_verts: &DMatrix<f32>, tgt_verts: &DMatrix<f32>, tgt_faces: &DMatrix<u32>) -> (DMatrix<f32>, MeshDistanceStats) {
+        let bvh = Bvh::new(tgt_verts, tgt_faces);
+        let face_normals = Self::compute_per_face_normals(tgt_verts, tgt_faces);
+        let vertex_normals = Self::compute_per_vertex_normals(tgt_verts, tgt_faces, &PerVertexNormalsWeightingType::Area);
+        let normal_at = |mat: &DMatrix<f32>, idx: usize| na::Vector3::new(mat[(idx, 0)], mat[(idx, 1)], mat[(idx, 2)]);
+
+        let mut distances = DMatrix::<f32>::zeros(src_verts.nrows(), 1);
+        for (idx, v) in src_verts.row_iter().enumerate() {
+            let point = na::Point3::new(v[0], v[1], v[2]);
+            let Some(cp) = bvh.closest_point(&point) else {
+                continue;
+            };
+            //the interpolated vertex normal gives the right sign also when the closest point is on an edge or a vertex, where the normal of a
+            // single face can point either way
+            let face = tgt_faces.row(cp.face as usize);
+            let mut normal = (0..3).fold(na::Vector3::zeros(), |acc, i| {
+                acc + normal_at(&vertex_normals, face[i] as usize) * cp.barycentric[i]
+            });
+            if !normal.iter().all(|x| x.is_finite()) || normal.norm_squared() < 1e-12 {
+                normal = normal_at(&face_normals, cp.face as usize);
+            }
+            let sign = if (point - cp.point).dot(&normal) < 0.0 { -1.0 } else { 1.0 };
+            distances[(idx, 0)] = sign * cp.distance;
+        }
+
+        let mut stats = MeshDistanceStats::default();
+        if !distances.is_empty() {
+            let nr_verts = distances.nrows() as f32;
+            stats.hausdorff = distances.iter().fold(0.0_f32, |acc, d| acc.max(d.abs()));
+            stats.mean = distances.iter().map(|d| d.abs()).sum::<f32>() / nr_verts;
+            stats.rms = (distances.iter().map(|d| d * d).sum::<f32>() / nr_verts).sqrt();
+            stats.min_signed = distances.min();
+            stats.max_signed = distances.max();
+        }
+        (distances, stats)
+    }
+
+    /// Runs [`Geom::mesh_distance`] from the vertices of the source entity to
+    /// the mesh of the target entity. Both are taken in world coordinates so
+    /// the entities can have different [`ModelMatrix`]
+    pub fn mesh_distance_entities(source: Entity, target: Entity, scene: &Scene) -> (DMatrix<f32>, MeshDistanceStats) {
+        let world_verts = |entity: Entity| {
+            let verts = scene.get_comp::<&Verts>(&entity).expect("The entity needs Verts").0.to_dmatrix();
+            match scene.get_comp::<&ModelMatrix>(&entity) {
+                Ok(mm) => Self::transform_verts(&verts, &mm.0),
+                Err(_) => verts,
+            }
+        };
+        let tgt_faces = scene.get_comp::<&Faces>(&target).expect("The target entity needs Faces").0.to_dmatrix();
+        Self::mesh_distance(&world_verts(source), &world_verts(target), &tgt_faces)
+    }
+}
+
+//deformation with blend shapes and linear blend skinning. These are the CPU references of the deform pass which evaluates the same on
+// the GPU
+impl Geom {
+    /// Adds to the rest vertices the shapes of the basis weighted by the
+    /// coefficients. The basis is the (K*N)x3 matrix of
+    /// [`crate::components::BlendShapes`]. Shapes without a coefficient are
+    /// ignored
+    pub fn apply_blend_shapes(verts: &DMatrix<f32>, basis: &DMatrix<f32>, coeffs: &[f32]) -> DMatrix<f32> {
+        let nr_verts = verts.nrows();
+        let nr_shapes = basis.nrows().checked_div(nr_verts).unwrap_or(0);
+        let mut verts_out = verts.clone();
+        for (k, coeff) in coeffs.iter().take(nr_shapes).enumerate() {
+            if *coeff != 0.0 {
+                verts_out += basis.rows(k * nr_verts, nr_verts) * *coeff;
+            }
+        }
+        verts_out
+    }
+
+    /// Transforms every vertex by the blend of the transforms of the joints
+    /// that influence it. The joints and weights are the Nx4 matrices of
+    /// [`crate::components::SkinWeights`]. Influences of joints that don't
+    /// have a transform are ignored
+    pub fn skin_verts(verts: &DMatrix<f32>, joints: &DMatrix<u32>, weights: &DMatrix<f32>, transforms: &[na::Matrix4<f32>]) -> DMatrix<f32> {
+        let mut verts_out = DMatrix::<f32>::zeros(verts.nrows(), 3);
+        for (idx, v) in verts.row_iter().enumerate() {
+            let mut blended = na::Matrix4::<f32>::zeros();
+            for slot in 0..joints.ncols() {
+                if let Some(transform) = transforms.get(joints[(idx, slot)] as usize) {
+                    blended += transform * weights[(idx, slot)];
+                }
+            }
+            let p = blended * na::Vector4::new(v[0], v[1], v[2], 1.0);
+            verts_out.row_mut(idx).copy_from(&p.xyz().transpose());
+        }
+        verts_out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::welded_mesh, topology::MeshTopology};
+
+    fn volume(verts: &DMatrix<f32>, faces: &DMatrix<u32>) -> f32 {
+        let p = |i: u32| na::Vector3::new(verts[(i as usize, 0)], verts[(i as usize, 1)], verts[(i as usize, 2)]);
+        faces.row_iter().map(|f| p(f[0]).dot(&p(f[1]).cross(&p(f[2]))) / 6.0).sum()
+    }
+
+    /// Grid of 3x3 vertices in the XY plane with the center vertex in the
+    /// interior
+    #[allow(clippy::cast_precision_loss)]
+    fn grid() -> (DMatrix<f32>, DMatrix<u32>) {
+        let verts = DMatrix::<f32>::from_fn(9, 3, |r, c| match c {
+            0 => (r % 3) as f32,
+            1 => (r / 3) as f32,
+            _ => 0.0,
+        });
+        let mut faces = Vec::new();
+        for y in 0..2 {
+            for x in 0..2 {
+                let a = y * 3 + x;
+                faces.extend([a, a + 1, a + 4, a, a + 4, a + 3]);
+            }
+        }
+        (verts, DMatrix::<u32>::from_row_slice(8, 3, &faces))
+    }
+
+    #[test]
+    fn decimate_reaches_target_and_stays_closed() {
+        let (verts, faces) = welded_mesh(Geom::build_ico_sphere(na::Point3::origin(), 1.0, 3));
+        assert_eq!(MeshTopology::new(&faces).stats().nr_boundary_loops, 0);
+        let uvs = verts.columns(0, 2).into_owned();
+        let colors = verts.abs();
+
+        let (verts_out, faces_out, attribs_out) = Geom::decimate(&verts, &faces, 200, &[&uvs, &colors]);
+        assert_eq!(faces_out.nrows(), 200);
+        let stats = MeshTopology::new(&faces_out).stats();
+        assert_eq!(stats.nr_boundary_loops, 0);
+        assert_eq!(stats.nr_non_manifold_edges, 0);
+        assert_eq!(stats.genus, Some(0));
+        assert_eq!(attribs_out.len(), 2);
+        assert_eq!(attribs_out[0].shape(), (verts_out.nrows(), 2));
+        assert_eq!(attribs_out[1].shape(), (verts_out.nrows(), 3));
+    }
+
+    #[test]
+    fn subdivide_loop_splits_faces_and_keeps_boundary() {
+        let (verts, faces) = grid();
+        let boundary_before = MeshTopology::new(&faces).boundary_loops();
+        assert_eq!(boundary_before.len(), 1);
+        let uvs = verts.columns(0, 2).into_owned();
+        let colors = DMatrix::<f32>::from_element(verts.nrows(), 3, 0.5);
+
+        let (verts_out, faces_out, attribs_out) = Geom::subdivide_loop(&verts, &faces, 1, &[&uvs, &colors]);
+        assert_eq!(faces_out.nrows(), faces.nrows() * 4);
+        let boundary_after = MeshTopology::new(&faces_out).boundary_loops();
+        assert_eq!(boundary_after.len(), 1);
+        assert_eq!(boundary_after[0].len(), boundary_before[0].len() * 2);
+        //the original vertices keep their index so the ones on the boundary must still be on it
+        assert!(boundary_before[0].iter().all(|v| boundary_after[0].contains(v)));
+        assert!(!boundary_after[0].contains(&4));
+
+        assert_eq!(attribs_out[0].shape(), (verts_out.nrows(), 2));
+        assert_eq!(attribs_out[1].shape(), (verts_out.nrows(), 3));
+        //uvs of the new vertices are the midpoints of their edges
+        assert!(attribs_out[0]
+            .rows(verts.nrows(), verts_out.nrows() - verts.nrows())
+            .iter()
+            .all(|uv| (uv * 2.0).fract().abs() < 1e-6));
+        assert!(attribs_out[1].iter().all(|c| (*c - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn taubin_preserves_volume() {
+        let (verts, faces) = welded_mesh(Geom::build_ico_sphere(na::Point3::origin(), 1.0, 3));
+        let volume_before = volume(&verts, &faces);
+
+        let taubin = Geom::smooth_taubin(&verts, &faces, 10, 0.5, -0.53);
+        let laplacian = Geom::smooth_laplacian(&verts, &faces, 10, 0.5);
+        assert_eq!(taubin.shape(), verts.shape());
+        assert!((volume(&taubin, &faces) / volume_before - 1.0).abs() < 0.01);
+        //plain laplacian smoothing shrinks the sphere noticeably
+        assert!(volume(&laplacian, &faces) / volume_before < 0.95);
+    }
+
+    #[test]
+    fn mesh_distance_is_signed() {
+        let (verts, faces) = welded_mesh(Geom::build_ico_sphere(na::Point3::origin(), 1.0, 3));
+        //the vertices of the icosphere are on the unit sphere so the scaled ones are 0.1 away from the surface, give or take the flat faces
+        let (outside, stats) = Geom::mesh_distance(&(&verts * 1.1), &verts, &faces);
+        assert!(outside.iter().all(|d| (*d - 0.1).abs() < 0.01));
+        assert!(stats.min_signed > 0.0);
+        let (inside, stats) = Geom::mesh_distance(&(&verts * 0.9), &verts, &faces);
+        assert!(inside.iter().all(|d| (*d + 0.1).abs() < 0.01));
+        assert!(stats.max_signed < 0.0);
+        assert!((stats.hausdorff - 0.1).abs() < 0.01);
+    }
+
+    #[test]
+    fn mesh_distance_to_itself_is_zero() {
+        let (verts, faces) = welded_mesh(Geom::build_ico_sphere(na::Point3::origin(), 1.0, 2));
+        let (distances, stats) = Geom::mesh_distance(&verts, &verts, &faces);
+        assert_eq!(distances.nrows(), verts.nrows());
+        assert!(stats.hausdorff < 1e-5);
+        assert!(stats.mean < 1e-5);
+        assert!(stats.rms < 1e-5);
+    }
+}
@@ -1,10 +1,13 @@
 use crate::{
     components::{
-        Colors, DiffuseImg, DiffuseTex, Faces, ImgConfig, LightEmit, MeshColorType, ModelMatrix, Name, NormalImg, NormalTex, Normals, PointColorType,
-        PosLookat, Projection, Renderable, RoughnessImg, RoughnessTex, ShadowCaster, ShadowMapDirty, UVs, Verts, VisLines, VisMesh, VisNormals,
-        VisPoints, VisWireframe,
+        Children, ClipPlane, Colormap, Colors, DiffuseImg, DiffuseTex, Faces, Highlight, ImgConfig, InputView, LightEmit, LineColorType,
+        MeshColorType, ModelMatrix, Name, NoClip, NormalImg, NormalTex, Normals, PointColorType, PointRenderMode, PosLookat, Projection, Renderable,
+        RoughnessImg, RoughnessTex, Scalars, ShadowCaster, ShadowMapDirty, UVs, Verts, ViewOverrides, VisLines, VisMesh, VisNormals, VisPoints,
+        VisScalars, VisWireframe,
     },
-    config::Config,
+    config::{AntiAliasing, Config, RecordFormat},
+    recorder::Recorder,
+    sequence::MeshSequence,
     viewer::Runner,
 };
 
@@ -12,9 +15,10 @@ use crate::plugin_manager::gui::window::{GuiWindowType, WindowPivot, WindowPosit
 
 use crate::{
     forward_renderer::Renderer,
-    geom::Geom,
+    geom::{Geom, MeshDistanceStats},
     plugin_manager::plugins::Plugins,
     scene::{Scene, GLOSS_FLOOR_NAME},
+    topology::{MeshStats, MeshTopology},
 };
 
 use egui::style::TextCursorStyle;
@@ -29,7 +33,7 @@ use egui::style::{HandleShape, NumericColorSpace, ScrollStyle};
 use gloss_utils::{
     abi_stable_aliases::std_types::{ROption::RSome, RString, RVec},
     memory::get_last_relevant_func_name,
-    tensor::DynamicMatrixOps,
+    tensor::{DynamicMatrixOps, DynamicTensorFloat2D, DynamicTensorOps},
 };
 
 use re_memory::{accounting_allocator, CallstackStatistics, MemoryUse};
@@ -79,6 +83,16 @@ pub struct GuiMainWidget {
     pub hovered_normal_tex: bool,
     pub hovered_roughness_tex: bool,
     default_texture: Option<easy_wgpu::texture::Texture>,
+    //topology stats of the selected mesh together with its entity and number of faces, so we only recompute them when those change
+    mesh_stats: Option<(Entity, usize, MeshStats)>,
+    //entities compared by the mesh distance tool and the statistics of the last comparison
+    compare_source: Option<Entity>,
+    compare_target: Option<Entity>,
+    compare_stats: Option<MeshDistanceStats>,
+    /// Draws an outline around the selected entity using the color and width
+    /// of `selection_highlight`
+    pub outline_selected: bool,
+    pub selection_highlight: Highlight,
     //gizmo stuff
     // gizmo_mode: GizmoMode,
     // gizmo_orientation: GizmoOrientation,
@@ -96,6 +110,15 @@ impl Default for GuiMainWidget {
             hovered_normal_tex: false,
             hovered_roughness_tex: false,
             default_texture: None,
+            mesh_stats: None,
+            compare_source: None,
+            compare_target: None,
+            compare_stats: None,
+            outline_selected: true,
+            selection_highlight: Highlight {
+                from_selection: true,
+                ..Default::default()
+            },
             // gizmo_mode: GizmoMode::Translate,
             // gizmo_orientation: GizmoOrientation::Local,
         }
@@ -285,6 +308,7 @@ impl Gui {
         let mut encoder = gpu
             .device()
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Gui") });
+        gpu.profiler().begin_scope("Egui", &mut encoder);
         {
             for (texture_id, image_delta) in &textures_delta.set {
                 self.egui_renderer.update_texture(gpu.device(), gpu.queue(), *texture_id, image_delta);
@@ -312,6 +336,7 @@ impl Gui {
             self.egui_renderer
                 .render(&mut render_pass.forget_lifetime(), paint_jobs.as_slice(), &screen_descriptor);
         }
+        gpu.profiler().end_scope(&mut encoder);
         gpu.queue().submit(Some(encoder.finish()));
         self.end_frame(scene);
     }
@@ -363,6 +388,24 @@ impl GuiMainWidget {
             }
         }
 
+        //draw the colorbar of the selected entity if it's colored by its scalars
+        if let Some(ent) = self.selected_entity {
+            if let Ok(c) = scene.get_comp::<&VisScalars>(&ent) {
+                let colored_by_scalars = scene.get_comp::<&VisMesh>(&ent).map_or(false, |v| v.color_type == MeshColorType::Scalar)
+                    || scene
+                        .get_comp::<&VisPoints>(&ent)
+                        .map_or(false, |v| v.color_type == PointColorType::Scalar)
+                    || scene.get_comp::<&VisLines>(&ent).map_or(false, |v| v.color_type == LineColorType::Scalar);
+                if c.show_colorbar && colored_by_scalars {
+                    let name = scene.get_comp::<&Name>(&ent).map(|n| n.0.clone()).unwrap_or_default();
+                    Self::draw_colorbar(ctx, &name, &c);
+                }
+            }
+        }
+
+        //handles for moving the clip planes along their normal
+        Self::draw_clip_plane_handles(ctx, scene, screen_width, screen_height);
+
         egui::SidePanel::left("my_left_panel").default_width(SIDE_PANEL_WIDTH).show(ctx, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 //Scene
@@ -377,29 +420,23 @@ impl GuiMainWidget {
                                     ui.spacing_mut().item_spacing.y = 0.0;
                                     ui.spacing_mut().button_padding.y = 4.0;
 
-                                    //get all entities that are renderable and sort by name
+                                    //get all entities that are renderable and sort by name. Children are shown indented below their
+                                    // parent so we start from the ones that are not below another renderable
                                     let entities = scene.get_renderables(true);
+                                    let roots: Vec<Entity> = entities
+                                        .iter()
+                                        .filter(|e| scene.get_parent(**e).map_or(true, |p| !entities.contains(&p)))
+                                        .copied()
+                                        .collect();
 
                                     //go through all visible meshes and show their name
-                                    // for (_cur_mesh_idx, e_ref) in scene.world.iter().enumerate() {
-                                    for entity in entities {
-                                        let e_ref = scene.world.entity(entity).unwrap();
-                                        //get the name of the mesh which acts like a unique id
-                                        let name = e_ref.get::<&Name>().expect("The entity has no name").0.clone();
-
-                                        //GUI for this concrete mesh
-                                        //if we click we can see options for vis
-                                        let _res = ui.selectable_value(&mut self.selected_mesh_name, name.clone(), &name);
-
-                                        if name == self.selected_mesh_name {
-                                            self.selected_entity = Some(entity);
-                                            //make a side window
-                                            self.draw_vis(ctx, renderer, scene, entity, command_buffer, callbacks_for_selected_mesh);
-                                        }
+                                    for entity in roots {
+                                        self.draw_entity_tree(ui, ctx, renderer, scene, entity, command_buffer, callbacks_for_selected_mesh);
                                     }
                                 });
                             });
                     });
+                    self.draw_selection_outline(ui);
                 });
 
                 // Params
@@ -422,13 +459,22 @@ impl GuiMainWidget {
                 // Cam
                 egui::CollapsingHeader::new("Camera").show(ui, |ui| self.draw_cam(ui, scene, command_buffer));
 
+                // Clipping
+                egui::CollapsingHeader::new("Clipping").show(ui, |ui| self.draw_clip_planes(ui, scene, command_buffer));
+
                 // Io
                 egui::CollapsingHeader::new("Io").show(ui, |ui| {
                     self.draw_io(ui, scene, command_buffer, self.selected_entity);
                 });
 
+                // Compare
+                egui::CollapsingHeader::new("Compare").show(ui, |ui| self.draw_mesh_compare(ui, scene, command_buffer));
+
+                // Recorder
+                egui::CollapsingHeader::new("Recorder").show(ui, |ui| self.draw_recorder(ui, scene, gpu, config));
+
                 // profiling
-                egui::CollapsingHeader::new("Profiling").show(ui, |ui| self.draw_profiling(ui, scene, command_buffer));
+                egui::CollapsingHeader::new("Profiling").show(ui, |ui| self.draw_profiling(ui, scene, gpu, command_buffer));
 
                 // Plugins
                 egui::CollapsingHeader::new("Plugins").show(ui, |ui| {
@@ -585,6 +631,49 @@ impl GuiMainWidget {
                 }
             });
         });
+
+        //the timeline is drawn after the side panel so that it only spans the rest of the screen
+        self.draw_timeline(ctx, scene);
+
+        //the selection may have changed while drawing the scene list
+        self.update_selection_highlight(scene);
+    }
+
+    fn draw_selection_outline(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.outline_selected, "Outline selected");
+            ui.color_edit_button_rgba_unmultiplied(&mut self.selection_highlight.color.data.0.as_mut_slice()[0]);
+        });
+        ui.add(Slider::new(&mut self.selection_highlight.width, 1.0..=20.0).text("Width"));
+    }
+
+    //keeps a highlight only on the selected entity. Highlights that were not added by the selection are left untouched
+    fn update_selection_highlight(&self, scene: &mut Scene) {
+        let selected = self
+            .selected_entity
+            .filter(|entity| self.outline_selected && scene.world.contains(*entity));
+
+        let stale: Vec<Entity> = scene
+            .world
+            .query::<&Highlight>()
+            .iter()
+            .filter(|(entity, highlight)| highlight.from_selection && Some(*entity) != selected)
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in stale {
+            scene.world.remove_one::<Highlight>(entity).ok();
+        }
+
+        let Some(entity) = selected else {
+            return;
+        };
+        if let Ok(mut highlight) = scene.get_comp::<&mut Highlight>(&entity) {
+            if highlight.from_selection {
+                *highlight = self.selection_highlight.clone();
+            }
+            return;
+        }
+        scene.world.insert_one(entity, self.selection_highlight.clone()).ok();
     }
 
     // if no selected mesh is set yet, any query with the name will fail.
@@ -608,6 +697,50 @@ impl GuiMainWidget {
         }
     }
 
+    //entry of the scene list for an entity followed by its renderable children which are indented under it
+    #[allow(clippy::too_many_arguments)]
+    fn draw_entity_tree(
+        &mut self,
+        ui: &mut Ui,
+        ctx: &egui::Context,
+        renderer: &Renderer,
+        scene: &mut Scene,
+        entity: Entity,
+        command_buffer: &mut CommandBuffer,
+        callbacks_for_selected_mesh: &mut [CbFnType],
+    ) {
+        //get the name of the mesh which acts like a unique id
+        let name = scene.get_comp::<&Name>(&entity).expect("The entity has no name").0.clone();
+
+        //GUI for this concrete mesh
+        //if we click we can see options for vis
+        let _res = ui.selectable_value(&mut self.selected_mesh_name, name.clone(), &name);
+
+        if name == self.selected_mesh_name {
+            self.selected_entity = Some(entity);
+            //make a side window
+            self.draw_vis(ctx, renderer, scene, entity, command_buffer, callbacks_for_selected_mesh);
+        }
+
+        let mut children: Vec<(String, Entity)> = scene
+            .get_comp::<&Children>(&entity)
+            .map(|c| c.0.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|c| scene.world.has::<Renderable>(*c).unwrap_or(false))
+            .filter_map(|c| scene.get_comp::<&Name>(&c).ok().map(|n| (n.0.clone(), c)))
+            .collect();
+        if children.is_empty() {
+            return;
+        }
+        children.sort();
+        ui.indent(entity, |ui| {
+            for (_, child) in children {
+                self.draw_entity_tree(ui, ctx, renderer, scene, child, command_buffer, callbacks_for_selected_mesh);
+            }
+        });
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn draw_vis(
         &mut self,
@@ -623,6 +756,7 @@ impl GuiMainWidget {
         let has_vis_lines = e_ref.has::<VisLines>();
         let _has_vis_wireframe = e_ref.has::<VisWireframe>();
         let has_vis_mesh = e_ref.has::<VisMesh>();
+        let has_vis_scalars = e_ref.has::<VisScalars>();
         let _has_vis_normals = e_ref.has::<VisNormals>();
         let mut _window = egui::Window::new("vis_points")
             // .auto_sized()
@@ -655,6 +789,12 @@ impl GuiMainWidget {
                     let mut c = scene.get_comp::<&mut VisLines>(&entity).unwrap();
                     self.draw_vis_lines(ui, scene, entity, command_buffer, has_vis_lines, &mut c);
                 }
+                //scalars
+                if has_vis_scalars {
+                    ui.add_space(SPACING_1);
+                    let mut c = scene.get_comp::<&mut VisScalars>(&entity).unwrap();
+                    self.draw_vis_scalars(ui, &mut c);
+                }
                 // TODO: Keep this?
                 //wireframe
                 // if has_vis_wireframe {
@@ -718,6 +858,7 @@ impl GuiMainWidget {
                             view,
                             proj,
                             na::Vector2::<f32>::new(screen_width as f32, screen_height as f32),
+                            scene,
                         );
 
                         let widget_max_size = egui::vec2(35.0, 35.0);
@@ -768,6 +909,16 @@ impl GuiMainWidget {
                         ui.set_min_width(60.0);
                         ui.selectable_value(&mut c.color_type, PointColorType::Solid, "Solid");
                         ui.selectable_value(&mut c.color_type, PointColorType::PerVert, "PerVert");
+                        ui.selectable_value(&mut c.color_type, PointColorType::Scalar, "Scalar");
+                    });
+                //render mode
+                egui::ComboBox::new(4, "Mode") //the id has to be unique to other comboboxes
+                    .selected_text(format!("{:?}", c.render_mode))
+                    .show_ui(ui, |ui| {
+                        ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                        ui.set_min_width(60.0);
+                        ui.selectable_value(&mut c.render_mode, PointRenderMode::Sprite, "Sprite");
+                        ui.selectable_value(&mut c.render_mode, PointRenderMode::Surfel, "Surfel");
                     });
             }
         });
@@ -836,6 +987,7 @@ impl GuiMainWidget {
                         ui.selectable_value(&mut c.color_type, MeshColorType::UV, "UV");
                         ui.selectable_value(&mut c.color_type, MeshColorType::Normal, "Normal");
                         ui.selectable_value(&mut c.color_type, MeshColorType::NormalViewCoords, "NormalViewCoords");
+                        ui.selectable_value(&mut c.color_type, MeshColorType::Scalar, "Scalar");
                     });
 
                 // UV scale slider
@@ -886,6 +1038,122 @@ impl GuiMainWidget {
         });
     }
 
+    fn draw_vis_scalars(&self, ui: &mut Ui, c: &mut VisScalars) {
+        ui.label("Scalars");
+        ui.separator();
+
+        egui::ComboBox::new(5, "Colormap") //the id has to be unique to other comboboxes
+            .selected_text(c.colormap.name())
+            .show_ui(ui, |ui| {
+                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                ui.set_min_width(60.0);
+                for colormap in Colormap::ALL {
+                    ui.selectable_value(&mut c.colormap, colormap, colormap.name());
+                }
+            });
+
+        //the range is only a uniform so it can be dragged around without re-uploading the scalars
+        let speed = ((c.max - c.min).abs() * 0.005).max(1e-4);
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut c.min).speed(speed));
+            ui.label("Min");
+            ui.add(egui::DragValue::new(&mut c.max).speed(speed));
+            ui.label("Max");
+        });
+        ui.checkbox(&mut c.clamp, "Clamp range");
+        if !c.clamp {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_rgba_unmultiplied(&mut c.out_of_range_color.data.0.as_mut_slice()[0]);
+                ui.label("Out of range color");
+            });
+        }
+
+        //isolines
+        ui.horizontal(|ui| {
+            ui.spacing_mut().slider_width = SIDE_PANEL_WIDTH / 3.0;
+            ui.add(Slider::new(&mut c.nr_isolines, 0..=50).text("Isolines"));
+        });
+        if c.nr_isolines > 0 {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().slider_width = SIDE_PANEL_WIDTH / 3.0;
+                ui.add(Slider::new(&mut c.isoline_width, 0.5..=5.0).text("IsoWidth"));
+            });
+            ui.horizontal(|ui| {
+                ui.color_edit_button_rgba_unmultiplied(&mut c.isoline_color.data.0.as_mut_slice()[0]);
+                ui.label("Isoline color");
+            });
+        }
+        ui.checkbox(&mut c.show_colorbar, "Show colorbar");
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn draw_colorbar(ctx: &egui::Context, name: &str, c: &VisScalars) {
+        const NR_SEGMENTS: u32 = 32;
+        const NR_LABELS: u32 = 5;
+        let to_color32 = |color: na::Vector3<f32>| Color32::from_rgb((color.x * 255.0) as u8, (color.y * 255.0) as u8, (color.z * 255.0) as u8);
+
+        egui::Area::new(egui::Id::new("scalars_colorbar"))
+            .interactable(false)
+            .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(RichText::new(name).size(12.0));
+                    let bar_size = egui::vec2(20.0, 160.0);
+                    let label_width = 60.0;
+                    let (rect, _) = ui.allocate_exact_size(egui::vec2(bar_size.x + label_width, bar_size.y), egui::Sense::hover());
+                    let bar_rect = egui::Rect::from_min_size(rect.min, bar_size);
+                    let painter = ui.painter();
+
+                    //gradient as a strip of quads, the low end of the colormap is at the bottom
+                    let mut mesh = egui::Mesh::default();
+                    for i in 0..=NR_SEGMENTS {
+                        let t = i as f32 / NR_SEGMENTS as f32;
+                        let y = bar_rect.bottom() - t * bar_rect.height();
+                        let color = to_color32(c.colormap.sample(t));
+                        mesh.colored_vertex(egui::pos2(bar_rect.left(), y), color);
+                        mesh.colored_vertex(egui::pos2(bar_rect.right(), y), color);
+                        if i > 0 {
+                            let idx = 2 * i;
+                            mesh.add_triangle(idx - 2, idx - 1, idx);
+                            mesh.add_triangle(idx - 1, idx + 1, idx);
+                        }
+                    }
+                    painter.add(egui::Shape::mesh(mesh));
+                    let stroke = ui.visuals().widgets.noninteractive.fg_stroke;
+                    painter.rect_stroke(bar_rect, 0.0, stroke);
+
+                    //isolines at the same values as they are drawn on the mesh
+                    let isoline_color = c.isoline_color.map(|x| (x * 255.0) as u8);
+                    let isoline_color = Color32::from_rgba_unmultiplied(isoline_color.x, isoline_color.y, isoline_color.z, isoline_color.w);
+                    for k in 1..=c.nr_isolines {
+                        let t = k as f32 / (c.nr_isolines + 1) as f32;
+                        let y = bar_rect.bottom() - t * bar_rect.height();
+                        painter.line_segment(
+                            [egui::pos2(bar_rect.left(), y), egui::pos2(bar_rect.right(), y)],
+                            Stroke::new(1.0, isoline_color),
+                        );
+                    }
+
+                    //ticks and values
+                    for i in 0..NR_LABELS {
+                        let t = i as f32 / (NR_LABELS - 1) as f32;
+                        let y = bar_rect.bottom() - t * bar_rect.height();
+                        let value = c.min + t * (c.max - c.min);
+                        painter.line_segment([egui::pos2(bar_rect.right(), y), egui::pos2(bar_rect.right() + 4.0, y)], stroke);
+                        painter.text(
+                            egui::pos2(bar_rect.right() + 6.0, y),
+                            Align2::LEFT_CENTER,
+                            float2string(value, 3),
+                            FontId::proportional(11.0),
+                            ui.visuals().text_color(),
+                        );
+                    }
+                });
+            });
+    }
+
     // TODO: Probably remove this, can always add back later if needed
     // fn draw_vis_wireframe(
     //     &mut self,
@@ -1005,6 +1273,49 @@ impl GuiMainWidget {
         for name in comp_names {
             ui.label(RichText::new(name).font(FontId::proportional(10.0)));
         }
+
+        if let (Ok(verts), Ok(faces)) = (scene.get_comp::<&Verts>(&entity), scene.get_comp::<&Faces>(&entity)) {
+            ui.add_space(SPACING_1);
+            egui::CollapsingHeader::new("Mesh stats").show(ui, |ui| self.draw_mesh_stats(ui, entity, &verts, &faces));
+        }
+    }
+
+    fn draw_mesh_stats(&mut self, ui: &mut Ui, entity: Entity, verts: &Verts, faces: &Faces) {
+        let nr_faces = faces.0.nrows();
+        let recompute = ui.button("Recompute").clicked();
+        let stale = self.mesh_stats.as_ref().map_or(true, |(e, n, _)| *e != entity || *n != nr_faces);
+        if stale || recompute {
+            //welded so that the seams of the uvs are not counted as boundaries
+            let stats = MeshTopology::new_welded(&verts.0.to_dmatrix(), &faces.0.to_dmatrix()).stats();
+            self.mesh_stats = Some((entity, nr_faces, stats));
+        }
+        let Some((_, _, stats)) = &self.mesh_stats else {
+            return;
+        };
+
+        let font = FontId::proportional(10.0);
+        ui.label(RichText::new(format!("Verts: {}", stats.nr_verts)).font(font.clone()));
+        ui.label(RichText::new(format!("Edges: {}", stats.nr_edges)).font(font.clone()));
+        ui.label(RichText::new(format!("Faces: {}", stats.nr_faces)).font(font.clone()));
+        ui.label(RichText::new(format!("Components: {}", stats.nr_components)).font(font.clone()));
+        ui.label(RichText::new(format!("Boundary loops: {}", stats.nr_boundary_loops)).font(font.clone()));
+        ui.label(RichText::new(format!("Euler characteristic: {}", stats.euler_characteristic)).font(font.clone()));
+        let genus = stats.genus.map_or("-".to_string(), |g| g.to_string());
+        ui.label(RichText::new(format!("Genus: {genus}")).font(font.clone()));
+        if stats.nr_non_manifold_edges > 0 {
+            ui.label(
+                RichText::new(format!("Non-manifold edges: {}", stats.nr_non_manifold_edges))
+                    .font(font.clone())
+                    .color(Color32::YELLOW),
+            );
+        }
+        if stats.nr_non_manifold_verts > 0 {
+            ui.label(
+                RichText::new(format!("Non-manifold verts: {}", stats.nr_non_manifold_verts))
+                    .font(font)
+                    .color(Color32::YELLOW),
+            );
+        }
     }
 
     fn draw_textures(
@@ -1236,6 +1547,22 @@ impl GuiMainWidget {
             ui.spacing_mut().slider_width = SIDE_PANEL_WIDTH / 3.0; //changes size od slider
             ui.add(Slider::new(&mut config.render.exposure, -5.0..=5.0).text("Exposure"))
         });
+        //anti-aliasing
+        egui::ComboBox::new(8, "AntiAliasing") //the id has to be unique to other comboboxes
+            .selected_text(format!("{:?}", config.render.antialiasing))
+            .show_ui(ui, |ui| {
+                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                ui.set_min_width(60.0);
+                ui.selectable_value(&mut config.render.antialiasing, AntiAliasing::None, "None");
+                ui.selectable_value(&mut config.render.antialiasing, AntiAliasing::Fxaa, "Fxaa");
+                ui.selectable_value(&mut config.render.antialiasing, AntiAliasing::Taa, "Taa");
+            });
+        if config.render.antialiasing == AntiAliasing::Taa {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().slider_width = SIDE_PANEL_WIDTH / 3.0; //changes size od slider
+                ui.add(Slider::new(&mut config.render.taa_history_weight, 0.0..=0.98).text("TaaHistory"))
+            });
+        }
     }
 
     #[allow(clippy::too_many_lines)]
@@ -1437,12 +1764,19 @@ impl GuiMainWidget {
     fn draw_cam(&mut self, ui: &mut Ui, scene: &mut Scene, _command_buffer: &mut CommandBuffer) {
         // get all entities that are renderable and sort by name
         let _entities = scene.get_lights(true);
-        let cam = scene.get_current_cam().unwrap();
+        let mut cam = scene.get_current_cam().unwrap();
 
-        if let Ok(mut projection) = scene.world.get::<&mut Projection>(cam.entity) {
-            let (mut near, mut far) = projection.near_far();
+        let is_orthographic = scene.world.get::<&Projection>(cam.entity).ok().map(|proj| proj.is_orthographic());
+        if let Some(mut is_orthographic) = is_orthographic {
             ui.label("Projection");
             ui.separator();
+            if ui.checkbox(&mut is_orthographic, "Orthographic").changed() {
+                cam.set_orthographic(is_orthographic, scene);
+            }
+        }
+
+        if let Ok(mut projection) = scene.world.get::<&mut Projection>(cam.entity) {
+            let (mut near, mut far) = projection.near_far();
             ui.spacing_mut().slider_width = SIDE_PANEL_WIDTH / 3.0; //changes size od slider
             let res = ui.add(Slider::new(&mut near, 1e-5..=1.0).text("near"));
             if res.changed() {
@@ -1453,10 +1787,191 @@ impl GuiMainWidget {
                 projection.set_far(far);
             }
         }
+
+        //when several cameras are rendered as views, choose which one is controlled by the mouse
+        let views = scene.get_views(true);
+        if !views.is_empty() {
+            let input_cam = scene.get_input_cam().unwrap();
+            let mut selected = input_cam.entity;
+            let view_name = |entity: Entity| {
+                scene
+                    .get_comp::<&Name>(&entity)
+                    .map_or_else(|_| format!("Camera {}", entity.id()), |name| name.0.clone())
+            };
+
+            ui.label("Views");
+            ui.separator();
+            egui::ComboBox::new(3, "Input view") //the id has to be unique to other comboboxes
+                .selected_text(view_name(selected))
+                .show_ui(ui, |ui| {
+                    ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                    ui.set_min_width(60.0);
+                    for entity in &views {
+                        ui.selectable_value(&mut selected, *entity, view_name(*entity));
+                    }
+                });
+            if selected != input_cam.entity {
+                scene.add_resource(InputView { entity: selected });
+            }
+
+            //overrides of the selected view, only added once they are changed
+            let mut wireframe_only = scene.get_comp::<&ViewOverrides>(&selected).map_or(false, |o| o.wireframe_only);
+            if ui.checkbox(&mut wireframe_only, "Wireframe only").changed() {
+                if scene.world.has::<ViewOverrides>(selected).unwrap_or(false) {
+                    scene.get_comp::<&mut ViewOverrides>(&selected).unwrap().wireframe_only = wireframe_only;
+                } else {
+                    let overrides = ViewOverrides {
+                        wireframe_only,
+                        ..Default::default()
+                    };
+                    scene.world.insert_one(selected, overrides).ok();
+                }
+            }
+        }
     }
 
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::maybe_infinite_iter)]
+    fn draw_clip_planes(&mut self, ui: &mut Ui, scene: &mut Scene, command_buffer: &mut CommandBuffer) {
+        if ui.button("Add plane").clicked() {
+            let name = (0..)
+                .map(|i| format!("clip_plane_{i}"))
+                .find(|name| scene.get_entity_with_name(name).is_none())
+                .unwrap();
+            let centroid = scene.get_centroid();
+            scene
+                .get_or_create_hidden_entity(&name)
+                .insert(ClipPlane::new(centroid, na::Vector3::new(1.0, 0.0, 0.0)));
+        }
+
+        //the selected entity can opt out of being cut
+        if let Some(entity) = self.selected_entity {
+            let mut is_clipped = !scene.world.has::<NoClip>(entity).unwrap_or(true);
+            if ui.checkbox(&mut is_clipped, "Cut selected").changed() {
+                if is_clipped {
+                    command_buffer.remove_one::<NoClip>(entity);
+                } else {
+                    command_buffer.insert_one(entity, NoClip);
+                }
+            }
+        }
+
+        let scale = scene.get_scale();
+        let centroid = scene.get_centroid();
+        let mut planes: Vec<Entity> = scene.world.query::<&ClipPlane>().iter().map(|(entity, _)| entity).collect();
+        planes.sort();
+        let mut removed = None;
+        for entity in planes {
+            let name = scene.get_comp::<&Name>(&entity).map(|n| n.0.clone()).unwrap_or_default();
+            let Ok(mut plane) = scene.get_comp::<&mut ClipPlane>(&entity) else {
+                continue;
+            };
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut plane.enabled, name);
+                    if ui.button("Remove").clicked() {
+                        removed = Some(entity);
+                    }
+                });
+                //the normal can be snapped to an axis or set freely
+                ui.horizontal(|ui| {
+                    for (label, axis) in [("X", na::Vector3::x()), ("Y", na::Vector3::y()), ("Z", na::Vector3::z())] {
+                        if ui.button(label).clicked() {
+                            plane.normal = axis;
+                        }
+                    }
+                    if ui.button("Flip").clicked() {
+                        plane.normal = -plane.normal;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut plane.normal.x).speed(0.01));
+                    ui.add(egui::DragValue::new(&mut plane.normal.y).speed(0.01));
+                    ui.add(egui::DragValue::new(&mut plane.normal.z).speed(0.01));
+                });
+                //offset of the plane along its normal, the range is centered around the scene
+                let normal = plane.unit_normal();
+                let mut offset = normal.dot(&plane.point.coords);
+                let center = normal.dot(&centroid.coords);
+                if ui
+                    .add(Slider::new(&mut offset, (center - scale)..=(center + scale)).text("Offset"))
+                    .changed()
+                {
+                    let delta = offset - normal.dot(&plane.point.coords);
+                    plane.point += normal * delta;
+                }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut plane.cap, "Cap");
+                    if plane.cap {
+                        ui.color_edit_button_rgba_unmultiplied(&mut plane.cap_color.data.0.as_mut_slice()[0]);
+                    }
+                });
+            });
+        }
+        if let Some(entity) = removed {
+            scene.despawn(entity);
+        }
+    }
+
+    /// Draws a handle at the point of every enabled clip plane with a line
+    /// along its normal. Dragging the handle moves the plane along the normal
+    #[allow(clippy::cast_precision_loss)]
+    fn draw_clip_plane_handles(ctx: &egui::Context, scene: &Scene, screen_width: u32, screen_height: u32) {
+        let Some(cam) = scene.get_current_cam() else {
+            return;
+        };
+        if !scene.world.has::<Projection>(cam.entity).unwrap_or(false) || scene.world.query::<&ClipPlane>().iter().next().is_none() {
+            return;
+        }
+        let view = cam.view_matrix(scene);
+        let proj = cam.proj_matrix(scene);
+        let viewport_size = na::Vector2::<f32>::new(screen_width as f32, screen_height as f32);
+        let pixels_per_point = ctx.pixels_per_point();
+        let to_screen = |point: na::Point3<f32>| {
+            let point_screen = cam.project(point, view, proj, viewport_size, scene);
+            egui::pos2(
+                point_screen.x / pixels_per_point,
+                (screen_height as f32 - point_screen.y) / pixels_per_point,
+            )
+        };
+        let handle_length = 0.1 * scene.get_scale();
+        let handle_radius = 7.0;
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("clip_plane_normals")));
+
+        for (entity, mut plane) in &mut scene.world.query::<&mut ClipPlane>() {
+            //planes behind the camera have no handle
+            let in_front = (view * plane.point.to_homogeneous()).z < 0.0;
+            if !plane.enabled || !in_front {
+                continue;
+            }
+            let normal = plane.unit_normal();
+            let start = to_screen(plane.point);
+            let end = to_screen(plane.point + normal * handle_length);
+            painter.line_segment([start, end], Stroke::new(2.0, Color32::WHITE));
+
+            egui::Area::new(egui::Id::new(("clip_plane_handle", entity)))
+                .fixed_pos(start - egui::vec2(handle_radius, handle_radius))
+                .movable(false)
+                .show(ctx, |ui| {
+                    let (rect, response) = ui.allocate_exact_size(egui::vec2(2.0 * handle_radius, 2.0 * handle_radius), egui::Sense::drag());
+                    let color = if response.hovered() || response.dragged() {
+                        Color32::WHITE
+                    } else {
+                        Color32::from_gray(180)
+                    };
+                    ui.painter()
+                        .circle(rect.center(), handle_radius - 1.0, color, Stroke::new(1.5, Color32::BLACK));
+                    //only the part of the drag along the projected normal moves the plane
+                    let dir_screen = end - start;
+                    if response.dragged() && dir_screen.length_sq() > 1e-6 {
+                        let delta = response.drag_delta().dot(dir_screen) / dir_screen.length_sq() * handle_length;
+                        plane.point += normal * delta;
+                    }
+                });
+        }
+    }
+
     fn draw_plugins(&mut self, ui: &mut Ui, _scene: &mut Scene, plugins: &Plugins, _command_buffer: &mut CommandBuffer) {
         // //get all entities that are renderable and sort by name
         // let entities = scene.get_lights(true);
@@ -1599,9 +2114,194 @@ impl GuiMainWidget {
         }
     }
 
+    //computes the signed distance from the vertices of the source to the surface of the target and shows it on the source with a
+    // diverging colormap
+    fn draw_mesh_compare(&mut self, ui: &mut Ui, scene: &mut Scene, command_buffer: &mut CommandBuffer) {
+        let entities = scene.get_renderables(true);
+        let name_of = |entity: Option<Entity>| {
+            entity
+                .and_then(|e| scene.get_comp::<&Name>(&e).ok().map(|n| n.0.clone()))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        //entities might have been despawned since they were selected
+        self.compare_source = self.compare_source.filter(|e| entities.contains(e));
+        self.compare_target = self.compare_target.filter(|e| entities.contains(e));
+
+        egui::ComboBox::new(6, "Source") //the id has to be unique to other comboboxes
+            .selected_text(name_of(self.compare_source))
+            .show_ui(ui, |ui| {
+                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                ui.set_min_width(60.0);
+                for entity in entities.iter().filter(|e| scene.world.has::<Verts>(**e).unwrap_or(false)) {
+                    ui.selectable_value(&mut self.compare_source, Some(*entity), name_of(Some(*entity)));
+                }
+            });
+        egui::ComboBox::new(7, "Target")
+            .selected_text(name_of(self.compare_target))
+            .show_ui(ui, |ui| {
+                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                ui.set_min_width(60.0);
+                for entity in entities.iter().filter(|e| scene.world.has::<Faces>(**e).unwrap_or(false)) {
+                    ui.selectable_value(&mut self.compare_target, Some(*entity), name_of(Some(*entity)));
+                }
+            });
+
+        let can_compare = matches!((self.compare_source, self.compare_target), (Some(src), Some(tgt)) if src != tgt);
+        if ui.add_enabled(can_compare, egui::Button::new("Compute distance")).clicked() {
+            let (source, target) = (self.compare_source.unwrap(), self.compare_target.unwrap());
+            let (distances, stats) = Geom::mesh_distance_entities(source, target, scene);
+            //symmetric range so that zero distance is in the middle of the colormap
+            let range = stats.min_signed.abs().max(stats.max_signed.abs()).max(1e-6);
+            command_buffer.insert(
+                source,
+                (
+                    Scalars(DynamicTensorFloat2D::from_dmatrix(&distances)),
+                    VisScalars {
+                        colormap: Colormap::Coolwarm,
+                        min: -range,
+                        max: range,
+                        ..Default::default()
+                    },
+                ),
+            );
+            if let Ok(mut vis_mesh) = scene.get_comp::<&mut VisMesh>(&source) {
+                vis_mesh.color_type = MeshColorType::Scalar;
+            }
+            if let Ok(mut vis_points) = scene.get_comp::<&mut VisPoints>(&source) {
+                vis_points.color_type = PointColorType::Scalar;
+            }
+            self.compare_stats = Some(stats);
+        }
+
+        if let Some(stats) = &self.compare_stats {
+            let font = FontId::proportional(10.0);
+            ui.label(RichText::new(format!("Hausdorff: {}", float2string(stats.hausdorff, 5))).font(font.clone()));
+            ui.label(RichText::new(format!("Mean: {}", float2string(stats.mean, 5))).font(font.clone()));
+            ui.label(RichText::new(format!("RMS: {}", float2string(stats.rms, 5))).font(font.clone()));
+            ui.label(
+                RichText::new(format!(
+                    "Signed range: [{}, {}]",
+                    float2string(stats.min_signed, 5),
+                    float2string(stats.max_signed, 5)
+                ))
+                .font(font),
+            );
+        }
+    }
+
+    fn draw_recorder(&mut self, ui: &mut Ui, scene: &mut Scene, gpu: &Gpu, config: &Config) {
+        if !scene.has_resource::<Recorder>() {
+            scene.add_resource(Recorder::from_config(&config.recorder));
+        }
+        let mut recorder = scene.get_resource::<&mut Recorder>().unwrap();
+        let is_recording = recorder.is_recording();
+
+        //settings can only be changed while we are not recording
+        ui.add_enabled_ui(!is_recording, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                ui.text_edit_singleline(&mut recorder.output_path);
+            });
+            egui::ComboBox::new(2, "Format") //the id has to be unique to other comboboxes
+                .selected_text(format!("{:?}", recorder.format))
+                .show_ui(ui, |ui| {
+                    ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                    ui.set_min_width(60.0);
+                    ui.selectable_value(&mut recorder.format, RecordFormat::Png, "Png");
+                    #[cfg(feature = "record-exr")]
+                    ui.selectable_value(&mut recorder.format, RecordFormat::Exr, "Exr");
+                    ui.selectable_value(&mut recorder.format, RecordFormat::Y4m, "Y4m");
+                });
+            ui.horizontal(|ui| {
+                ui.spacing_mut().slider_width = SIDE_PANEL_WIDTH / 3.0;
+                ui.add(Slider::new(&mut recorder.fps, 1.0..=120.0).text("fps"));
+            });
+        });
+
+        if is_recording {
+            if ui.add(egui::Button::new(RichText::new("Stop").color(Color32::RED))).clicked() {
+                recorder.stop(gpu);
+            }
+            ui.label("Frames: ".to_owned() + &recorder.nr_frames_written().to_string());
+        } else if ui.add(egui::Button::new("Record")).clicked() {
+            recorder.start();
+        }
+    }
+
+    //timeline with the playback controls of the mesh sequence of the selected entity
+    fn draw_timeline(&self, ctx: &egui::Context, scene: &Scene) {
+        let Some(entity) = self.selected_entity else {
+            return;
+        };
+        let Ok(mut sequence) = scene.get_comp::<&mut MeshSequence>(&entity) else {
+            return;
+        };
+        egui::TopBottomPanel::bottom("timeline").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if sequence.playing {
+                    if ui.button("Pause").clicked() {
+                        sequence.pause();
+                    }
+                } else if ui.button("Play").clicked() {
+                    sequence.play();
+                }
+                ui.checkbox(&mut sequence.looping, "Loop");
+                ui.spacing_mut().slider_width = SIDE_PANEL_WIDTH / 2.0;
+                ui.add(Slider::new(&mut sequence.speed, 0.1..=4.0).text("speed"));
+
+                //scrubbing pauses the playback so the frame stays where it was dropped
+                let last_frame = sequence.nr_frames().saturating_sub(1);
+                let mut frame = sequence.current_frame();
+                ui.spacing_mut().slider_width = (ui.available_width() - 100.0).max(SIDE_PANEL_WIDTH);
+                if ui.add(Slider::new(&mut frame, 0..=last_frame).text("frame")).changed() {
+                    sequence.pause();
+                    sequence.set_frame(frame);
+                }
+            });
+        });
+    }
+
+    //shows the timings of the gpu scopes as a flame table where every scope is indented under the one that contains it and the bar shows
+    // its fraction of the whole frame
+    fn draw_gpu_timings(ui: &mut Ui, gpu: &Gpu) {
+        let profiler = gpu.profiler();
+        if !profiler.is_supported() {
+            ui.label("GPU timestamps are not supported on this device");
+            return;
+        }
+        let mut enabled = profiler.is_enabled();
+        if ui.checkbox(&mut enabled, "GPU timings").changed() {
+            profiler.set_enabled(enabled);
+        }
+        if !enabled {
+            return;
+        }
+
+        let timings = profiler.timings();
+        let total_ms: f32 = timings.iter().filter(|t| t.depth == 0).map(|t| t.avg_ms).sum();
+        ui.label(format!("GPU frame: {total_ms:.3} ms"));
+        egui::Grid::new("grid_gpu_timings").striped(true).show(ui, |ui| {
+            for timing in &timings {
+                ui.horizontal(|ui| {
+                    #[allow(clippy::cast_precision_loss)]
+                    ui.add_space(timing.depth as f32 * 10.0);
+                    ui.label(&timing.name);
+                });
+                let fraction = if total_ms > 0.0 { timing.avg_ms / total_ms } else { 0.0 };
+                ui.add(
+                    egui::ProgressBar::new(fraction)
+                        .desired_width(SIDE_PANEL_WIDTH / 2.0)
+                        .text(format!("{:.3} ms", timing.avg_ms)),
+                )
+                .on_hover_text(format!("min {:.3} ms, max {:.3} ms", timing.min_ms, timing.max_ms));
+                ui.end_row();
+            }
+        });
+    }
+
     #[allow(clippy::too_many_lines)]
     #[allow(unused_variables)]
-    fn draw_profiling(&mut self, ui: &mut Ui, _scene: &mut Scene, _command_buffer: &mut CommandBuffer) {
+    fn draw_profiling(&mut self, ui: &mut Ui, _scene: &mut Scene, gpu: &Gpu, _command_buffer: &mut CommandBuffer) {
         // TODO: Keep or remove?
         // cfg_if::cfg_if! {
         //     if #[cfg(feature = "peak-alloc")] {
@@ -1667,6 +2367,10 @@ impl GuiMainWidget {
             re_memory::accounting_allocator::set_tracking_callstacks(is_tracking);
         }
 
+        ui.add_space(10.0);
+        Self::draw_gpu_timings(ui, gpu);
+        ui.add_space(10.0);
+
         // //make a memory bar where we show all the memory and the parts that are free in
         // // red, parts that are allocated in red only works in wasm because it
         // // uses a linear memory model
@@ -0,0 +1,217 @@
+use na::DMatrix;
+use nalgebra as na;
+
+/// Static kd-tree over a set of 3D points for nearest neighbour and radius
+/// queries. The tree is stored implicitly: every range of points is split at
+/// its median along the axis of largest spread and the median point is the
+/// node itself
+pub struct KdTree {
+    points: Vec<na::Point3<f32>>,
+    //index of every point in the original matrix
+    indices: Vec<u32>,
+    //split axis of the node stored at the same position as the point
+    axes: Vec<u8>,
+}
+
+impl KdTree {
+    /// Builds the tree from points of size Nx3
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(verts: &DMatrix<f32>) -> Self {
+        let mut tree = Self {
+            points: verts.row_iter().map(|v| na::Point3::new(v[0], v[1], v[2])).collect(),
+            indices: (0..verts.nrows() as u32).collect(),
+            axes: vec![0; verts.nrows()],
+        };
+        let mut stack = vec![(0, tree.points.len())];
+        while let Some((lo, hi)) = stack.pop() {
+            if hi <= lo {
+                continue;
+            }
+            let mut min = na::Point3::new(f32::MAX, f32::MAX, f32::MAX);
+            let mut max = na::Point3::new(f32::MIN, f32::MIN, f32::MIN);
+            for p in &tree.points[lo..hi] {
+                min = min.inf(p);
+                max = max.sup(p);
+            }
+            let axis = (max - min).imax();
+            let mid = lo + (hi - lo) / 2;
+            //sort the points together with their indices
+            let mut order: Vec<(na::Point3<f32>, u32)> = tree.points[lo..hi].iter().copied().zip(tree.indices[lo..hi].iter().copied()).collect();
+            order.select_nth_unstable_by(mid - lo, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+            for (i, (p, idx)) in order.into_iter().enumerate() {
+                tree.points[lo + i] = p;
+                tree.indices[lo + i] = idx;
+            }
+            tree.axes[mid] = axis as u8;
+            stack.extend([(lo, mid), (mid + 1, hi)]);
+        }
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The `k` points closest to the query as pairs of (index, squared
+    /// distance), sorted from closest to furthest. The query point itself is
+    /// included if it's part of the tree
+    pub fn knn(&self, point: &na::Point3<f32>, k: usize) -> Vec<(u32, f32)> {
+        let mut result: Vec<(u32, f32)> = Vec::with_capacity(k + 1);
+        if k > 0 {
+            self.knn_inner(0, self.points.len(), point, k, &mut result);
+        }
+        result
+    }
+
+    /// Indices of all the points within `radius` of the query, in no
+    /// particular order
+    pub fn within_radius(&self, point: &na::Point3<f32>, radius: f32) -> Vec<u32> {
+        let mut result = Vec::new();
+        self.radius_inner(0, self.points.len(), point, radius * radius, &mut result);
+        result
+    }
+
+    fn knn_inner(&self, lo: usize, hi: usize, point: &na::Point3<f32>, k: usize, result: &mut Vec<(u32, f32)>) {
+        if hi <= lo {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let dist2 = (self.points[mid] - point).norm_squared();
+        if result.len() < k || dist2 < result[result.len() - 1].1 {
+            let pos = result.partition_point(|(_, d)| *d <= dist2);
+            result.insert(pos, (self.indices[mid], dist2));
+            result.truncate(k);
+        }
+        let axis = self.axes[mid] as usize;
+        let diff = point[axis] - self.points[mid][axis];
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.knn_inner(near.0, near.1, point, k, result);
+        //the other side can only contain closer points if the splitting plane is closer than the worst one
+        if result.len() < k || diff * diff < result[result.len() - 1].1 {
+            self.knn_inner(far.0, far.1, point, k, result);
+        }
+    }
+
+    fn radius_inner(&self, lo: usize, hi: usize, point: &na::Point3<f32>, radius2: f32, result: &mut Vec<u32>) {
+        if hi <= lo {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if (self.points[mid] - point).norm_squared() <= radius2 {
+            result.push(self.indices[mid]);
+        }
+        let axis = self.axes[mid] as usize;
+        let diff = point[axis] - self.points[mid][axis];
+        if diff <= 0.0 || diff * diff <= radius2 {
+            self.radius_inner(lo, mid, point, radius2, result);
+        }
+        if diff >= 0.0 || diff * diff <= radius2 {
+            self.radius_inner(mid + 1, hi, point, radius2, result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::{Geom, PointNeighbourhood};
+
+    #[allow(clippy::cast_precision_loss)]
+    fn points() -> DMatrix<f32> {
+        DMatrix::<f32>::from_fn(300, 3, |r, c| ((r * 31 + c * 17) % 97) as f32 * 0.01 + (r as f32 * 0.13 + c as f32).cos())
+    }
+
+    #[test]
+    fn knn_matches_brute_force() {
+        let verts = points();
+        let tree = KdTree::new(&verts);
+        for q in [
+            na::Point3::new(0.1, 0.5, -0.2),
+            na::Point3::new(1.0, 1.0, 1.0),
+            na::Point3::new(-3.0, 0.0, 0.0),
+        ] {
+            let mut expected: Vec<(u32, f32)> = verts
+                .row_iter()
+                .enumerate()
+                .map(|(i, v)| (u32::try_from(i).unwrap(), (na::Point3::new(v[0], v[1], v[2]) - q).norm_squared()))
+                .collect();
+            expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let knn = tree.knn(&q, 8);
+            let expected_dists: Vec<f32> = expected[..8].iter().map(|(_, d)| *d).collect();
+            let knn_dists: Vec<f32> = knn.iter().map(|(_, d)| *d).collect();
+            assert_eq!(expected_dists, knn_dists);
+        }
+    }
+
+    #[test]
+    fn radius_matches_brute_force() {
+        let verts = points();
+        let tree = KdTree::new(&verts);
+        let q = na::Point3::new(0.5, 0.5, 0.5);
+        let mut expected: Vec<u32> = verts
+            .row_iter()
+            .enumerate()
+            .filter(|(_, v)| (na::Point3::new(v[0], v[1], v[2]) - q).norm_squared() <= 0.6 * 0.6)
+            .map(|(i, _)| u32::try_from(i).unwrap())
+            .collect();
+        let mut found = tree.within_radius(&q, 0.6);
+        expected.sort_unstable();
+        found.sort_unstable();
+        assert_eq!(expected, found);
+    }
+
+    //10x10 grid with a spacing of 0.1 on the plane z=0
+    #[allow(clippy::cast_precision_loss)]
+    fn plane_points() -> DMatrix<f32> {
+        DMatrix::<f32>::from_fn(100, 3, |r, c| match c {
+            0 => (r % 10) as f32 * 0.1,
+            1 => (r / 10) as f32 * 0.1,
+            _ => 0.0,
+        })
+    }
+
+    #[test]
+    fn point_normals_face_the_viewpoint() {
+        let verts = plane_points();
+        for viewpoint_z in [5.0, -5.0] {
+            let viewpoint = na::Point3::new(0.45, 0.45, viewpoint_z);
+            let normals = Geom::estimate_point_normals(&verts, PointNeighbourhood::Knn(8), &viewpoint);
+            let expected = na::RowVector3::new(0.0, 0.0, f32::signum(viewpoint_z));
+            for normal in normals.row_iter() {
+                assert!((normal - expected).norm() < 1e-4, "normal {normal} is not {expected}");
+            }
+        }
+    }
+
+    #[test]
+    fn voxel_downsample_averages_points_and_attributes() {
+        let verts = DMatrix::<f32>::from_row_slice(3, 3, &[0.1, 0.1, 0.1, 0.3, 0.5, 0.1, 1.5, 0.2, 0.2]);
+        let colors = DMatrix::<f32>::from_row_slice(3, 3, &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0]);
+        let (verts_down, attribs_down) = Geom::voxel_downsample(&verts, 1.0, &[&colors]);
+        //the first two points share a voxel and the voxels keep the order in which they are first seen
+        let expected_verts = DMatrix::<f32>::from_row_slice(2, 3, &[0.2, 0.3, 0.1, 1.5, 0.2, 0.2]);
+        let expected_colors = DMatrix::<f32>::from_row_slice(2, 3, &[0.5, 0.0, 0.5, 0.0, 1.0, 0.0]);
+        assert!((verts_down - expected_verts).norm() < 1e-6);
+        assert_eq!(attribs_down.len(), 1);
+        assert!((&attribs_down[0] - expected_colors).norm() < 1e-6);
+    }
+
+    #[test]
+    fn statistical_outlier_mask_removes_injected_outlier() {
+        let plane = plane_points();
+        let mut verts = plane.clone().insert_row(plane.nrows(), 0.0);
+        verts.row_mut(plane.nrows()).copy_from_slice(&[10.0, 10.0, 10.0]);
+        let mask = Geom::statistical_outlier_mask(&verts, 8, 2.0);
+        assert_eq!(mask.len(), verts.nrows());
+        assert!(!mask[plane.nrows()], "the outlier was kept");
+        assert!(mask[..plane.nrows()].iter().all(|keep| *keep), "a point of the plane was removed");
+    }
+}
@@ -14,6 +14,7 @@ use re_memory::{AccountingAllocator, MemoryUse};
 static GLOBAL: AccountingAllocator<std::alloc::System> = AccountingAllocator::new(std::alloc::System);
 
 pub mod actor;
+pub mod bvh;
 pub mod camera;
 pub mod components;
 pub mod config;
@@ -21,10 +22,16 @@ pub mod forward_renderer;
 pub mod geom;
 #[cfg(feature = "with-gui")]
 pub mod gui;
+pub mod kdtree;
 pub mod light;
 pub mod logger;
 pub mod plugin_manager;
+pub mod recorder;
 pub mod scene;
+pub mod sequence;
+#[cfg(test)]
+mod test_utils;
+pub mod topology;
 pub mod viewer;
 pub mod viewer_headless;
 
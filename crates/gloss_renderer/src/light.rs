@@ -44,6 +44,7 @@ impl Light {
         let proj = scene.get_comp::<&Projection>(&self.entity).unwrap();
         match *proj {
             Projection::WithFov(ref proj) => proj.proj_matrix(),
+            Projection::Orthographic(ref proj) => proj.proj_matrix(),
             Projection::WithIntrinsics(_) => {
                 panic!("We don't deal with light that have projection as intrinsics")
             }
@@ -57,6 +58,7 @@ impl Light {
         let proj = scene.get_comp::<&Projection>(&self.entity).unwrap();
         match *proj {
             Projection::WithFov(ref proj) => proj.proj_matrix_reverse_z(),
+            Projection::Orthographic(ref proj) => proj.proj_matrix_reverse_z(),
             Projection::WithIntrinsics(_) => {
                 panic!("We don't deal with light that have projection as intrinsics")
             }
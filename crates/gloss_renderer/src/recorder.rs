@@ -0,0 +1,386 @@
+use crate::config::{RecordFormat, RecorderConfig};
+
+use easy_wgpu::{gpu::Gpu, texture::Texture};
+use gloss_img::DynImage;
+use gloss_utils::numerical;
+use image::RgbaImage;
+use log::{error, info, warn};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Staging buffer into which a rendered frame is copied so that it can later
+/// be mapped and read on the CPU
+struct ReadbackSlot {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    bytes_per_row_unpadded: u32,
+    bytes_per_row_padded: u32,
+    pending_frame: Option<u32>, //index of the frame that was copied into this buffer and still needs to be written to disk
+    is_mapped: Arc<AtomicBool>, //gets set by the map_async callback once the buffer can be read
+}
+impl ReadbackSlot {
+    fn new(gpu: &Gpu, tex: &Texture) -> Self {
+        let width = tex.width();
+        let height = tex.height();
+        let format = tex.texture.format();
+        let bytes_per_row_unpadded = format.block_copy_size(None).unwrap() * width;
+        let bytes_per_row_padded = numerical::align(bytes_per_row_unpadded, wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = gpu.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("recorder_readback"),
+            size: u64::from(bytes_per_row_padded * height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer,
+            width,
+            height,
+            format,
+            bytes_per_row_unpadded,
+            bytes_per_row_padded,
+            pending_frame: None,
+            is_mapped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn matches(&self, tex: &Texture) -> bool {
+        self.width == tex.width() && self.height == tex.height() && self.format == tex.texture.format()
+    }
+}
+
+/// Records consecutive frames rendered by [`Viewer`](crate::viewer::Viewer)
+/// or [`ViewerHeadless`](crate::viewer_headless::ViewerHeadless) into a
+/// numbered PNG/EXR image sequence or into a Y4M video. The recorder is stored
+/// as a resource in the scene so it can be controlled from code, from the GUI
+/// or from python. While recording, the viewers override the dt of every frame
+/// with `1/fps` so that the recording plays back at the correct speed
+/// independently of how long each frame took to render and save.
+///
+/// The readback is double-buffered: the copy of frame N is only mapped and
+/// written to disk after frame N+1 has been submitted, so we don't need to
+/// stall and wait for the GPU after every frame.
+pub struct Recorder {
+    pub output_path: String, //directory for image sequences or file path for y4m
+    pub format: RecordFormat,
+    pub fps: f32,
+    is_recording: bool,
+    nr_frames_captured: u32,
+    nr_frames_written: u32,
+    slots: [Option<ReadbackSlot>; 2],
+    cur_slot: usize, //slot which the next captured frame will be copied into
+    y4m_writer: Option<BufWriter<File>>,
+    y4m_size: (u32, u32),
+}
+
+impl Recorder {
+    pub fn new(output_path: &str, format: RecordFormat, fps: f32) -> Self {
+        Self {
+            output_path: output_path.to_string(),
+            format,
+            fps,
+            is_recording: false,
+            nr_frames_captured: 0,
+            nr_frames_written: 0,
+            slots: [None, None],
+            cur_slot: 0,
+            y4m_writer: None,
+            y4m_size: (0, 0),
+        }
+    }
+
+    pub fn from_config(config: &RecorderConfig) -> Self {
+        Self::new(&config.output_path, config.format, config.fps)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    /// Number of frames that were written to disk since the last call to
+    /// [`Recorder::start`]
+    pub fn nr_frames_written(&self) -> u32 {
+        self.nr_frames_written
+    }
+
+    /// Time in seconds that passes between two recorded frames
+    pub fn frame_dt(&self) -> f32 {
+        1.0 / self.fps
+    }
+
+    /// Starts a new recording. Image sequences are written inside the
+    /// `output_path` directory which is created if it doesn't exist.
+    pub fn start(&mut self) {
+        if self.is_recording {
+            warn!("Recorder is already recording, ignoring start()");
+            return;
+        }
+        if self.fps <= 0.0 {
+            error!("Recorder fps has to be positive but it is {}", self.fps);
+            return;
+        }
+        if self.format == RecordFormat::Exr && !cfg!(feature = "record-exr") {
+            error!("Recording EXR sequences needs the record-exr feature of gloss-renderer");
+            return;
+        }
+        let dir = match self.format {
+            RecordFormat::Png | RecordFormat::Exr => PathBuf::from(&self.output_path),
+            RecordFormat::Y4m => self.y4m_path().parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+        if !dir.as_os_str().is_empty() {
+            if let Err(e) = fs::create_dir_all(&dir) {
+                error!("Recorder could not create directory {dir:?}: {e}");
+                return;
+            }
+        }
+
+        self.nr_frames_captured = 0;
+        self.nr_frames_written = 0;
+        self.cur_slot = 0;
+        self.y4m_writer = None;
+        self.is_recording = true;
+        info!("Started recording {:?} at {} fps to {}", self.format, self.fps, self.output_path);
+    }
+
+    /// Stops the recording, writes to disk the frames that are still in flight
+    /// and finalizes the video file.
+    pub fn stop(&mut self, gpu: &Gpu) {
+        if !self.is_recording {
+            return;
+        }
+        //if both slots are pending, the current one holds the older frame
+        for idx in [self.cur_slot, 1 - self.cur_slot] {
+            self.finish_slot(gpu, idx);
+        }
+        if let Some(mut writer) = self.y4m_writer.take() {
+            if let Err(e) = writer.flush() {
+                error!("Recorder could not finalize the video: {e}");
+            }
+        }
+        self.slots = [None, None]; //release the staging memory
+        self.is_recording = false;
+        info!("Stopped recording. Wrote {} frames to {}", self.nr_frames_written, self.output_path);
+    }
+
+    /// Copies the texture into a staging buffer and writes the previously
+    /// captured frame to disk. Does nothing if we are not recording.
+    /// # Panics
+    /// Will panic if the format of the texture cannot be converted to an image
+    pub fn capture(&mut self, gpu: &Gpu, tex: &Texture) {
+        if !self.is_recording {
+            return;
+        }
+
+        //the slot we copy into has already been read back, so we can freely recreate it in case the rendered texture changed size
+        let idx = self.cur_slot;
+        if !self.slots[idx].as_ref().map_or(false, |slot| slot.matches(tex)) {
+            self.slots[idx] = Some(ReadbackSlot::new(gpu, tex));
+        }
+
+        let frame_idx = self.nr_frames_captured;
+        {
+            let slot = self.slots[idx].as_mut().unwrap();
+            let mut encoder = gpu.device().create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Recorder Encoder"),
+            });
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    aspect: wgpu::TextureAspect::All,
+                    texture: &tex.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &slot.buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(slot.bytes_per_row_padded),
+                        rows_per_image: Some(slot.height),
+                    },
+                },
+                tex.extent(),
+            );
+            gpu.queue().submit(Some(encoder.finish()));
+
+            let is_mapped = slot.is_mapped.clone();
+            slot.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| match result {
+                Ok(()) => is_mapped.store(true, Ordering::Release),
+                Err(e) => error!("Recorder could not map the readback buffer: {e}"),
+            });
+            slot.pending_frame = Some(frame_idx);
+        }
+        self.nr_frames_captured += 1;
+
+        //let the copy progress without blocking and then read the previous frame which is most likely finished by now
+        gpu.device().poll(wgpu::Maintain::Poll);
+        let prev = 1 - idx;
+        self.finish_slot(gpu, prev);
+        self.cur_slot = prev;
+    }
+
+    /// Waits for the frame in the slot to be mapped, reads it and writes it to
+    /// disk. Does nothing if the slot has no pending frame.
+    fn finish_slot(&mut self, gpu: &Gpu, idx: usize) {
+        let Some(slot) = self.slots[idx].as_mut() else {
+            return;
+        };
+        let Some(frame_idx) = slot.pending_frame.take() else {
+            return;
+        };
+        if !slot.is_mapped.load(Ordering::Acquire) {
+            gpu.device().poll(wgpu::Maintain::Wait);
+        }
+        if !slot.is_mapped.load(Ordering::Acquire) {
+            error!("Recorder skipped frame {frame_idx} because the readback buffer could not be mapped");
+            return;
+        }
+
+        let img = {
+            let data = slot.buffer.slice(..).get_mapped_range();
+            let data_unpadded = Texture::remove_padding(&data, slot.bytes_per_row_unpadded, slot.bytes_per_row_padded, slot.height);
+            Texture::img_from_bytes(&data_unpadded, slot.width, slot.height, slot.format)
+        };
+        slot.buffer.unmap();
+        slot.is_mapped.store(false, Ordering::Release);
+
+        self.write_frame(&img, frame_idx);
+    }
+
+    fn write_frame(&mut self, img: &DynImage, frame_idx: u32) {
+        let res = match self.format {
+            RecordFormat::Png => DynImage::ImageRgba8(img.to_rgba8())
+                .save(self.frame_path(frame_idx, "png"))
+                .map_err(|e| e.to_string()),
+            RecordFormat::Exr => DynImage::ImageRgba32F(img.to_rgba32f())
+                .save(self.frame_path(frame_idx, "exr"))
+                .map_err(|e| e.to_string()),
+            RecordFormat::Y4m => self.write_y4m_frame(&img.to_rgba8()).map_err(|e| e.to_string()),
+        };
+        match res {
+            Ok(()) => self.nr_frames_written += 1,
+            Err(e) => error!("Recorder could not write frame {frame_idx}: {e}"),
+        }
+    }
+
+    fn frame_path(&self, frame_idx: u32, extension: &str) -> PathBuf {
+        Path::new(&self.output_path).join(format!("frame_{frame_idx:06}.{extension}"))
+    }
+
+    fn y4m_path(&self) -> PathBuf {
+        Path::new(&self.output_path).with_extension("y4m")
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn write_y4m_frame(&mut self, img: &RgbaImage) -> io::Result<()> {
+        let (w, h) = img.dimensions();
+        if self.y4m_writer.is_none() {
+            let mut writer = BufWriter::new(File::create(self.y4m_path())?);
+            //fps is stored as a ratio so we keep 3 decimals of it
+            let fps_num = (self.fps * 1000.0).round() as u32;
+            writeln!(writer, "YUV4MPEG2 W{w} H{h} F{fps_num}:1000 Ip A1:1 C444")?;
+            self.y4m_writer = Some(writer);
+            self.y4m_size = (w, h);
+        }
+        if self.y4m_size != (w, h) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frame has size {w}x{h} but the video has size {}x{}", self.y4m_size.0, self.y4m_size.1),
+            ));
+        }
+        let writer = self.y4m_writer.as_mut().unwrap();
+        writer.write_all(b"FRAME\n")?;
+        writer.write_all(&rgba_to_yuv444(img))?;
+        Ok(())
+    }
+}
+
+/// Converts to planar YCbCr using BT.601 with limited range which is what
+/// players assume by default for Y4M
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn rgba_to_yuv444(img: &RgbaImage) -> Vec<u8> {
+    let nr_pixels = (img.width() * img.height()) as usize;
+    let mut planes = vec![0u8; nr_pixels * 3];
+    let (y_plane, uv_planes) = planes.split_at_mut(nr_pixels);
+    let (u_plane, v_plane) = uv_planes.split_at_mut(nr_pixels);
+    for (i, p) in img.pixels().enumerate() {
+        let r = f32::from(p[0]) / 255.0;
+        let g = f32::from(p[1]) / 255.0;
+        let b = f32::from(p[2]) / 255.0;
+        y_plane[i] = (16.0 + 65.481 * r + 128.553 * g + 24.966 * b).round() as u8;
+        u_plane[i] = (128.0 - 37.797 * r - 74.203 * g + 112.0 * b).round() as u8;
+        v_plane[i] = (128.0 + 112.0 * r - 93.786 * g - 18.214 * b).round() as u8;
+    }
+    planes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// Empty directory in the system temp dir that is unique to the test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gloss_recorder_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn record_two_frames(recorder: &mut Recorder) {
+        recorder.start();
+        assert!(recorder.is_recording());
+        let img = DynImage::ImageRgba8(RgbaImage::from_pixel(4, 2, Rgba([255, 128, 0, 255])));
+        recorder.write_frame(&img, 0);
+        recorder.write_frame(&img, 1);
+        assert_eq!(recorder.nr_frames_written(), 2);
+    }
+
+    #[test]
+    fn y4m_has_header_and_frames() {
+        let dir = test_dir("y4m");
+        let mut recorder = Recorder::new(dir.join("video").to_str().unwrap(), RecordFormat::Y4m, 30.0);
+        record_two_frames(&mut recorder);
+        recorder.y4m_writer.take().unwrap().flush().unwrap();
+
+        let data = fs::read(dir.join("video.y4m")).unwrap();
+        let header = b"YUV4MPEG2 W4 H2 F30000:1000 Ip A1:1 C444\n";
+        assert!(data.starts_with(header));
+        //every frame is a FRAME marker followed by the 3 full resolution planes
+        let frame_size = b"FRAME\n".len() + 4 * 2 * 3;
+        assert_eq!(data.len(), header.len() + 2 * frame_size);
+        assert!(data[header.len()..].starts_with(b"FRAME\n"));
+        assert!(data[header.len() + frame_size..].starts_with(b"FRAME\n"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn png_sequence_is_numbered() {
+        let dir = test_dir("png");
+        let mut recorder = Recorder::new(dir.to_str().unwrap(), RecordFormat::Png, 30.0);
+        record_two_frames(&mut recorder);
+        assert!(dir.join("frame_000000.png").is_file());
+        assert!(dir.join("frame_000001.png").is_file());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "record-exr")]
+    fn exr_sequence_is_numbered() {
+        let dir = test_dir("exr");
+        let mut recorder = Recorder::new(dir.to_str().unwrap(), RecordFormat::Exr, 30.0);
+        record_two_frames(&mut recorder);
+        assert!(dir.join("frame_000000.exr").is_file());
+        assert!(dir.join("frame_000001.exr").is_file());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
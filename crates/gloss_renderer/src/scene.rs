@@ -1,15 +1,17 @@
 #![allow(clippy::doc_markdown)]
 
-use gloss_hecs::{CommandBuffer, Component, ComponentRef, DynamicBundle, Entity, EntityBuilder, World};
-use log::{error, trace};
+use gloss_hecs::{Changed, CommandBuffer, Component, ComponentRef, DynamicBundle, Entity, EntityBuilder, World};
+use log::{error, trace, warn};
 
 use crate::{
     actor::Actor,
+    bvh::{Bvh, ClosestPoint, Hit},
     camera::Camera,
     components::{
-        CamController, ColorsGPU, DiffuseImg, DiffuseTex, EdgesGPU, EnvironmentMapGpu, FacesGPU, ImgConfig, LightEmit, MeshColorType, MetalnessTex,
-        ModelMatrix, Name, NormalTex, NormalsGPU, PosLookat, Projection, ProjectionWithFov, Renderable, RoughnessTex, ShadowCaster, ShadowMap,
-        TangentsGPU, UVsGPU, Verts, VertsGPU, VisLines, VisMesh, VisPoints,
+        CamController, Children, ColorsGPU, DiffuseImg, DiffuseTex, EdgesGPU, EnvironmentMapGpu, Faces, FacesGPU, ImgConfig, InputView, LightEmit,
+        LocalModelMatrix, LocalPosLookat, MeshBvh, MeshColorType, MetalnessTex, ModelMatrix, Name, NormalTex, NormalsGPU, Parent, PosLookat,
+        Projection, ProjectionWithFov, Renderable, RoughnessTex, ScalarsGPU, ShadowCaster, ShadowMap, TangentsGPU, TargetResolution, UVsGPU,
+        UndistortedBounds, Verts, VertsGPU, Viewport, VisLines, VisMesh, VisPoints,
     },
     config::{Config, FloorTexture, FloorType, LightConfig},
     geom::Geom,
@@ -20,6 +22,7 @@ use gloss_utils::abi_stable_aliases::std_types::{RHashMap, RString};
 use gloss_utils::abi_stable_aliases::StableAbi;
 use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorOps};
 use nalgebra as na;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub static GLOSS_FLOOR_NAME: &str = "floor";
 pub static GLOSS_CAM_NAME: &str = "gloss_camera";
@@ -99,18 +102,21 @@ impl Scene {
         EntityMut::new(&mut self.world, *entity_ref)
     }
 
-    /// Despawns entity with a certain name and all it's components
+    /// Despawns entity with a certain name and all it's components together
+    /// with all of its children
     pub fn despawn_with_name(&mut self, name: &str) {
-        //get the entity from the world if there is one, despawn it from the world and
-        // remove it from our internal hashmap
         if let Some(entity) = self.get_entity_with_name(name) {
-            let _ = self.world.despawn(entity);
-            let r_name = RString::from(name.to_string());
-            let _ = self.name2entity.remove(&r_name);
+            self.despawn(entity);
         }
     }
 
+    /// Despawns the entity and recursively all of its children
     pub fn despawn(&mut self, entity: Entity) {
+        for child in self.get_children(entity) {
+            self.despawn(child);
+        }
+        self.remove_parent(entity);
+
         //if the entity has a name get it so we can remove it form our hashmap of
         // name2entity
         let name = self.get_comp::<&Name>(&entity).map(|x| RString::from(x.0.to_string()));
@@ -220,6 +226,50 @@ impl Scene {
         entities
     }
 
+    /// Returns the cameras that have a [`Viewport`] and are therefore rendered
+    /// as separate views. When sorting, the named cameras come first sorted by
+    /// name followed by the unnamed ones sorted by entity id
+    pub fn get_views(&self, sorted_by_name: bool) -> Vec<Entity> {
+        let mut entities_with_name = Vec::new();
+        for (entity_cam, (_, name)) in self.world.query::<(&Viewport, Option<&Name>)>().iter() {
+            entities_with_name.push((entity_cam, name.map(|name| name.0.clone())));
+        }
+        //sort by name
+        if sorted_by_name {
+            entities_with_name.sort_by(|a, b| match (&a.1, &b.1) {
+                (Some(name_a), Some(name_b)) => name_a.cmp(name_b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => a.0.id().cmp(&b.0.id()),
+            });
+        }
+        let entities = entities_with_name.iter().map(|x| x.0).collect();
+        entities
+    }
+
+    pub fn has_views(&self) -> bool {
+        self.world.query::<&Viewport>().iter().next().is_some()
+    }
+
+    /// Returns the camera that should receive mouse and touch input. This is
+    /// the view selected by the [`InputView`] resource. If there is no valid
+    /// selection it's the current camera, unless the current camera is not
+    /// rendered because it's not one of the views, in which case it's the
+    /// first view.
+    pub fn get_input_cam(&self) -> Option<Camera> {
+        if let Ok(input_view) = self.get_resource::<&InputView>() {
+            if self.world.has::<Viewport>(input_view.entity).unwrap_or(false) {
+                return Some(Camera::from_entity(input_view.entity));
+            }
+        }
+        let cur_cam = self.get_current_cam();
+        let views = self.get_views(true);
+        match cur_cam {
+            Some(cam) if views.is_empty() || views.contains(&cam.entity) => Some(cam),
+            _ => views.first().map(|entity| Camera::from_entity(*entity)).or(cur_cam),
+        }
+    }
+
     #[allow(clippy::missing_panics_doc)]
     pub fn get_renderables(&self, sorted_by_name: bool) -> Vec<Entity> {
         let mut entities_with_name = Vec::new();
@@ -271,17 +321,14 @@ impl Scene {
         let mut min_point_global = na::Point3::<f32>::new(f32::MAX, f32::MAX, f32::MAX);
         let mut max_point_global = na::Point3::<f32>::new(f32::MIN, f32::MIN, f32::MIN);
 
-        for (_entity, (verts, model_matrix_opt, name, _)) in self.world.query::<(&Verts, Option<&ModelMatrix>, &Name, &Renderable)>().iter() {
+        for (entity, (verts, name, _)) in self.world.query::<(&Verts, &Name, &Renderable)>().iter() {
             if name.0 == GLOSS_FLOOR_NAME {
                 continue;
             }
 
-            //Some meshes may not have a model matrix yet because the prepass hasn't run
-            let model_matrix = if let Some(mm) = model_matrix_opt {
-                mm.clone()
-            } else {
-                ModelMatrix::default()
-            };
+            //Some meshes may not have a model matrix yet because the prepass hasn't run and the ones with a parent might not have been
+            // propagated yet, so we compose it from the hierarchy
+            let model_matrix = ModelMatrix(self.get_world_matrix(entity));
 
             trace!("scale for mesh {}", name.0);
 
@@ -309,16 +356,12 @@ impl Scene {
     pub fn get_min_y(&self) -> f32 {
         let mut min_y_global = f32::MAX;
 
-        for (_entity, (verts, model_matrix_opt, name, _)) in self.world.query::<(&Verts, Option<&ModelMatrix>, &Name, &Renderable)>().iter() {
+        for (entity, (verts, name, _)) in self.world.query::<(&Verts, &Name, &Renderable)>().iter() {
             if name.0 == GLOSS_FLOOR_NAME {
                 continue;
             }
 
-            let model_matrix = if let Some(mm) = model_matrix_opt {
-                mm.clone()
-            } else {
-                ModelMatrix::default()
-            };
+            let model_matrix = ModelMatrix(self.get_world_matrix(entity));
 
             //get min and max vertex in obj coords
             let v_world = Geom::transform_verts(&verts.0.to_dmatrix(), &model_matrix.0);
@@ -639,6 +682,397 @@ impl Scene {
         ent_opt.map(|ent| Actor::from_entity(*ent))
     }
 
+    /// Caches the [`UndistortedBounds`] of the cameras with lens distortion
+    /// and recomputes them when their intrinsics or target resolution change
+    pub fn update_undistorted_bounds(&mut self) {
+        let mut command_buffer = CommandBuffer::new();
+        for (entity, (proj, target_res, bounds)) in self.world.query_mut::<(&Projection, &TargetResolution, Option<&UndistortedBounds>)>() {
+            let Projection::WithIntrinsics(intrinsics) = proj else {
+                continue;
+            };
+            if intrinsics.distortion.is_none() || bounds.map_or(false, |b| b.is_valid_for(intrinsics, target_res.width, target_res.height)) {
+                continue;
+            }
+            command_buffer.insert_one(entity, UndistortedBounds::new(intrinsics, target_res.width, target_res.height));
+        }
+        command_buffer.run_on(&mut self.world);
+    }
+
+    /// Marks the bvhs of meshes whose vertices or faces changed since the last
+    /// time the trackers were cleared so that the next query refits or
+    /// rebuilds them
+    pub fn mark_changed_bvhs(&mut self) {
+        for (_entity, (mut mesh_bvh, changed_verts, changed_faces)) in self.world.query_mut::<(&mut MeshBvh, Changed<Verts>, Changed<Faces>)>() {
+            mesh_bvh.needs_refit |= changed_verts;
+            mesh_bvh.needs_rebuild |= changed_faces;
+        }
+    }
+
+    /// Builds the bvh of the visible renderables that don't have one yet and
+    /// refits or rebuilds the ones whose vertices or faces changed. The bvh is
+    /// in object coordinates so changing the [`ModelMatrix`] doesn't
+    /// invalidate it. Calling this before [`Scene::raycast`] or
+    /// [`Scene::closest_point`] caches the bvhs so that they are not built
+    /// again for every query
+    pub fn update_bvhs(&mut self) {
+        self.mark_changed_bvhs();
+        let mut command_buffer = CommandBuffer::new();
+        for (entity, (verts, faces, mesh_bvh, vis_mesh)) in self
+            .world
+            .query_mut::<(&Verts, &Faces, Option<&mut MeshBvh>, Option<&VisMesh>)>()
+            .with::<&Renderable>()
+        {
+            if vis_mesh.map_or(false, |v| !v.show_mesh) {
+                continue;
+            }
+            match mesh_bvh {
+                Some(mut mesh_bvh)
+                    if !mesh_bvh.needs_rebuild && mesh_bvh.bvh.nr_verts() == verts.0.nrows() && mesh_bvh.bvh.nr_faces() == faces.0.nrows() =>
+                {
+                    if mesh_bvh.needs_refit {
+                        mesh_bvh.bvh.refit(&verts.0.to_dmatrix());
+                        mesh_bvh.needs_refit = false;
+                    }
+                }
+                _ => command_buffer.insert_one(entity, MeshBvh::new(Bvh::new(&verts.0.to_dmatrix(), &faces.0.to_dmatrix()))),
+            }
+        }
+        command_buffer.run_on(&mut self.world);
+    }
+
+    /// Calls `f` with the bvh of every visible renderable mesh. Meshes whose
+    /// bvh is missing or outdated get a temporary one which is only built for
+    /// this call, [`Scene::update_bvhs`] caches them instead
+    fn for_each_mesh_bvh(&self, mut f: impl FnMut(Entity, &Bvh, Option<&ModelMatrix>)) {
+        let mut query = self
+            .world
+            .query::<(
+                &Verts,
+                &Faces,
+                Option<&MeshBvh>,
+                Option<&ModelMatrix>,
+                Option<&VisMesh>,
+                Changed<Verts>,
+                Changed<Faces>,
+            )>()
+            .with::<&Renderable>();
+        for (entity, (verts, faces, mesh_bvh, model_matrix, vis_mesh, changed_verts, changed_faces)) in query.iter() {
+            if vis_mesh.map_or(false, |v| !v.show_mesh) {
+                continue;
+            }
+            let up_to_date = mesh_bvh.filter(|b| {
+                !changed_verts
+                    && !changed_faces
+                    && !b.needs_refit
+                    && !b.needs_rebuild
+                    && b.bvh.nr_verts() == verts.0.nrows()
+                    && b.bvh.nr_faces() == faces.0.nrows()
+            });
+            if let Some(mesh_bvh) = up_to_date {
+                f(entity, &mesh_bvh.bvh, model_matrix);
+            } else {
+                f(entity, &Bvh::new(&verts.0.to_dmatrix(), &faces.0.to_dmatrix()), model_matrix);
+            }
+        }
+    }
+
+    /// Casts a ray in world coordinates against the triangles of all visible
+    /// renderables and returns the closest hit. The direction doesn't need to
+    /// be normalized, `t` is measured in units of it
+    pub fn raycast(&self, origin: &na::Point3<f32>, dir: &na::Vector3<f32>) -> Option<Hit> {
+        let mut closest: Option<Hit> = None;
+        self.for_each_mesh_bvh(|entity, bvh, model_matrix| {
+            let tf_obj_world = model_matrix.map_or_else(na::SimilarityMatrix3::identity, |mm| mm.0.inverse());
+            //the direction is not normalized in object space so t is the same in both spaces
+            let origin_obj = tf_obj_world * origin;
+            let dir_obj = tf_obj_world * dir;
+            let t_max = closest.map_or(f32::MAX, |hit| hit.t);
+            if let Some(hit) = bvh.raycast(&origin_obj, &dir_obj, t_max) {
+                closest = Some(Hit {
+                    entity,
+                    face: hit.face,
+                    barycentric: hit.barycentric,
+                    t: hit.t,
+                });
+            }
+        });
+        closest
+    }
+
+    /// Point on the triangles of all visible renderables that is closest to a
+    /// point in world coordinates
+    pub fn closest_point(&self, point: &na::Point3<f32>) -> Option<ClosestPoint> {
+        let mut closest: Option<ClosestPoint> = None;
+        self.for_each_mesh_bvh(|entity, bvh, model_matrix| {
+            let tf_world_obj = model_matrix.map_or_else(na::SimilarityMatrix3::identity, |mm| mm.0);
+            let point_obj = tf_world_obj.inverse() * point;
+            let Some(cp) = bvh.closest_point(&point_obj) else {
+                return;
+            };
+            let distance = cp.distance * tf_world_obj.scaling();
+            if distance < closest.map_or(f32::MAX, |c| c.distance) {
+                closest = Some(ClosestPoint {
+                    entity,
+                    face: cp.face,
+                    barycentric: cp.barycentric,
+                    point: tf_world_obj * cp.point,
+                    distance,
+                });
+            }
+        });
+        closest
+    }
+
+    /// Attaches `child` to `parent` while keeping the pose of the child in the
+    /// world. Attaching an entity to itself or to one of its descendants is
+    /// ignored
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        if self.is_ancestor(child, parent) {
+            warn!("cannot attach an entity to itself or to one of its descendants");
+            return;
+        }
+        self.remove_parent(child);
+
+        let tf_parent_world = self.get_world_matrix(parent).inverse();
+        let model_matrix = self.get_comp::<&ModelMatrix>(&child).ok().map(|mm| mm.0);
+        if let Some(model_matrix) = model_matrix {
+            let _ = self.world.insert_one(child, LocalModelMatrix(tf_parent_world * model_matrix));
+        }
+        let pos_lookat = self.get_comp::<&PosLookat>(&child).ok().map(|p| p.transformed(&tf_parent_world));
+        if let Some(pos_lookat) = pos_lookat {
+            let _ = self.world.insert_one(child, LocalPosLookat(pos_lookat));
+        }
+        let _ = self.world.insert_one(child, Parent(parent));
+
+        self.insert_if_doesnt_exist::<Children>(parent);
+        if let Ok(mut children) = self.get_comp::<&mut Children>(&parent) {
+            children.0.push(child);
+        }
+    }
+
+    /// Detaches an entity from its parent while keeping its pose in the world
+    pub fn remove_parent(&mut self, child: Entity) {
+        let Ok(parent) = self.world.remove_one::<Parent>(child) else {
+            return;
+        };
+        let _ = self.world.remove_one::<LocalModelMatrix>(child);
+        let _ = self.world.remove_one::<LocalPosLookat>(child);
+        let no_children_left = if let Ok(mut children) = self.get_comp::<&mut Children>(&parent.0) {
+            children.0.retain(|c| *c != child);
+            children.0.is_empty()
+        } else {
+            false
+        };
+        if no_children_left {
+            let _ = self.world.remove_one::<Children>(parent.0);
+        }
+    }
+
+    pub fn get_parent(&self, entity: Entity) -> Option<Entity> {
+        self.get_comp::<&Parent>(&entity).ok().map(|p| p.0)
+    }
+
+    /// Entities whose [`Parent`] is `entity`. Unlike the [`Children`] component
+    /// this is also up to date for parents that were inserted since the last
+    /// frame
+    pub fn get_children(&self, entity: Entity) -> Vec<Entity> {
+        self.world
+            .query::<&Parent>()
+            .iter()
+            .filter(|(_, p)| p.0 == entity)
+            .map(|(e, _)| e)
+            .collect()
+    }
+
+    /// Whether `ancestor` is `entity` itself or any entity above it in the
+    /// hierarchy
+    pub fn is_ancestor(&self, ancestor: Entity, entity: Entity) -> bool {
+        let mut cur = Some(entity);
+        //the number of entities bounds the depth so we don't loop forever on a cycle
+        for _ in 0..=self.world.len() {
+            match cur {
+                Some(e) if e == ancestor => return true,
+                Some(e) => cur = self.get_parent(e),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Transform from the object coordinates of an entity to the world. For
+    /// entities with a [`Parent`] it's composed from the local transforms up
+    /// the hierarchy so it's correct even before [`Scene::propagate_transforms`]
+    /// ran. Cameras and lights use the pose given by their [`PosLookat`]
+    pub fn get_world_matrix(&self, entity: Entity) -> na::SimilarityMatrix3<f32> {
+        let mut tf_world_obj = na::SimilarityMatrix3::<f32>::identity();
+        let mut cur = entity;
+        for _ in 0..=self.world.len() {
+            let local = self.get_comp::<&LocalModelMatrix>(&cur).ok().map(|l| l.0).or_else(|| {
+                self.get_comp::<&LocalPosLookat>(&cur)
+                    .ok()
+                    .map(|l| na::SimilarityMatrix3::from_isometry(l.0.model_matrix_isometry(), 1.0))
+            });
+            match (self.get_parent(cur), local) {
+                (Some(parent), Some(local)) => {
+                    tf_world_obj = local * tf_world_obj;
+                    cur = parent;
+                }
+                _ => break,
+            }
+        }
+        self.get_own_world_matrix(cur) * tf_world_obj
+    }
+
+    //world transform of an entity as stored in its own components without looking at the hierarchy
+    fn get_own_world_matrix(&self, entity: Entity) -> na::SimilarityMatrix3<f32> {
+        if let Ok(model_matrix) = self.get_comp::<&ModelMatrix>(&entity) {
+            model_matrix.0
+        } else if let Ok(pos_lookat) = self.get_comp::<&PosLookat>(&entity) {
+            na::SimilarityMatrix3::from_isometry(pos_lookat.model_matrix_isometry(), 1.0)
+        } else {
+            na::SimilarityMatrix3::identity()
+        }
+    }
+
+    /// Computes the [`ModelMatrix`], or the [`PosLookat`] for cameras and
+    /// lights, of every entity with a [`Parent`] from the world transform of
+    /// the parent and the local transform of the entity. Parents are processed
+    /// before their children. If the world transform of a child was written
+    /// since the last frame, its local transform is updated from it instead, so
+    /// children can be moved either way. It also keeps the [`Children`] in sync
+    /// and detaches entities whose parent doesn't exist anymore or which are
+    /// part of a cycle. Called by the prepass every frame
+    #[allow(clippy::too_many_lines)]
+    pub fn propagate_transforms(&mut self) {
+        //gather the links and detach the entities whose parent was despawned
+        let mut links: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        let mut orphans = Vec::new();
+        for (entity, parent) in self.world.query::<&Parent>().iter() {
+            if self.world.contains(parent.0) {
+                links.entry(parent.0).or_default().push(entity);
+            } else {
+                orphans.push(entity);
+            }
+        }
+        for orphan in orphans {
+            self.remove_parent(orphan);
+        }
+
+        //order the children so that parents come first, starting from the entities at the top of the hierarchy
+        let roots: Vec<Entity> = links.keys().filter(|p| self.get_parent(**p).is_none()).copied().collect();
+        let mut order: Vec<(Entity, Entity)> = Vec::new();
+        let mut queue: VecDeque<Entity> = roots.into_iter().collect();
+        while let Some(parent) = queue.pop_front() {
+            for child in links.get(&parent).into_iter().flatten() {
+                order.push((*child, parent));
+                queue.push_back(*child);
+            }
+        }
+        let reached: HashSet<Entity> = order.iter().map(|(child, _)| *child).collect();
+        //entities that can't be reached from the top are in a cycle or below one. Detaching one entity breaks the cycle and the rest of it is
+        // reached from there on the next frame
+        let unreached: Vec<Entity> = links.values().flatten().filter(|c| !reached.contains(c)).copied().collect();
+        for entity in unreached {
+            if self.get_parent(entity).map_or(false, |parent| self.is_ancestor(entity, parent)) {
+                warn!("entity {entity:?} is part of a cycle in the hierarchy, detaching it from its parent");
+                self.remove_parent(entity);
+            }
+        }
+
+        //keep the order of the existing children and append the new ones at the end
+        let mut command_buffer = CommandBuffer::new();
+        for (entity, ()) in self.world.query::<()>().with::<&Children>().iter() {
+            if !links.contains_key(&entity) {
+                command_buffer.remove_one::<Children>(entity);
+            }
+        }
+        for (parent, linked) in &links {
+            let linked: Vec<Entity> = linked.iter().filter(|c| reached.contains(c)).copied().collect();
+            let current = self.get_comp::<&Children>(parent).map(|c| c.0.clone()).unwrap_or_default();
+            let mut children: Vec<Entity> = current.iter().filter(|c| linked.contains(c)).copied().collect();
+            for child in linked {
+                if !children.contains(&child) {
+                    children.push(child);
+                }
+            }
+            if children.is_empty() {
+                command_buffer.remove_one::<Children>(*parent);
+            } else if children != current {
+                command_buffer.insert_one(*parent, Children(children));
+            }
+        }
+
+        let mut poses: HashMap<Entity, ChildPose> = HashMap::new();
+        for (entity, (mm, mm_changed, local_mm, local_mm_changed, pl, pl_changed, local_pl, local_pl_changed)) in self
+            .world
+            .query::<(
+                Option<&ModelMatrix>,
+                Option<Changed<ModelMatrix>>,
+                Option<&LocalModelMatrix>,
+                Option<Changed<LocalModelMatrix>>,
+                Option<&PosLookat>,
+                Option<Changed<PosLookat>>,
+                Option<&LocalPosLookat>,
+                Option<Changed<LocalPosLookat>>,
+            )>()
+            .with::<&Parent>()
+            .iter()
+        {
+            let pose = ChildPose {
+                model_matrix: mm.map(|m| (m.0, mm_changed.unwrap_or(false))),
+                local_model_matrix: local_mm.map(|m| (m.0, local_mm_changed.unwrap_or(false))),
+                pos_lookat: pl.map(|p| (p.clone(), pl_changed.unwrap_or(false))),
+                local_pos_lookat: local_pl.map(|p| (p.0.clone(), local_pl_changed.unwrap_or(false))),
+            };
+            poses.insert(entity, pose);
+        }
+
+        let mut tfs_world_obj: HashMap<Entity, na::SimilarityMatrix3<f32>> = HashMap::new();
+        for (child, parent) in order {
+            let Some(pose) = poses.get(&child) else {
+                continue; //detached because it was part of a cycle
+            };
+            let tf_world_parent = *tfs_world_obj.entry(parent).or_insert_with(|| self.get_own_world_matrix(parent));
+
+            let tf_world_child = if let Some((model_matrix, mm_changed)) = &pose.model_matrix {
+                //the local matrix wins unless only the world one was written since the last frame
+                let local = match &pose.local_model_matrix {
+                    Some((local, local_changed)) if *local_changed || !*mm_changed => *local,
+                    _ => {
+                        let local = tf_world_parent.inverse() * model_matrix;
+                        command_buffer.insert_one(child, LocalModelMatrix(local));
+                        local
+                    }
+                };
+                let tf_world_child = tf_world_parent * local;
+                if tf_world_child != *model_matrix {
+                    command_buffer.insert_one(child, ModelMatrix(tf_world_child));
+                }
+                tf_world_child
+            } else if let Some((pos_lookat, pl_changed)) = &pose.pos_lookat {
+                let local = match &pose.local_pos_lookat {
+                    Some((local, local_changed)) if *local_changed || !*pl_changed => local.clone(),
+                    _ => {
+                        let local = pos_lookat.transformed(&tf_world_parent.inverse());
+                        command_buffer.insert_one(child, LocalPosLookat(local.clone()));
+                        local
+                    }
+                };
+                let world = local.transformed(&tf_world_parent);
+                let tf_world_child = na::SimilarityMatrix3::from_isometry(world.model_matrix_isometry(), 1.0);
+                if world.position != pos_lookat.position || world.lookat != pos_lookat.lookat || world.up != pos_lookat.up {
+                    command_buffer.insert_one(child, world);
+                }
+                tf_world_child
+            } else {
+                //entities without a transform of their own only group their children
+                tf_world_parent * pose.local_model_matrix.map_or_else(na::SimilarityMatrix3::identity, |(local, _)| local)
+            };
+            tfs_world_obj.insert(child, tf_world_child);
+        }
+        command_buffer.run_on(&mut self.world);
+    }
+
     pub fn has_floor(&self) -> bool {
         self.name2entity.get(GLOSS_FLOOR_NAME).is_some()
     }
@@ -651,6 +1085,7 @@ impl Scene {
             command_buffer.remove_one::<UVsGPU>(entity);
             command_buffer.remove_one::<NormalsGPU>(entity);
             command_buffer.remove_one::<ColorsGPU>(entity);
+            command_buffer.remove_one::<ScalarsGPU>(entity);
             command_buffer.remove_one::<EdgesGPU>(entity);
             command_buffer.remove_one::<FacesGPU>(entity);
             command_buffer.remove_one::<TangentsGPU>(entity);
@@ -666,6 +1101,14 @@ impl Scene {
     }
 }
 
+//transforms of an entity that has a parent together with whether each of them was written since the last frame
+struct ChildPose {
+    model_matrix: Option<(na::SimilarityMatrix3<f32>, bool)>,
+    local_model_matrix: Option<(na::SimilarityMatrix3<f32>, bool)>,
+    pos_lookat: Option<(PosLookat, bool)>,
+    local_pos_lookat: Option<(PosLookat, bool)>,
+}
+
 /// A mutable reference to a particular [`Entity`] and all of its components
 pub struct EntityMut<'w> {
     world: &'w mut World,
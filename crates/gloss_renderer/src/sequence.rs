@@ -0,0 +1,299 @@
+use crate::{
+    components::{Faces, Normals, Tangents, Verts},
+    geom::Geom,
+    plugin_manager::{runner::RunnerState, systems::LogicSystem},
+    scene::Scene,
+};
+
+use gloss_hecs::CommandBuffer;
+use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
+use log::{error, warn};
+use nalgebra::DMatrix;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Where the vertices of every frame of a [`MeshSequence`] come from
+#[derive(Clone)]
+pub enum SequenceSource {
+    /// All frames in memory as a row-major [frames, verts, 3] array. The
+    /// data is shared so cloning the sequence is cheap
+    Memory { data: Arc<Vec<f32>>, nr_verts: usize },
+    /// One OBJ or PLY file per frame which is only read once the frame is
+    /// shown. The read happens on the thread running the logic systems, so
+    /// big meshes can stall the playback, see [`MeshSequence::preload`]
+    Files(Vec<PathBuf>),
+}
+
+/// Per-frame vertex data of a 4D capture together with its playback state.
+/// The sequence is advanced by [`sequence_playback_system`] using the dt of
+/// the runner and the vertices of the current frame are inserted as the
+/// [`Verts`] of the entity. The topology is the one of the entity, so every
+/// frame has to have the same number of vertices in the same order
+#[derive(Clone)]
+pub struct MeshSequence {
+    source: SequenceSource,
+    nr_frames: usize,
+    pub fps: f32,
+    pub speed: f32,
+    pub playing: bool,
+    pub looping: bool,
+    time: f32,                  //seconds since the first frame, without taking the speed into account
+    shown_frame: Option<usize>, //frame which is currently stored in the Verts of the entity
+}
+
+impl MeshSequence {
+    /// Creates a sequence from a row-major array of shape [frames, verts, 3]
+    /// # Panics
+    /// Will panic if the length of the data is not `nr_frames*nr_verts*3`
+    pub fn from_array(data: Vec<f32>, nr_frames: usize, nr_verts: usize) -> Self {
+        assert_eq!(
+            data.len(),
+            nr_frames * nr_verts * 3,
+            "the sequence data needs to have shape [{nr_frames}, {nr_verts}, 3]"
+        );
+        Self::new(
+            SequenceSource::Memory {
+                data: Arc::new(data),
+                nr_verts,
+            },
+            nr_frames,
+        )
+    }
+
+    /// Creates a sequence from a list of Nx3 vertex matrices, one per frame
+    /// # Panics
+    /// Will panic if the frames don't all have the same number of vertices
+    pub fn from_frames(frames: &[DMatrix<f32>]) -> Self {
+        let nr_verts = frames.first().map_or(0, DMatrix::nrows);
+        let mut data = Vec::with_capacity(frames.len() * nr_verts * 3);
+        for frame in frames {
+            assert_eq!(frame.shape(), (nr_verts, 3), "all frames of a sequence need to be Nx3 with the same N");
+            for row in frame.row_iter() {
+                data.extend(row.iter());
+            }
+        }
+        Self::from_array(data, frames.len(), nr_verts)
+    }
+
+    /// Creates a sequence from a list of OBJ or PLY files, one per frame in
+    /// the given order. The files are only read when their frame is shown
+    pub fn from_files(paths: Vec<PathBuf>) -> Self {
+        let nr_frames = paths.len();
+        Self::new(SequenceSource::Files(paths), nr_frames)
+    }
+
+    /// Creates a sequence from all the OBJ and PLY files in a folder. The files
+    /// are sorted by name, comparing runs of digits by their numeric value so
+    /// that `frame_2.obj` comes before `frame_10.obj`
+    /// # Errors
+    /// Will return an error if the folder cannot be read
+    pub fn from_folder(path: &Path) -> io::Result<Self> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(path)?
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|e| e.to_str())
+                        .map_or(false, |e| e.eq_ignore_ascii_case("obj") || e.eq_ignore_ascii_case("ply"))
+            })
+            .collect();
+        if paths.is_empty() {
+            warn!("no OBJ or PLY files found in {path:?}");
+        }
+        paths.sort_by_cached_key(|p| natural_sort_key(&p.file_name().unwrap_or_default().to_string_lossy()));
+        Ok(Self::from_files(paths))
+    }
+
+    /// Reads all the frames of a sequence created from files into memory so
+    /// that playing it back doesn't touch the disk anymore. Does nothing for
+    /// sequences that are already in memory
+    /// # Errors
+    /// Will return the index of the first frame that could not be read, in
+    /// which case the sequence keeps reading its frames from the files
+    pub fn preload(&mut self) -> Result<(), usize> {
+        if matches!(self.source, SequenceSource::Memory { .. }) {
+            return Ok(());
+        }
+        let frames = (0..self.nr_frames)
+            .map(|frame| self.frame_verts(frame).ok_or(frame))
+            .collect::<Result<Vec<_>, _>>()?;
+        let nr_verts = frames.first().map_or(0, DMatrix::nrows);
+        if let Some(frame) = frames.iter().position(|f| f.nrows() != nr_verts) {
+            error!("frame {frame} of the sequence has a different number of vertices than the first one");
+            return Err(frame);
+        }
+        self.source = Self::from_frames(&frames).source;
+        Ok(())
+    }
+
+    fn new(source: SequenceSource, nr_frames: usize) -> Self {
+        Self {
+            source,
+            nr_frames,
+            fps: 30.0,
+            speed: 1.0,
+            playing: true,
+            looping: true,
+            time: 0.0,
+            shown_frame: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_fps(mut self, fps: f32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    pub fn source(&self) -> &SequenceSource {
+        &self.source
+    }
+    pub fn nr_frames(&self) -> usize {
+        self.nr_frames
+    }
+    /// Length of the sequence in seconds when played at speed 1
+    #[allow(clippy::cast_precision_loss)]
+    pub fn duration(&self) -> f32 {
+        self.nr_frames as f32 / self.fps
+    }
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.duration());
+    }
+
+    /// Index of the frame that corresponds to the current time
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn current_frame(&self) -> usize {
+        ((self.time * self.fps).floor() as usize).min(self.nr_frames.saturating_sub(1))
+    }
+    /// Jumps to a frame. The vertices of it are inserted the next time the
+    /// playback system runs, so when rendering headless pausing the sequence
+    /// and setting the frame before `update()` always renders frame N no
+    /// matter the dt
+    #[allow(clippy::cast_precision_loss)]
+    pub fn set_frame(&mut self, frame: usize) {
+        let frame = frame.min(self.nr_frames.saturating_sub(1));
+        //we put the time in the middle of the frame so that rounding errors don't show the previous one
+        self.time = (frame as f32 + 0.5) / self.fps;
+    }
+
+    /// Starts playing. A sequence that stopped at its end starts again from
+    /// the first frame
+    pub fn play(&mut self) {
+        if !self.looping && self.time >= self.duration() {
+            self.time = 0.0;
+        }
+        self.playing = true;
+    }
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Advances the time by `dt` seconds scaled by the speed. Non-looping
+    /// sequences pause once they reach the end
+    pub fn advance(&mut self, dt: f32) {
+        if !self.playing || self.nr_frames == 0 {
+            return;
+        }
+        let duration = self.duration();
+        self.time += dt * self.speed;
+        if self.looping {
+            self.time = self.time.rem_euclid(duration);
+        } else if self.time >= duration || self.time < 0.0 {
+            self.time = self.time.clamp(0.0, duration);
+            self.playing = false;
+        }
+    }
+
+    /// Vertices of a frame as a Nx3 matrix. Frames stored in files are read
+    /// from disk every time this is called
+    pub fn frame_verts(&self, frame: usize) -> Option<DMatrix<f32>> {
+        if frame >= self.nr_frames {
+            return None;
+        }
+        match &self.source {
+            SequenceSource::Memory { data, nr_verts } => {
+                let frame_size = nr_verts * 3;
+                let frame_data = &data[frame * frame_size..(frame + 1) * frame_size];
+                Some(DMatrix::from_row_slice(*nr_verts, 3, frame_data))
+            }
+            SequenceSource::Files(paths) => {
+                let path = &paths[frame];
+                let builder = Geom::build_from_file(path.to_str().unwrap_or_default());
+                let verts = builder.get::<&Verts>().map(|v| v.0.to_dmatrix());
+                if verts.is_none() {
+                    error!("frame {frame} of the sequence has no vertices: {path:?}");
+                }
+                verts
+            }
+        }
+    }
+}
+
+//splits a file name into runs of digits and non digits so that numbers are compared by value
+fn natural_sort_key(name: &str) -> Vec<(String, u64)> {
+    let mut key = Vec::new();
+    let mut chars = name.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let is_digit = c.is_ascii_digit();
+        let mut run = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_digit() != is_digit {
+                break;
+            }
+            run.push(c);
+            chars.next();
+        }
+        if is_digit {
+            key.push((String::new(), run.parse().unwrap_or(u64::MAX)));
+        } else {
+            key.push((run, 0));
+        }
+    }
+    key
+}
+
+/// Advances every [`MeshSequence`] by the dt of the runner and inserts the
+/// vertices of the new frame into the entity. Entities with faces get their
+/// normals and tangents removed so that the prepass recomputes them for the
+/// new frame
+#[allow(clippy::missing_panics_doc)]
+pub extern "C" fn sequence_playback_system(scene: &mut Scene, runner: &mut RunnerState) {
+    let dt = runner.dt().as_secs_f32();
+    let mut command_buffer = CommandBuffer::new();
+    for (entity, (mut sequence, faces)) in scene.world.query_mut::<(&mut MeshSequence, Option<&Faces>)>() {
+        sequence.advance(dt);
+        if sequence.playing {
+            runner.request_redraw();
+        }
+        let frame = sequence.current_frame();
+        if sequence.shown_frame == Some(frame) {
+            continue;
+        }
+        if let Some(verts) = sequence.frame_verts(frame) {
+            command_buffer.insert_one(entity, Verts(DynamicTensorFloat2D::from_dmatrix(&verts)));
+            if faces.is_some() {
+                command_buffer.remove_one::<Normals>(entity);
+                command_buffer.remove_one::<Tangents>(entity);
+            }
+            //only a frame that was loaded counts as shown so that a failed one is retried
+            sequence.shown_frame = Some(frame);
+            runner.request_redraw();
+        }
+    }
+    command_buffer.run_on(&mut scene.world);
+}
+
+/// The logic system that plays back the mesh sequences. It's added by default
+/// to both [`Viewer`](crate::viewer::Viewer) and
+/// [`ViewerHeadless`](crate::viewer_headless::ViewerHeadless)
+pub fn sequence_playback_logic_system() -> LogicSystem {
+    LogicSystem::new(sequence_playback_system).with_name("sequence_playback")
+}
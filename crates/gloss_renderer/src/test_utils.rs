@@ -0,0 +1,42 @@
+//! Helpers shared by the unit tests of several modules
+
+use crate::{
+    components::{Faces, Verts},
+    scene::Scene,
+};
+use gloss_hecs::EntityBuilder;
+use gloss_utils::tensor::DynamicMatrixOps;
+use na::DMatrix;
+use nalgebra as na;
+use std::collections::HashMap;
+
+/// Vertices and faces of a mesh builder as they are inserted in the scene
+pub(crate) fn mesh_of(builder: EntityBuilder) -> (DMatrix<f32>, DMatrix<u32>) {
+    let mut scene = Scene::new();
+    let entity = scene.get_or_create_entity("mesh").insert_builder(builder).entity();
+    let verts = scene.get_comp::<&Verts>(&entity).unwrap().0.to_dmatrix();
+    let faces = scene.get_comp::<&Faces>(&entity).unwrap().0.to_dmatrix();
+    (verts, faces)
+}
+
+/// Vertices and faces of a mesh builder with the vertices closer than
+/// about 1e-4 merged, so that the seams of the primitives are closed
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn welded_mesh(builder: EntityBuilder) -> (DMatrix<f32>, DMatrix<u32>) {
+    let (verts, faces) = mesh_of(builder);
+
+    let mut pos2welded: HashMap<[i32; 3], u32> = HashMap::new();
+    let mut welded = Vec::new();
+    let old2welded: Vec<u32> = verts
+        .row_iter()
+        .map(|v| {
+            *pos2welded.entry([0, 1, 2].map(|c| (v[c] * 1e4).round() as i32)).or_insert_with(|| {
+                welded.extend([v[0], v[1], v[2]]);
+                (welded.len() / 3 - 1) as u32
+            })
+        })
+        .collect();
+    let verts = DMatrix::<f32>::from_row_slice(welded.len() / 3, 3, &welded);
+    let faces = faces.map(|v| old2welded[v as usize]);
+    (verts, faces)
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use na::DMatrix;
+use nalgebra as na;
+
+/// Marks a half-edge without a twin
+const NO_TWIN: u32 = u32::MAX;
+
+/// Half-edge going from `origin` towards the origin of `next`. Half-edges are
+/// stored per face so the half-edge `3*f+i` starts at the corner `i` of face
+/// `f`
+#[derive(Clone, Copy, Debug)]
+pub struct HalfEdge {
+    pub origin: u32,
+    pub face: u32,
+    pub next: u32,
+    twin: u32,
+}
+impl HalfEdge {
+    /// Half-edge going in the opposite direction in the neighbouring face.
+    /// It's `None` for boundary and non-manifold edges
+    pub fn twin(&self) -> Option<u32> {
+        (self.twin != NO_TWIN).then_some(self.twin)
+    }
+}
+
+/// Summary of the topology of a mesh
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeshStats {
+    pub nr_verts: usize,
+    pub nr_edges: usize,
+    pub nr_faces: usize,
+    pub nr_components: usize,
+    pub nr_boundary_loops: usize,
+    pub nr_non_manifold_edges: usize,
+    pub nr_non_manifold_verts: usize,
+    pub euler_characteristic: i64,
+    /// Only defined for orientable manifolds, so it's `None` if there are any
+    /// non-manifold edges or vertices
+    pub genus: Option<i64>,
+}
+
+/// Half-edge topology built from the faces of a triangle mesh that answers
+/// adjacency queries. Edges shared by more than two faces, or by two faces
+/// with inconsistent winding, don't get twins and are reported as non-manifold
+#[allow(clippy::struct_field_names)]
+pub struct MeshTopology {
+    half_edges: Vec<HalfEdge>,
+    //outgoing half-edges of every vertex
+    vert2half_edges: Vec<Vec<u32>>,
+    non_manifold_edges: Vec<(u32, u32)>,
+    nr_edges: usize,
+}
+
+impl MeshTopology {
+    /// Builds the topology from a matrix of faces of size Nx3. The number of
+    /// vertices is the largest index used by the faces plus one
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(faces: &DMatrix<u32>) -> Self {
+        let nr_verts = faces.iter().max().map_or(0, |max| *max as usize + 1);
+        let mut half_edges = Vec::with_capacity(faces.nrows() * 3);
+        let mut vert2half_edges = vec![Vec::new(); nr_verts];
+        for (idx_face, face) in faces.row_iter().enumerate() {
+            for i in 0..3 {
+                let idx_he = (idx_face * 3 + i) as u32;
+                half_edges.push(HalfEdge {
+                    origin: face[i],
+                    face: idx_face as u32,
+                    next: (idx_face * 3 + (i + 1) % 3) as u32,
+                    twin: NO_TWIN,
+                });
+                vert2half_edges[face[i] as usize].push(idx_he);
+            }
+        }
+
+        //group the half-edges by undirected edge
+        let mut edge2half_edges: HashMap<(u32, u32), Vec<u32>> = HashMap::new();
+        for (idx_he, he) in half_edges.iter().enumerate() {
+            let dest = half_edges[he.next as usize].origin;
+            edge2half_edges
+                .entry((he.origin.min(dest), he.origin.max(dest)))
+                .or_default()
+                .push(idx_he as u32);
+        }
+
+        let mut non_manifold_edges = Vec::new();
+        for (edge, hes) in &edge2half_edges {
+            match hes[..] {
+                [_] => {}
+                [he0, he1] if half_edges[he0 as usize].origin != half_edges[he1 as usize].origin => {
+                    half_edges[he0 as usize].twin = he1;
+                    half_edges[he1 as usize].twin = he0;
+                }
+                _ => non_manifold_edges.push(*edge),
+            }
+        }
+        non_manifold_edges.sort_unstable();
+
+        Self {
+            half_edges,
+            vert2half_edges,
+            non_manifold_edges,
+            nr_edges: edge2half_edges.len(),
+        }
+    }
+
+    /// Same as [`MeshTopology::new`] but the vertices at the same position
+    /// are merged first. Meshes usually duplicate the vertices along their uv
+    /// or normal seams, which would otherwise be reported as boundaries and
+    /// change the genus. Positions closer than about 1e-5 of the size of the
+    /// mesh are merged
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new_welded(verts: &DMatrix<f32>, faces: &DMatrix<u32>) -> Self {
+        let size = (0..verts.ncols())
+            .map(|c| verts.column(c).max() - verts.column(c).min())
+            .fold(0.0, f32::max);
+        let cell_size = (size * 1e-5).max(f32::EPSILON);
+        //every vertex is replaced by the first one with the same position
+        let mut pos2welded: HashMap<[i64; 3], u32> = HashMap::new();
+        let old2welded: Vec<u32> = verts
+            .row_iter()
+            .enumerate()
+            .map(|(idx, v)| {
+                *pos2welded
+                    .entry([0, 1, 2].map(|c| (v[c] / cell_size).round() as i64))
+                    .or_insert(idx as u32)
+            })
+            .collect();
+        Self::new(&faces.map(|v| old2welded[v as usize]))
+    }
+
+    pub fn half_edges(&self) -> &[HalfEdge] {
+        &self.half_edges
+    }
+
+    pub fn nr_verts(&self) -> usize {
+        self.vert2half_edges.len()
+    }
+
+    pub fn nr_faces(&self) -> usize {
+        self.half_edges.len() / 3
+    }
+
+    pub fn nr_edges(&self) -> usize {
+        self.nr_edges
+    }
+
+    /// Vertex where the half-edge ends
+    pub fn dest(&self, idx_he: u32) -> u32 {
+        self.half_edges[self.half_edges[idx_he as usize].next as usize].origin
+    }
+
+    /// Half-edge that ends where this one starts, within the same face
+    pub fn prev(&self, idx_he: u32) -> u32 {
+        let he = &self.half_edges[idx_he as usize];
+        self.half_edges[he.next as usize].next
+    }
+
+    /// Faces that use the vertex
+    pub fn vertex_faces(&self, vert: u32) -> impl Iterator<Item = u32> + '_ {
+        self.vert2half_edges[vert as usize].iter().map(|he| self.half_edges[*he as usize].face)
+    }
+
+    /// Vertices that share an edge with the vertex
+    pub fn vertex_neighbours(&self, vert: u32) -> Vec<u32> {
+        let mut neighbours: Vec<u32> = self.vert2half_edges[vert as usize]
+            .iter()
+            .flat_map(|he| [self.dest(*he), self.half_edges[self.prev(*he) as usize].origin])
+            .collect();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        neighbours
+    }
+
+    /// Faces that share a manifold edge with the face
+    pub fn face_neighbours(&self, face: u32) -> Vec<u32> {
+        (0..3)
+            .filter_map(|i| self.half_edges[(face * 3 + i) as usize].twin())
+            .map(|twin| self.half_edges[twin as usize].face)
+            .collect()
+    }
+
+    /// Half-edges on the boundary, which are the ones without a twin on a
+    /// manifold edge
+    pub fn is_boundary_half_edge(&self, idx_he: u32) -> bool {
+        let he = &self.half_edges[idx_he as usize];
+        if he.twin().is_some() {
+            return false;
+        }
+        let dest = self.dest(idx_he);
+        self.non_manifold_edges
+            .binary_search(&(he.origin.min(dest), he.origin.max(dest)))
+            .is_err()
+    }
+
+    /// Edges as pairs of vertices with the smaller index first that are shared
+    /// by more than two faces or by two faces with inconsistent winding
+    pub fn non_manifold_edges(&self) -> &[(u32, u32)] {
+        &self.non_manifold_edges
+    }
+
+    /// Vertices whose faces don't form a single fan, like the tip of two cones
+    /// touching each other
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn non_manifold_verts(&self) -> Vec<u32> {
+        (0..self.nr_verts() as u32).filter(|v| self.nr_fans(*v) > 1).collect()
+    }
+
+    /// Closed loops of boundary vertices. Every hole of the mesh is a loop
+    pub fn boundary_loops(&self) -> Vec<Vec<u32>> {
+        let mut visited = vec![false; self.half_edges.len()];
+        let mut loops = Vec::new();
+        for start in 0..self.half_edges.len() {
+            if visited[start] || !self.is_boundary_half_edge(u32::try_from(start).unwrap()) {
+                continue;
+            }
+            let mut boundary_loop = Vec::new();
+            let mut cur = u32::try_from(start).unwrap();
+            loop {
+                visited[cur as usize] = true;
+                boundary_loop.push(self.half_edges[cur as usize].origin);
+                //continue with a boundary half-edge that leaves from where this one ends
+                let next = self.vert2half_edges[self.dest(cur) as usize]
+                    .iter()
+                    .copied()
+                    .find(|he| !visited[*he as usize] && self.is_boundary_half_edge(*he));
+                match next {
+                    Some(next) => cur = next,
+                    None => break,
+                }
+            }
+            loops.push(boundary_loop);
+        }
+        loops
+    }
+
+    /// Number of connected components, where faces are connected if they
+    /// share a vertex
+    pub fn nr_connected_components(&self) -> usize {
+        //union-find with path halving
+        fn find(parents: &mut [usize], mut x: usize) -> usize {
+            while parents[x] != x {
+                parents[x] = parents[parents[x]];
+                x = parents[x];
+            }
+            x
+        }
+        let mut parents: Vec<usize> = (0..self.nr_verts()).collect();
+        for he in &self.half_edges {
+            let (a, b) = (
+                find(&mut parents, he.origin as usize),
+                find(&mut parents, self.half_edges[he.next as usize].origin as usize),
+            );
+            parents[a] = b;
+        }
+        let mut roots: Vec<usize> = (0..self.nr_verts())
+            .filter(|v| !self.vert2half_edges[*v].is_empty())
+            .map(|v| find(&mut parents, v))
+            .collect();
+        roots.sort_unstable();
+        roots.dedup();
+        roots.len()
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn stats(&self) -> MeshStats {
+        let nr_verts = self.vert2half_edges.iter().filter(|hes| !hes.is_empty()).count();
+        let nr_components = self.nr_connected_components();
+        let nr_boundary_loops = self.boundary_loops().len();
+        let nr_non_manifold_verts = self.non_manifold_verts().len();
+        let euler_characteristic = nr_verts as i64 - self.nr_edges as i64 + self.nr_faces() as i64;
+        //chi = 2c - 2g - b for a manifold with c components
+        let genus = (self.non_manifold_edges.is_empty() && nr_non_manifold_verts == 0)
+            .then(|| (2 * nr_components as i64 - nr_boundary_loops as i64 - euler_characteristic) / 2);
+        MeshStats {
+            nr_verts,
+            nr_edges: self.nr_edges,
+            nr_faces: self.nr_faces(),
+            nr_components,
+            nr_boundary_loops,
+            nr_non_manifold_edges: self.non_manifold_edges.len(),
+            nr_non_manifold_verts,
+            euler_characteristic,
+            genus,
+        }
+    }
+
+    /// Number of fans of faces around the vertex. Faces are in the same fan if
+    /// they can be reached by crossing manifold edges around the vertex
+    fn nr_fans(&self, vert: u32) -> usize {
+        let outgoing = &self.vert2half_edges[vert as usize];
+        let mut visited = vec![false; outgoing.len()];
+        let mut nr_fans = 0;
+        for start in 0..outgoing.len() {
+            if visited[start] {
+                continue;
+            }
+            nr_fans += 1;
+            //flood through the outgoing half-edges of neighbouring faces in both directions
+            let mut stack = vec![outgoing[start]];
+            while let Some(he) = stack.pop() {
+                let Some(pos) = outgoing.iter().position(|o| *o == he) else {
+                    continue;
+                };
+                if visited[pos] {
+                    continue;
+                }
+                visited[pos] = true;
+                if let Some(twin) = self.half_edges[self.prev(he) as usize].twin() {
+                    stack.push(twin);
+                }
+                if let Some(twin) = self.half_edges[he as usize].twin() {
+                    stack.push(self.half_edges[twin as usize].next);
+                }
+            }
+        }
+        nr_fans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geom::Geom,
+        test_utils::{mesh_of, welded_mesh},
+    };
+
+    /// Outwards facing tetrahedron made of the vertices `v`
+    fn tetrahedron(v: [u32; 4]) -> [u32; 12] {
+        [v[0], v[2], v[1], v[0], v[1], v[3], v[0], v[3], v[2], v[1], v[2], v[3]]
+    }
+
+    #[test]
+    fn closed_ico_sphere() {
+        let (_, faces) = welded_mesh(Geom::build_ico_sphere(na::Point3::origin(), 1.0, 2));
+        let stats = MeshTopology::new(&faces).stats();
+        assert_eq!(stats.nr_components, 1);
+        assert_eq!(stats.nr_boundary_loops, 0);
+        assert_eq!(stats.nr_non_manifold_edges, 0);
+        assert_eq!(stats.nr_non_manifold_verts, 0);
+        assert_eq!(stats.euler_characteristic, 2);
+        assert_eq!(stats.genus, Some(0));
+    }
+
+    #[test]
+    fn torus_has_genus_one() {
+        let (_, faces) = welded_mesh(Geom::build_torus(na::Point3::origin(), 1.0, 0.3, 12, 24));
+        let stats = MeshTopology::new(&faces).stats();
+        assert_eq!(stats.nr_components, 1);
+        assert_eq!(stats.nr_boundary_loops, 0);
+        assert_eq!(stats.euler_characteristic, 0);
+        assert_eq!(stats.genus, Some(1));
+    }
+
+    #[test]
+    fn seams_are_welded() {
+        //the torus duplicates the vertices along its uv seams
+        let (verts, faces) = mesh_of(Geom::build_torus(na::Point3::origin(), 1.0, 0.3, 12, 24));
+        assert!(MeshTopology::new(&faces).stats().nr_boundary_loops > 0);
+        let stats = MeshTopology::new_welded(&verts, &faces).stats();
+        assert_eq!(stats.nr_boundary_loops, 0);
+        assert_eq!(stats.genus, Some(1));
+    }
+
+    #[test]
+    fn quad_has_one_boundary_loop() {
+        let faces = DMatrix::<u32>::from_row_slice(2, 3, &[0, 1, 2, 0, 2, 3]);
+        let topology = MeshTopology::new(&faces);
+        let loops = topology.boundary_loops();
+        assert_eq!(loops.len(), 1);
+        let mut boundary = loops[0].clone();
+        boundary.sort_unstable();
+        assert_eq!(boundary, vec![0, 1, 2, 3]);
+        let stats = topology.stats();
+        assert_eq!(stats.nr_edges, 5);
+        assert_eq!(stats.genus, Some(0));
+    }
+
+    #[test]
+    fn tetrahedra_sharing_a_vertex() {
+        let faces: Vec<u32> = tetrahedron([0, 1, 2, 3]).into_iter().chain(tetrahedron([0, 4, 5, 6])).collect();
+        let topology = MeshTopology::new(&DMatrix::<u32>::from_row_slice(8, 3, &faces));
+        assert_eq!(topology.non_manifold_verts(), vec![0]);
+        assert_eq!(topology.nr_fans(0), 2);
+        assert_eq!(topology.nr_fans(1), 1);
+        let stats = topology.stats();
+        assert_eq!(stats.nr_components, 1);
+        assert_eq!(stats.nr_boundary_loops, 0);
+        assert_eq!(stats.nr_non_manifold_edges, 0);
+        assert_eq!(stats.nr_non_manifold_verts, 1);
+        assert_eq!(stats.genus, None);
+    }
+
+    #[test]
+    fn three_faces_on_one_edge() {
+        let faces = DMatrix::<u32>::from_row_slice(3, 3, &[0, 1, 2, 1, 0, 3, 0, 1, 4]);
+        let topology = MeshTopology::new(&faces);
+        assert_eq!(topology.non_manifold_edges(), &[(0, 1)]);
+        assert!(topology.half_edges().iter().all(|he| he.twin().is_none()));
+        let stats = topology.stats();
+        assert_eq!(stats.nr_non_manifold_edges, 1);
+        assert_eq!(stats.genus, None);
+    }
+
+    #[test]
+    fn inconsistent_winding_is_non_manifold() {
+        //both faces go from 0 to 1 so the edge can't be shared by an orientable surface
+        let faces = DMatrix::<u32>::from_row_slice(2, 3, &[0, 1, 2, 0, 1, 3]);
+        let topology = MeshTopology::new(&faces);
+        assert_eq!(topology.non_manifold_edges(), &[(0, 1)]);
+        assert!(!topology.is_boundary_half_edge(0));
+        assert_eq!(topology.stats().genus, None);
+    }
+}
@@ -6,16 +6,18 @@ use crate::gui::Gui;
 use crate::plugin_manager::GuiSystem;
 use crate::{
     camera::Camera,
-    components::Projection,
-    config::Config,
+    components::{Projection, Viewport},
+    config::{Config, RecordFormat},
     forward_renderer::{render_passes::blit_pass::BlitPass, renderer::Renderer},
     geom::Geom,
     logger::gloss_setup_logger_from_config,
     plugin_manager::{
         plugins::{Plugin, Plugins},
-        systems::{LogicSystem, SystemMetadata},
+        systems::{EventSystem, LogicSystem, SystemMetadata},
     },
+    recorder::Recorder,
     scene::{Scene, GLOSS_CAM_NAME},
+    sequence::sequence_playback_logic_system,
     set_panic_hook,
 };
 
@@ -62,10 +64,10 @@ pub struct GpuResources {
 
     #[cfg(feature = "with-gui")]
     pub gui: Option<Gui>,
-    _blit_pass: BlitPass, // Copies the final rendered texture towards screen for visualization
+    blit_pass: BlitPass, // Copies the final rendered texture towards screen for visualization
     pub redraw_requested: bool, /* When we request a redraw we set this to true. If we are to request another redraw, if this is set to true we
-                           * ignore the call. We need this because the call to window.redraw_requested() is quite expensive and it can be
-                           * called multiple times a frame if there are multiple inputs */
+                          * ignore the call. We need this because the call to window.redraw_requested() is quite expensive and it can be
+                          * called multiple times a frame if there are multiple inputs */
 
     // The order of properties in a struct is the order in which items are dropped.
     // wgpu seems to require that the device be dropped last, otherwise there is a resource leak
@@ -113,7 +115,7 @@ impl GpuResources {
         let mut desired_features = wgpu::Features::empty();
         cfg_if::cfg_if! {
             if #[cfg(not(target_arch = "wasm32"))]{
-                desired_features = desired_features.union(wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES));
+                desired_features = desired_features.union(wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS).union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES));
                 desired_features = desired_features.union(wgpu::Features::POLYGON_MODE_POINT);
                 desired_features = desired_features.union(wgpu::Features::POLYGON_MODE_LINE);
             }
@@ -209,7 +211,7 @@ impl GpuResources {
             renderer,
             #[cfg(feature = "with-gui")]
             gui,
-            _blit_pass: blit_pass,
+            blit_pass,
             redraw_requested: false,
         }
     }
@@ -349,12 +351,18 @@ impl Viewer {
         let mut scene = Scene::new();
         let camera = Camera::new(GLOSS_CAM_NAME, &mut scene, false); //TODO make it another entity inside the Scene
 
+        //mesh sequences are played back by a builtin system so they advance with the same dt as the rest of the logic systems
+        let mut plugins = Plugins::new();
+        plugins
+            .logic_systems
+            .push(Tuple2(sequence_playback_logic_system(), SystemMetadata::default()));
+
         Self {
             gpu_res: None,
             runner,
             scene,
             camera,
-            plugins: Plugins::new(),
+            plugins,
             canvas_id_parsed: canvas_id_parsed.clone(),
             config: config.clone(),
             window_size,
@@ -492,6 +500,13 @@ impl Viewer {
         //camera
     }
 
+    /// Camera that receives the mouse and touch input. This is the view
+    /// selected by the [`InputView`](crate::components::InputView) resource or
+    /// the main camera if there is no view selected.
+    pub fn input_cam(&self) -> Camera {
+        self.scene.get_input_cam().unwrap_or_else(|| Camera::from_entity(self.camera.entity))
+    }
+
     //Only a conveneince function so that you don't have to do
     // viewer.gpu_res.request_redraw()
     pub fn request_redraw(&mut self) {
@@ -782,48 +797,59 @@ impl Viewer {
     /// Processes events like mouse drag, scroll etc. If it matches one of the
     /// events, returns true
     #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_precision_loss)]
     fn process_input_events(&mut self, event: &WindowEvent) -> bool {
+        //when rendering several views, the input goes to the selected view
+        let mut camera = self.input_cam();
+
         //camera has not yet been initialized so there is nothing to do
-        if !self.camera.is_initialized(&self.scene) {
+        if !camera.is_initialized(&self.scene) {
             return false;
         }
 
+        //the mouse position is relative to the viewport of the camera
+        let (vp_x, vp_y, vp_width, vp_height) = self
+            .scene
+            .get_comp::<&Viewport>(&camera.entity)
+            .map_or((0, 0, self.window_size.width, self.window_size.height), |viewport| {
+                viewport.to_pixels(self.window_size.width, self.window_size.height)
+            });
+
         let gpu_res = self.gpu_res.as_mut().unwrap();
 
         let consumed = match event {
             WindowEvent::MouseInput { button, state, .. } => {
                 // self.camera.mouse_pressed = *state == ElementState::Pressed;
                 if *state == ElementState::Pressed {
-                    self.camera.mouse_pressed(button, &mut self.scene);
+                    camera.mouse_pressed(button, &mut self.scene);
                 } else {
-                    self.camera.mouse_released(&mut self.scene);
+                    camera.mouse_released(&mut self.scene);
                 }
                 true
             }
             WindowEvent::MouseWheel { delta, .. } => {
-                self.camera.process_mouse_scroll(delta, &mut self.scene);
+                camera.process_mouse_scroll(delta, &mut self.scene);
                 true
             }
             WindowEvent::CursorMoved { position, .. } => {
-                self.camera.process_mouse_move(
-                    position.x as f32,
-                    position.y as f32,
-                    self.window_size.width,
-                    self.window_size.height,
+                camera.process_mouse_move(
+                    position.x as f32 - vp_x as f32,
+                    position.y as f32 - vp_y as f32,
+                    vp_width,
+                    vp_height,
                     &mut self.scene,
                 );
                 true
             }
             WindowEvent::Touch(touch) => {
                 if touch.phase == TouchPhase::Started {
-                    self.camera.touch_pressed(touch, &mut self.scene);
+                    camera.touch_pressed(touch, &mut self.scene);
                 }
                 if touch.phase == TouchPhase::Ended || touch.phase == TouchPhase::Cancelled {
-                    self.camera.touch_released(touch, &mut self.scene);
+                    camera.touch_released(touch, &mut self.scene);
                 }
                 if touch.phase == TouchPhase::Moved {
-                    self.camera
-                        .process_touch_move(touch, self.window_size.width, self.window_size.height, &mut self.scene);
+                    camera.process_touch_move(touch, vp_width, vp_height, &mut self.scene);
                 }
                 true
             }
@@ -914,7 +940,7 @@ impl Viewer {
                         // if self.gpu_res.as_ref().unwrap().gui.wants_pointer_input() {
                         if let Some(ref gui) = self.gpu_res.as_mut().unwrap().gui {
                             if gui.wants_pointer_input() {
-                                self.camera.mouse_released(&mut self.scene);
+                                self.input_cam().mouse_released(&mut self.scene);
                             }
                         }
                     }else{ //if we don't have a gui, we just process input events
@@ -975,6 +1001,12 @@ impl Viewer {
         }
 
         self.runner.update_dt();
+        //while recording, time advances by a fixed amount every frame so that the recording plays at the correct speed
+        if let Ok(recorder) = self.scene.get_resource::<&Recorder>() {
+            if recorder.is_recording() {
+                self.runner.override_dt(recorder.frame_dt());
+            }
+        }
         debug!("after update dt it is {:?}", self.runner.dt());
         self.runner.time_last_frame = Instant::now();
 
@@ -1000,6 +1032,7 @@ impl Viewer {
         }
 
         let gpu_res = self.gpu_res.as_mut().unwrap();
+        gpu_res.gpu.profiler().begin_frame(gpu_res.gpu.device());
         self.plugins.run_logic_systems(gpu_res, &mut self.scene, &mut self.runner, true);
 
         //get surface texture (which can fail and return an SurfaceError)
@@ -1013,18 +1046,40 @@ impl Viewer {
 
         self.camera.on_window_resize(out_width, out_height, &mut self.scene);
 
-        //TODO return the textured final so we can just plug it into blit pass without
-        // doing renderer.rendered_tex
-        gpu_res.renderer.render_to_view(
-            &out_view,
-            // out_width,
-            // out_height,
-            &gpu_res.gpu,
-            &mut self.camera,
-            &mut self.scene,
-            &mut self.config,
-            dt,
-        );
+        //when recording we need the rendered image in a texture which we can download, so we render offscreen and blit it to screen
+        let is_recording = self.scene.get_resource::<&Recorder>().map_or(false, |r| r.is_recording());
+        //if any camera has a viewport we render all of them and compose them together
+        let has_views = self.scene.has_views();
+        if is_recording {
+            if has_views {
+                gpu_res
+                    .renderer
+                    .render_views_to_texture(&gpu_res.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+            } else {
+                gpu_res
+                    .renderer
+                    .render_to_texture(&gpu_res.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+            }
+            gpu_res.blit_pass.run(&gpu_res.gpu, gpu_res.renderer.rendered_tex(), &out_view);
+            if let Ok(mut recorder) = self.scene.get_resource::<&mut Recorder>() {
+                recorder.capture(&gpu_res.gpu, gpu_res.renderer.rendered_tex());
+            }
+        } else if has_views {
+            gpu_res
+                .renderer
+                .render_views_to_view(&out_view, &gpu_res.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+        } else {
+            gpu_res.renderer.render_to_view(
+                &out_view,
+                // out_width,
+                // out_height,
+                &gpu_res.gpu,
+                &mut self.camera,
+                &mut self.scene,
+                &mut self.config,
+                dt,
+            );
+        }
 
         //render gui
         //TODO pass the whole renderer and the scene so we can do gui stuff on them
@@ -1042,6 +1097,8 @@ impl Viewer {
             );
         }
 
+        gpu_res.gpu.profiler().end_frame(gpu_res.gpu.device(), gpu_res.gpu.queue());
+
         //swap
         output.present();
 
@@ -1068,6 +1125,7 @@ impl Viewer {
         }
 
         let gpu_res = self.gpu_res.as_mut().unwrap();
+        gpu_res.gpu.profiler().begin_frame(gpu_res.gpu.device());
         self.plugins.run_logic_systems(gpu_res, &mut self.scene, &mut self.runner, true);
 
         //get surface texture (which can fail and return an SurfaceError)
@@ -1083,9 +1141,20 @@ impl Viewer {
 
         //TODO return the textured final so we can just plug it into blit pass without
         // doing renderer.rendered_tex
-        gpu_res
-            .renderer
-            .render_to_texture(&gpu_res.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+        if self.scene.has_views() {
+            gpu_res
+                .renderer
+                .render_views_to_texture(&gpu_res.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+        } else {
+            gpu_res
+                .renderer
+                .render_to_texture(&gpu_res.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+        }
+
+        //capture before drawing the gui on top
+        if let Ok(mut recorder) = self.scene.get_resource::<&mut Recorder>() {
+            recorder.capture(&gpu_res.gpu, gpu_res.renderer.rendered_tex());
+        }
 
         //render gui
         //TODO pass the whole renderer and the scene so we can do gui stuff on them
@@ -1104,6 +1173,8 @@ impl Viewer {
             );
         }
 
+        gpu_res.gpu.profiler().end_frame(gpu_res.gpu.device(), gpu_res.gpu.queue());
+
         self.runner.first_time = false;
         self.runner.frame_is_started = false;
 
@@ -1216,6 +1287,7 @@ impl Viewer {
         // we lost it https://docs.rs/winit/latest/winit/event/enum.Event.html
         info!("RS: suspend");
         self.runner.is_running = false;
+        self.stop_recording(); //the readback buffers belong to the gpu that we are about to drop
         self.scene.remove_all_gpu_components();
         self.gpu_res.take();
         self.camera.reset_all_touch_presses(&mut self.scene);
@@ -1227,6 +1299,8 @@ impl Viewer {
     // animation. Therefore we do a warmup at the first rendered frame
     pub fn warmup(&mut self) {
         debug!("Starting warmup");
+        //warmup frames should not end up in a recording
+        let recorder = self.scene.remove_resource::<Recorder>().ok();
         // self.resume(&self.runner.event_loop);
         self.start_frame();
         self.run_manual_plugins(); //auto plugins will run when we do self.render(), but here we also need to run
@@ -1236,6 +1310,9 @@ impl Viewer {
         // the event loop is already running so the .take() will fails
         let _ = self.render();
         self.reset_for_first_time();
+        if let Some(recorder) = recorder {
+            self.scene.add_resource(recorder);
+        }
         debug!("finished warmup");
     }
 
@@ -1247,6 +1324,10 @@ impl Viewer {
         self.plugins.logic_systems.push(Tuple2(sys, SystemMetadata::default()));
     }
 
+    pub fn add_event_system(&mut self, sys: EventSystem) {
+        self.plugins.event_systems.push(Tuple2(sys, SystemMetadata::default()));
+    }
+
     #[cfg(feature = "with-gui")]
     pub fn add_gui_system(&mut self, sys: GuiSystem) {
         self.plugins.gui_systems.push(Tuple2(sys, SystemMetadata::default()));
@@ -1312,6 +1393,31 @@ impl Viewer {
         self.runner.override_dt(new_dt);
     }
 
+    /// Starts recording every rendered frame to `output_path`. For image
+    /// sequences this is a directory, for y4m it's the path of the video.
+    /// While recording, the dt of each frame is fixed to `1/fps`. The
+    /// recorder can also be toggled from the GUI.
+    pub fn start_recording(&mut self, output_path: &str, format: RecordFormat, fps: f32) {
+        self.stop_recording();
+        let mut recorder = Recorder::new(output_path, format, fps);
+        recorder.start();
+        self.scene.add_resource(recorder);
+    }
+
+    /// Stops the recording and flushes to disk the frames that are still
+    /// being downloaded from the GPU
+    pub fn stop_recording(&mut self) {
+        if let Some(gpu_res) = self.gpu_res.as_ref() {
+            if let Ok(mut recorder) = self.scene.get_resource::<&mut Recorder>() {
+                recorder.stop(&gpu_res.gpu);
+            }
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.scene.get_resource::<&Recorder>().map_or(false, |r| r.is_recording())
+    }
+
     pub fn get_final_tex(&self) -> &Texture {
         let tex = self.gpu_res.as_ref().unwrap().renderer.rendered_tex();
         tex
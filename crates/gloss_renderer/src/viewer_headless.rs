@@ -1,10 +1,13 @@
 cfg_if::cfg_if! {
     if #[cfg(not(target_arch = "wasm32"))] {
         use crate::components::{TargetResolution, TargetResolutionUpdate};
-        use crate::config::Config;
-        use crate::forward_renderer::renderer::Renderer;
+        use crate::config::{Config, RecordFormat};
+        use crate::forward_renderer::{render_passes::aov_pass::Aov, renderer::Renderer};
         use crate::logger::gloss_setup_logger_from_config;
         use crate::plugin_manager::plugins::{Plugin, Plugins};
+        use crate::plugin_manager::systems::{LogicSystem, SystemMetadata};
+        use crate::recorder::Recorder;
+        use crate::sequence::sequence_playback_logic_system;
         use crate::scene::Scene;
         use crate::set_panic_hook;
         use crate::viewer::supported_backends;
@@ -12,6 +15,7 @@ cfg_if::cfg_if! {
 
         use easy_wgpu::gpu::Gpu;
         use easy_wgpu::texture::Texture;
+        use gloss_utils::abi_stable_aliases::std_types::Tuple2;
 
         use log::debug;
         use pollster::FutureExt;
@@ -83,6 +87,9 @@ impl RunnerHeadless {
             self.dt = self.time_last_frame.elapsed();
         }
     }
+    pub fn override_dt(&mut self, new_dt: f32) {
+        self.dt = Duration::from_secs_f32(new_dt);
+    }
     pub fn dt(&self) -> Duration {
         self.dt
     }
@@ -157,7 +164,7 @@ impl ViewerHeadless {
         cfg_if::cfg_if! {
             if #[cfg(not(target_arch = "wasm32"))]{
                 // println!("compiling with time query");
-                desired_features = desired_features.union(wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES));
+                desired_features = desired_features.union(wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS).union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES));
                 desired_features = desired_features.union(wgpu::Features::POLYGON_MODE_POINT);
                 desired_features = desired_features.union(wgpu::Features::POLYGON_MODE_LINE);
             }
@@ -212,12 +219,18 @@ impl ViewerHeadless {
         );
         let renderer = Renderer::new(&gpu, &config.render, None); //moves the device and queue into it and takes ownership of them
 
+        //mesh sequences are played back by a builtin system so they advance with the same dt as the rest of the logic systems
+        let mut plugins = Plugins::new();
+        plugins
+            .logic_systems
+            .push(Tuple2(sequence_playback_logic_system(), SystemMetadata::default()));
+
         Self {
             gpu,
             renderer,
             camera,
             scene,
-            plugins: Plugins::new(),
+            plugins,
             config: config.clone(),
             runner,
         }
@@ -226,6 +239,9 @@ impl ViewerHeadless {
     pub fn insert_plugin<T: Plugin + 'static>(&mut self, plugin: &T) {
         self.plugins.insert_plugin(plugin);
     }
+    pub fn add_logic_system(&mut self, sys: LogicSystem) {
+        self.plugins.logic_systems.push(Tuple2(sys, SystemMetadata::default()));
+    }
     #[allow(clippy::missing_panics_doc)]
     pub fn run_manual_plugins(&mut self) {
         self.plugins.run_logic_systems_headless(&mut self.scene, &mut self.runner, false);
@@ -233,12 +249,23 @@ impl ViewerHeadless {
     //wasm cannot compile the run_return() call so we just disable this whole
     // function
     pub fn update(&mut self) {
-        self.render(None);
+        self.render(None, 1);
+    }
+
+    /// Same as [`ViewerHeadless::update`] but renders the frame `nr_samples`
+    /// times, each with a different subpixel jitter of the camera, and
+    /// averages them into a supersampled image. The logic systems run only
+    /// once so the scene is the same for all the samples which makes the
+    /// result deterministic. Useful for high quality stills when MSAA is
+    /// disabled. Cameras with a [`crate::components::Viewport`] are not
+    /// supersampled.
+    pub fn update_supersampled(&mut self, nr_samples: u32) {
+        self.render(None, nr_samples);
     }
 
     pub fn render_from_cam(&mut self, cam: &mut Camera) {
         //we replace our defaul
-        self.render(Some(cam));
+        self.render(Some(cam), 1);
     }
 
     //called at the beggining of the render and sets the time that all systems will
@@ -253,6 +280,12 @@ impl ViewerHeadless {
         }
 
         self.runner.update_dt();
+        //while recording, time advances by a fixed amount every frame so that the recording plays at the correct speed
+        if let Ok(recorder) = self.scene.get_resource::<&Recorder>() {
+            if recorder.is_recording() {
+                self.runner.override_dt(recorder.frame_dt());
+            }
+        }
         debug!("after update dt it is {:?}", self.runner.dt());
         self.runner.time_last_frame = Instant::now();
 
@@ -261,11 +294,12 @@ impl ViewerHeadless {
         self.runner.dt
     }
 
-    fn render(&mut self, other_cam: Option<&mut Camera>) {
+    fn render(&mut self, mut other_cam: Option<&mut Camera>, nr_samples: u32) {
         if self.runner.first_time {
             self.runner.time_init = Instant::now();
         }
 
+        self.gpu.profiler().begin_frame(self.gpu.device());
         self.plugins.run_logic_systems_headless(&mut self.scene, &mut self.runner, true);
 
         let dt = self.runner.dt();
@@ -273,8 +307,35 @@ impl ViewerHeadless {
         //we render to an internal texture since we have no surface
         // let out_view = self.renderer.rendered_tex().view;
 
-        self.renderer
-            .render_to_texture(&self.gpu, other_cam.unwrap_or(&mut self.camera), &mut self.scene, &mut self.config, dt);
+        //views are composed into the final texture by their own passes so they are not supersampled
+        let render_views = other_cam.is_none() && self.scene.has_views();
+        let nr_samples = if render_views { 1 } else { nr_samples.max(1) };
+        let supersample = nr_samples > 1;
+        if supersample {
+            self.renderer.passes.antialiasing_pass.begin_accumulation();
+        }
+        for _ in 0..nr_samples {
+            //if any camera has a viewport we render all the views in one frame and compose them into the final texture, unless a
+            // concrete camera was requested
+            if let Some(cam) = other_cam.as_deref_mut() {
+                self.renderer.render_to_texture(&self.gpu, cam, &mut self.scene, &mut self.config, dt);
+            } else if render_views {
+                self.renderer
+                    .render_views_to_texture(&self.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+            } else {
+                self.renderer
+                    .render_to_texture(&self.gpu, &mut self.camera, &mut self.scene, &mut self.config, dt);
+            }
+        }
+        if supersample {
+            self.renderer.passes.antialiasing_pass.end_accumulation();
+        }
+
+        if let Ok(mut recorder) = self.scene.get_resource::<&mut Recorder>() {
+            recorder.capture(&self.gpu, self.renderer.rendered_tex());
+        }
+
+        self.gpu.profiler().end_frame(self.gpu.device(), self.gpu.queue());
 
         self.runner.first_time = false;
         self.runner.frame_is_started = false;
@@ -282,6 +343,8 @@ impl ViewerHeadless {
 
     pub fn warmup(&mut self) {
         debug!("Starting warmup");
+        //warmup frames should not end up in a recording
+        let recorder = self.scene.remove_resource::<Recorder>().ok();
         self.start_frame();
         self.run_manual_plugins(); //auto plugins will run when we do self.render(), but here we also need to run
                                    // the manual ones
@@ -290,6 +353,9 @@ impl ViewerHeadless {
         #[cfg(target_arch = "wasm32")] //
         self.render();
         self.reset_for_first_time();
+        if let Some(recorder) = recorder {
+            self.scene.add_resource(recorder);
+        }
         debug!("finished warmup");
     }
 
@@ -302,12 +368,51 @@ impl ViewerHeadless {
         tex
     }
 
+    /// Texture of a single view from the last frame. Returns None if the
+    /// camera doesn't have a [`Viewport`](crate::components::Viewport)
+    pub fn get_view_tex(&self, camera: &Camera) -> Option<&Texture> {
+        self.renderer.view_tex(camera.entity)
+    }
+
     pub fn get_final_depth(&self) -> &Texture {
         let depth = self.renderer.depth_buffer();
         depth
     }
 
+    /// Texture of an aov from the last frame. Returns None if the aov is not
+    /// enabled in the [`AovConfig`](crate::config::AovConfig) or if the camera
+    /// has lens distortion. The aovs are not rendered for views.
+    pub fn get_aov_tex(&self, aov: Aov) -> Option<&Texture> {
+        self.renderer.aov_tex(aov)
+    }
+
     pub fn set_size(&mut self, width: u32, height: u32) {
         self.camera.set_target_res(width, height, &mut self.scene);
     }
+
+    pub fn override_dt(&mut self, new_dt: f32) {
+        self.runner.override_dt(new_dt);
+    }
+
+    /// Starts recording every rendered frame to `output_path`. For image
+    /// sequences this is a directory, for y4m it's the path of the video.
+    /// While recording, the dt of each frame is fixed to `1/fps`.
+    pub fn start_recording(&mut self, output_path: &str, format: RecordFormat, fps: f32) {
+        self.stop_recording();
+        let mut recorder = Recorder::new(output_path, format, fps);
+        recorder.start();
+        self.scene.add_resource(recorder);
+    }
+
+    /// Stops the recording and flushes to disk the frames that are still
+    /// being downloaded from the GPU
+    pub fn stop_recording(&mut self) {
+        if let Ok(mut recorder) = self.scene.get_resource::<&mut Recorder>() {
+            recorder.stop(&self.gpu);
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.scene.get_resource::<&Recorder>().map_or(false, |r| r.is_recording())
+    }
 }
@@ -80,6 +80,19 @@ impl DynamicTensorFloat2D {
     pub fn from_candle(tensor: Tensor<Candle, 2, Float>) -> Self {
         DynamicTensorFloat2D::Candle(tensor)
     }
+    /// Returns the underlying array when the tensor lives on the `NdArray`
+    /// backend. The array shares the data with the tensor so it can be viewed
+    /// without making a copy. Other backends return None
+    pub fn as_ndarray(&self) -> Option<ndarray::ArcArray<f32, ndarray::Ix2>> {
+        match self {
+            DynamicTensorFloat2D::NdArray(tensor) => {
+                //cloning a tensor on the NdArray backend only clones the Arc to the data
+                let array = tensor.clone().into_primitive().tensor().array;
+                array.into_dimensionality::<ndarray::Ix2>().ok()
+            }
+            _ => None,
+        }
+    }
 }
 
 /// From methods for converting from Tensor to `DynamicTensor`
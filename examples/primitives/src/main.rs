@@ -32,5 +32,40 @@ fn main() {
             ..Default::default()
         });
 
+    // Spheres, cylinder, cone, capsule and torus in a row behind the cube
+    let row = [
+        ("uv_sphere", Geom::build_uv_sphere(na::Point3::<f32>::new(-4.5, 1.0, -4.0), 0.8, 16, 32)),
+        ("ico_sphere", Geom::build_ico_sphere(na::Point3::<f32>::new(-2.7, 1.0, -4.0), 0.8, 3)),
+        (
+            "cylinder",
+            Geom::build_cylinder(na::Point3::<f32>::new(-0.9, 1.0, -4.0), 0.6, 2.0, 32, true),
+        ),
+        ("cone", Geom::build_cone(na::Point3::<f32>::new(0.9, 1.0, -4.0), 0.7, 2.0, 32, true)),
+        ("capsule", Geom::build_capsule(na::Point3::<f32>::new(2.7, 1.0, -4.0), 0.5, 2.0, 8, 32)),
+        ("torus", Geom::build_torus(na::Point3::<f32>::new(4.5, 1.0, -4.0), 0.6, 0.2, 16, 32)),
+    ];
+    for (name, builder) in row {
+        viewer.scene.get_or_create_entity(name).insert_builder(builder).insert(VisMesh {
+            solid_color: na::Vector4::<f32>::new(0.2, 0.6, 1.0, 1.0),
+            ..Default::default()
+        });
+    }
+
+    // Arrow pointing from the cube towards the row
+    viewer.scene.get_or_create_entity("arrow").insert_builder(Geom::build_arrow(
+        na::Point3::<f32>::new(0.0, 2.5, 0.0),
+        na::Point3::<f32>::new(0.0, 2.5, -3.0),
+        0.05,
+        0.15,
+        0.4,
+        16,
+    ));
+
+    // Axes at the origin
+    viewer
+        .scene
+        .get_or_create_entity("axes")
+        .insert_builder(Geom::build_axes(na::Point3::<f32>::new(0.0, 0.01, 0.0), 3.0, 3.0));
+
     viewer.run();
 }
@@ -1,5 +1,5 @@
 use gloss_renderer::{
-    components::{DiffuseImg, ImgConfig, NormalImg, VisMesh, VisPoints},
+    components::{DiffuseImg, ImgConfig, NormalImg, PointRenderMode, VisMesh, VisPoints},
     config::LogLevel,
     geom::Geom,
     gloss_setup_logger,
@@ -33,6 +33,8 @@ async fn create_test_scene(viewer: &mut Viewer) {
         })
         .insert(VisPoints {
             show_points: true,
+            point_size: 3.0,
+            render_mode: PointRenderMode::Surfel,
             ..Default::default()
         });
 }
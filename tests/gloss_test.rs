@@ -1,2 +1,504 @@
-// TODO: This used to be called viewer_headless.rs, but it did nothing. Do we
-// want to do something here?
+// Tests of the renderer as a whole, most of them render with a headless viewer
+// and check the resulting images or gpu buffers
+
+use gloss_renderer::{
+    camera::Camera,
+    components::{
+        BlendShapeCoeffs, BlendShapes, Children, ClipPlane, Faces, Highlight, JointTransforms, LensDistortion, ModelMatrix, NoClip, Normals,
+        NormalsGPU, PosLookat, Projection, ProjectionWithIntrinsics, SkinWeights, Verts, VertsGPU, ViewOverrides, Viewport,
+    },
+    config::{AntiAliasing, Config},
+    forward_renderer::render_passes::aov_pass::Aov,
+    geom::{Geom, PerVertexNormalsWeightingType},
+    scene::Scene,
+    sequence::MeshSequence,
+    viewer_headless::ViewerHeadless,
+};
+use gloss_utils::tensor::{DynamicMatrixOps, DynamicTensorFloat2D};
+use nalgebra as na;
+
+/// Copies a vertex buffer back to the CPU as a Nx3 matrix
+fn download_vertex_buffer(viewer: &ViewerHeadless, buf: &wgpu::Buffer, nr_verts: usize) -> na::DMatrix<f32> {
+    let device = viewer.gpu.device();
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback"),
+        size: buf.size(),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    encoder.copy_buffer_to_buffer(buf, 0, &readback, 0, buf.size());
+    viewer.gpu.queue().submit(Some(encoder.finish()));
+    readback.slice(..).map_async(wgpu::MapMode::Read, |res| res.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let data: Vec<f32> = bytemuck::cast_slice(&readback.slice(..).get_mapped_range()).to_vec();
+    //the buffer is row major so we read it as the transposed matrix
+    na::DMatrix::<f32>::from_column_slice(3, nr_verts, &data[..nr_verts * 3]).transpose()
+}
+
+/// Headless viewer of 64x64 pixels showing an icosphere named "sphere" with
+/// radius 1 at the origin
+fn sphere_viewer(config: &Config) -> ViewerHeadless {
+    let mut viewer = ViewerHeadless::new_with_config(64, 64, config);
+    viewer
+        .scene
+        .get_or_create_entity("sphere")
+        .insert_builder(Geom::build_ico_sphere(na::Point3::origin(), 1.0, 2));
+    viewer
+}
+
+/// Renders a frame and returns the RGBA bytes of the final image
+fn render_bytes(viewer: &mut ViewerHeadless) -> Vec<u8> {
+    render_supersampled_bytes(viewer, 1)
+}
+
+/// Same as [`render_bytes`] but averages `nr_samples` jittered renders
+fn render_supersampled_bytes(viewer: &mut ViewerHeadless, nr_samples: u32) -> Vec<u8> {
+    viewer.start_frame();
+    viewer.update_supersampled(nr_samples);
+    let img = pollster::block_on(viewer.get_final_tex().download_to_cpu(viewer.gpu.device(), viewer.gpu.queue()));
+    img.as_bytes().to_vec()
+}
+
+/// Projects a grid of points in front of the camera, unprojects them back and
+/// checks that both the points and their pixels agree
+#[allow(clippy::cast_precision_loss)]
+fn assert_project_unproject_roundtrip(camera: &Camera, scene: &Scene) {
+    let (width, height) = camera.get_target_res(scene);
+    let viewport_size = na::Vector2::new(width as f32, height as f32);
+    let view = camera.view_matrix(scene);
+    let proj = camera.proj_matrix(scene);
+    for x in -3..=3 {
+        for y in -3..=3 {
+            for z in -1..=1 {
+                let point = na::Point3::new(x as f32 * 0.4, y as f32 * 0.3, z as f32);
+                let win = camera.project(point, view, proj, viewport_size, scene);
+                let point_back = camera.unproject(win.into(), view, proj, viewport_size, scene);
+                assert!((point_back - point.coords).norm() < 1e-2, "{point} unprojected to {point_back}");
+                let win_back = camera.project(point_back.into(), view, proj, viewport_size, scene);
+                assert!(
+                    (win_back.xy() - win.xy()).norm() < 0.05,
+                    "{point} projected to {win} and then to {win_back}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn deform_gpu_matches_cpu() {
+    let mut viewer = sphere_viewer(&Config::new(None));
+    let entity = viewer.scene.get_entity_with_name("sphere").unwrap();
+    let verts = viewer.scene.get_comp::<&Verts>(&entity).unwrap().0.to_dmatrix();
+    let faces = viewer.scene.get_comp::<&Faces>(&entity).unwrap().0.to_dmatrix();
+    let nr_verts = verts.nrows();
+
+    //two shapes, one stretching along y and one pushing every vertex along x by an amount that depends on its index
+    let nr_shapes = 2;
+    let basis = na::DMatrix::<f32>::from_fn(nr_shapes * nr_verts, 3, |r, c| {
+        let (k, v) = (r / nr_verts, r % nr_verts);
+        match (k, c) {
+            (0, 1) => verts[(v, 1)],
+            (1, 0) => (v % 7) as f32 * 0.01,
+            _ => 0.0,
+        }
+    });
+    let coeffs = vec![0.5, -1.5];
+    //two joints split along the height of the sphere with a smooth blend in the middle
+    let dense_weights = na::DMatrix::<f32>::from_fn(nr_verts, 2, |r, c| {
+        let w = (verts[(r, 1)] * 2.0 + 0.5).clamp(0.0, 1.0);
+        if c == 0 {
+            1.0 - w
+        } else {
+            w
+        }
+    });
+    let skin = SkinWeights::from_dense(&dense_weights);
+    let transforms = vec![
+        na::Matrix4::new_translation(&na::Vector3::new(0.0, -0.2, 0.0)),
+        na::Isometry3::new(na::Vector3::new(0.1, 0.3, 0.0), na::Vector3::new(0.0, 0.0, 0.7)).to_homogeneous(),
+    ];
+
+    viewer
+        .scene
+        .world
+        .insert(
+            entity,
+            (
+                BlendShapes(DynamicTensorFloat2D::from_dmatrix(&basis)),
+                BlendShapeCoeffs(coeffs.clone()),
+                skin.clone(),
+                JointTransforms(transforms.clone()),
+            ),
+        )
+        .unwrap();
+    viewer.start_frame();
+    viewer.update();
+
+    //cpu reference
+    let expected_verts = Geom::skin_verts(
+        &Geom::apply_blend_shapes(&verts, &basis, &coeffs),
+        &skin.joints.to_dmatrix(),
+        &skin.weights.to_dmatrix(),
+        &transforms,
+    );
+    let expected_normals = Geom::compute_per_vertex_normals(&expected_verts, &faces, &PerVertexNormalsWeightingType::Area);
+
+    let gpu_verts = {
+        let verts_gpu = viewer.scene.get_comp::<&VertsGPU>(&entity).unwrap();
+        download_vertex_buffer(&viewer, &verts_gpu.buf, nr_verts)
+    };
+    let gpu_normals = {
+        let normals_gpu = viewer.scene.get_comp::<&NormalsGPU>(&entity).unwrap();
+        download_vertex_buffer(&viewer, &normals_gpu.buf, nr_verts)
+    };
+    assert!(
+        (gpu_verts - expected_verts).abs().max() < 1e-4,
+        "deformed vertices differ from the cpu reference"
+    );
+    assert!(
+        (gpu_normals - expected_normals).abs().max() < 1e-3,
+        "deformed normals differ from the cpu reference"
+    );
+}
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn sequence_renders_requested_frame() {
+    let mut viewer = sphere_viewer(&Config::new(None));
+    let entity = viewer.scene.get_entity_with_name("sphere").unwrap();
+    let verts = viewer.scene.get_comp::<&Verts>(&entity).unwrap().0.to_dmatrix();
+    let frames: Vec<na::DMatrix<f32>> = (0..10).map(|i| &verts * (1.0 + i as f32 * 0.1)).collect();
+
+    let mut sequence = MeshSequence::from_frames(&frames);
+    sequence.pause();
+    sequence.set_frame(7);
+    viewer.scene.world.insert_one(entity, sequence).unwrap();
+
+    //a large dt should not move a paused sequence away from the requested frame
+    viewer.start_frame();
+    viewer.override_dt(1.0);
+    viewer.update();
+
+    let shown = viewer.scene.get_comp::<&Verts>(&entity).unwrap().0.to_dmatrix();
+    assert_eq!(shown, frames[7], "the sequence did not show the requested frame");
+    let normals = viewer.scene.get_comp::<&Normals>(&entity).unwrap().0.to_dmatrix();
+    let expected_normals = Geom::compute_per_vertex_normals(
+        &frames[7],
+        &viewer.scene.get_comp::<&Faces>(&entity).unwrap().0.to_dmatrix(),
+        &PerVertexNormalsWeightingType::Area,
+    );
+    assert!(
+        (normals - expected_normals).abs().max() < 1e-5,
+        "the normals were not recomputed for the new frame"
+    );
+}
+
+#[test]
+fn children_follow_parent_and_despawn_with_it() {
+    let mut scene = Scene::new();
+    let body = scene
+        .get_or_create_entity("body")
+        .insert(ModelMatrix::default().with_translation(&na::Vector3::new(1.0, 0.0, 0.0)))
+        .entity();
+    let garment = scene
+        .get_or_create_entity("garment")
+        .insert(ModelMatrix::default().with_translation(&na::Vector3::new(1.0, 2.0, 0.0)))
+        .entity();
+    let button = scene.get_or_create_entity("button").insert(ModelMatrix::default()).entity();
+    scene.set_parent(garment, body);
+    scene.set_parent(button, garment);
+    scene.set_parent(body, button); //would create a cycle so it's ignored
+    assert_eq!(scene.get_parent(body), None);
+
+    //attaching keeps the pose in the world
+    scene.propagate_transforms();
+    scene.world.clear_trackers();
+    let world_translation = |scene: &Scene, entity| scene.get_comp::<&ModelMatrix>(&entity).unwrap().0.isometry.translation.vector;
+    assert!((world_translation(&scene, garment) - na::Vector3::new(1.0, 2.0, 0.0)).norm() < 1e-6);
+    assert_eq!(scene.get_comp::<&Children>(&body).unwrap().0, vec![garment]);
+
+    //moving the parent moves the whole subtree
+    scene
+        .world
+        .insert_one(body, ModelMatrix::default().with_translation(&na::Vector3::new(0.0, 0.0, 5.0)))
+        .unwrap();
+    scene.propagate_transforms();
+    assert!((world_translation(&scene, garment) - na::Vector3::new(0.0, 2.0, 5.0)).norm() < 1e-6);
+    assert!((world_translation(&scene, button) - na::Vector3::new(-1.0, 0.0, 5.0)).norm() < 1e-6);
+    assert!((scene.get_world_matrix(button).isometry.translation.vector - na::Vector3::new(-1.0, 0.0, 5.0)).norm() < 1e-6);
+
+    scene.despawn(body);
+    assert!(!scene.world.contains(garment));
+    assert!(!scene.world.contains(button));
+}
+
+#[test]
+fn clip_planes_cut_everything_except_opted_out_entities() {
+    let mut config = Config::new(None);
+    config.core.auto_add_floor = false;
+    let mut viewer = sphere_viewer(&config);
+    let entity = viewer.scene.get_entity_with_name("sphere").unwrap();
+    let img_whole = render_bytes(&mut viewer);
+    //the corner is not covered by the sphere so it has the background color
+    let (corner, center) = (0..4, (32 * 64 + 32) * 4..(32 * 64 + 32) * 4 + 4);
+    assert_ne!(img_whole[center.clone()], img_whole[corner.clone()], "the sphere was not rendered");
+
+    //the whole sphere is on the negative side of the plane
+    let plane = ClipPlane::new(na::Point3::new(0.0, 0.0, 2.0), na::Vector3::new(0.0, 0.0, 1.0));
+    assert!(plane.clips(&na::Point3::origin()));
+    let plane_entity = viewer.scene.get_or_create_hidden_entity("clip_plane").insert(plane).entity();
+    let img_clipped = render_bytes(&mut viewer);
+    assert_eq!(img_clipped[center.clone()], img_clipped[corner], "the clip plane did not cut the sphere");
+
+    viewer.scene.world.insert_one(entity, NoClip).unwrap();
+    let img_opted_out = render_bytes(&mut viewer);
+    assert_eq!(img_whole, img_opted_out, "an entity with NoClip was cut");
+    viewer.scene.world.remove_one::<NoClip>(entity).unwrap();
+
+    //cutting away the half facing the camera shows the inside of the sphere which the cap fills
+    let cam = (*viewer.scene.get_comp::<&PosLookat>(&viewer.camera.entity).unwrap()).clone();
+    let plane = ClipPlane::new(na::Point3::origin(), cam.lookat - cam.position);
+    viewer.scene.world.insert_one(plane_entity, plane.clone()).unwrap();
+    let img_hollow = render_bytes(&mut viewer);
+    viewer
+        .scene
+        .world
+        .insert_one(plane_entity, plane.with_cap(na::Vector4::new(1.0, 0.0, 0.0, 1.0)))
+        .unwrap();
+    let img_capped = render_bytes(&mut viewer);
+    assert_ne!(img_hollow[center.clone()], img_capped[center.clone()], "the cap did not fill the cut");
+    assert_ne!(img_capped[center.clone()], img_whole[center], "the sphere was not cut");
+}
+
+#[test]
+fn highlight_outlines_only_the_surroundings_of_the_entity() {
+    let mut viewer = sphere_viewer(&Config::new(None));
+    let entity = viewer.scene.get_entity_with_name("sphere").unwrap();
+    let img_plain = render_bytes(&mut viewer);
+
+    viewer
+        .scene
+        .world
+        .insert_one(entity, Highlight::new(na::Vector4::new(0.0, 1.0, 0.0, 1.0), 3.0))
+        .unwrap();
+    let img_highlighted = render_bytes(&mut viewer);
+    assert_ne!(img_plain, img_highlighted, "the highlight did not draw an outline");
+    //the center of the sphere is covered by the sphere itself so the outline doesn't reach it
+    let center = (32 * 64 + 32) * 4;
+    assert_eq!(img_plain[center..center + 4], img_highlighted[center..center + 4]);
+
+    viewer.scene.world.remove_one::<Highlight>(entity).unwrap();
+    let img_removed = render_bytes(&mut viewer);
+    assert_eq!(img_plain, img_removed, "the outline stayed after removing the highlight");
+}
+
+#[test]
+fn views_are_composed_side_by_side() {
+    let mut config = Config::new(None);
+    config.core.auto_add_floor = false;
+    let mut viewer = sphere_viewer(&config);
+    let pose = PosLookat::new(na::Point3::new(0.0, 0.0, 5.0), na::Point3::origin());
+    let mut add_view = |name: &str, viewport: Viewport| {
+        let cam = Camera::new(name, &mut viewer.scene, true);
+        viewer.scene.world.insert(cam.entity, (pose.clone(), viewport)).unwrap();
+        cam
+    };
+    let left = add_view("left", Viewport::new(0.0, 0.0, 0.5, 1.0));
+    let right = add_view("right", Viewport::new(0.5, 0.0, 0.5, 1.0));
+    let overrides = ViewOverrides {
+        bg_color: Some(na::Vector4::new(1.0, 0.0, 0.0, 1.0)),
+        ..Default::default()
+    };
+    viewer.scene.world.insert_one(right.entity, overrides).unwrap();
+
+    let img = render_bytes(&mut viewer);
+    let view_bytes = |viewer: &ViewerHeadless, cam: &Camera| {
+        let tex = viewer.get_view_tex(cam).expect("the camera was not rendered as a view");
+        assert_eq!((tex.width(), tex.height()), (32, 64), "the view does not have the size of its viewport");
+        pollster::block_on(tex.download_to_cpu(viewer.gpu.device(), viewer.gpu.queue()))
+            .as_bytes()
+            .to_vec()
+    };
+    let (img_left, img_right) = (view_bytes(&viewer, &left), view_bytes(&viewer, &right));
+    let px_view = |x: usize, y: usize| (y * 32 + x) * 4..(y * 32 + x) * 4 + 4;
+    let px_out = |x: usize, y: usize| (y * 64 + x) * 4..(y * 64 + x) * 4 + 4;
+    let close = |a: &[u8], b: &[u8]| a.iter().zip(b).all(|(x, y)| x.abs_diff(*y) <= 1);
+
+    assert_ne!(
+        img_left[px_view(16, 32)],
+        img_left[px_view(0, 0)],
+        "the sphere was not rendered in the view"
+    );
+    assert_ne!(
+        img_left[px_view(0, 0)],
+        img_right[px_view(0, 0)],
+        "the background of the view was not overridden"
+    );
+    assert!(close(&img[px_out(0, 0)], &img_left[px_view(0, 0)]), "the left view is not on the left");
+    assert!(
+        close(&img[px_out(16, 32)], &img_left[px_view(16, 32)]),
+        "the left view is not on the left"
+    );
+    assert!(
+        close(&img[px_out(32, 0)], &img_right[px_view(0, 0)]),
+        "the right view is not on the right"
+    );
+    assert!(
+        close(&img[px_out(48, 32)], &img_right[px_view(16, 32)]),
+        "the right view is not on the right"
+    );
+
+    //cameras that stop being views are not rendered anymore
+    viewer.scene.world.remove_one::<Viewport>(right.entity).unwrap();
+    render_bytes(&mut viewer);
+    assert!(viewer.get_view_tex(&right).is_none());
+    assert!(viewer.get_view_tex(&left).is_some());
+}
+
+#[test]
+fn antialiasing_without_msaa_is_deterministic() {
+    //same as the reference tests which disable msaa since it's not reproducible
+    let mut config = Config::new(None);
+    config.render.msaa_nr_samples = 1;
+    let mut viewer = sphere_viewer(&config);
+    let img_aliased = render_bytes(&mut viewer);
+
+    let img_supersampled = render_supersampled_bytes(&mut viewer, 8);
+    assert_ne!(img_aliased, img_supersampled, "the supersampling did not smooth the edges");
+    assert_eq!(
+        img_supersampled,
+        render_supersampled_bytes(&mut viewer, 8),
+        "the supersampled stills differ"
+    );
+
+    viewer.config.render.antialiasing = AntiAliasing::Fxaa;
+    let img_fxaa = render_bytes(&mut viewer);
+    assert_ne!(img_aliased, img_fxaa, "fxaa did not smooth the edges");
+    assert_eq!(img_fxaa, render_bytes(&mut viewer), "fxaa is not deterministic");
+}
+
+#[test]
+fn project_unproject_roundtrip() {
+    let mut scene = Scene::new();
+    let mut camera = Camera::new("camera", &mut scene, true);
+    scene
+        .world
+        .insert_one(camera.entity, PosLookat::new(na::Point3::new(0.0, 0.0, 5.0), na::Point3::origin()))
+        .unwrap();
+    camera.set_target_res(640, 480, &mut scene);
+    assert!(!camera.is_orthographic(&scene));
+    assert_project_unproject_roundtrip(&camera, &scene);
+
+    camera.set_orthographic(true, &mut scene);
+    assert!(camera.is_orthographic(&scene));
+    assert_project_unproject_roundtrip(&camera, &scene);
+}
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn distorted_project_unproject_roundtrip() {
+    let distortions = [
+        LensDistortion::BrownConrady {
+            k1: -0.2,
+            k2: 0.05,
+            k3: 0.0,
+            p1: 0.001,
+            p2: -0.0005,
+        },
+        LensDistortion::KannalaBrandt {
+            k1: 0.05,
+            k2: 0.01,
+            k3: 0.0,
+            k4: 0.0,
+        },
+    ];
+    for distortion in distortions {
+        let intrinsics = ProjectionWithIntrinsics {
+            fx: 500.0,
+            fy: 500.0,
+            cx: 320.0,
+            cy: 240.0,
+            near: 0.1,
+            far: 100.0,
+            distortion: Some(distortion),
+        };
+
+        //the pixels and the normalized coordinates map to each other
+        for x in (0..=640).step_by(80) {
+            for y in (0..=480).step_by(80) {
+                let px = na::Vector2::new(x as f32, y as f32);
+                let normalized = intrinsics.pixel_to_normalized(&px).unwrap();
+                assert!(
+                    (intrinsics.normalized_to_pixel(&normalized) - px).norm() < 1e-2,
+                    "{distortion:?} at pixel {px}"
+                );
+            }
+        }
+
+        let mut scene = Scene::new();
+        let mut camera = Camera::new("camera", &mut scene, true);
+        scene
+            .world
+            .insert(
+                camera.entity,
+                (
+                    PosLookat::new(na::Point3::new(0.0, 0.0, 5.0), na::Point3::origin()),
+                    Projection::WithIntrinsics(intrinsics),
+                ),
+            )
+            .unwrap();
+        camera.set_target_res(640, 480, &mut scene);
+        assert_project_unproject_roundtrip(&camera, &scene);
+    }
+}
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn instance_id_aov_matches_the_final_image() {
+    //without msaa both passes rasterize the sphere in exactly the same pixels
+    let mut config = Config::new(None);
+    config.render.msaa_nr_samples = 1;
+    config.core.auto_add_floor = false;
+    config.aov.instance_id = true;
+    let mut viewer = sphere_viewer(&config);
+    let sphere = viewer.scene.get_entity_with_name("sphere").unwrap();
+    let final_img = render_bytes(&mut viewer);
+    let final_px: Vec<&[u8]> = final_img.chunks(4).collect();
+    let aov_tex = viewer.get_aov_tex(Aov::InstanceId).unwrap();
+    let aov_img = pollster::block_on(aov_tex.download_to_cpu(viewer.gpu.device(), viewer.gpu.queue()));
+    let ids: &[f32] = bytemuck::cast_slice(aov_img.as_bytes());
+    assert_eq!(final_px.len(), ids.len());
+
+    //the corner is not covered by the sphere so it has the background color
+    let background = final_px[0];
+    let nr_covered = final_px.iter().filter(|px| **px != background).count();
+    assert!(nr_covered > 0, "the sphere was not rendered");
+    for (idx, (px, id)) in final_px.iter().zip(ids).enumerate() {
+        let expected = if *px == background { -1.0 } else { sphere.id() as f32 };
+        assert_eq!(*id, expected, "the instance id at pixel {idx} doesn't match the final image");
+    }
+
+    //cameras with lens distortion don't render aovs since they wouldn't line up with the warped image
+    let intrinsics = ProjectionWithIntrinsics {
+        fx: 60.0,
+        fy: 60.0,
+        cx: 32.0,
+        cy: 32.0,
+        near: 0.1,
+        far: 100.0,
+        distortion: Some(LensDistortion::BrownConrady {
+            k1: -0.1,
+            k2: 0.0,
+            k3: 0.0,
+            p1: 0.0,
+            p2: 0.0,
+        }),
+    };
+    let camera = viewer.camera.entity;
+    viewer.scene.world.insert_one(camera, Projection::WithIntrinsics(intrinsics)).unwrap();
+    viewer.start_frame();
+    viewer.update();
+    assert!(viewer.get_aov_tex(Aov::InstanceId).is_none());
+}